@@ -0,0 +1,503 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::error::BufferVecError;
+
+/// A cap on the total size in bytes of the GPU buffers of the vectors that are registered with it.
+///
+/// A budget is a shared handle: clones refer to the same budget. Vectors are registered with
+/// [BufferVec::set_budget] and [IndexBufferVec::set_budget], after which every allocation of a new
+/// buffer is accounted for. When a vector needs to grow and the growth would exceed the budget's
+/// [limit], the budget invokes the eviction callback (see [set_eviction_callback]) with the
+/// number of bytes that must be freed and a [BudgetEntry] for each of the other registered
+/// vectors. The application may then free memory by shrinking (e.g. with
+/// [BufferVec::convert_usage]) or [destroying](crate::BufferVec::destroy) some of its vectors.
+///
+/// If the callback did not free enough memory (or no callback was set), the vector does not grow:
+/// fallible operations such as [BufferVec::try_update] and [BufferVec::try_reserve] return
+/// [BufferVecError::AllocationFailed] and leave the vector unmodified, and their panicking
+/// counterparts panic.
+///
+/// The separate buffer of a [SlicedLen::Previous] upload is charged to the budget as well, for as
+/// long as the upload is in progress; [BufferVec::try_update_sliced] fails in the same way if the
+/// budget cannot make room for it.
+///
+/// Only growth is checked against the budget. Registering a vector, reallocating without growing
+/// (e.g. by [BufferVec::recreate]) and lowering the [limit] never fail or evict, even if the
+/// budget is exceeded as a result.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use web_glitz_buffer_vec::{BufferVec, MemoryBudget};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let budget = MemoryBudget::new(64 * 1024 * 1024);
+///
+/// budget.set_eviction_callback(|bytes_needed, entries| {
+///     // Entries are ordered from least to most recently used.
+///     for entry in entries {
+///         println!("{:?} holds {} bytes", entry.label, entry.bytes);
+///     }
+/// });
+///
+/// let mut vec = BufferVec::new(context, UsageHint::StaticDraw).with_label("terrain");
+///
+/// vec.set_budget(Some(&budget));
+/// vec.update([1.0f32, 2.0, 3.0]);
+///
+/// assert_eq!(budget.used(), 12);
+///
+/// // At the start of every frame:
+/// budget.advance_frame();
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [BufferVec::set_budget]: crate::BufferVec::set_budget
+/// [IndexBufferVec::set_budget]: crate::IndexBufferVec::set_budget
+/// [BufferVec::convert_usage]: crate::BufferVec::convert_usage
+/// [BufferVec::try_update]: crate::BufferVec::try_update
+/// [BufferVec::try_reserve]: crate::BufferVec::try_reserve
+/// [BufferVec::recreate]: crate::BufferVec::recreate
+/// [BufferVec::try_update_sliced]: crate::BufferVec::try_update_sliced
+/// [SlicedLen::Previous]: crate::SlicedLen::Previous
+/// [limit]: MemoryBudget::limit
+/// [set_eviction_callback]: MemoryBudget::set_eviction_callback
+#[derive(Clone)]
+pub struct MemoryBudget {
+    state: Arc<Mutex<BudgetState>>,
+}
+
+/// The callback a [MemoryBudget] invokes to request that memory is freed.
+type EvictionCallback = Box<dyn FnMut(usize, &[BudgetEntry]) + Send>;
+
+struct BudgetState {
+    limit: usize,
+    frame: u64,
+    entries: Vec<(u64, BudgetEntry)>,
+    reserved: usize,
+    evict: Option<EvictionCallback>,
+}
+
+impl BudgetState {
+    fn used(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(_, entry)| entry.bytes)
+            .sum::<usize>()
+            .saturating_add(self.reserved)
+    }
+
+    fn entry_mut(&mut self, id: u64) -> Option<&mut BudgetEntry> {
+        self.entries
+            .iter_mut()
+            .find(|(entry_id, _)| *entry_id == id)
+            .map(|(_, entry)| entry)
+    }
+
+    /// The number of bytes by which the budget would be exceeded if the vector identified by
+    /// `id` held `bytes` bytes, next to the reserved bytes.
+    fn excess(&self, id: u64, bytes: usize) -> usize {
+        let others: usize = self
+            .entries
+            .iter()
+            .filter(|(entry_id, _)| *entry_id != id)
+            .map(|(_, entry)| entry.bytes)
+            .sum();
+
+        others
+            .saturating_add(self.reserved)
+            .saturating_add(bytes)
+            .saturating_sub(self.limit)
+    }
+}
+
+impl MemoryBudget {
+    /// Creates a new budget that allows at most `limit` bytes for the buffers of its vectors.
+    pub fn new(limit: usize) -> Self {
+        MemoryBudget {
+            state: Arc::new(Mutex::new(BudgetState {
+                limit,
+                frame: 0,
+                entries: Vec::new(),
+                reserved: 0,
+                evict: None,
+            })),
+        }
+    }
+
+    /// The maximum total size in bytes of the buffers of the vectors registered with this budget.
+    pub fn limit(&self) -> usize {
+        self.state().limit
+    }
+
+    /// Sets the maximum total size in bytes of the buffers of the vectors registered with this
+    /// budget.
+    ///
+    /// Lowering the limit below the bytes currently [used] does not evict; the new limit applies
+    /// to the next growth of a vector.
+    ///
+    /// [used]: MemoryBudget::used
+    pub fn set_limit(&self, limit: usize) {
+        self.state().limit = limit;
+    }
+
+    /// The total size in bytes of the buffers of the vectors currently registered with this
+    /// budget, including the separate buffers of their [SlicedLen::Previous] uploads in progress.
+    ///
+    /// [SlicedLen::Previous]: crate::SlicedLen::Previous
+    pub fn used(&self) -> usize {
+        self.state().used()
+    }
+
+    /// The current frame, as counted by [advance_frame].
+    ///
+    /// [advance_frame]: MemoryBudget::advance_frame
+    pub fn frame(&self) -> u64 {
+        self.state().frame
+    }
+
+    /// Advances the frame counter that is recorded as the [last_used_frame] of a vector when a
+    /// view on the vector is obtained.
+    ///
+    /// Call this once at the start of every frame.
+    ///
+    /// [last_used_frame]: BudgetEntry::last_used_frame
+    pub fn advance_frame(&self) {
+        self.state().frame += 1;
+    }
+
+    /// Sets the callback that is invoked when a vector needs to grow beyond the limit of this
+    /// budget, replacing any previous callback.
+    ///
+    /// The callback receives the number of bytes that must be freed for the growth to succeed and
+    /// an entry for every other vector registered with this budget, ordered from least to most
+    /// recently used. The vector that needs to grow is not included, as it is borrowed for the
+    /// duration of the callback.
+    ///
+    /// The callback must not grow any vector registered with this budget: while the callback runs,
+    /// such growth fails as if there were no callback. Vectors registered with this budget hold a
+    /// handle to it, so a callback that captures such a vector should capture it with a
+    /// [Weak](std::sync::Weak) reference, to avoid a reference cycle.
+    ///
+    /// The callback must be [Send], as the budget is shared by the vectors registered with it. If
+    /// the vectors to free cannot be captured (e.g. because they are not [Send] themselves), the
+    /// callback may instead record which vectors to free; the growth then fails with
+    /// [BufferVecError::AllocationFailed] and may be retried once the application freed them.
+    pub fn set_eviction_callback<F>(&self, callback: F)
+    where
+        F: FnMut(usize, &[BudgetEntry]) + Send + 'static,
+    {
+        self.state().evict = Some(Box::new(callback));
+    }
+
+    /// Locks the state of this budget.
+    ///
+    /// The state is only modified by simple bookkeeping that cannot panic halfway, so a poisoned
+    /// lock is recovered from rather than propagated.
+    fn state(&self) -> MutexGuard<'_, BudgetState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Registers the vector identified by `id` with this budget, with a buffer of `bytes` bytes.
+    pub(crate) fn register(&self, id: u64, label: Option<&str>, bytes: usize) -> BudgetHandle {
+        let mut state = self.state();
+        let frame = state.frame;
+
+        state.entries.push((
+            id,
+            BudgetEntry {
+                label: label.map(|label| label.to_string()),
+                bytes,
+                last_used_frame: frame,
+            },
+        ));
+
+        BudgetHandle {
+            budget: self.clone(),
+            id,
+        }
+    }
+}
+
+impl fmt::Debug for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state();
+
+        f.debug_struct("MemoryBudget")
+            .field("limit", &state.limit)
+            .field("used", &state.used())
+            .field("frame", &state.frame)
+            .field("vecs", &state.entries.len())
+            .finish()
+    }
+}
+
+/// Describes a vector registered with a [MemoryBudget], as passed to the budget's eviction
+/// callback.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BudgetEntry {
+    /// The label of the vector, if it has one.
+    pub label: Option<String>,
+
+    /// The size in bytes of the vector's buffer.
+    pub bytes: usize,
+
+    /// The last [frame](MemoryBudget::frame) in which a view on the vector was obtained, or in
+    /// which the vector was registered if no view has been obtained since.
+    pub last_used_frame: u64,
+}
+
+/// The registration of a vector with a [MemoryBudget]; unregisters the vector when dropped.
+pub(crate) struct BudgetHandle {
+    budget: MemoryBudget,
+    id: u64,
+}
+
+impl BudgetHandle {
+    /// Requests that the vector's buffer grows to `bytes` bytes, invoking the eviction callback if
+    /// that would exceed the limit.
+    ///
+    /// Returns an error if the callback did not free enough memory, in which case the recorded
+    /// size of the vector's buffer does not change.
+    pub(crate) fn request(&self, bytes: usize) -> Result<(), BufferVecError> {
+        self.make_room(bytes, bytes)?;
+        self.set_bytes(bytes);
+
+        Ok(())
+    }
+
+    /// Requests room for a separate buffer of `bytes` bytes next to the vector's buffer, invoking
+    /// the eviction callback if that would exceed the limit.
+    ///
+    /// Returns a reservation that holds the room until it is dropped, or an error if the callback
+    /// did not free enough memory.
+    pub(crate) fn reserve(&self, bytes: usize) -> Result<BudgetReservation, BufferVecError> {
+        let own = self
+            .budget
+            .state()
+            .entry_mut(self.id)
+            .map_or(0, |entry| entry.bytes);
+
+        self.make_room(own.saturating_add(bytes), bytes)?;
+        self.budget.state().reserved += bytes;
+
+        Ok(BudgetReservation {
+            budget: self.budget.clone(),
+            bytes,
+        })
+    }
+
+    /// Ensures the vector's buffer may hold `bytes` bytes within the limit, invoking the eviction
+    /// callback if necessary, or returns an error for an allocation of `requested_bytes` bytes.
+    fn make_room(&self, bytes: usize, requested_bytes: usize) -> Result<(), BufferVecError> {
+        let excess = self.budget.state().excess(self.id, bytes);
+
+        if excess > 0 {
+            let evict = self.budget.state().evict.take();
+
+            if let Some(mut evict) = evict {
+                let mut entries: Vec<_> = self
+                    .budget
+                    .state()
+                    .entries
+                    .iter()
+                    .filter(|(id, _)| *id != self.id)
+                    .map(|(_, entry)| entry.clone())
+                    .collect();
+
+                entries.sort_by_key(|entry| entry.last_used_frame);
+                evict(excess, &entries);
+
+                let mut state = self.budget.state();
+
+                if state.evict.is_none() {
+                    state.evict = Some(evict);
+                }
+            }
+
+            if self.budget.state().excess(self.id, bytes) > 0 {
+                return Err(BufferVecError::AllocationFailed { requested_bytes });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records that the vector's buffer now holds `bytes` bytes, without checking the limit.
+    pub(crate) fn set_bytes(&self, bytes: usize) {
+        if let Some(entry) = self.budget.state().entry_mut(self.id) {
+            entry.bytes = bytes;
+        }
+    }
+
+    /// Records that the vector's label changed to `label`.
+    pub(crate) fn set_label(&self, label: Option<&str>) {
+        if let Some(entry) = self.budget.state().entry_mut(self.id) {
+            entry.label = label.map(|label| label.to_string());
+        }
+    }
+
+    /// Records that the vector was used in the current frame.
+    pub(crate) fn touch(&self) {
+        let mut state = self.budget.state();
+        let frame = state.frame;
+
+        if let Some(entry) = state.entry_mut(self.id) {
+            entry.last_used_frame = frame;
+        }
+    }
+}
+
+impl Drop for BudgetHandle {
+    fn drop(&mut self) {
+        let id = self.id;
+
+        self.budget
+            .state()
+            .entries
+            .retain(|(entry_id, _)| *entry_id != id);
+    }
+}
+
+/// Room in a [MemoryBudget] held for a buffer that is not (yet) the buffer of a vector; releases
+/// the room when dropped.
+pub(crate) struct BudgetReservation {
+    budget: MemoryBudget,
+    bytes: usize,
+}
+
+impl Drop for BudgetReservation {
+    fn drop(&mut self) {
+        let mut state = self.budget.state();
+
+        state.reserved = state.reserved.saturating_sub(self.bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_request_within_limit() {
+        let budget = MemoryBudget::new(100);
+        let a = budget.register(0, None, 40);
+        let b = budget.register(1, None, 0);
+
+        assert_eq!(b.request(60), Ok(()));
+        assert_eq!(budget.used(), 100);
+
+        a.set_bytes(0);
+
+        assert_eq!(budget.used(), 60);
+    }
+
+    #[test]
+    fn test_request_evicts() {
+        let budget = MemoryBudget::new(100);
+        let a = budget.register(0, Some("a"), 30);
+        let b = budget.register(1, Some("b"), 50);
+        let c = budget.register(2, Some("c"), 0);
+
+        budget.advance_frame();
+        b.touch();
+
+        let requested = Arc::new(AtomicUsize::new(0));
+        let evict_requested = requested.clone();
+
+        budget.set_eviction_callback(move |bytes_needed, entries| {
+            evict_requested.store(bytes_needed, Ordering::Relaxed);
+
+            let labels: Vec<_> = entries.iter().map(|e| e.label.as_deref()).collect();
+
+            assert_eq!(labels, vec![Some("a"), Some("b")]);
+
+            a.set_bytes(0);
+        });
+
+        assert_eq!(c.request(40), Ok(()));
+        assert_eq!(requested.load(Ordering::Relaxed), 20);
+        assert_eq!(budget.used(), 90);
+
+        drop(b);
+
+        assert_eq!(budget.used(), 40);
+    }
+
+    #[test]
+    fn test_request_fails_if_not_enough_freed() {
+        let budget = MemoryBudget::new(100);
+        let _a = budget.register(0, None, 80);
+        let b = budget.register(1, None, 10);
+
+        assert_eq!(
+            b.request(30),
+            Err(BufferVecError::AllocationFailed {
+                requested_bytes: 30
+            })
+        );
+        assert_eq!(budget.used(), 90);
+
+        budget.set_eviction_callback(|_, _| {});
+
+        assert!(b.request(30).is_err());
+        assert_eq!(b.request(20), Ok(()));
+    }
+
+    #[test]
+    fn test_reserve() {
+        let budget = MemoryBudget::new(100);
+        let a = budget.register(0, None, 30);
+        let b = budget.register(1, None, 10);
+
+        let reservation = b.reserve(50).unwrap();
+
+        assert_eq!(budget.used(), 90);
+        assert_eq!(
+            b.reserve(20).err(),
+            Some(BufferVecError::AllocationFailed {
+                requested_bytes: 20
+            })
+        );
+        assert!(a.request(40).is_err());
+
+        drop(reservation);
+
+        assert_eq!(budget.used(), 40);
+        assert_eq!(a.request(40), Ok(()));
+    }
+
+    #[test]
+    fn test_reserve_evicts() {
+        let budget = MemoryBudget::new(100);
+        let a = budget.register(0, None, 60);
+        let b = budget.register(1, None, 20);
+
+        budget.set_eviction_callback(move |bytes_needed, _| {
+            assert_eq!(bytes_needed, 20);
+
+            a.set_bytes(0);
+        });
+
+        let _reservation = b.reserve(40).unwrap();
+
+        assert_eq!(budget.used(), 60);
+    }
+
+    #[test]
+    fn test_budget_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<MemoryBudget>();
+        assert_send_sync::<BudgetHandle>();
+        assert_send_sync::<BudgetReservation>();
+    }
+}
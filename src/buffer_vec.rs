@@ -1,15 +1,83 @@
-use std::borrow::Borrow;
+use std::any::type_name;
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::future::Future;
+use std::mem;
 use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::sync::Arc;
 
 use web_glitz::buffer::{Buffer, BufferView, UsageHint};
-use web_glitz::runtime::RenderingContext;
+use web_glitz::runtime::{Connection, RenderingContext};
+use web_glitz::task::{sequence_iter, GpuTask};
 
-use crate::util::new_capacity_amortized;
+use crate::budget::{BudgetHandle, BudgetReservation, MemoryBudget};
+use crate::debug_info::DebugInfo;
+use crate::encoder::VertexEncoder;
+use crate::error::{BufferVecError, CapacityError};
+use crate::frame_sync::FrameClock;
+use crate::gpu_safe::{as_bytes_mut, patch_fields, GpuSafe};
+use crate::growth::{align_capacity, Doubling, GrowthPolicy, Never, ShrinkPolicy};
+use crate::leak_tracking::LiveBuffer;
+use crate::maintenance::{plan_shrink, MaintenanceBudget, MaintenanceReport};
+use crate::realloc_guard::{ReallocGuard, ReallocWatchers};
+use crate::sliced_upload::{SliceTarget, SlicedLen, SlicedUpload};
+use crate::snapshot::GpuSnapshot;
+#[cfg(feature = "stats")]
+use crate::stats::CapacityHistograms;
+use crate::stats::{suggest_shrink_capacity, LengthHistory, ReallocTracker, VecStats};
+use crate::update_log::{UpdateLog, UpdateRecord};
+use crate::update_transaction::{apply_writes, UpdateTransaction};
+use crate::upload_queue::{next_source_id, Rollback, UploadQueue, UploadReport, UploadTicket};
+use crate::upload_verification::{UploadMismatch, UploadVerifier};
+use crate::util::{
+    bug, capacity_limit, check_buffer_size, checked_byte_size, checked_new_capacity, checked_range,
+    checked_read_range, chunk_ranges, describe_vec, dirty_runs, div_ceil, error_message,
+    extend_concat, plan_copy_within, plan_rotate_left, range_bug, AssertNonZeroSized,
+    CowUploadData, FenceTask, Flattened, SharedUploadData, UploadData, MAX_COPY_CHUNKS,
+};
+#[cfg(all(feature = "debug-validate", debug_assertions))]
+use crate::validation::assert_invariants;
+use crate::validation::{violations_result, InvariantViolation};
+#[cfg(feature = "wasm-test")]
+use crate::wasm_test::check_allocation;
+
+/// The views on the data before and after the split index returned by
+/// [BufferVec::try_split_views].
+type SplitViews<'a, T> = (BufferView<'a, [T]>, BufferView<'a, [T]>);
+
+/// A buffer that was replaced by a reallocation, together with its leak-tracking token.
+type ReplacedBuffer<T> = (Buffer<[MaybeUninit<T>]>, LiveBuffer);
+
+/// The state of a [BufferVec] from before a queued update, which the vector returns to if the
+/// update is cancelled with [BufferVec::cancel_queued_update].
+struct PreviousState<T> {
+    len: usize,
+    initialized_len: usize,
+    restore_progress: Option<usize>,
+    sliced_upload: Option<u64>,
+    poisoned: bool,
+    destroyed: bool,
+    shadow: Option<Vec<T>>,
+    buffer: Option<ReplacedBuffer<T>>,
+}
 
 /// A growable GPU buffer for data that may be used to store GPU accessiable data that may be used
 /// in WebGlitz tasks.
 ///
-/// Elements must implement [Copy].
+/// Elements must implement [Copy]. Zero-sized element types are rejected at compile time:
+///
+/// ```compile_fail
+/// use web_glitz::buffer::UsageHint;
+/// use web_glitz::runtime::single_threaded::SingleThreadedContext;
+/// use web_glitz_buffer_vec::BufferVec;
+///
+/// fn create(context: SingleThreadedContext) -> BufferVec<SingleThreadedContext, ()> {
+///     BufferVec::new(context, UsageHint::StaticDraw)
+/// }
+///
+/// let _ = create as fn(_) -> _;
+/// ```
 ///
 /// # Example
 /// ```
@@ -64,17 +132,93 @@ use crate::util::new_capacity_amortized;
 /// Here `context` is a WebGlitz [RenderingContext]. For details on rendering with WebGlitz, see the
 /// [web_glitz::rendering] module documentation.
 ///
+/// # Context loss
+///
+/// When the WebGL context is lost, the GPU buffer backing a [BufferVec] is lost with it. A
+/// [BufferVec] may opt into keeping a CPU-side shadow copy of its data (see [set_resilient]), in
+/// which case it can be restored with [recreate] once the context has been restored (see also
+/// [MemoryRegistry] for restoring many vectors at once).
+///
+/// Between the loss of the context and the call to [recreate], [update] may still be called: the
+/// upload itself is discarded, but the shadow copy is updated, so the new data will be restored by
+/// [recreate]. Views obtained with [as_buffer_view] in this period refer to the lost buffer and
+/// must not be used in tasks; tasks that use them will not execute. Any view obtained before
+/// [recreate] is invalid after [recreate]; use [generation] to detect this.
+///
+/// A vector that is not resilient can be moved to a new context with [rebind_context], given the
+/// data to upload. Until then, any operation on the vector submits its commands to the old context,
+/// which discards them: no operation fails or panics because the context was lost, but neither
+/// does it have any effect on the GPU.
+///
+/// # Threads
+///
+/// The bookkeeping of a [BufferVec] is [Send]: state it shares with other values (such as a
+/// [MemoryBudget] registration, a [FrameClock], a [ReallocGuard] or a
+/// [growth](BufferVec::set_growth_policy) policy) is synchronized, and the
+/// [shrink](BufferVec::set_shrink_policy) policy must be [Send]. Whether the vector itself is [Send]
+/// therefore only depends on its context type `Rc` and on WebGlitz's buffer type.
+///
+/// WebGlitz buffers are currently neither [Send] nor [Sync], and WebGlitz only provides a
+/// single-threaded runtime (the WebGL context can only be used from the thread that created it),
+/// so in practice a [BufferVec] must be used from the thread that created it.
+///
+/// Data may still be produced on another thread, such as a web worker with a shared memory, and
+/// then passed to [update] on the vector's thread. The `Send + Sync` bound on the data, and
+/// therefore on the element type `T`, is imposed by WebGlitz's upload commands and cannot be
+/// relaxed by this crate.
+///
 /// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [set_resilient]: BufferVec::set_resilient
+/// [recreate]: BufferVec::recreate
+/// [update]: BufferVec::update
+/// [as_buffer_view]: BufferVec::as_buffer_view
+/// [generation]: BufferVec::generation
+/// [rebind_context]: BufferVec::rebind_context
+/// [MemoryRegistry]: crate::MemoryRegistry
+/// [MemoryBudget]: crate::MemoryBudget
+/// [FrameClock]: crate::FrameClock
+/// [ReallocGuard]: crate::ReallocGuard
 pub struct BufferVec<Rc, T> {
     context: Rc,
     len: usize,
     buffer: Buffer<[MaybeUninit<T>]>,
+    live_buffer: LiveBuffer,
+    generation: u64,
+    stats: VecStats,
+    shadow: Option<Vec<T>>,
+    max_capacity: Option<usize>,
+    fill_on_grow: Option<T>,
+    initialized_len: usize,
+    destroyed: bool,
+    poisoned: bool,
+    source_id: u64,
+    realloc_tracker: ReallocTracker,
+    realloc_watchers: ReallocWatchers,
+    update_log: Option<UpdateLog>,
+    upload_verifier: Option<UploadVerifier<T>>,
+    length_history: LengthHistory,
+    #[cfg(feature = "stats")]
+    histograms: CapacityHistograms,
+    label: Option<String>,
+    budget: Option<BudgetHandle>,
+    frame_clock: Option<FrameClock>,
+    updated_frame: Option<u64>,
+    restore_progress: Option<usize>,
+    sliced_upload: Option<u64>,
+    sliced_uploads: u64,
+    conversion_staging: Option<Arc<Vec<T>>>,
+    growth: Arc<dyn GrowthPolicy + Send + Sync>,
+    capacity_alignment: usize,
+    shrink: Box<dyn ShrinkPolicy + Send>,
+    max_buffer_size: Option<usize>,
+    masked_update_gap: usize,
+    modifications: u64,
 }
 
 impl<Rc, T> BufferVec<Rc, T>
 where
     Rc: RenderingContext,
-    T: Copy + 'static,
+    T: Copy + Send + Sync + 'static,
 {
     /// Creates a new buffer-backed vector with 0 capacity for the given [RenderingContext].
     ///
@@ -100,13 +244,11 @@ where
     /// [RenderingContext]: web_glitz::runtime::RenderingContext
     /// [UsageHint]: web_glitz::buffer::UsageHint
     pub fn new(context: Rc, usage: UsageHint) -> Self {
+        let () = AssertNonZeroSized::<T>::OK;
+
         let buffer = context.create_buffer_slice_uninit(0, usage);
 
-        BufferVec {
-            context,
-            len: 0,
-            buffer,
-        }
+        Self::from_parts(context, buffer, 0, 0)
     }
 
     /// Creates a new buffer-backed vector with the specified `capacity` for the given
@@ -131,28 +273,180 @@ where
     ///
     /// Here context is a [RenderingContext].
     ///
+    /// # Panics
+    ///
+    /// Panics if the size in bytes of `capacity` elements does not fit in a `usize`.
+    ///
     /// [RenderingContext]: web_glitz::runtime::RenderingContext
     /// [UsageHint]: web_glitz::buffer::UsageHint
     pub fn with_capacity(context: Rc, usage: UsageHint, capacity: usize) -> Self {
+        Self::try_with_capacity(context, usage, capacity).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a new buffer-backed vector with the specified `capacity` for the given
+    /// [RenderingContext], or returns an error if the buffer cannot be allocated.
+    ///
+    /// See [with_capacity] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let vec = BufferVec::<_, [f32; 4]>::try_with_capacity(context, UsageHint::StaticDraw, 10);
+    ///
+    /// assert!(vec.is_ok());
+    /// # }
+    /// ```
+    ///
+    /// Here context is a [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [with_capacity]: BufferVec::with_capacity
+    pub fn try_with_capacity(
+        context: Rc,
+        usage: UsageHint,
+        capacity: usize,
+    ) -> Result<Self, BufferVecError> {
+        let () = AssertNonZeroSized::<T>::OK;
+
+        let bytes = checked_byte_size::<T>(capacity)?;
+
+        #[cfg(feature = "wasm-test")]
+        check_allocation(bytes)?;
+
         let buffer = context.create_buffer_slice_uninit(capacity, usage);
 
+        Ok(Self::from_parts(context, buffer, bytes, capacity))
+    }
+
+    /// Creates a vector for the given `context` that is backed by `buffer`, which holds `capacity`
+    /// elements and occupies `bytes` bytes.
+    fn from_parts(
+        context: Rc,
+        buffer: Buffer<[MaybeUninit<T>]>,
+        bytes: usize,
+        capacity: usize,
+    ) -> Self {
         BufferVec {
             context,
             len: 0,
             buffer,
+            live_buffer: LiveBuffer::new(bytes),
+            generation: 0,
+            stats: VecStats::new(capacity),
+            shadow: None,
+            max_capacity: None,
+            fill_on_grow: None,
+            initialized_len: 0,
+            destroyed: false,
+            poisoned: false,
+            source_id: next_source_id(),
+            realloc_tracker: ReallocTracker::new(),
+            realloc_watchers: ReallocWatchers::new(),
+            update_log: None,
+            upload_verifier: None,
+            length_history: LengthHistory::new(),
+            #[cfg(feature = "stats")]
+            histograms: CapacityHistograms::default(),
+            label: None,
+            budget: None,
+            frame_clock: None,
+            updated_frame: None,
+            restore_progress: None,
+            sliced_upload: None,
+            sliced_uploads: 0,
+            conversion_staging: None,
+            growth: Arc::new(Doubling),
+            capacity_alignment: 1,
+            shrink: Box::new(Never),
+            max_buffer_size: None,
+            masked_update_gap: 0,
+            modifications: 0,
         }
     }
 
+    /// Creates a new buffer-backed vector with 0 capacity for the given [RenderingContext],
+    /// requiring the element type to be [GpuSafe].
+    ///
+    /// Behaves exactly like [new], but rejects element types with padding bytes or an unspecified
+    /// layout at compile time:
+    ///
+    /// ```compile_fail
+    /// use web_glitz::buffer::UsageHint;
+    /// use web_glitz::runtime::single_threaded::SingleThreadedContext;
+    /// use web_glitz_buffer_vec::BufferVec;
+    ///
+    /// #[derive(Clone, Copy)]
+    /// struct Padded {
+    ///     a: u8,
+    ///     b: u32,
+    /// }
+    ///
+    /// fn create(context: SingleThreadedContext) -> BufferVec<SingleThreadedContext, Padded> {
+    ///     BufferVec::new_gpu_safe(context, UsageHint::StaticDraw)
+    /// }
+    /// ```
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [GpuSafe]: crate::GpuSafe
+    /// [new]: BufferVec::new
+    pub fn new_gpu_safe(context: Rc, usage: UsageHint) -> Self
+    where
+        T: GpuSafe,
+    {
+        Self::new(context, usage)
+    }
+
+    /// Creates a new buffer-backed vector with the specified `capacity` for the given
+    /// [RenderingContext], requiring the element type to be [GpuSafe].
+    ///
+    /// Behaves exactly like [with_capacity], see [new_gpu_safe].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size in bytes of `capacity` elements does not fit in a `usize`.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [GpuSafe]: crate::GpuSafe
+    /// [with_capacity]: BufferVec::with_capacity
+    /// [new_gpu_safe]: BufferVec::new_gpu_safe
+    pub fn with_capacity_gpu_safe(context: Rc, usage: UsageHint, capacity: usize) -> Self
+    where
+        T: GpuSafe,
+    {
+        Self::with_capacity(context, usage, capacity)
+    }
+
     /// Replaces the data in the buffer with the given `data`, resizing the buffer if necessary.
     ///
     /// Returns `true` if a new buffer was allocated, `false` otherwise.
     ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to grow and the size in bytes of the `data` does not fit in a
+    /// `usize`.
+    ///
     /// # Guarantees
     ///
     /// Any task submitted from the same thread that called `update` after the update will see the
     /// new data. Any task that does not fence submitted from the same thread that called `update`
     /// before the update will see the old data. No other guarantees are given.
     ///
+    /// As the vector can only be used from the thread that created it (see
+    /// [Threads](BufferVec#threads)), and WebGlitz only runs tasks on that same thread, all tasks
+    /// that use the vector are ordered with respect to its updates; no additional fencing is needed
+    /// for data that was produced on another thread and handed to `update`.
+    ///
+    /// The `data` is moved into the upload command as is; it is never cloned or copied into a
+    /// temporary (`D` is not required to implement [Clone]). Data that is already shared, such as
+    /// a `&'static [T]` baked into the binary or an `Arc<[T]>`, is uploaded straight from the
+    /// shared allocation, see also [update_cow]. The only copy is the shadow copy kept by a
+    /// [resilient](BufferVec::set_resilient) vector.
+    ///
     /// # Example
     ///
     /// ```
@@ -170,77 +464,4363 @@ where
     /// Here `context` is a WebGlitz [RenderingContext].
     ///
     /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update_cow]: BufferVec::update_cow
     pub fn update<D>(&mut self, data: D) -> bool
     where
         D: Borrow<[T]> + Send + Sync + 'static,
     {
-        let BufferVec {
-            context,
-            len,
-            buffer,
-        } = self;
+        self.try_update(data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
 
-        *len = data.borrow().len();
+    /// Replaces the data in the buffer with the given `data`, resizing the buffer if necessary, or
+    /// returns an error if the buffer needed to be resized and a new buffer could not be allocated.
+    ///
+    /// Returns `Ok(true)` if a new buffer was allocated, `Ok(false)` otherwise. If an error is
+    /// returned, the vector is left unmodified.
+    ///
+    /// See [update] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.set_max_capacity(Some(2));
+    ///
+    /// assert!(vec.try_update([1, 2, 3]).is_err());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    pub fn try_update<D>(&mut self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "update",
+            label = self.label.as_deref().unwrap_or_else(|| type_name::<Self>()),
+            len = data.borrow().len() as u64,
+            bytes = (data.borrow().len() * mem::size_of::<T>()) as u64,
+            reallocated = tracing::field::Empty
+        )
+        .entered();
 
-        let current_capacity = buffer.len();
+        let reallocated = self.prepare_update(data.borrow())?.is_some();
 
-        let reallocated = if let Some(new_capacity) = new_capacity_amortized(current_capacity, *len) {
-            *buffer = context
-                .create_buffer_slice_uninit(new_capacity, buffer.usage_hint())
-                .into();
+        #[cfg(feature = "tracing")]
+        span.record("reallocated", &reallocated);
 
-            true
-        } else {
-            false
-        };
+        self.submit_upload(0..self.len, data);
+        self.poisoned = false;
+        self.finish_operation(UpdateRecord::Update { len: self.len });
 
-        let view = buffer.get(0..*len).unwrap();
+        Ok(reallocated)
+    }
 
-        let upload_task = unsafe {
-            // Note: the view data range is not actually guaranteed to be initialized, but we're
-            // only writing, not reading.
-            view.assume_init().upload_command(data)
-        };
+    /// Replaces the data in the buffer with the given `data`, which is either borrowed for the
+    /// `'static` lifetime or owned, resizing the buffer if necessary.
+    ///
+    /// Accepts geometry baked into the binary and generated geometry uniformly; in both cases the
+    /// data is uploaded without being copied. See [update] for details.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use std::borrow::Cow;
+    ///
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// static QUAD: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update_cow(Cow::Borrowed(&QUAD[..]));
+    /// vec.update_cow(Cow::Owned(vec![[0.5, 0.5]; 8]));
+    ///
+    /// assert_eq!(vec.len(), 8);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    pub fn update_cow(&mut self, data: Cow<'static, [T]>) -> bool {
+        self.try_update_cow(data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given `data`, which is either borrowed for the
+    /// `'static` lifetime or owned, resizing the buffer if necessary, or returns an error if a new
+    /// buffer could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_cow] for details.
+    ///
+    /// [update_cow]: BufferVec::update_cow
+    pub fn try_update_cow(&mut self, data: Cow<'static, [T]>) -> Result<bool, BufferVecError> {
+        self.try_update(CowUploadData(data))
+    }
+
+    /// Replaces the data in the buffer with the given `data` like [update], and returns a future
+    /// that resolves once the GPU has completed the upload.
+    ///
+    /// A fence is inserted after the upload; the future resolves when the runtime observes that the
+    /// fence has been signalled (the single-threaded runtime checks its fences on a short timeout).
+    /// Awaiting is rarely necessary: tasks submitted to the same context after the update always
+    /// observe the new data, as the GPU executes commands in submission order. Only await the
+    /// future to order work that does not go through the context's submission order, such as work
+    /// that starts from a different logical task and must not begin before the data is resident.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # async fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update_awaitable([1, 2, 3]).await;
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    pub fn update_awaitable<D>(&mut self, data: D) -> impl Future<Output = ()>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.update(data);
 
-        context.submit(upload_task);
+        self.context.submit(FenceTask::new())
+    }
 
-        reallocated
+    /// Replaces the data in the buffer with the given `data` like [update], but enqueues the
+    /// upload in the given `queue` rather than submitting it.
+    ///
+    /// The length and capacity of the vector change immediately; the new data is uploaded when the
+    /// `queue` is submitted. Replaces any update for this vector that is still in the `queue`. See
+    /// [UploadQueue] for details.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// [update]: BufferVec::update
+    pub fn enqueue_update<D>(&mut self, data: D, queue: &mut UploadQueue) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.try_enqueue_update(data, queue)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
     }
 
-    /// The number of elements this vector can hold without allocating a new buffer.
-    pub fn capacity(&self) -> usize {
-        self.buffer.len()
+    /// Replaces the data in the buffer with the given `data` like [try_update], but enqueues the
+    /// upload in the given `queue` rather than submitting it.
+    ///
+    /// See [enqueue_update] for details.
+    ///
+    /// [try_update]: BufferVec::try_update
+    /// [enqueue_update]: BufferVec::enqueue_update
+    pub fn try_enqueue_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.enqueue_full_update(data, queue)
+            .map(|(reallocated, _)| reallocated)
     }
 
-    /// Returns a view on the data in the buffer.
+    /// Replaces the data in the buffer with the given `data` like [enqueue_update], but returns an
+    /// [UploadTicket] that may be used to cancel the upload before the `queue` is submitted.
+    ///
+    /// Intended for data that may become obsolete before the `queue` is submitted, such as a
+    /// streamed chunk that is unloaded again. Pass the ticket to [cancel_queued_update] to remove
+    /// the upload from the `queue` and restore the vector's previous length, capacity and data.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
     ///
     /// # Example
     ///
     /// ```
     /// # use web_glitz::runtime::RenderingContext;
     /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
-    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz_buffer_vec::{BufferVec, UploadQueue};
     /// use web_glitz::buffer::UsageHint;
     ///
-    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    /// let mut queue = UploadQueue::new();
+    /// let mut vec = BufferVec::new(context, UsageHint::StreamDraw);
     ///
-    /// vec.update([1, 2, 3]);
+    /// vec.update([1, 2]);
     ///
-    /// let view = vec.as_buffer_view();
+    /// let ticket = vec.enqueue_cancellable_update([3, 4, 5], &mut queue);
     ///
-    /// assert_eq!(view.len(), 3);
+    /// assert_eq!(vec.len(), 3);
+    ///
+    /// // The chunk was unloaded before the queue was submitted.
+    /// assert!(vec.cancel_queued_update(ticket));
+    /// assert_eq!(vec.len(), 2);
+    /// assert!(queue.is_empty());
     /// # }
     /// ```
     ///
     /// Here `context` is a WebGlitz [RenderingContext].
     ///
     /// [RenderingContext]: web_glitz::runtime::RenderingContext
-    pub fn as_buffer_view(&self) -> BufferView<[T]>
+    /// [update]: BufferVec::update
+    /// [enqueue_update]: BufferVec::enqueue_update
+    /// [cancel_queued_update]: BufferVec::cancel_queued_update
+    pub fn enqueue_cancellable_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> UploadTicket
     where
-        T: Copy + 'static,
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.try_enqueue_cancellable_update(data, queue)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given `data` like [try_enqueue_update], but returns
+    /// an [UploadTicket] that may be used to cancel the upload before the `queue` is submitted.
+    ///
+    /// See [enqueue_cancellable_update] for details.
+    ///
+    /// [try_enqueue_update]: BufferVec::try_enqueue_update
+    /// [enqueue_cancellable_update]: BufferVec::enqueue_cancellable_update
+    pub fn try_enqueue_cancellable_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> Result<UploadTicket, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.enqueue_full_update(data, queue)
+            .map(|(_, ticket)| ticket)
+    }
+
+    /// Removes the upload identified by the `ticket` from its [UploadQueue] and restores the
+    /// vector to its state from before the update, including its length, capacity, buffer and
+    /// CPU-side state.
+    ///
+    /// Returns `true` if the upload was cancelled. Returns `false` and does nothing if the upload
+    /// is no longer pending (the queue was submitted or dropped, or the upload was replaced by a
+    /// later update, see [UploadTicket::is_pending]), or if the vector was modified after the
+    /// update was enqueued. Cancelling the latest of several updates that were enqueued one after
+    /// the other restores the state from before the first of them, as the earlier updates were
+    /// dropped from the queue.
+    ///
+    /// See [enqueue_cancellable_update] for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `ticket` was issued for an update of a different vector.
+    ///
+    /// [enqueue_cancellable_update]: BufferVec::enqueue_cancellable_update
+    pub fn cancel_queued_update(&mut self, ticket: UploadTicket) -> bool {
+        if ticket.source() != self.source_id {
+            panic!(
+                "Cannot cancel the queued update of another vector with {}.",
+                describe_vec("buffer vec", self.label.as_deref())
+            );
+        }
+
+        let previous = match ticket.cancel(self.modifications) {
+            Some(state) => match state.downcast::<PreviousState<T>>() {
+                Ok(previous) => *previous,
+                Err(_) => bug(format_args!(
+                    "the rollback of a queued update does not belong to a buffer vec"
+                )),
+            },
+            None => return false,
+        };
+
+        self.len = previous.len;
+        self.initialized_len = previous.initialized_len;
+        self.restore_progress = previous.restore_progress;
+        self.sliced_upload = previous.sliced_upload;
+        self.poisoned = previous.poisoned;
+        self.destroyed = previous.destroyed;
+
+        if let (Some(shadow), Some(previous)) = (&mut self.shadow, previous.shadow) {
+            *shadow = previous;
+        }
+
+        if let Some((buffer, live_buffer)) = previous.buffer {
+            self.buffer = buffer;
+            self.live_buffer = live_buffer;
+            self.generation += 1;
+            self.sync_budget();
+        }
+
+        self.finish_replace();
+
+        true
+    }
+
+    /// Enqueues the upload of the given `data` as the new data of the vector in the `queue`,
+    /// returning whether a new buffer was allocated and the ticket for the upload.
+    ///
+    /// The queue keeps the state of the vector from before the update, to restore it if the upload
+    /// is cancelled.
+    fn enqueue_full_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> Result<(bool, UploadTicket), BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
     {
-        let BufferVec { len, buffer, .. } = self;
+        let stamp = self.modifications;
+        let previous = PreviousState {
+            len: self.len,
+            initialized_len: self.initialized_len,
+            restore_progress: self.restore_progress,
+            sliced_upload: self.sliced_upload,
+            poisoned: self.poisoned,
+            destroyed: self.destroyed,
+            shadow: self.shadow.as_mut().map(mem::take),
+            buffer: None,
+        };
+
+        let replaced = match self.prepare_update(data.borrow()) {
+            Ok(replaced) => replaced,
+            Err(err) => {
+                if let (Some(shadow), Some(previous)) = (&mut self.shadow, previous.shadow) {
+                    *shadow = previous;
+                }
+
+                return Err(err);
+            }
+        };
+        let reallocated = replaced.is_some();
+
+        // An earlier update that is still queued is dropped from the queue without ever being
+        // uploaded, so cancelling this update must return to the state from before the earlier
+        // update. That is only possible if the vector was not modified in between.
+        let previous = match queue.take_rollback(self.source_id) {
+            None => Some(previous),
+            Some(Some(rollback)) if rollback.stamp == stamp => rollback
+                .state
+                .downcast::<PreviousState<T>>()
+                .ok()
+                .map(|previous| *previous),
+            Some(_) => None,
+        };
+        let previous = previous.map(|mut previous| {
+            if previous.buffer.is_none() {
+                previous.buffer = replaced;
+            }
+
+            previous
+        });
+
+        let bytes = self.len as u64 * mem::size_of::<T>() as u64;
+        let upload_task = upload_command(self.uninit_view(0..self.len), data);
+
+        self.stats.record_upload(self.len, mem::size_of::<T>());
+
+        #[cfg(feature = "stats")]
+        self.histograms.update_lengths.record(self.len);
+
+        self.realloc_tracker.record_upload();
+        self.length_history.record(self.len);
+        self.record_updated_frame();
+        self.poisoned = false;
+        self.finish_operation(UpdateRecord::Update { len: self.len });
+
+        let rollback = previous.map(|previous| Rollback {
+            stamp: self.modifications,
+            state: Box::new(previous),
+        });
+        let ticket = queue.enqueue_full_update(self.source_id, bytes, upload_task, rollback);
+
+        Ok((reallocated, ticket))
+    }
+
+    /// Sets up the vector to receive the given `data` as its new data, allocating a new buffer if
+    /// necessary, but does not upload the `data`.
+    ///
+    /// Returns the buffer that was replaced if a new buffer was allocated. Leaves the vector
+    /// poisoned; the caller is responsible for uploading the data and clearing the poison flag.
+    fn prepare_update(&mut self, data: &[T]) -> Result<Option<ReplacedBuffer<T>>, BufferVecError> {
+        let new_len = data.len();
+        let grown_capacity = self.grown_capacity(new_len)?;
+        let shrunk_capacity =
+            self.shrunk_capacity(grown_capacity.unwrap_or_else(|| self.capacity()), new_len);
+        let new_capacity = grown_capacity.or(shrunk_capacity);
+
+        self.poisoned = true;
+        self.len = new_len;
+        self.restore_progress = None;
+        self.sliced_upload = None;
+        self.initialized_len = self.initialized_len.max(new_len);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.clear();
+            shadow.extend_from_slice(data);
+        }
+
+        Ok(new_capacity.map(|new_capacity| self.reallocate(new_capacity, false)))
+    }
 
-        unsafe { buffer.get(0..*len).unwrap().assume_init() }
+    /// Replaces the data in the buffer with `len` elements that are written by the closure `f`,
+    /// resizing the buffer if necessary.
+    ///
+    /// `f` receives a staging slice of exactly `len` elements, each initialized to
+    /// `T::default()`. When `f` returns, the staging slice is uploaded without being copied
+    /// again: the data can be computed in place rather than being collected into a [Vec] first.
+    /// Like [update_converted], stages into an allocation the vector keeps and reuses. Returns
+    /// `true` if a new buffer was allocated, `false` otherwise. See also [write_with_uninit].
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StreamDraw);
+    ///
+    /// vec.write_with(100, |slice| {
+    ///     for (i, element) in slice.iter_mut().enumerate() {
+    ///         *element = i as f32 * 0.5;
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(vec.len(), 100);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    /// [update_converted]: BufferVec::update_converted
+    /// [write_with_uninit]: BufferVec::write_with_uninit
+    pub fn write_with<F>(&mut self, len: usize, f: F) -> bool
+    where
+        T: Default,
+        F: FnOnce(&mut [T]),
+    {
+        let mut staging = self.take_staging(len);
+
+        staging.resize(len, T::default());
+        f(&mut staging);
+
+        self.update_staged(staging)
+    }
+
+    /// Replaces the data in the buffer with `len` elements that are written by the closure `f`
+    /// into an uninitialized staging slice, resizing the buffer if necessary.
+    ///
+    /// Like [write_with], but does not require `T: Default` and does not initialize the staging
+    /// slice before handing it to `f`.
+    ///
+    /// # Safety
+    ///
+    /// `f` must initialize every element of the slice it receives.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// [write_with]: BufferVec::write_with
+    /// [update]: BufferVec::update
+    pub unsafe fn write_with_uninit<F>(&mut self, len: usize, f: F) -> bool
+    where
+        F: FnOnce(&mut [MaybeUninit<T>]),
+    {
+        let mut staging = self.take_staging(len);
+
+        f(&mut staging.spare_capacity_mut()[..len]);
+
+        staging.set_len(len);
+
+        self.update_staged(staging)
+    }
+
+    /// Replaces the data in the buffer with the concatenation of the given `parts`, resizing the
+    /// buffer if necessary.
+    ///
+    /// Avoids concatenating the parts on the caller's side: like [update_converted], the parts are
+    /// staged into an allocation the vector keeps and reuses, and uploaded with a single command, so
+    /// the buffer grows at most once. Returns `true` if a new buffer was allocated, `false`
+    /// otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StreamDraw);
+    ///
+    /// let terrain = vec![1, 2, 3];
+    /// let props = vec![4, 5];
+    ///
+    /// vec.update_concat(&[&terrain[..], &props[..]]);
+    ///
+    /// assert_eq!(vec.len(), 5);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    /// [update_converted]: BufferVec::update_converted
+    pub fn update_concat(&mut self, parts: &[&[T]]) -> bool {
+        self.try_update_concat(parts)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the concatenation of the given `parts`, resizing the
+    /// buffer if necessary, or returns an error if a new buffer could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_concat] for details.
+    ///
+    /// [update_concat]: BufferVec::update_concat
+    pub fn try_update_concat(&mut self, parts: &[&[T]]) -> Result<bool, BufferVecError> {
+        let mut staging = self.take_staging(0);
+
+        extend_concat(&mut staging, parts)?;
+
+        self.try_update_staged(staging)
+    }
+
+    /// Replaces the data in the buffer with the given source `data`, converted element by element
+    /// with the `convert` function, resizing the buffer if necessary.
+    ///
+    /// Intended for uploading data in a narrower format than it is kept in on the CPU. The
+    /// converted elements are written to a staging allocation that the vector keeps and reuses for
+    /// later conversions, once WebGlitz has executed the upload and released the data; if the
+    /// previous upload is still pending, a new allocation is made. The staging allocation keeps the
+    /// capacity of the largest conversion until the vector is dropped.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StreamDraw);
+    ///
+    /// let colors = [0.0f32, 0.5, 1.0];
+    ///
+    /// vec.update_converted(&colors, |channel| (channel * 255.0) as u8);
+    ///
+    /// assert_eq!(vec.len(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    pub fn update_converted<S, F>(&mut self, data: &[S], convert: F) -> bool
+    where
+        F: FnMut(&S) -> T,
+    {
+        self.try_update_converted(data, convert)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given source `data`, converted element by element
+    /// with the `convert` function, resizing the buffer if necessary, or returns an error if a new
+    /// buffer could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_converted] for details.
+    ///
+    /// [update_converted]: BufferVec::update_converted
+    pub fn try_update_converted<S, F>(
+        &mut self,
+        data: &[S],
+        convert: F,
+    ) -> Result<bool, BufferVecError>
+    where
+        F: FnMut(&S) -> T,
+    {
+        let mut staging = self.take_staging(data.len());
+
+        staging.extend(data.iter().map(convert));
+
+        self.try_update_staged(staging)
+    }
+
+    /// Takes the staging allocation for a new upload of `len` elements: the allocation of a
+    /// previous upload if WebGlitz has released it, otherwise a new one. The returned [Vec] is
+    /// empty and has a capacity of at least `len`.
+    fn take_staging(&mut self, len: usize) -> Vec<T> {
+        match self.conversion_staging.take().map(Arc::try_unwrap) {
+            Some(Ok(mut staging)) => {
+                staging.clear();
+                staging.reserve(len);
+
+                staging
+            }
+            _ => Vec::with_capacity(len),
+        }
+    }
+
+    /// Uploads the `staging` allocation obtained with [take_staging](Self::take_staging) and keeps
+    /// it for reuse by a later upload.
+    fn try_update_staged(&mut self, staging: Vec<T>) -> Result<bool, BufferVecError> {
+        let staging = self.share_staging(staging);
+
+        self.try_update(staging)
+    }
+
+    /// Wraps the `staging` allocation obtained with [take_staging](Self::take_staging) for upload
+    /// and keeps it for reuse by a later upload.
+    fn share_staging(&mut self, staging: Vec<T>) -> SharedUploadData<T> {
+        let staging = Arc::new(staging);
+
+        self.conversion_staging = Some(staging.clone());
+
+        SharedUploadData(staging)
+    }
+
+    /// Like [try_update_staged](Self::try_update_staged), but panics if the upload fails.
+    fn update_staged(&mut self, staging: Vec<T>) -> bool {
+        self.try_update_staged(staging)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given source `data`, encoded element by element
+    /// with the `encoder`, resizing the buffer if necessary.
+    ///
+    /// Intended for uploading vertex data in a packed format, see [VertexEncoder] for the stock
+    /// encoders. Like [update_converted], encodes into a reusable staging allocation.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::{BufferVec, Unorm8x4};
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut colors = BufferVec::new(context, UsageHint::StreamDraw);
+    ///
+    /// colors.update_encoded(&Unorm8x4, &[[1.0, 0.5, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]]);
+    ///
+    /// assert_eq!(colors.len(), 2);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [VertexEncoder]: crate::VertexEncoder
+    /// [update_converted]: BufferVec::update_converted
+    /// [update]: BufferVec::update
+    pub fn update_encoded<S, E>(&mut self, encoder: &E, data: &[S]) -> bool
+    where
+        E: VertexEncoder<S, T>,
+    {
+        self.try_update_encoded(encoder, data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given source `data`, encoded element by element
+    /// with the `encoder`, resizing the buffer if necessary, or returns an error if a new buffer
+    /// could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_encoded] for details.
+    ///
+    /// [update_encoded]: BufferVec::update_encoded
+    pub fn try_update_encoded<S, E>(
+        &mut self,
+        encoder: &E,
+        data: &[S],
+    ) -> Result<bool, BufferVecError>
+    where
+        E: VertexEncoder<S, T>,
+    {
+        self.try_update_converted(data, |src| encoder.encode(src))
+    }
+
+    /// Replaces the data in the buffer with the given nested array `data`, flattened into
+    /// `N * data.len()` elements, resizing the buffer if necessary.
+    ///
+    /// The nested arrays are uploaded as they are laid out in memory, without copying them into a
+    /// flattened temporary. Useful when data is produced in groups, such as `[f32; 3]` positions
+    /// for a flat `f32` stream, or `[V; 4]` quads for a vector of `V` vertices. Afterwards, the
+    /// length of the vector is the flattened length. See [update] for details.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::<_, f32>::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update_flattened(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]]);
+    /// vec.append_flattened([[0.0, 1.0, 0.0]]);
+    ///
+    /// assert_eq!(vec.len(), 9);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    pub fn update_flattened<const N: usize, D>(&mut self, data: D) -> bool
+    where
+        D: Borrow<[[T; N]]> + Send + Sync + 'static,
+    {
+        self.try_update_flattened(data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given nested array `data`, flattened into
+    /// `N * data.len()` elements, resizing the buffer if necessary, or returns an error if a new
+    /// buffer could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_flattened] for details.
+    ///
+    /// [update_flattened]: BufferVec::update_flattened
+    pub fn try_update_flattened<const N: usize, D>(
+        &mut self,
+        data: D,
+    ) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[[T; N]]> + Send + Sync + 'static,
+    {
+        self.try_update(Flattened::new(data))
+    }
+
+    /// Appends the given nested array `data`, flattened into `N * data.len()` elements, to the end
+    /// of the vector, growing the buffer if necessary.
+    ///
+    /// Like [update_flattened], uploads the nested arrays without copying them. See [append] for
+    /// details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [append].
+    ///
+    /// [update_flattened]: BufferVec::update_flattened
+    /// [append]: BufferVec::append
+    pub fn append_flattened<const N: usize, D>(&mut self, data: D) -> bool
+    where
+        D: Borrow<[[T; N]]> + Send + Sync + 'static,
+    {
+        self.try_append_flattened(data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Appends the given nested array `data`, flattened into `N * data.len()` elements, to the end
+    /// of the vector, growing the buffer if necessary, or returns an error if a new buffer could
+    /// not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [append_flattened] for details.
+    ///
+    /// [append_flattened]: BufferVec::append_flattened
+    pub fn try_append_flattened<const N: usize, D>(
+        &mut self,
+        data: D,
+    ) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[[T; N]]> + Send + Sync + 'static,
+    {
+        self.try_append(Flattened::new(data))
+    }
+
+    /// Reserves capacity for at least `additional` more elements than the current length of the
+    /// vector, preserving the current data.
+    ///
+    /// Does nothing if the capacity is already sufficient. If a new buffer is allocated, the
+    /// current data is copied into the new buffer on the GPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the required capacity exceeds the [max_capacity] or if the size in bytes of the
+    /// required capacity does not fit in a `usize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    /// vec.reserve(10);
+    ///
+    /// assert!(vec.capacity() >= 13);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [max_capacity]: BufferVec::max_capacity
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Reserves capacity for at least `additional` more elements than the current length of the
+    /// vector, preserving the current data, or returns an error if a new buffer could not be
+    /// allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. WebGlitz does not report GPU
+    /// allocation failures, so all checks are made up front: against the [max_capacity], the
+    /// [max_buffer_size], the size in bytes and the [MemoryBudget], if any. See [reserve] for
+    /// details.
+    ///
+    /// [reserve]: BufferVec::reserve
+    /// [max_capacity]: BufferVec::max_capacity
+    /// [max_buffer_size]: BufferVec::max_buffer_size
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), BufferVecError> {
+        let required_capacity = self.len.checked_add(additional).ok_or(CapacityError {
+            len: usize::MAX,
+            element_size: mem::size_of::<T>(),
+        })?;
+
+        let new_capacity = self.grown_capacity(required_capacity)?;
+
+        if let Some(new_capacity) = new_capacity {
+            let poisoned = mem::replace(&mut self.poisoned, true);
+
+            self.reallocate(new_capacity, true);
+            self.poisoned = poisoned;
+        }
+
+        self.finish_operation(UpdateRecord::Reserve { additional });
+
+        Ok(())
+    }
+
+    /// Appends the given `data` to the end of the vector, growing the buffer if necessary.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise. If a new buffer is
+    /// allocated, the current data is copied into the new buffer on the GPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the required capacity exceeds the [max_capacity] or if the size in bytes of the
+    /// required capacity does not fit in a `usize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    /// vec.append([4, 5]);
+    ///
+    /// assert_eq!(vec.len(), 5);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [max_capacity]: BufferVec::max_capacity
+    pub fn append<D>(&mut self, data: D) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.try_append(data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Appends the given `data` to the end of the vector, growing the buffer if necessary, or
+    /// returns an error if the buffer needed to grow and a new buffer could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [append] for details.
+    ///
+    /// [append]: BufferVec::append
+    pub fn try_append<D>(&mut self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        let additional = data.borrow().len();
+        let new_len = self.len.checked_add(additional).ok_or(CapacityError {
+            len: usize::MAX,
+            element_size: mem::size_of::<T>(),
+        })?;
+        let new_capacity = self.grown_capacity(new_len)?;
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.extend_from_slice(data.borrow());
+        }
+
+        let reallocated = if let Some(new_capacity) = new_capacity {
+            self.reallocate(new_capacity, true);
+
+            true
+        } else {
+            false
+        };
+
+        let start = self.len;
+
+        self.len = new_len;
+        self.initialized_len = self.initialized_len.max(new_len);
+
+        if additional > 0 {
+            self.submit_upload(start..new_len, data);
+        }
+
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Append { additional });
+
+        Ok(reallocated)
+    }
+
+    /// Appends the `value` to the end of the vector if it fits in the current capacity, returning
+    /// its index, or returns the `value` back if the vector is at capacity.
+    ///
+    /// Never allocates a new buffer; on success, submits a single-element upload. Mirrors
+    /// [Vec::push_within_capacity].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::with_capacity(context, UsageHint::DynamicDraw, 2);
+    ///
+    /// assert_eq!(vec.push_within_capacity(1), Ok(0));
+    /// assert_eq!(vec.push_within_capacity(2), Ok(1));
+    /// assert_eq!(vec.push_within_capacity(3), Err(3));
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [Vec::push_within_capacity]: std::vec::Vec::push_within_capacity
+    pub fn push_within_capacity(&mut self, value: T) -> Result<usize, T> {
+        let index = self.len;
+
+        if index >= self.capacity() {
+            return Err(value);
+        }
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.push(value);
+        }
+
+        self.len += 1;
+        self.initialized_len = self.initialized_len.max(self.len);
+        self.submit_upload(index..index + 1, UploadData(vec![value]));
+        self.finish_operation(UpdateRecord::Append { additional: 1 });
+
+        Ok(index)
+    }
+
+    /// Overwrites several ranges of elements at once: each `(offset, data)` part overwrites the
+    /// elements starting at `offset` with its `data`.
+    ///
+    /// All parts are submitted together as a single task. Parts may overlap, in which case the
+    /// part that comes last in `parts` wins. Never allocates a new buffer; the length of the
+    /// vector does not change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the parts extends beyond the length of the vector. All parts are checked
+    /// before any data is uploaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.update([0; 100]);
+    /// vec.update_parts(&[(2, &[1, 1][..]), (50, &[2][..]), (97, &[3, 3, 3][..])]);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn update_parts(&mut self, parts: &[(usize, &[T])]) {
+        self.try_update_parts(parts)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Overwrites several ranges of elements at once, or returns an error if any of the parts
+    /// extends beyond the length of the vector.
+    ///
+    /// If an error is returned, nothing is uploaded and the vector is left unmodified. See
+    /// [update_parts] for details.
+    ///
+    /// [update_parts]: BufferVec::update_parts
+    pub fn try_update_parts(&mut self, parts: &[(usize, &[T])]) -> Result<(), BufferVecError> {
+        for (offset, data) in parts {
+            checked_range(*offset, data.len(), self.len)?;
+        }
+
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            for (offset, data) in parts {
+                shadow[*offset..offset + data.len()].copy_from_slice(data);
+            }
+        }
+
+        self.submit_parts(parts);
+        self.poisoned = poisoned;
+
+        let count = parts.iter().map(|(_, data)| data.len()).sum();
+
+        self.finish_operation(UpdateRecord::Write { count });
+
+        Ok(())
+    }
+
+    /// Replaces the data in the buffer with the given `data`, uploading only the elements that are
+    /// marked as dirty in the `dirty` mask.
+    ///
+    /// The `data` is the complete new data of the vector and `dirty` holds a flag for each of its
+    /// elements, which is `true` if the element changed since the previous update. Elements that
+    /// are not marked as dirty must be equal to the current elements of the vector. Runs of dirty
+    /// elements are uploaded as separate parts, which are submitted together as a single task; runs
+    /// that are separated by at most [masked_update_gap] clean elements are merged into one part
+    /// (uploading the clean elements in between), to trade a few redundant bytes for fewer upload
+    /// commands.
+    ///
+    /// If the length of the `data` differs from the current length of the vector, the mask is
+    /// ignored and all `data` is uploaded, as with [update].
+    ///
+    /// Returns the number of upload commands and bytes that were submitted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `data` and `dirty` differ, or if the vector needs to grow and the
+    /// size in bytes of the `data` does not fit in a `usize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.update([0u32; 6]);
+    ///
+    /// let report = vec.update_masked(
+    ///     [1, 1, 0, 0, 0, 2],
+    ///     &[true, true, false, false, false, true],
+    /// );
+    ///
+    /// assert_eq!(report.commands, 2);
+    /// assert_eq!(report.bytes, 3 * 4);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [masked_update_gap]: BufferVec::masked_update_gap
+    /// [update]: BufferVec::update
+    pub fn update_masked<D>(&mut self, data: D, dirty: &[bool]) -> UploadReport
+    where
+        D: Borrow<[T]>,
+    {
+        self.try_update_masked(data, dirty)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given `data`, uploading only the elements that are
+    /// marked as dirty in the `dirty` mask, or returns an error if a new buffer could not be
+    /// allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_masked] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `data` and `dirty` differ.
+    ///
+    /// [update_masked]: BufferVec::update_masked
+    pub fn try_update_masked<D>(
+        &mut self,
+        data: D,
+        dirty: &[bool],
+    ) -> Result<UploadReport, BufferVecError>
+    where
+        D: Borrow<[T]>,
+    {
+        let data = data.borrow();
+
+        assert_eq!(
+            data.len(),
+            dirty.len(),
+            "The dirty mask must hold exactly one flag for each element of the data."
+        );
+
+        let element_size = mem::size_of::<T>() as u64;
+
+        if data.len() != self.len {
+            self.try_update_concat(&[data])?;
+
+            return Ok(UploadReport {
+                commands: if data.is_empty() { 0 } else { 1 },
+                bytes: data.len() as u64 * element_size,
+            });
+        }
+
+        let parts: Vec<_> = dirty_runs(dirty, self.masked_update_gap)
+            .into_iter()
+            .map(|run| (run.start, &data[run]))
+            .collect();
+
+        self.try_update_parts(&parts)?;
+
+        Ok(UploadReport {
+            commands: parts.len(),
+            bytes: parts
+                .iter()
+                .map(|(_, part)| part.len() as u64 * element_size)
+                .sum(),
+        })
+    }
+
+    /// The largest number of clean elements between two runs of dirty elements for which
+    /// [update_masked] merges the runs into a single upload.
+    ///
+    /// See [set_masked_update_gap].
+    ///
+    /// [update_masked]: BufferVec::update_masked
+    /// [set_masked_update_gap]: BufferVec::set_masked_update_gap
+    pub fn masked_update_gap(&self) -> usize {
+        self.masked_update_gap
+    }
+
+    /// Sets the largest number of clean elements between two runs of dirty elements for which
+    /// [update_masked] merges the runs into a single upload; the default is `0`, which only merges
+    /// adjacent runs.
+    ///
+    /// [update_masked]: BufferVec::update_masked
+    pub fn set_masked_update_gap(&mut self, gap: usize) {
+        self.masked_update_gap = gap;
+    }
+
+    /// Starts replacing the data in the buffer with the given `data` in slices, spreading the
+    /// upload over several frames.
+    ///
+    /// Intended for data sets that are too large to upload in a single frame without stalling.
+    /// Returns a [SlicedUpload] that owns the `data`; call [SlicedUpload::step] with this vector
+    /// once per frame to upload the next slice of at most `bytes_per_step` bytes (but at least one
+    /// element), until it reports that it is done. The `len` decides the length of the vector
+    /// while the upload is in progress, so that data that has not been uploaded yet is never
+    /// drawn:
+    ///
+    /// - [SlicedLen::Previous]: the vector keeps its previous data until the upload completes. The
+    ///   new data is uploaded to a separate buffer, which replaces the vector's buffer (incrementing
+    ///   the [generation]) when the last slice is uploaded. The separate buffer is charged to the
+    ///   vector's [MemoryBudget], if any, from the start of the upload, next to the vector's
+    ///   buffer.
+    /// - [SlicedLen::CompletedPrefix]: the vector is emptied when the upload starts and its length
+    ///   grows with each slice that is uploaded.
+    ///
+    /// Starting another update of all data of this vector, including another sliced update,
+    /// cancels the upload; see [SlicedUpload] for details.
+    ///
+    /// Each slice is copied into a staging allocation for WebGlitz, so the data is not copied as a
+    /// whole; the allocation is reused by the next slice once WebGlitz has released it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to grow to fit the `data` and cannot, or if for
+    /// [SlicedLen::Previous] the separate buffer cannot be allocated; see [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::{BufferVec, SlicedLen};
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut points = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// points.update([0.0f32; 16]);
+    ///
+    /// let mut upload = points.update_sliced(vec![1.0f32; 1024], 1024, SlicedLen::Previous);
+    ///
+    /// // Then once per frame:
+    /// let progress = upload.step(&mut points);
+    ///
+    /// assert_eq!(progress.uploaded_bytes, 1024);
+    /// assert_eq!(progress.total_bytes, 4096);
+    ///
+    /// // The previous data is drawn until the upload is done.
+    /// assert_eq!(points.len(), 16);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [generation]: BufferVec::generation
+    /// [update]: BufferVec::update
+    pub fn update_sliced<D>(
+        &mut self,
+        data: D,
+        bytes_per_step: usize,
+        len: SlicedLen,
+    ) -> SlicedUpload<T, D>
+    where
+        D: Borrow<[T]>,
+    {
+        self.try_update_sliced(data, bytes_per_step, len)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Starts replacing the data in the buffer with the given `data` in slices, or returns an
+    /// error if a new buffer could not be allocated.
+    ///
+    /// For [SlicedLen::Previous], the separate buffer must fit in the vector's [MemoryBudget], if
+    /// any, next to the vector's buffer; otherwise [BufferVecError::AllocationFailed] is returned
+    /// before any data is uploaded.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_sliced] for details.
+    ///
+    /// [update_sliced]: BufferVec::update_sliced
+    pub fn try_update_sliced<D>(
+        &mut self,
+        data: D,
+        bytes_per_step: usize,
+        len: SlicedLen,
+    ) -> Result<SlicedUpload<T, D>, BufferVecError>
+    where
+        D: Borrow<[T]>,
+    {
+        let new_len = data.borrow().len();
+
+        let target = match len {
+            SlicedLen::Previous => {
+                let capacity = self
+                    .planned_capacity(new_len)?
+                    .unwrap_or_else(|| self.capacity());
+                let reservation = self.reserve_budget(capacity)?;
+                let buffer = self
+                    .context
+                    .create_buffer_slice_uninit(capacity, self.buffer.usage_hint());
+
+                SliceTarget::Staging(
+                    buffer,
+                    LiveBuffer::new(capacity * mem::size_of::<T>()),
+                    reservation,
+                )
+            }
+            SlicedLen::CompletedPrefix => {
+                let grown_capacity = self.grown_capacity(new_len)?;
+
+                self.len = 0;
+                self.restore_progress = None;
+
+                if let Some(shadow) = &mut self.shadow {
+                    shadow.clear();
+                }
+
+                if let Some(capacity) = grown_capacity {
+                    self.reallocate(capacity, false);
+                }
+
+                self.finish_replace();
+
+                SliceTarget::Vec
+            }
+        };
+
+        self.sliced_uploads += 1;
+        self.sliced_upload = Some(self.sliced_uploads);
+
+        Ok(SlicedUpload::new(
+            data,
+            self.source_id,
+            self.sliced_uploads,
+            self.generation,
+            bytes_per_step,
+            target,
+        ))
+    }
+
+    /// The identifier of this vector as the source of uploads.
+    pub(crate) fn source_id(&self) -> u64 {
+        self.source_id
+    }
+
+    /// The ticket of the [SlicedUpload] in progress for this vector, if any.
+    pub(crate) fn sliced_upload(&self) -> Option<u64> {
+        self.sliced_upload
+    }
+
+    /// Uploads a slice of the data of a [SlicedUpload] to the `offset` of the `target`.
+    pub(crate) fn upload_slice(&mut self, target: &SliceTarget<T>, offset: usize, data: &[T]) {
+        if data.is_empty() {
+            return;
+        }
+
+        let end = offset + data.len();
+        let mut staging = self.take_staging(data.len());
+
+        staging.extend_from_slice(data);
+
+        let staging = self.share_staging(staging);
+
+        match target {
+            SliceTarget::Vec => {
+                if let Some(shadow) = &mut self.shadow {
+                    shadow.extend_from_slice(data);
+                }
+
+                self.len = end;
+                self.initialized_len = self.initialized_len.max(end);
+                self.submit_upload(offset..end, staging);
+                self.finish_operation(UpdateRecord::Append {
+                    additional: data.len(),
+                });
+            }
+            SliceTarget::Staging(buffer, ..) => {
+                self.stats.record_upload(data.len(), mem::size_of::<T>());
+
+                #[cfg(feature = "stats")]
+                self.histograms.update_lengths.record(data.len());
+
+                self.context
+                    .submit(upload_command(self.view_of(buffer, offset..end), staging));
+                self.verify_upload(buffer, offset..end, data);
+            }
+        }
+    }
+
+    /// Completes a [SlicedUpload] of the given `data` after its last slice was uploaded to the
+    /// `target`.
+    pub(crate) fn finish_sliced_upload(&mut self, target: SliceTarget<T>, data: &[T]) {
+        self.sliced_upload = None;
+
+        if let SliceTarget::Staging(buffer, live_buffer, reservation) = target {
+            let capacity = buffer.len();
+
+            // The separate buffer is charged to the budget as the vector's buffer from here on.
+            drop(reservation);
+
+            self.stats.record_reallocation(capacity);
+            #[cfg(feature = "stats")]
+            self.histograms.reallocation_capacities.record(capacity);
+            self.track_reallocation(self.buffer.len(), capacity);
+
+            if let Some(shadow) = &mut self.shadow {
+                shadow.clear();
+                shadow.extend_from_slice(data);
+            }
+
+            self.buffer = buffer;
+            self.live_buffer = live_buffer;
+            self.len = data.len();
+            self.generation += 1;
+            self.initialized_len = self.len;
+            self.destroyed = false;
+            self.restore_progress = None;
+            self.length_history.record(self.len);
+            self.record_updated_frame();
+            self.sync_budget();
+            self.fill_uninitialized();
+            self.finish_replace();
+        }
+    }
+
+    /// Starts a transaction that groups several modifications of this vector into a single update.
+    ///
+    /// The modifications are staged on the [UpdateTransaction] and applied when it is committed:
+    /// all writes are submitted together as a single task and the vector reallocates at most once
+    /// (incrementing the [generation] at most once), sized for the final length. Dropping the
+    /// transaction without committing it discards the staged modifications.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.update([0; 10]);
+    ///
+    /// let mut transaction = vec.begin_update();
+    ///
+    /// transaction.truncate(8);
+    /// transaction.update_range(0, &[1, 1]);
+    /// transaction.update_range(4, &[2]);
+    /// transaction.extend_from_slice(&[3; 20]);
+    /// transaction.commit();
+    ///
+    /// assert_eq!(vec.len(), 28);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [generation]: BufferVec::generation
+    pub fn begin_update(&mut self) -> UpdateTransaction<'_, Rc, T> {
+        UpdateTransaction::new(self)
+    }
+
+    /// Moves all elements of `other` to the end of this vector, leaving `other` empty, without
+    /// downloading any data: the elements are copied on the GPU.
+    ///
+    /// Returns the range of this vector's elements that now holds the elements moved from `other`.
+    /// Appending from an empty vector does nothing and returns an empty range. The capacity of
+    /// `other` is not changed. If this vector needs to grow, the current data is preserved as with
+    /// [append].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` belongs to a different context, if this vector is resilient and `other`
+    /// is not (see [set_resilient]), or if this vector needs to grow and cannot (see [append]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut scene = BufferVec::new(context.clone(), UsageHint::StaticDraw);
+    /// let mut mesh = BufferVec::new(context, UsageHint::StreamCopy);
+    ///
+    /// scene.update([1, 2, 3]);
+    /// mesh.update([4, 5]);
+    ///
+    /// let range = scene.append_from(&mut mesh);
+    ///
+    /// assert_eq!(range, 3..5);
+    /// assert!(mesh.is_empty());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [append]: BufferVec::append
+    /// [set_resilient]: BufferVec::set_resilient
+    pub fn append_from(&mut self, other: &mut BufferVec<Rc, T>) -> Range<usize> {
+        let start = self.len;
+        let additional = other.len;
+
+        if additional == 0 {
+            return start..start;
+        }
+
+        if self.shadow.is_some() && other.shadow.is_none() {
+            panic!("Cannot append from a buffer vec that is not resilient to one that is.");
+        }
+
+        let new_len = start.checked_add(additional).unwrap_or_else(|| {
+            panic!(
+                "{}",
+                CapacityError {
+                    len: usize::MAX,
+                    element_size: mem::size_of::<T>(),
+                }
+            )
+        });
+        let new_capacity = self
+            .grown_capacity(new_len)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())));
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(new_capacity) = new_capacity {
+            self.reallocate(new_capacity, true);
+        }
+
+        let copy_task = self
+            .uninit_view(start..new_len)
+            .copy_from_command(other.uninit_view(0..additional));
+
+        self.context.submit(copy_task);
+
+        if let (Some(shadow), Some(other_shadow)) = (&mut self.shadow, &mut other.shadow) {
+            shadow.append(other_shadow);
+        } else if let Some(other_shadow) = &mut other.shadow {
+            other_shadow.clear();
+        }
+
+        self.len = new_len;
+        self.initialized_len = self.initialized_len.max(new_len);
+        self.poisoned = poisoned;
+
+        other.len = 0;
+
+        self.finish_operation(UpdateRecord::Append { additional });
+        other.finish_operation(UpdateRecord::Remove { count: additional });
+
+        start..new_len
+    }
+
+    /// Overwrites the elements starting at `offset` with the given `data`.
+    ///
+    /// Never allocates a new buffer; the length of the vector does not change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset + data.len()` exceeds the length of the vector.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    /// vec.update_range(1, [4, 5]);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn update_range<D>(&mut self, offset: usize, data: D)
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.try_update_range(offset, data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Overwrites the elements starting at `offset` with the given `data`, or returns an error if
+    /// `offset + data.len()` exceeds the length of the vector.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_range] for details.
+    ///
+    /// [update_range]: BufferVec::update_range
+    pub fn try_update_range<D>(&mut self, offset: usize, data: D) -> Result<(), BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        let end = checked_range(offset, data.borrow().len(), self.len)?.end;
+
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow[offset..end].copy_from_slice(data.borrow());
+        }
+
+        if offset < end {
+            self.submit_upload(offset..end, data);
+        }
+
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Write {
+            count: end - offset,
+        });
+
+        Ok(())
+    }
+
+    /// Overwrites a single field of each element in the `elements` range with the consecutive
+    /// field values in `data`, leaving the other fields unchanged.
+    ///
+    /// The field is `field_size` bytes long and starts at `byte_offset_in_element` within each
+    /// element; `data` holds one field value per element in the range. Intended for interleaved
+    /// vertex data of which only one attribute changes, such as positions during an animation.
+    ///
+    /// This is a convenience for patching the field on the CPU, not a per-field upload: WebGlitz
+    /// can only upload whole elements, so the field values are patched into a copy of the range
+    /// taken from the vector's CPU-side shadow copy, which is then uploaded with [update_range].
+    /// The whole elements in the range are uploaded, using as much bandwidth as [update_range]
+    /// would; elements outside of the range are not uploaded. The vector must be
+    /// [resilient](BufferVec::set_resilient).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient, if the field does not fit within an element, if the
+    /// length of `data` is not `field_size` times the number of elements in the range, or if the
+    /// range extends beyond the length of the vector.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// // Interleaved `[x, y, r, g]` vertices.
+    /// let mut vertices = BufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vertices.set_resilient(true);
+    /// vertices.update([[0.0f32, 0.0, 1.0, 0.0], [1.0, 0.0, 0.0, 1.0]]);
+    ///
+    /// // Move both vertices, keeping their colors.
+    /// let positions = [[0.5f32, 0.5], [1.5, 0.5]];
+    /// let bytes: Vec<u8> = positions.iter().flatten().flat_map(|c| c.to_ne_bytes()).collect();
+    ///
+    /// vertices.patch_field_range(0, 8, 0..2, &bytes);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update_range]: BufferVec::update_range
+    pub fn patch_field_range(
+        &mut self,
+        byte_offset_in_element: usize,
+        field_size: usize,
+        elements: Range<usize>,
+        data: &[u8],
+    ) where
+        T: GpuSafe,
+    {
+        self.try_patch_field_range(byte_offset_in_element, field_size, elements, data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Overwrites a single field of each element in the `elements` range with the consecutive
+    /// field values in `data`, or returns an error if the vector is not resilient or the range
+    /// extends beyond the length of the vector.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [patch_field_range] for
+    /// details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not fit within an element, or if the length of `data` is not
+    /// `field_size` times the number of elements in the range.
+    ///
+    /// [patch_field_range]: BufferVec::patch_field_range
+    pub fn try_patch_field_range(
+        &mut self,
+        byte_offset_in_element: usize,
+        field_size: usize,
+        elements: Range<usize>,
+        data: &[u8],
+    ) -> Result<(), BufferVecError>
+    where
+        T: GpuSafe,
+    {
+        let element_size = mem::size_of::<T>();
+
+        assert!(
+            byte_offset_in_element
+                .checked_add(field_size)
+                .filter(|end| *end <= element_size)
+                .is_some(),
+            "A field of {} bytes at offset {} does not fit within an element of {} bytes.",
+            field_size,
+            byte_offset_in_element,
+            element_size
+        );
+
+        let range = checked_range(
+            elements.start,
+            elements.end.saturating_sub(elements.start),
+            self.len,
+        )?;
+
+        assert_eq!(
+            data.len(),
+            field_size * range.len(),
+            "The field data must hold exactly one field value per element in the range."
+        );
+
+        let shadow = self.shadow.as_ref().ok_or(BufferVecError::NotResilient)?;
+        let mut patched = shadow[range.clone()].to_vec();
+
+        patch_fields(
+            as_bytes_mut(&mut patched),
+            element_size,
+            byte_offset_in_element,
+            field_size,
+            data,
+        );
+
+        self.try_update_range(range.start, UploadData(patched))
+    }
+
+    /// Inserts the `value` at position `index`, shifting all elements after it to the right.
+    ///
+    /// The shifted elements are moved with GPU-side copies rather than being uploaded again;
+    /// inserting at the end of the vector is equivalent to appending a single element. May
+    /// allocate a new buffer, see [append].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the vector's length, or if the vector needs to grow and
+    /// cannot (see [append]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 4]);
+    /// vec.insert(2, 3);
+    ///
+    /// assert_eq!(vec.len(), 4);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [append]: BufferVec::append
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.len;
+
+        if index > len {
+            panic!(
+                "Insertion index {} is out of bounds for {} of length {}.",
+                index,
+                describe_vec("buffer vec", self.label.as_deref()),
+                len
+            );
+        }
+
+        if index == len {
+            self.append(UploadData(vec![value]));
+
+            return;
+        }
+
+        let new_len = len + 1;
+        let new_capacity = self
+            .grown_capacity(new_len)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())));
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.insert(index, value);
+        }
+
+        self.len = new_len;
+
+        if let Some(new_capacity) = new_capacity {
+            self.reallocate_copying(
+                new_capacity,
+                self.buffer.usage_hint(),
+                &[(0..index, 0), (index..len, index + 1)],
+            );
+        } else {
+            self.copy_within_buffer(index..len, index + 1);
+            self.initialized_len = self.initialized_len.max(new_len);
+        }
+
+        self.submit_upload(index..index + 1, UploadData(vec![value]));
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Append { additional: 1 });
+    }
+
+    /// Removes the element at position `index`, shifting all elements after it to the left.
+    ///
+    /// The shifted elements are moved with GPU-side copies rather than being uploaded again. Does
+    /// not change the capacity of the vector. See also [swap_remove_gpu], which is cheaper if the
+    /// order of the elements does not need to be preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    /// vec.remove(1);
+    ///
+    /// assert_eq!(vec.len(), 2);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [swap_remove_gpu]: BufferVec::swap_remove_gpu
+    pub fn remove(&mut self, index: usize) {
+        let len = self.len;
+
+        if index >= len {
+            panic!(
+                "Removal index {} is out of bounds for {} of length {}.",
+                index,
+                describe_vec("buffer vec", self.label.as_deref()),
+                len
+            );
+        }
+
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.remove(index);
+        }
+
+        self.copy_within_buffer(index + 1..len, index);
+        self.len = len - 1;
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Remove { count: 1 });
+    }
+
+    /// Removes the element at position `index`, replacing it with the last element.
+    ///
+    /// Does not preserve the order of the elements, but copies at most a single element on the
+    /// GPU. Does not change the capacity of the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove_gpu(&mut self, index: usize) {
+        let len = self.len;
+
+        if index >= len {
+            panic!(
+                "Swap-remove index {} is out of bounds for {} of length {}.",
+                index,
+                describe_vec("buffer vec", self.label.as_deref()),
+                len
+            );
+        }
+
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.swap_remove(index);
+        }
+
+        if index != len - 1 {
+            self.copy_within_buffer(len - 1..len, index);
+        }
+
+        self.len = len - 1;
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Remove { count: 1 });
+    }
+
+    /// Copies the elements in the `source` range to the position starting at `target`, entirely on
+    /// the GPU.
+    ///
+    /// Mirrors [slice::copy_within]: the ranges may overlap, and the length of the vector does
+    /// not change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either range is not within the vector's length, or if the start of the `source`
+    /// range is greater than its end.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3, 4, 5]);
+    ///
+    /// // The data is now [1, 2, 1, 2, 3].
+    /// vec.copy_within(0..3, 2);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn copy_within(&mut self, source: Range<usize>, target: usize) {
+        let len = self.len;
+
+        if source.start > source.end || source.end > len {
+            panic!(
+                "Source range {}..{} is out of bounds for {} of length {}.",
+                source.start,
+                source.end,
+                describe_vec("buffer vec", self.label.as_deref()),
+                len
+            );
+        }
+
+        if target > len - source.len() {
+            panic!(
+                "Target range {}..{} is out of bounds for {} of length {}.",
+                target,
+                target.saturating_add(source.len()),
+                describe_vec("buffer vec", self.label.as_deref()),
+                len
+            );
+        }
+
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.copy_within(source.clone(), target);
+        }
+
+        let count = source.len();
+
+        self.copy_within_buffer(source, target);
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Write { count });
+    }
+
+    /// Rotates the elements in place such that the element at `n` becomes the first element,
+    /// entirely on the GPU.
+    ///
+    /// Mirrors [slice::rotate_left], except that `n` is reduced modulo the length of the vector;
+    /// rotating an empty vector does nothing. The elements are staged through a temporary scratch
+    /// buffer; the length and capacity of the vector do not change.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.update([1, 2, 3, 4, 5]);
+    ///
+    /// // The data is now [3, 4, 5, 1, 2].
+    /// vec.rotate_left(2);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn rotate_left(&mut self, n: usize) {
+        let len = self.len;
+
+        if len == 0 {
+            return;
+        }
+
+        let mid = n % len;
+
+        if mid == 0 {
+            return;
+        }
+
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.rotate_left(mid);
+        }
+
+        let (scratch, _live_scratch) = self.create_scratch(len);
+        let to_scratch = scratch.copy_from_command(self.uninit_view(0..len));
+
+        self.context.submit(to_scratch);
+
+        for (source, target) in plan_rotate_left(len, mid) {
+            let target = target..target + source.len();
+            let from_scratch = self
+                .uninit_view(target)
+                .copy_from_command(self.view_of(&scratch, source));
+
+            self.context.submit(from_scratch);
+        }
+
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Write { count: len });
+    }
+
+    /// Rotates the elements in place such that the last `n` elements move to the front, entirely
+    /// on the GPU.
+    ///
+    /// Mirrors [slice::rotate_right], except that `n` is reduced modulo the length of the vector.
+    /// See [rotate_left] for details.
+    ///
+    /// [rotate_left]: BufferVec::rotate_left
+    pub fn rotate_right(&mut self, n: usize) {
+        let len = self.len;
+
+        if len > 0 {
+            self.rotate_left(len - n % len);
+        }
+    }
+
+    /// Migrates the vector to a new buffer with the given usage hint, preserving the data.
+    ///
+    /// If `shrink_to_fit` is `true`, the new buffer's capacity is the vector's length, otherwise
+    /// the current capacity is kept. The data is copied into the new buffer on the GPU. Does
+    /// nothing if the buffer already uses the `new_hint`. Otherwise, increments the [generation].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StreamDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// // The data is final, migrate it to a buffer that is optimized for static data.
+    /// vec.convert_usage(UsageHint::StaticDraw, true);
+    ///
+    /// assert_eq!(vec.capacity(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [generation]: BufferVec::generation
+    pub fn convert_usage(&mut self, new_hint: UsageHint, shrink_to_fit: bool) {
+        if mem::discriminant(&new_hint) == mem::discriminant(&self.buffer.usage_hint()) {
+            return;
+        }
+
+        let capacity = if shrink_to_fit {
+            self.len
+        } else {
+            self.capacity()
+        };
+
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        self.reallocate_copying(capacity, new_hint, &[(0..self.len, 0)]);
+        self.poisoned = poisoned;
+        self.finish_replace();
+    }
+
+    /// Shrinks the capacity of the vector to the larger of its [len] and `min_capacity`,
+    /// preserving the data.
+    ///
+    /// Does nothing if the capacity is already at or below that size. Otherwise, allocates a new
+    /// buffer, copies the data on the GPU and increments the [generation]. See [suggest_shrink]
+    /// for a suggested capacity.
+    ///
+    /// [len]: BufferVec::len
+    /// [generation]: BufferVec::generation
+    /// [suggest_shrink]: BufferVec::suggest_shrink
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let capacity = min_capacity.max(self.len);
+
+        if capacity < self.capacity() {
+            let poisoned = mem::replace(&mut self.poisoned, true);
+
+            self.reallocate_copying(capacity, self.buffer.usage_hint(), &[(0..self.len, 0)]);
+            self.poisoned = poisoned;
+        }
+
+        self.finish_operation(UpdateRecord::ShrinkTo { min_capacity });
+    }
+
+    /// Captures the current data in this vector in a new, independent GPU buffer.
+    ///
+    /// The snapshot's buffer holds exactly [len] elements; the data is copied on the GPU. If the
+    /// vector is resilient, the snapshot also captures the shadow copy, so that it can be restored
+    /// into a resilient vector (see [set_resilient]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// let snapshot = vec.snapshot_gpu();
+    ///
+    /// vec.update([4, 5, 6, 7]);
+    /// vec.restore_gpu(&snapshot);
+    ///
+    /// assert_eq!(vec.len(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [len]: BufferVec::len
+    /// [set_resilient]: BufferVec::set_resilient
+    pub fn snapshot_gpu(&self) -> GpuSnapshot<T> {
+        let buffer = self
+            .context
+            .create_buffer_slice_uninit(self.len, UsageHint::StaticCopy);
+
+        if self.len > 0 {
+            let copy_task = buffer.copy_from_command(self.uninit_view(0..self.len));
+
+            self.context.submit(copy_task);
+        }
+
+        GpuSnapshot {
+            buffer,
+            _live_buffer: LiveBuffer::new(self.len * mem::size_of::<T>()),
+            shadow: self.shadow.clone(),
+        }
+    }
+
+    /// Replaces the data in this vector with the data in the `snapshot`, growing the buffer if
+    /// necessary.
+    ///
+    /// The data is copied on the GPU; the vector's length is set to the snapshot's length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `snapshot` belongs to a different context, if this vector is resilient and
+    /// the snapshot was taken from a vector that was not, or if the vector needs to grow and
+    /// cannot (see [update]).
+    ///
+    /// [update]: BufferVec::update
+    pub fn restore_gpu(&mut self, snapshot: &GpuSnapshot<T>) {
+        let new_len = snapshot.len();
+
+        if self.shadow.is_some() && snapshot.shadow.is_none() {
+            panic!("Cannot restore a resilient buffer vec from a snapshot of a non-resilient one.");
+        }
+
+        let new_capacity = self
+            .grown_capacity(new_len)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())));
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        self.len = new_len;
+
+        if let (Some(shadow), Some(snapshot_shadow)) = (&mut self.shadow, &snapshot.shadow) {
+            shadow.clear();
+            shadow.extend_from_slice(snapshot_shadow);
+        }
+
+        if let Some(new_capacity) = new_capacity {
+            self.reallocate(new_capacity, false);
+        }
+
+        if new_len > 0 {
+            let copy_task = self
+                .uninit_view(0..new_len)
+                .copy_from_command(self.view_of(&snapshot.buffer, 0..snapshot.buffer.len()));
+
+            self.context.submit(copy_task);
+        }
+
+        self.initialized_len = self.initialized_len.max(new_len);
+        self.poisoned = poisoned;
+        self.finish_operation(UpdateRecord::Update { len: new_len });
+    }
+
+    /// The maximum number of elements this vector may grow to, or `None` if no limit applies.
+    ///
+    /// See [set_max_capacity].
+    ///
+    /// [set_max_capacity]: BufferVec::set_max_capacity
+    pub fn max_capacity(&self) -> Option<usize> {
+        self.max_capacity
+    }
+
+    /// Sets the maximum number of elements this vector may grow to.
+    ///
+    /// When the vector grows, the new capacity is clamped to the `max_capacity`. Operations that
+    /// require a greater capacity fail with [BufferVecError::CapacityLimitExceeded] (or panic for
+    /// the infallible variants). Does not affect a vector's current capacity if it already exceeds
+    /// the new `max_capacity`.
+    pub fn set_max_capacity(&mut self, max_capacity: Option<usize>) {
+        self.max_capacity = max_capacity;
+    }
+
+    /// The maximum size in bytes of the buffer backing this vector, if any.
+    ///
+    /// See [set_max_buffer_size].
+    ///
+    /// [set_max_buffer_size]: BufferVec::set_max_buffer_size
+    pub fn max_buffer_size(&self) -> Option<usize> {
+        self.max_buffer_size
+    }
+
+    /// Sets the maximum size in bytes of the buffer backing this vector.
+    ///
+    /// WebGL does not report the largest buffer an implementation can allocate; allocations that
+    /// are too large fail inside the implementation, typically by losing the context. If the
+    /// application knows a safe limit for its target devices, setting it here makes growth clamp
+    /// to the limit, and makes operations that require a larger buffer fail with
+    /// [BufferVecError::BufferSizeLimitExceeded] (or panic for the infallible variants) before
+    /// anything is allocated. Does not affect a vector's current capacity if it already exceeds
+    /// the new limit.
+    ///
+    /// See also [max_supported_capacity].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::{BufferVec, BufferVecError};
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.set_max_buffer_size(Some(20));
+    ///
+    /// assert_eq!(vec.max_supported_capacity(), 5);
+    ///
+    /// vec.update([1u32, 2, 3]);
+    ///
+    /// assert_eq!(vec.capacity(), 4);
+    ///
+    /// vec.update([1u32, 2, 3, 4, 5]);
+    ///
+    /// assert_eq!(vec.capacity(), 5);
+    /// assert_eq!(
+    ///     vec.try_update([1u32, 2, 3, 4, 5, 6]),
+    ///     Err(BufferVecError::BufferSizeLimitExceeded {
+    ///         requested_bytes: 24,
+    ///         limit_bytes: 20,
+    ///     })
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [max_supported_capacity]: BufferVec::max_supported_capacity
+    pub fn set_max_buffer_size(&mut self, max_buffer_size: Option<usize>) {
+        self.max_buffer_size = max_buffer_size;
+    }
+
+    /// Sets the maximum size in bytes of the buffer backing this vector and returns the vector,
+    /// for use when creating the vector.
+    ///
+    /// See [set_max_buffer_size].
+    ///
+    /// [set_max_buffer_size]: BufferVec::set_max_buffer_size
+    pub fn with_max_buffer_size(mut self, max_buffer_size: usize) -> Self {
+        self.set_max_buffer_size(Some(max_buffer_size));
+
+        self
+    }
+
+    /// The largest capacity in elements this vector may grow to.
+    ///
+    /// Accounts for the [max_capacity], the [max_buffer_size] and the largest size in bytes that
+    /// fits in a `usize`.
+    ///
+    /// [max_capacity]: BufferVec::max_capacity
+    /// [max_buffer_size]: BufferVec::max_buffer_size
+    pub fn max_supported_capacity(&self) -> usize {
+        let size_limit = self.max_buffer_size.unwrap_or(usize::MAX) / mem::size_of::<T>();
+
+        self.max_capacity
+            .map_or(size_limit, |max_capacity| max_capacity.min(size_limit))
+    }
+
+    /// Sets the [GrowthPolicy] that decides the new capacity when this vector needs to grow and
+    /// returns the vector, for use when creating the vector.
+    ///
+    /// See [set_growth_policy].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz_buffer_vec::growth::Exact;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw).with_growth_policy(Exact);
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// assert_eq!(vec.capacity(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [set_growth_policy]: BufferVec::set_growth_policy
+    pub fn with_growth_policy<P>(mut self, policy: P) -> Self
+    where
+        P: GrowthPolicy + Send + Sync + 'static,
+    {
+        self.set_growth_policy(policy);
+
+        self
+    }
+
+    /// Sets the [GrowthPolicy] that decides the new capacity when this vector needs to grow.
+    ///
+    /// The default policy is [Doubling]. The capacity the policy decides on is still clamped to
+    /// the [max_capacity]. Does not affect the vector's current capacity. The policy must be
+    /// [Send] and [Sync], as it is shared with vectors split off from this one (see [split_off]).
+    ///
+    /// [max_capacity]: BufferVec::max_capacity
+    /// [split_off]: BufferVec::split_off
+    pub fn set_growth_policy<P>(&mut self, policy: P)
+    where
+        P: GrowthPolicy + Send + Sync + 'static,
+    {
+        self.growth = Arc::new(policy);
+    }
+
+    /// The multiple to which the capacity is rounded up when this vector grows.
+    ///
+    /// See [set_capacity_alignment].
+    ///
+    /// [set_capacity_alignment]: BufferVec::set_capacity_alignment
+    pub fn capacity_alignment(&self) -> usize {
+        self.capacity_alignment
+    }
+
+    /// Sets the multiple to which the capacity is rounded up when this vector grows; the default
+    /// is `1`.
+    ///
+    /// Applies to the capacity the [GrowthPolicy] decides on, before it is clamped to the
+    /// [max_capacity]. If rounding up would overflow, the capacity is not rounded. Does not affect
+    /// the vector's current capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alignment` is `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.set_capacity_alignment(256);
+    /// vec.update([1, 2, 3]);
+    ///
+    /// assert_eq!(vec.capacity(), 256);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [max_capacity]: BufferVec::max_capacity
+    pub fn set_capacity_alignment(&mut self, alignment: usize) {
+        assert!(alignment != 0, "Capacity alignment must not be zero.");
+
+        self.capacity_alignment = alignment;
+    }
+
+    /// Sets the [ShrinkPolicy] that decides whether this vector shrinks when its data is replaced
+    /// and returns the vector, for use when creating the vector.
+    ///
+    /// See [set_shrink_policy].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz_buffer_vec::growth::Immediate;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw).with_shrink_policy(Immediate);
+    ///
+    /// vec.update([1, 2, 3, 4, 5]);
+    /// vec.update([1, 2]);
+    ///
+    /// assert_eq!(vec.capacity(), 2);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [set_shrink_policy]: BufferVec::set_shrink_policy
+    pub fn with_shrink_policy<P>(mut self, policy: P) -> Self
+    where
+        P: ShrinkPolicy + Send + 'static,
+    {
+        self.set_shrink_policy(policy);
+
+        self
+    }
+
+    /// Sets the [ShrinkPolicy] that decides whether this vector shrinks when its data is
+    /// replaced.
+    ///
+    /// The policy is consulted on every update that replaces all data (such as [update]); if it
+    /// decides on a smaller capacity, the new data is uploaded to a new, smaller buffer. The
+    /// default policy is [Never].
+    ///
+    /// [update]: BufferVec::update
+    pub fn set_shrink_policy<P>(&mut self, policy: P)
+    where
+        P: ShrinkPolicy + Send + 'static,
+    {
+        self.shrink = Box::new(policy);
+    }
+
+    /// The value used to initialize the elements beyond the length of the vector when a new buffer
+    /// is allocated, if any.
+    ///
+    /// See [set_fill_on_grow].
+    ///
+    /// [set_fill_on_grow]: BufferVec::set_fill_on_grow
+    pub fn fill_on_grow(&self) -> Option<T> {
+        self.fill_on_grow
+    }
+
+    /// Sets a `value` used to initialize the elements beyond the length of the vector whenever a
+    /// new buffer is allocated, or `None` to leave these elements uninitialized (the default).
+    ///
+    /// When set, any part of the current buffer that has never been initialized is immediately
+    /// filled with the `value`. From then on, the vector's entire capacity is initialized on the
+    /// GPU, which allows binding the entire capacity with [as_capacity_view].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.set_fill_on_grow(Some(0));
+    /// vec.update([1, 2, 3]);
+    ///
+    /// // Elements beyond the first 3 are initialized to `0`.
+    /// let view = vec.as_capacity_view();
+    ///
+    /// assert_eq!(view.len(), vec.capacity());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [as_capacity_view]: BufferVec::as_capacity_view
+    pub fn set_fill_on_grow(&mut self, value: Option<T>) {
+        self.fill_on_grow = value;
+
+        self.fill_uninitialized();
+    }
+
+    /// Returns the capacity to which the vector would grow to hold `new_len` elements, or `None` if
+    /// the current capacity suffices, without charging a [MemoryBudget].
+    fn planned_capacity(&self, new_len: usize) -> Result<Option<usize>, BufferVecError> {
+        check_buffer_size::<T>(self.capacity(), new_len, self.max_buffer_size)?;
+
+        checked_new_capacity::<T>(
+            self.capacity(),
+            new_len,
+            capacity_limit::<T>(self.max_capacity, self.max_buffer_size),
+            &*self.growth,
+            self.capacity_alignment,
+        )
+    }
+
+    /// Returns the capacity to which the vector must grow to hold `new_len` elements, or `None` if
+    /// the current capacity suffices.
+    ///
+    /// If the vector must grow and is registered with a [MemoryBudget], the growth is charged to
+    /// the budget, which may evict other vectors to make room.
+    fn grown_capacity(&self, new_len: usize) -> Result<Option<usize>, BufferVecError> {
+        let new_capacity = self.planned_capacity(new_len)?;
+
+        if let Some(new_capacity) = new_capacity {
+            self.charge_budget(new_capacity)?;
+        }
+
+        Ok(new_capacity)
+    }
+
+    /// Charges growth to a buffer of the given `capacity` to the [MemoryBudget] of this vector, if
+    /// any, which may evict other vectors to make room. With the `wasm-test` feature, also fails if
+    /// a `TestContext` injected a failure for the allocation.
+    ///
+    /// Call only once the growth is certain, right before reallocating.
+    fn charge_budget(&self, capacity: usize) -> Result<(), BufferVecError> {
+        let bytes = capacity * mem::size_of::<T>();
+
+        #[cfg(feature = "wasm-test")]
+        check_allocation(bytes)?;
+
+        match &self.budget {
+            Some(budget) => budget.request(bytes),
+            None => Ok(()),
+        }
+    }
+
+    /// Reserves room for a separate buffer of the given `capacity` next to the buffer of this
+    /// vector in its [MemoryBudget], if any, which may evict other vectors to make room. With the
+    /// `wasm-test` feature, also fails if a `TestContext` injected a failure for the allocation.
+    fn reserve_budget(&self, capacity: usize) -> Result<Option<BudgetReservation>, BufferVecError> {
+        let bytes = capacity * mem::size_of::<T>();
+
+        #[cfg(feature = "wasm-test")]
+        check_allocation(bytes)?;
+
+        match &self.budget {
+            Some(budget) => budget.reserve(bytes).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Consults the [ShrinkPolicy] of this vector for a vector of the given `capacity` that now
+    /// holds `len` elements, and returns the capacity to shrink to, if any.
+    fn shrunk_capacity(&mut self, capacity: usize, len: usize) -> Option<usize> {
+        let alignment = self.capacity_alignment;
+
+        self.shrink
+            .shrink(capacity, len)
+            .map(|new_capacity| align_capacity(new_capacity.max(len), alignment))
+            .filter(|new_capacity| *new_capacity < capacity)
+    }
+
+    /// Marks this vector as used in the current frame of its [MemoryBudget], if any.
+    fn touch_budget(&self) {
+        if let Some(budget) = &self.budget {
+            budget.touch();
+        }
+    }
+
+    /// Records the current size of the buffer with the [MemoryBudget] of this vector, if any.
+    fn sync_budget(&self) {
+        if let Some(budget) = &self.budget {
+            budget.set_bytes(self.capacity() * mem::size_of::<T>());
+        }
+    }
+
+    /// Allocates a new buffer with the given `capacity`, optionally copying the current data into
+    /// the new buffer.
+    ///
+    /// If data is not preserved, then the caller is responsible for uploading the data for the
+    /// current length. Returns the buffer that was replaced.
+    fn reallocate(&mut self, capacity: usize, preserve: bool) -> ReplacedBuffer<T> {
+        let usage = self.buffer.usage_hint();
+
+        if preserve {
+            self.reallocate_copying(capacity, usage, &[(0..self.len, 0)])
+        } else {
+            self.reallocate_copying(capacity, usage, &[])
+        }
+    }
+
+    /// Allocates a new buffer with the given `capacity` and `usage` hint and copies each of the
+    /// given source ranges of the current buffer into the new buffer, starting at the associated
+    /// target offset.
+    ///
+    /// The caller is responsible for uploading the data for any part of the current length that
+    /// is not covered by the copies. Returns the buffer that was replaced.
+    fn reallocate_copying(
+        &mut self,
+        capacity: usize,
+        usage: UsageHint,
+        copies: &[(Range<usize>, usize)],
+    ) -> ReplacedBuffer<T> {
+        let buffer = self.context.create_buffer_slice_uninit(capacity, usage);
+
+        self.stats.record_reallocation(capacity);
+        #[cfg(feature = "stats")]
+        self.histograms.reallocation_capacities.record(capacity);
+        self.track_reallocation(self.buffer.len(), capacity);
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            label = self.label.as_deref().unwrap_or_else(|| type_name::<Self>()),
+            capacity = capacity as u64,
+            "buffer vec reallocated"
+        );
+
+        for (source, target) in copies {
+            if !source.is_empty() {
+                let target = *target..target + source.len();
+                let copy_task = self
+                    .view_of(&buffer, target)
+                    .copy_from_command(self.uninit_view(source.clone()));
+
+                self.context.submit(copy_task);
+            }
+        }
+
+        let replaced_buffer = mem::replace(&mut self.buffer, buffer);
+        let replaced_live_buffer = mem::replace(
+            &mut self.live_buffer,
+            LiveBuffer::new(capacity * mem::size_of::<T>()),
+        );
+
+        self.generation += 1;
+        self.initialized_len = self.len;
+        self.destroyed = false;
+        self.sync_budget();
+
+        self.fill_uninitialized();
+
+        (replaced_buffer, replaced_live_buffer)
+    }
+
+    /// Copies the `source` range of the buffer to the `target` offset within the same buffer.
+    ///
+    /// The source and target ranges may overlap: WebGL does not allow copying between overlapping
+    /// ranges of the same buffer, so an overlapping copy is split into a small number of
+    /// non-overlapping copies, or, if that would take too many copies, staged through a temporary
+    /// scratch buffer.
+    fn copy_within_buffer(&self, source: Range<usize>, target: usize) {
+        if let Some(plan) = plan_copy_within(source.clone(), target, MAX_COPY_CHUNKS) {
+            for (source, target) in plan {
+                let target = target..target + source.len();
+                let copy_task = self
+                    .uninit_view(target)
+                    .copy_from_command(self.uninit_view(source));
+
+                self.context.submit(copy_task);
+            }
+
+            return;
+        }
+
+        let (scratch, _live_scratch) = self.create_scratch(source.len());
+        let target = target..target + source.len();
+
+        let to_scratch = scratch.copy_from_command(self.uninit_view(source));
+
+        self.context.submit(to_scratch);
+
+        let from_scratch = self
+            .uninit_view(target)
+            .copy_from_command(self.view_of(&scratch, 0..scratch.len()));
+
+        self.context.submit(from_scratch);
+    }
+
+    /// Creates a temporary scratch buffer of `len` elements for staging copies on the GPU, together
+    /// with the [LiveBuffer] that accounts for it while it is alive.
+    fn create_scratch(&self, len: usize) -> (Buffer<[MaybeUninit<T>]>, LiveBuffer) {
+        let scratch = self
+            .context
+            .create_buffer_slice_uninit::<T>(len, UsageHint::StreamCopy);
+
+        (scratch, LiveBuffer::new(len * mem::size_of::<T>()))
+    }
+
+    /// Fills the part of the buffer that has never been initialized with the fill value, if a fill
+    /// value was set.
+    fn fill_uninitialized(&mut self) {
+        if let Some(value) = self.fill_on_grow {
+            let capacity = self.capacity();
+
+            if self.initialized_len < capacity {
+                let fill = vec![value; capacity - self.initialized_len];
+
+                self.submit_upload(self.initialized_len..capacity, UploadData(fill));
+                self.initialized_len = capacity;
+            }
+        }
+    }
+
+    /// A view on the `range` of the buffer, which may not be initialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `range` is out of bounds for the buffer, see [view_of].
+    ///
+    /// [view_of]: BufferVec::view_of
+    fn uninit_view(&self, range: Range<usize>) -> BufferView<'_, [MaybeUninit<T>]> {
+        self.view_of(&self.buffer, range)
+    }
+
+    /// A view on the `range` of the `buffer`, which is either the buffer of this vector or a
+    /// buffer that is used to copy data to or from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the state of this vector if the `range` is out of bounds for the `buffer`,
+    /// which indicates a bug in the bookkeeping of this vector.
+    fn view_of<'a, U>(&self, buffer: &'a Buffer<[U]>, range: Range<usize>) -> BufferView<'a, [U]> {
+        match buffer.get(range.clone()) {
+            Some(view) => view,
+            None => range_bug(
+                "buffer vec",
+                self.label.as_deref(),
+                &range,
+                buffer.len(),
+                self.len,
+                self.capacity(),
+            ),
+        }
+    }
+
+    /// Submits a single task that uploads each `(offset, data)` part to the buffer.
+    fn submit_parts(&mut self, parts: &[(usize, &[T])]) {
+        for (_, data) in parts.iter().filter(|(_, data)| !data.is_empty()) {
+            self.stats.record_upload(data.len(), mem::size_of::<T>());
+
+            #[cfg(feature = "stats")]
+            self.histograms.update_lengths.record(data.len());
+            self.realloc_tracker.record_upload();
+            self.length_history.record(self.len);
+            self.record_updated_frame();
+        }
+
+        let commands: Vec<_> = parts
+            .iter()
+            .filter(|(_, data)| !data.is_empty())
+            .map(|(offset, data)| {
+                let view = self.uninit_view(*offset..offset + data.len());
+
+                unsafe {
+                    // Note: the view data range is not actually guaranteed to be initialized, but
+                    // we're only writing, not reading.
+                    view.assume_init().upload_command(UploadData(data.to_vec()))
+                }
+            })
+            .collect();
+
+        if !commands.is_empty() {
+            self.context.submit(sequence_iter(commands));
+        }
+
+        for (offset, data) in parts.iter().filter(|(_, data)| !data.is_empty()) {
+            self.verify_upload(&self.buffer, *offset..offset + data.len(), data);
+        }
+    }
+
+    /// Submits a command that reads back the `range` of the `buffer` and compares it with the
+    /// `source` data that was just uploaded to it, if upload verification is enabled.
+    fn verify_upload(&self, buffer: &Buffer<[MaybeUninit<T>]>, range: Range<usize>, source: &[T]) {
+        if let Some(verifier) = &self.upload_verifier {
+            // SAFETY: the range was just uploaded to, so it is initialized when the download runs.
+            let view = unsafe { self.view_of(buffer, range.clone()).assume_init() };
+            let task = verifier.verify_task(
+                view.download_command(),
+                source.to_vec(),
+                range,
+                self.label.as_deref(),
+            );
+
+            self.context.submit(task);
+        }
+    }
+
+    /// Submits a command that uploads the `data` to the `range` of the buffer.
+    fn submit_upload<D>(&mut self, range: Range<usize>, data: D)
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.stats.record_upload(range.len(), mem::size_of::<T>());
+
+        #[cfg(feature = "stats")]
+        self.histograms.update_lengths.record(range.len());
+        self.realloc_tracker.record_upload();
+        self.length_history.record(self.len);
+        self.record_updated_frame();
+
+        let source = self
+            .upload_verifier
+            .as_ref()
+            .map(|_| data.borrow().to_vec());
+
+        self.context
+            .submit(upload_command(self.uninit_view(range.clone()), data));
+
+        if let Some(source) = source {
+            self.verify_upload(&self.buffer, range, &source);
+        }
+    }
+
+    /// Configures the reallocation warning: when more than `max_reallocations` reallocations
+    /// happen within `window` uploads, a warning is logged with the `log` crate. Defaults to more
+    /// than 4 reallocations within 16 uploads.
+    ///
+    /// Frequent reallocations usually mean the vector should have been created with a larger
+    /// initial capacity. Only has an effect if the `log` feature is enabled. Reallocations that do
+    /// not change the capacity (such as those caused by [convert_usage]) are not counted.
+    ///
+    /// [convert_usage]: BufferVec::convert_usage
+    pub fn set_realloc_warning(&mut self, max_reallocations: usize, window: usize) {
+        self.realloc_tracker
+            .set_thresholds(max_reallocations, window);
+    }
+
+    /// Disables the reallocation warning for this vector, for vectors that are expected to
+    /// reallocate often.
+    ///
+    /// See [set_realloc_warning] to enable the warning again.
+    ///
+    /// [set_realloc_warning]: BufferVec::set_realloc_warning
+    pub fn suppress_realloc_warnings(&mut self) {
+        self.realloc_tracker.suppress();
+    }
+
+    /// Enables or disables recording of the operations that modify this vector, for reproducing its
+    /// capacity evolution with [replay_capacity].
+    ///
+    /// Full updates, appends (including inserts), removals, [reserve] and [shrink_to] are recorded
+    /// along with their lengths; in-place writes (such as partial updates, copies and rotations)
+    /// as [UpdateRecord::Write], and operations that set the length or capacity directly (such as
+    /// usage conversions and restores) as [UpdateRecord::Replace]. The records are kept in a
+    /// bounded log that discards its oldest records once it holds 4096 records. Enabling recording
+    /// starts a new log; disabling it discards the log. Retrieve the records with
+    /// [update_records].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::growth::{Doubling, Never};
+    /// use web_glitz_buffer_vec::{replay_capacity, BufferVec, UpdateRecord};
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.record_updates(true);
+    /// vec.update([1u32, 2, 3]);
+    /// vec.append([4u32]);
+    ///
+    /// let records = vec.update_records();
+    ///
+    /// assert_eq!(records[1], UpdateRecord::Update { len: 3 });
+    /// assert_eq!(records[2], UpdateRecord::Append { additional: 1 });
+    ///
+    /// let capacities = replay_capacity(&records, 4, &Doubling, &mut Never);
+    ///
+    /// assert_eq!(capacities.last(), Some(&vec.capacity()));
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [replay_capacity]: crate::replay_capacity
+    /// [reserve]: BufferVec::reserve
+    /// [shrink_to]: BufferVec::shrink_to
+    /// [update_records]: BufferVec::update_records
+    pub fn record_updates(&mut self, enabled: bool) {
+        self.update_log = if enabled {
+            Some(UpdateLog::new(self.len, self.capacity()))
+        } else {
+            None
+        };
+    }
+
+    /// Returns `true` if the operations on this vector are recorded, see [record_updates].
+    ///
+    /// [record_updates]: BufferVec::record_updates
+    pub fn is_recording_updates(&self) -> bool {
+        self.update_log.is_some()
+    }
+
+    /// The operations recorded since [record_updates] was enabled, preceded by an
+    /// [UpdateRecord::Start] record with the length and capacity of the vector before the oldest
+    /// of them. Empty if recording is disabled.
+    ///
+    /// [record_updates]: BufferVec::record_updates
+    pub fn update_records(&self) -> Vec<UpdateRecord> {
+        match &self.update_log {
+            Some(log) => log.records(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Completes a mutating operation: counts the modification (see
+    /// [cancel_queued_update](Self::cancel_queued_update)), records the `record` in the update log
+    /// of this vector, if recording is enabled, and checks the invariants of the vector in debug
+    /// builds with the `debug-validate` feature.
+    fn finish_operation(&mut self, record: UpdateRecord) {
+        let capacity = self.capacity();
+
+        self.modifications += 1;
+
+        if let Some(log) = &mut self.update_log {
+            log.record(record, self.len, capacity);
+        }
+
+        #[cfg(all(feature = "debug-validate", debug_assertions))]
+        assert_invariants(self.debug_validate(), "buffer vec", self.label.as_deref());
+    }
+
+    /// Completes an operation that set the length or capacity of this vector without consulting
+    /// its growth or shrink policy, see [finish_operation](Self::finish_operation).
+    fn finish_replace(&mut self) {
+        let record = UpdateRecord::Replace {
+            len: self.len,
+            capacity: self.capacity(),
+        };
+
+        self.finish_operation(record);
+    }
+
+    /// Enables or disables upload verification, a diagnostic mode in which every upload is read
+    /// back from the GPU and compared byte for byte with the uploaded data.
+    ///
+    /// **This is extremely slow.** Every upload is followed by a download of the same range, which
+    /// stalls the GPU pipeline, and a copy of the uploaded data is kept until the comparison
+    /// resolves. Only enable it while chasing a suspected driver bug, never in production.
+    ///
+    /// With debug assertions enabled, a mismatch panics when the read-back resolves, with the
+    /// label of the vector, the uploaded range and the offset of the first differing byte in the
+    /// message; otherwise the mismatch is recorded and may be retrieved with [upload_mismatches].
+    /// Disabling verification discards the recorded mismatches.
+    ///
+    /// Verifies the uploads the vector submits itself, including partial updates, appends and the
+    /// slices of a [SlicedUpload]. Uploads enqueued in an [UploadQueue] and copies between buffers
+    /// on the GPU (such as when the vector reallocates) are not verified. Requires an element type
+    /// that implements [GpuSafe], as the bytes of other types may include padding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.set_verify_uploads(true);
+    /// vec.update([1u32, 2, 3]);
+    ///
+    /// // Later, once the read-back has resolved.
+    /// assert!(vec.upload_mismatches().is_empty());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [upload_mismatches]: BufferVec::upload_mismatches
+    /// [SlicedUpload]: crate::SlicedUpload
+    /// [GpuSafe]: crate::GpuSafe
+    pub fn set_verify_uploads(&mut self, verify: bool)
+    where
+        T: GpuSafe,
+    {
+        if verify {
+            if self.upload_verifier.is_none() {
+                self.upload_verifier = Some(UploadVerifier::new());
+            }
+        } else {
+            self.upload_verifier = None;
+        }
+    }
+
+    /// Returns `true` if the uploads to this vector are verified, see [set_verify_uploads].
+    ///
+    /// [set_verify_uploads]: BufferVec::set_verify_uploads
+    pub fn is_verifying_uploads(&self) -> bool {
+        self.upload_verifier.is_some()
+    }
+
+    /// The uploads that read back different data than was uploaded since verification was enabled
+    /// with [set_verify_uploads].
+    ///
+    /// Always empty with debug assertions enabled, as mismatches panic instead, and for uploads
+    /// whose read-back has not resolved yet.
+    ///
+    /// [set_verify_uploads]: BufferVec::set_verify_uploads
+    pub fn upload_mismatches(&self) -> Vec<UploadMismatch> {
+        match &self.upload_verifier {
+            Some(verifier) => verifier.mismatches(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns a guard that asserts that this vector does not reallocate while it is alive.
+    ///
+    /// With debug assertions enabled, any reallocation of the vector while the guard is alive
+    /// panics, with the label of the vector and its old and new capacities in the message;
+    /// otherwise the reallocation is recorded on the guard, see [ReallocGuard]. The guard does not
+    /// borrow the vector. Useful in performance tests, to verify that a steady-state frame never
+    /// allocates a new buffer.
+    ///
+    /// Reallocations that do not change the capacity (such as those caused by [convert_usage])
+    /// count as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::<_, u16>::with_capacity(context, UsageHint::DynamicDraw, 64);
+    ///
+    /// let guard = vec.expect_no_realloc();
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// assert!(guard.is_clean());
+    /// # }
+    /// ```
+    ///
+    /// [convert_usage]: BufferVec::convert_usage
+    pub fn expect_no_realloc(&mut self) -> ReallocGuard {
+        let guard = ReallocGuard::new();
+
+        self.realloc_watchers.add(&guard);
+
+        guard
+    }
+
+    /// Adds the given `guard` to the guards watching this vector for reallocations.
+    pub(crate) fn watch_reallocations(&mut self, guard: &ReallocGuard) {
+        self.realloc_watchers.add(guard);
+    }
+
+    /// Records a reallocation from `old_capacity` to `new_capacity` and warns if the vector
+    /// reallocates suspiciously often.
+    fn track_reallocation(&mut self, old_capacity: usize, new_capacity: usize) {
+        self.realloc_watchers
+            .check(self.label.as_deref(), old_capacity, new_capacity);
+
+        if old_capacity != new_capacity && self.realloc_tracker.record_reallocation() {
+            #[cfg(feature = "log")]
+            log::warn!(
+                "{} reallocated from a capacity of {} to {} elements and has reallocated \
+                 often; consider creating it with a larger initial capacity.",
+                self.label.as_deref().unwrap_or_else(|| type_name::<Self>()),
+                old_capacity,
+                new_capacity
+            );
+        }
+    }
+
+    /// Sets the label of this vector and returns it, for use when creating the vector.
+    ///
+    /// See [set_label].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let vec = BufferVec::<_, u16>::new(context, UsageHint::StaticDraw).with_label("terrain");
+    ///
+    /// assert_eq!(vec.label(), Some("terrain"));
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [set_label]: BufferVec::set_label
+    pub fn with_label<L>(mut self, label: L) -> Self
+    where
+        L: Into<String>,
+    {
+        self.set_label(label);
+
+        self
+    }
+
+    /// Sets the label of this vector.
+    ///
+    /// The label identifies the vector in panic messages, in its [Debug] output and, if the
+    /// `log` or `tracing` features are enabled, in logs and traces. Vectors without a label are
+    /// identified by their type.
+    ///
+    /// [Debug]: std::fmt::Debug
+    pub fn set_label<L>(&mut self, label: L)
+    where
+        L: Into<String>,
+    {
+        self.label = Some(label.into());
+
+        if let Some(budget) = &self.budget {
+            budget.set_label(self.label.as_deref());
+        }
+    }
+
+    /// The label of this vector, if it has one.
+    ///
+    /// See [set_label].
+    ///
+    /// [set_label]: BufferVec::set_label
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Registers this vector with the given [MemoryBudget], or unregisters it from its current
+    /// budget if `None`.
+    ///
+    /// The current capacity of the vector is charged to the budget without checking the budget's
+    /// limit. From then on, any growth of the vector must fit in the budget (see [MemoryBudget]),
+    /// and the vector is unregistered automatically when it is dropped.
+    pub fn set_budget(&mut self, budget: Option<&MemoryBudget>) {
+        // Drop the current registration first, in case the vector is re-registered with the same
+        // budget.
+        self.budget = None;
+        self.budget = budget.map(|budget| {
+            budget.register(
+                self.source_id,
+                self.label.as_deref(),
+                self.capacity() * mem::size_of::<T>(),
+            )
+        });
+    }
+
+    /// Sets the [FrameClock] used to record the frame in which this vector was last updated, see
+    /// [last_updated_frame], or stops recording if `None`.
+    ///
+    /// The clock is typically obtained from a [FrameSync](crate::FrameSync).
+    ///
+    /// [last_updated_frame]: BufferVec::last_updated_frame
+    pub fn set_frame_clock(&mut self, clock: Option<&FrameClock>) {
+        self.frame_clock = clock.cloned();
+        self.updated_frame = None;
+    }
+
+    /// The frame of this vector's [FrameClock] in which the vector was last updated, or `None` if
+    /// the vector has no clock or has not been updated since the clock was set.
+    ///
+    /// Every upload of data counts as an update, including uploads enqueued in an [UploadQueue];
+    /// these record the frame in which they were enqueued.
+    pub fn last_updated_frame(&self) -> Option<u64> {
+        self.updated_frame
+    }
+
+    /// Records the current frame of the vector's clock as the frame of its last update.
+    fn record_updated_frame(&mut self) {
+        if let Some(clock) = &self.frame_clock {
+            self.updated_frame = Some(clock.frame());
+        }
+    }
+
+    /// The number of elements in this vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements this vector can hold without allocating a new buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the capacity this vector would have after an [update] with `len` elements, or an
+    /// error if such an update would fail.
+    ///
+    /// Applies the vector's [GrowthPolicy], capacity alignment and [max_capacity] without
+    /// allocating or modifying anything. Does not consult the [ShrinkPolicy], whose decision may
+    /// depend on the history of updates, nor the [MemoryBudget], which may refuse the growth.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// assert_eq!(vec.capacity_after(4).unwrap(), 4);
+    /// assert_eq!(vec.capacity_after(5).unwrap(), 8);
+    /// assert!(!vec.will_reallocate(4).unwrap());
+    /// assert!(vec.will_reallocate(5).unwrap());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    /// [max_capacity]: BufferVec::max_capacity
+    pub fn capacity_after(&self, len: usize) -> Result<usize, BufferVecError> {
+        Ok(self
+            .planned_capacity(len)?
+            .unwrap_or_else(|| self.capacity()))
+    }
+
+    /// Returns whether an [update] with `len` elements would allocate a new buffer, or an error if
+    /// such an update would fail.
+    ///
+    /// See [capacity_after].
+    ///
+    /// [update]: BufferVec::update
+    /// [capacity_after]: BufferVec::capacity_after
+    pub fn will_reallocate(&self, len: usize) -> Result<bool, BufferVecError> {
+        Ok(self.planned_capacity(len)?.is_some())
+    }
+
+    /// Counter that is incremented every time this vector allocates a new buffer.
+    ///
+    /// Views obtained with [as_buffer_view] before the generation changed refer to a buffer that is
+    /// no longer used by this vector.
+    ///
+    /// [as_buffer_view]: BufferVec::as_buffer_view
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Upload and allocation statistics for this vector.
+    ///
+    /// See [VecStats] for details.
+    pub fn stats(&self) -> VecStats {
+        self.stats
+    }
+
+    /// Resets the [stats] for this vector; the peak capacity is reset to the current capacity.
+    ///
+    /// [stats]: BufferVec::stats
+    pub fn reset_stats(&mut self) {
+        self.stats = VecStats::new(self.capacity());
+    }
+
+    /// Histograms of the update lengths and reallocation capacities this vector saw, for tuning
+    /// growth policies.
+    ///
+    /// Recording is allocation-free. Only available with the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn stats_histograms(&self) -> CapacityHistograms {
+        self.histograms
+    }
+
+    /// Resets the [stats_histograms] for this vector.
+    ///
+    /// [stats_histograms]: BufferVec::stats_histograms
+    #[cfg(feature = "stats")]
+    pub fn reset_stats_histograms(&mut self) {
+        self.histograms = CapacityHistograms::default();
+    }
+
+    /// The fraction of the capacity that is in use, between `0.0` and `1.0`.
+    ///
+    /// Returns `1.0` for a vector without capacity.
+    pub fn occupancy(&self) -> f32 {
+        let capacity = self.capacity();
+
+        if capacity == 0 {
+            1.0
+        } else {
+            self.len as f32 / capacity as f32
+        }
+    }
+
+    /// The number of bytes allocated for this vector's buffer beyond its [len].
+    ///
+    /// [len]: BufferVec::len
+    pub fn wasted_bytes(&self) -> usize {
+        (self.capacity() - self.len) * mem::size_of::<T>()
+    }
+
+    /// A snapshot of the state of this vector, for display in diagnostics overlays.
+    ///
+    /// See [DebugInfo] for details.
+    pub fn debug_info(&self) -> DebugInfo {
+        DebugInfo {
+            label: self.label.clone(),
+            element_type: type_name::<T>(),
+            len: self.len,
+            capacity: self.capacity(),
+            bytes: self.capacity() * mem::size_of::<T>(),
+            usage: self.buffer.usage_hint(),
+            generation: self.generation,
+            reallocations: self.stats.reallocations,
+            last_updated_frame: self.updated_frame,
+        }
+    }
+
+    /// Checks the internal invariants of this vector, returning all violations found.
+    ///
+    /// Verifies that the length does not exceed the capacity, that the shadow copy of a resilient
+    /// vector (see [set_resilient]) holds exactly [len] elements, that all elements up to [len]
+    /// were initialized, and that no failed update left the vector poisoned. Does not involve the
+    /// GPU.
+    ///
+    /// Intended for tests and debugging; with the `debug-validate` feature, debug builds call it
+    /// automatically at the end of mutating methods and panic on violations.
+    ///
+    /// [set_resilient]: BufferVec::set_resilient
+    /// [len]: BufferVec::len
+    pub fn debug_validate(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut violations = Vec::new();
+        let capacity = self.capacity();
+
+        if self.len > capacity {
+            violations.push(InvariantViolation::LenExceedsCapacity {
+                len: self.len,
+                capacity,
+            });
+        }
+
+        if let Some(shadow) = &self.shadow {
+            if shadow.len() != self.len {
+                violations.push(InvariantViolation::CpuLenMismatch {
+                    cpu_len: shadow.len(),
+                    len: self.len,
+                });
+            }
+        }
+
+        if self.initialized_len < self.len {
+            violations.push(InvariantViolation::UninitializedElements {
+                initialized_len: self.initialized_len,
+                len: self.len,
+            });
+        }
+
+        if self.poisoned {
+            violations.push(InvariantViolation::Poisoned);
+        }
+
+        violations_result(violations)
+    }
+
+    /// Suggests a smaller capacity for this vector if it is consistently using only a small part
+    /// of its capacity, or `None` if shrinking is not worthwhile.
+    ///
+    /// Based on the lengths the vector had at its most recent uploads: suggests the next power of
+    /// two at or above the largest of these, but only if that would at least halve the capacity.
+    /// This is advisory only; use [shrink_to] to actually shrink the vector.
+    ///
+    /// [shrink_to]: BufferVec::shrink_to
+    pub fn suggest_shrink(&self) -> Option<usize> {
+        let recent_max = self.length_history.max()?;
+
+        suggest_shrink_capacity(self.capacity(), self.len, recent_max)
+    }
+
+    /// Performs pending housekeeping for this vector, moving at most as many bytes as the `budget`
+    /// allows, and reports the work done and the work that remains.
+    ///
+    /// Intended to be called opportunistically when nothing time-critical is happening, such as
+    /// during a loading screen. Currently the only housekeeping is shrinking the vector to the
+    /// capacity suggested by [suggest_shrink], which copies the data to a new buffer on the GPU;
+    /// it is skipped if that copy does not fit in the budget. With [MaintenanceBudget::ZERO],
+    /// does nothing and only reports the pending work.
+    ///
+    /// [suggest_shrink]: BufferVec::suggest_shrink
+    pub fn maintenance(&mut self, budget: MaintenanceBudget) -> MaintenanceReport {
+        let (target, report) = plan_shrink(
+            self.capacity(),
+            self.len,
+            mem::size_of::<T>(),
+            self.suggest_shrink(),
+            budget,
+        );
+
+        if let Some(target) = target {
+            self.shrink_to(target);
+        }
+
+        report
+    }
+
+    /// Whether or not this vector keeps a CPU-side shadow copy of its data that allows it to be
+    /// restored after context loss.
+    ///
+    /// See [set_resilient].
+    ///
+    /// [set_resilient]: BufferVec::set_resilient
+    pub fn is_resilient(&self) -> bool {
+        self.shadow.is_some()
+    }
+
+    /// Enables or disables keeping a CPU-side shadow copy of the data in this vector, which allows
+    /// the vector to be restored with [recreate] after the context was lost.
+    ///
+    /// Disabling resilience releases the shadow copy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `resilient` is `true`, the vector is not already resilient and the vector is not
+    /// empty: the vector cannot shadow data that was uploaded before resilience was enabled.
+    ///
+    /// [recreate]: BufferVec::recreate
+    pub fn set_resilient(&mut self, resilient: bool) {
+        if resilient {
+            if self.shadow.is_none() {
+                if self.len != 0 {
+                    panic!(
+                        "Cannot make a non-empty buffer vec resilient; enable resilience before \
+                         the first update."
+                    );
+                }
+
+                self.shadow = Some(Vec::new());
+                // A queued update enqueued without a CPU-side copy cannot restore one.
+                self.modifications += 1;
+            }
+        } else {
+            self.shadow = None;
+        }
+    }
+
+    /// Allocates a new buffer for the given `context` at the current capacity and re-uploads the
+    /// shadowed data, preserving the length of the vector.
+    ///
+    /// Intended to be used to restore the vector after the context was lost: `context` may be the
+    /// restored context or an entirely new context. Increments the [generation].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient (see [set_resilient]).
+    ///
+    /// [generation]: BufferVec::generation
+    /// [set_resilient]: BufferVec::set_resilient
+    pub fn recreate(&mut self, context: Rc) {
+        let shadow = self.shadow.as_ref().unwrap_or_else(|| {
+            panic!(
+                "Cannot recreate {}, as it is not resilient.",
+                describe_vec("buffer vec", self.label.as_deref())
+            )
+        });
+        let data = UploadData(shadow.clone());
+
+        self.context = context;
+        self.reallocate(self.capacity(), false);
+        self.submit_upload(0..self.len, data);
+        self.poisoned = false;
+        self.restore_progress = None;
+        self.finish_replace();
+    }
+
+    /// Returns `false` while the vector is being restored by a [RecoveryPlan] and not all of its
+    /// data has been re-uploaded yet, `true` otherwise.
+    ///
+    /// Until the vector is restored, the contents of its buffer beyond the data that was already
+    /// re-uploaded are unspecified; a renderer should skip drawing the vector. Replacing all data
+    /// of the vector (as with [update]) marks it as restored.
+    ///
+    /// [RecoveryPlan]: crate::RecoveryPlan
+    /// [update]: BufferVec::update
+    pub fn is_restored(&self) -> bool {
+        self.restore_progress.is_none()
+    }
+
+    /// Allocates a new buffer for the given `context` at the current capacity like [recreate], but
+    /// leaves the shadowed data to be uploaded in chunks with [restore_step].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient (see [set_resilient]).
+    ///
+    /// [recreate]: BufferVec::recreate
+    /// [restore_step]: BufferVec::restore_step
+    /// [set_resilient]: BufferVec::set_resilient
+    pub(crate) fn begin_restore(&mut self, context: Rc) {
+        if self.shadow.is_none() {
+            panic!(
+                "Cannot recreate {}, as it is not resilient.",
+                describe_vec("buffer vec", self.label.as_deref())
+            );
+        }
+
+        self.context = context;
+        self.reallocate(self.capacity(), false);
+        self.poisoned = false;
+        self.restore_progress = if self.len > 0 { Some(0) } else { None };
+        self.finish_replace();
+    }
+
+    /// Re-uploads the next elements of the shadowed data after [begin_restore], at most as many as
+    /// fit in `max_bytes` but at least one, and returns the number of bytes uploaded.
+    ///
+    /// [begin_restore]: BufferVec::begin_restore
+    pub(crate) fn restore_step(&mut self, max_bytes: usize) -> usize {
+        let restored = match self.restore_progress {
+            Some(restored) => restored.min(self.len),
+            None => return 0,
+        };
+        let end = self
+            .len
+            .min(restored + (max_bytes / mem::size_of::<T>()).max(1));
+
+        if end > restored {
+            if let Some(shadow) = &self.shadow {
+                let data = UploadData(shadow[restored..end].to_vec());
+
+                self.submit_upload(restored..end, data);
+            }
+        }
+
+        self.restore_progress = if end < self.len { Some(end) } else { None };
+        self.finish_operation(UpdateRecord::Write {
+            count: end - restored,
+        });
+
+        (end - restored) * mem::size_of::<T>()
+    }
+
+    /// The number of bytes of shadowed data that remain to be re-uploaded by [restore_step].
+    ///
+    /// [restore_step]: BufferVec::restore_step
+    pub(crate) fn restore_remaining_bytes(&self) -> usize {
+        self.restore_progress.map_or(0, |restored| {
+            self.len.saturating_sub(restored) * mem::size_of::<T>()
+        })
+    }
+
+    /// The CPU-side shadow copy of the data in this vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient; callers must only use this on vectors they made
+    /// resilient.
+    pub(crate) fn shadow(&self) -> &[T] {
+        match &self.shadow {
+            Some(shadow) => shadow,
+            None => bug(format_args!(
+                "{} has no shadow copy",
+                describe_vec("buffer vec", self.label.as_deref())
+            )),
+        }
+    }
+
+    /// The CPU-side shadow copy of the data in this vector, for modification.
+    ///
+    /// The caller is responsible for uploading the modified elements, see [modify_shadow].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient, see [shadow].
+    ///
+    /// [modify_shadow]: BufferVec::modify_shadow
+    /// [shadow]: BufferVec::shadow
+    pub(crate) fn shadow_mut(&mut self) -> &mut [T] {
+        match &mut self.shadow {
+            Some(shadow) => shadow,
+            None => bug(format_args!(
+                "{} has no shadow copy",
+                describe_vec("buffer vec", self.label.as_deref())
+            )),
+        }
+    }
+
+    /// Modifies the shadow copy with `f`, which returns a result and the range of elements it
+    /// changed, then uploads the changed range and sets the length of the vector to the new length
+    /// of the shadow copy.
+    ///
+    /// `f` may only shrink the shadow copy (or leave its length unchanged); elements beyond the new
+    /// length need not be included in the changed range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient, or if `f` grows the shadow copy.
+    pub(crate) fn modify_shadow<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<T>) -> (R, Range<usize>),
+    {
+        let shadow = self
+            .shadow
+            .as_mut()
+            .expect("Cannot modify the shadow copy of a buffer vec that is not resilient.");
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        let (result, changed) = f(shadow);
+        let new_len = shadow.len();
+
+        assert!(
+            new_len <= self.len,
+            "The shadow copy of a buffer vec must not grow."
+        );
+
+        let changed = changed.start.min(new_len)..changed.end.min(new_len);
+        let written = changed.len();
+
+        if !changed.is_empty() {
+            let data = UploadData(shadow[changed.clone()].to_vec());
+
+            self.submit_upload(changed, data);
+        }
+
+        let record = if new_len < self.len {
+            UpdateRecord::Remove {
+                count: self.len - new_len,
+            }
+        } else {
+            UpdateRecord::Write { count: written }
+        };
+
+        self.len = new_len;
+        self.poisoned = poisoned;
+        self.finish_operation(record);
+
+        result
+    }
+
+    /// Applies the modifications staged by an [UpdateTransaction]: keeps the first `kept`
+    /// elements of the current data, sets the length to `len` and applies the `writes` in order.
+    ///
+    /// The `writes` must cover all elements in `kept..len`.
+    pub(crate) fn try_apply_staged(
+        &mut self,
+        kept: usize,
+        len: usize,
+        writes: &[(usize, Vec<T>)],
+    ) -> Result<bool, BufferVecError> {
+        let new_capacity = self.grown_capacity(len)?;
+        let poisoned = mem::replace(&mut self.poisoned, true);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.truncate(kept);
+            apply_writes(shadow, writes);
+        }
+
+        self.len = len;
+
+        let reallocated = if let Some(new_capacity) = new_capacity {
+            let usage = self.buffer.usage_hint();
+
+            self.reallocate_copying(new_capacity, usage, &[(0..kept, 0)]);
+
+            true
+        } else {
+            self.initialized_len = self.initialized_len.max(len);
+
+            false
+        };
+
+        let parts: Vec<(usize, &[T])> = writes
+            .iter()
+            .map(|(offset, data)| (*offset, data.as_slice()))
+            .collect();
+
+        self.submit_parts(&parts);
+        self.poisoned = poisoned;
+        self.finish_replace();
+
+        Ok(reallocated)
+    }
+
+    /// Releases the GPU buffer backing this vector immediately, rather than when the vector is
+    /// dropped.
+    ///
+    /// Leaves the vector empty, with a capacity of `0`; the vector remains usable: a subsequent
+    /// [update] allocates a new buffer. Views obtained with [as_buffer_view] on a destroyed vector
+    /// are empty. Releases the shadow copy of a resilient vector (the vector remains resilient) and
+    /// clears the [poisoned](BufferVec::is_poisoned) state. Increments the [generation].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    /// vec.destroy();
+    ///
+    /// assert!(vec.is_destroyed());
+    /// assert_eq!(vec.capacity(), 0);
+    /// assert_eq!(vec.as_buffer_view().len(), 0);
+    ///
+    /// vec.update([4, 5]);
+    ///
+    /// assert!(!vec.is_destroyed());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: BufferVec::update
+    /// [as_buffer_view]: BufferVec::as_buffer_view
+    /// [generation]: BufferVec::generation
+    pub fn destroy(&mut self) {
+        self.buffer = self
+            .context
+            .create_buffer_slice_uninit(0, self.buffer.usage_hint());
+        self.live_buffer = LiveBuffer::new(0);
+        self.len = 0;
+        self.initialized_len = 0;
+        self.generation += 1;
+        self.destroyed = true;
+        self.poisoned = false;
+        self.sync_budget();
+
+        if let Some(shadow) = &mut self.shadow {
+            *shadow = Vec::new();
+        }
+
+        self.finish_replace();
+    }
+
+    /// Whether or not the GPU buffer backing this vector was released with [destroy] and no new
+    /// buffer has been allocated since.
+    ///
+    /// [destroy]: BufferVec::destroy
+    pub fn is_destroyed(&self) -> bool {
+        self.destroyed
+    }
+
+    /// Whether or not an operation that modifies this vector was interrupted (by a panic that was
+    /// caught) before all of its commands were submitted.
+    ///
+    /// The data in a poisoned vector is unspecified, even though its [len] may suggest otherwise.
+    /// The poisoned state is cleared by replacing all data with [update], by [recreate] or by
+    /// [destroy].
+    ///
+    /// [len]: BufferVec::len
+    /// [update]: BufferVec::update
+    /// [recreate]: BufferVec::recreate
+    /// [destroy]: BufferVec::destroy
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Reads the element at position `index` back from the GPU, or returns an error if `index` is
+    /// out of bounds.
+    ///
+    /// Downloads only the requested element. Nothing is submitted if an error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # async fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// assert_eq!(vec.read_element(1).unwrap().await, 2);
+    /// assert!(vec.read_element(3).is_err());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn read_element(&self, index: usize) -> Result<impl Future<Output = T>, BufferVecError> {
+        let range = checked_range(index, 1, self.len)?;
+        let download = self.download(range);
+
+        Ok(async move { download.await[0] })
+    }
+
+    /// Reads the elements in the given `range` back from the GPU, or returns an error if the
+    /// `range` is not within the vector's length.
+    ///
+    /// Downloads only the requested elements. Nothing is submitted if an error is returned. See
+    /// [read_back_chunked] for reading large vectors in full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # async fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3, 4]);
+    ///
+    /// assert_eq!(vec.read_range(1..3).unwrap().await, vec![2, 3]);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [read_back_chunked]: BufferVec::read_back_chunked
+    pub fn read_range(
+        &self,
+        range: Range<usize>,
+    ) -> Result<impl Future<Output = Vec<T>>, BufferVecError> {
+        let range = checked_read_range(range, self.len)?;
+        let download = self.download(range);
+
+        Ok(async move { download.await.into_vec() })
+    }
+
+    /// Reads all elements back from the GPU in downloads of at most `chunk_len` elements each.
+    ///
+    /// Each chunk is only submitted after the previous chunk has been received and copied into
+    /// the result, so that at most one chunk is held besides the result at any time; a single
+    /// download of all elements (such as with [read_range]) may briefly need twice the memory of
+    /// the data. The vector remains borrowed until the returned future resolves, so the data
+    /// cannot change between chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # async fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3, 4, 5]);
+    ///
+    /// assert_eq!(vec.read_back_chunked(2).await, vec![1, 2, 3, 4, 5]);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [read_range]: BufferVec::read_range
+    pub fn read_back_chunked(&self, chunk_len: usize) -> impl Future<Output = Vec<T>> + '_ {
+        let ranges = chunk_ranges(self.len, chunk_len);
+
+        async move {
+            let mut data = Vec::with_capacity(self.len);
+
+            for range in ranges {
+                data.extend_from_slice(&self.download(range).await);
+            }
+
+            data
+        }
+    }
+
+    /// Submits a command that downloads the `range` of the buffer.
+    fn download(&self, range: Range<usize>) -> impl Future<Output = Box<[T]>> {
+        let view = unsafe { self.uninit_view(range).assume_init() };
+
+        self.context.submit(view.download_command())
+    }
+
+    /// Moves the vector to the given `context`, allocating a new buffer for it and uploading the
+    /// given `data`.
+    ///
+    /// Intended for vectors that are not resilient (see [set_resilient]), to move them to a new
+    /// context after the old context was lost or replaced; resilient vectors can use [recreate]
+    /// instead. The vector's length becomes the length of the `data`. Keeps the current capacity
+    /// if it suffices for the `data`. Like [update], accepts any data that can be borrowed as a
+    /// slice, and uploads it without copying. Allocates exactly one buffer and increments the
+    /// [generation] once, regardless of the vector's shrink policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow to fit the `data` and cannot (see [update]).
+    ///
+    /// [set_resilient]: BufferVec::set_resilient
+    /// [recreate]: BufferVec::recreate
+    /// [generation]: BufferVec::generation
+    /// [update]: BufferVec::update
+    pub fn rebind_context<D>(&mut self, context: Rc, data: D)
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        let len = data.borrow().len();
+        let capacity = self
+            .grown_capacity(len)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+            .unwrap_or_else(|| self.capacity());
+
+        self.context = context;
+        self.len = len;
+        self.restore_progress = None;
+        self.sliced_upload = None;
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.clear();
+            shadow.extend_from_slice(data.borrow());
+        }
+
+        self.reallocate(capacity, false);
+        self.submit_upload(0..len, data);
+        self.poisoned = false;
+        self.finish_operation(UpdateRecord::Update { len });
+    }
+
+    /// Returns a view on the data in the buffer.
+    ///
+    /// The view covers the first [len] elements of the buffer. On a vector that has never been
+    /// updated (or that was updated with empty data) this is a valid, empty view, regardless of the
+    /// vector's capacity.
+    ///
+    /// If the vector is registered with a [MemoryBudget], marks the vector as used in the budget's
+    /// current frame.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// assert_eq!(vec.as_buffer_view().len(), 0);
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// let view = vec.as_buffer_view();
+    ///
+    /// assert_eq!(view.len(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [len]: BufferVec::len
+    pub fn as_buffer_view(&self) -> BufferView<[T]>
+    where
+        T: Copy + 'static,
+    {
+        self.touch_budget();
+
+        unsafe { self.uninit_view(0..self.len).assume_init() }
+    }
+
+    /// Returns two views on the data in the buffer, split at the given index: the first covers the
+    /// elements `0..at`, the second the elements `at..len`.
+    ///
+    /// Useful for binding parts of the data in separate draw calls. Does not submit any GPU work;
+    /// neither view extends beyond the vector's length. If `at` is `0` the first view is empty, if
+    /// `at` is equal to the length the second view is empty.
+    ///
+    /// If the vector is registered with a [MemoryBudget], marks the vector as used in the budget's
+    /// current frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the vector's length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([0; 10]);
+    ///
+    /// let (opaque, transparent) = vec.split_views(6);
+    ///
+    /// assert_eq!(opaque.len(), 6);
+    /// assert_eq!(transparent.len(), 4);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn split_views(&self, at: usize) -> (BufferView<'_, [T]>, BufferView<'_, [T]>) {
+        self.try_split_views(at)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Returns two views on the data in the buffer, split at the given index, or an error if `at`
+    /// is greater than the vector's length.
+    ///
+    /// See [split_views] for details.
+    ///
+    /// [split_views]: BufferVec::split_views
+    pub fn try_split_views(&self, at: usize) -> Result<SplitViews<'_, T>, BufferVecError> {
+        checked_range(0, at, self.len)?;
+
+        self.touch_budget();
+
+        let head = self.uninit_view(0..at);
+        let tail = self.uninit_view(at..self.len);
+
+        Ok(unsafe { (head.assume_init(), tail.assume_init()) })
+    }
+
+    /// Returns an iterator over consecutive views of `chunk_len` elements of the data in the
+    /// buffer; the last view may be shorter.
+    ///
+    /// Useful for drawing a large buffer in batches, e.g. to keep each draw call under a vertex
+    /// count limit. The views never extend beyond the vector's length. See also [chunk_count].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([0; 10]);
+    ///
+    /// let lengths: Vec<usize> = vec.view_chunks(4).map(|view| view.len()).collect();
+    ///
+    /// assert_eq!(lengths, vec![4, 4, 2]);
+    /// assert_eq!(vec.chunk_count(4), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [chunk_count]: BufferVec::chunk_count
+    pub fn view_chunks(&self, chunk_len: usize) -> impl Iterator<Item = BufferView<[T]>> + '_ {
+        self.touch_budget();
+
+        chunk_ranges(self.len, chunk_len)
+            .map(move |range| unsafe { self.uninit_view(range).assume_init() })
+    }
+
+    /// The number of views [view_chunks] yields for the given `chunk_len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is `0`.
+    ///
+    /// [view_chunks]: BufferVec::view_chunks
+    pub fn chunk_count(&self, chunk_len: usize) -> usize {
+        assert!(chunk_len != 0, "Chunk length must not be zero.");
+
+        div_ceil(self.len, chunk_len)
+    }
+
+    /// Returns a view on the part of the buffer beyond the vector's length, which may not have
+    /// been initialized.
+    ///
+    /// Intended as an output target for GPU passes that produce new elements, such as transform
+    /// feedback. After such a pass, use [set_len] to include the new elements in the vector.
+    ///
+    /// [set_len]: BufferVec::set_len
+    pub fn spare_capacity(&self) -> BufferView<[MaybeUninit<T>]> {
+        self.uninit_view(self.len..self.capacity())
+    }
+
+    /// Sets the length of the vector to `new_len` without uploading any data.
+    ///
+    /// If the vector is resilient, shortening the vector truncates the shadow copy (see
+    /// [set_resilient]).
+    ///
+    /// # Safety
+    ///
+    /// The elements in the range `0..new_len` must have been initialized on the GPU, e.g. by a
+    /// pass that writes into the [spare_capacity].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` exceeds the vector's capacity, or if the vector is resilient and
+    /// `new_len` exceeds the current length: the shadow copy cannot include data that was
+    /// produced on the GPU.
+    ///
+    /// [set_resilient]: BufferVec::set_resilient
+    /// [spare_capacity]: BufferVec::spare_capacity
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        assert!(
+            new_len <= self.capacity(),
+            "New length {} exceeds the capacity {} of the buffer vec.",
+            new_len,
+            self.capacity()
+        );
+
+        if let Some(shadow) = &mut self.shadow {
+            assert!(
+                new_len <= self.len,
+                "Cannot extend the length of a resilient buffer vec with `set_len`."
+            );
+
+            shadow.truncate(new_len);
+        }
+
+        self.len = new_len;
+        self.initialized_len = self.initialized_len.max(new_len);
+        self.finish_replace();
+    }
+
+    /// Returns a view on the vector's entire capacity, including the elements beyond the vector's
+    /// length.
+    ///
+    /// Intended for pipelines that bind a buffer's entire capacity, e.g. because the number of
+    /// elements to process is supplied separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if part of the buffer's capacity may not have been initialized. The entire capacity
+    /// is guaranteed to be initialized if a fill value was set with [set_fill_on_grow], or if the
+    /// vector's length has been equal to its capacity at some point since the current buffer was
+    /// allocated.
+    ///
+    /// [set_fill_on_grow]: BufferVec::set_fill_on_grow
+    pub fn as_capacity_view(&self) -> BufferView<[T]> {
+        if self.initialized_len < self.capacity() {
+            panic!(
+                "Cannot view the capacity of a buffer vec that may not be initialized; set a fill \
+                 value with `set_fill_on_grow`."
+            );
+        }
+
+        self.touch_budget();
+
+        unsafe { self.uninit_view(0..self.capacity()).assume_init() }
+    }
+}
+
+impl<Rc, T> BufferVec<Rc, T>
+where
+    Rc: RenderingContext + Clone,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Splits the vector into two at the given index, returning a new vector that holds the
+    /// elements `at..len`; this vector keeps the elements `0..at`.
+    ///
+    /// The new vector's buffer holds exactly the split-off elements, which are copied on the GPU.
+    /// The new vector uses the same usage hint, [growth policy](BufferVec::set_growth_policy),
+    /// capacity alignment, maximum capacity, maximum buffer size and fill value as this vector, and
+    /// is resilient if this vector is. It does not share this vector's label, shrink policy (which
+    /// may be stateful) or [MemoryBudget]. The capacity of this vector does not change. If `at` is
+    /// equal to the length, returns an empty vector without submitting any GPU work.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the vector's length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+    /// use web_glitz_buffer_vec::BufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// vec.update([1, 2, 3, 4, 5]);
+    ///
+    /// let tail = vec.split_off(2);
+    ///
+    /// assert_eq!(vec.len(), 2);
+    /// assert_eq!(tail.len(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn split_off(&mut self, at: usize) -> BufferVec<Rc, T> {
+        if at > self.len {
+            panic!(
+                "Split index {} is out of bounds for a buffer vec of length {}.",
+                at, self.len
+            );
+        }
+
+        let tail_len = self.len - at;
+        let mut tail =
+            BufferVec::with_capacity(self.context.clone(), self.buffer.usage_hint(), tail_len);
+
+        tail.max_capacity = self.max_capacity;
+        tail.fill_on_grow = self.fill_on_grow;
+        tail.growth = self.growth.clone();
+        tail.capacity_alignment = self.capacity_alignment;
+        tail.max_buffer_size = self.max_buffer_size;
+        tail.shadow = self.shadow.as_mut().map(|shadow| shadow.split_off(at));
+
+        if tail_len > 0 {
+            let copy_task = tail
+                .buffer
+                .copy_from_command(self.uninit_view(at..self.len));
+
+            self.context.submit(copy_task);
+        }
+
+        tail.len = tail_len;
+        tail.initialized_len = tail_len;
+        self.len = at;
+        self.finish_operation(UpdateRecord::Remove { count: tail_len });
+
+        tail
+    }
+}
+
+#[cfg(feature = "half")]
+impl<Rc> BufferVec<Rc, half::f16>
+where
+    Rc: RenderingContext,
+{
+    /// Replaces the data in the buffer with the given `data`, converted to half precision,
+    /// resizing the buffer if necessary.
+    ///
+    /// Halves the upload bandwidth for attribute data that does not need full precision, such as
+    /// colors or texture coordinates. Conversion follows [f16::from_f32](half::f16::from_f32):
+    /// values are rounded to the nearest representable value, values too large in magnitude become
+    /// positive or negative infinity, values too small in magnitude become zero or subnormal, and
+    /// NaN remains NaN. See [update_converted] for how the converted data is staged.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [update].
+    ///
+    /// [update]: BufferVec::update
+    /// [update_converted]: BufferVec::update_converted
+    pub fn update_from_f32(&mut self, data: &[f32]) -> bool {
+        self.update_converted(data, |value| half::f16::from_f32(*value))
+    }
+
+    /// Replaces the data in the buffer with the given `data`, converted to half precision,
+    /// resizing the buffer if necessary, or returns an error if a new buffer could not be
+    /// allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [update_from_f32] for details.
+    ///
+    /// [update_from_f32]: BufferVec::update_from_f32
+    pub fn try_update_from_f32(&mut self, data: &[f32]) -> Result<bool, BufferVecError> {
+        self.try_update_converted(data, |value| half::f16::from_f32(*value))
+    }
+}
+
+impl<Rc, T> fmt::Debug for BufferVec<Rc, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufferVec")
+            .field("label", &self.label)
+            .field("len", &self.len)
+            .field("capacity", &self.buffer.len())
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// Returns a command that uploads the given `data` to the `view`.
+fn upload_command<T, D>(
+    view: BufferView<'_, [MaybeUninit<T>]>,
+    data: D,
+) -> impl GpuTask<Connection, Output = ()>
+where
+    T: Copy + 'static,
+    D: Borrow<[T]> + Send + Sync + 'static,
+{
+    unsafe {
+        // Note: the view data range is not actually guaranteed to be initialized, but we're only
+        // writing, not reading.
+        view.assume_init().upload_command(data)
     }
 }
@@ -0,0 +1,64 @@
+use web_glitz::buffer::UsageHint;
+
+/// A snapshot of the state of a buffer vector, for display in diagnostics overlays.
+///
+/// Obtained with `debug_info` on the vector types, or for every vector in a [MemoryRegistry] with
+/// [MemoryRegistry::iter_debug_info]. Implements `serde::Serialize` with the `serde` feature; the
+/// usage hint is serialized as its name, e.g. `"StaticDraw"`.
+///
+/// [MemoryRegistry]: crate::MemoryRegistry
+/// [MemoryRegistry::iter_debug_info]: crate::MemoryRegistry::iter_debug_info
+#[derive(Clone, Debug)]
+pub struct DebugInfo {
+    /// The label of the vector, if it has one.
+    pub label: Option<String>,
+
+    /// The name of the element type of the vector, as returned by [std::any::type_name].
+    pub element_type: &'static str,
+
+    /// The number of elements in the vector.
+    pub len: usize,
+
+    /// The number of elements the vector can hold without allocating a new buffer.
+    pub capacity: usize,
+
+    /// The size in bytes of the vector's buffer.
+    pub bytes: usize,
+
+    /// The usage hint of the vector's buffer.
+    pub usage: UsageHint,
+
+    /// The generation of the vector, which is incremented every time it allocates a new buffer.
+    pub generation: u64,
+
+    /// The number of times the vector allocated a new buffer since its statistics were last reset.
+    pub reallocations: u64,
+
+    /// The frame in which the vector was last updated, if it has a frame clock and was updated
+    /// since the clock was set.
+    pub last_updated_frame: Option<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DebugInfo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("DebugInfo", 9)?;
+
+        state.serialize_field("label", &self.label)?;
+        state.serialize_field("element_type", self.element_type)?;
+        state.serialize_field("len", &self.len)?;
+        state.serialize_field("capacity", &self.capacity)?;
+        state.serialize_field("bytes", &self.bytes)?;
+        state.serialize_field("usage", &format!("{:?}", self.usage))?;
+        state.serialize_field("generation", &self.generation)?;
+        state.serialize_field("reallocations", &self.reallocations)?;
+        state.serialize_field("last_updated_frame", &self.last_updated_frame)?;
+
+        state.end()
+    }
+}
@@ -0,0 +1,160 @@
+use std::borrow::Borrow;
+use std::mem;
+
+use web_glitz::buffer::{BufferView, UsageHint};
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::BufferVec;
+
+/// A pair of [BufferVec]s, a front and a back vector, where updates go to the back vector and
+/// [swap] exchanges the two.
+///
+/// Intended for data that is rendered from one buffer while the next version is written to the
+/// other, or for data that is compared with its previous version, such as previous-frame positions
+/// for motion blur: after an update and a [swap], the front holds the new data and the back holds
+/// the previous data. Both sides may be bound in the same render pass, see [views]. The two sides
+/// may hold a different number of elements, see [front_len] and [back_len].
+///
+/// # Views, swapping and generations
+///
+/// [swap] only exchanges which vector is the front and which is the back; it never allocates a new
+/// buffer or submits GPU work. A view obtained before a swap keeps referring to the same buffer
+/// and remains valid for a task that was already submitted: WebGL executes commands in submission
+/// order, so a task submitted before a subsequent update of that buffer reads the data from before
+/// the update. A view is only invalidated when its buffer is reallocated, which is reflected by the
+/// [generation](BufferVec::generation) of the side it was taken from; the generations swap along
+/// with the sides.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use web_glitz_buffer_vec::DoubleBufferedVec;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut positions = DoubleBufferedVec::new(context, UsageHint::StreamDraw);
+///
+/// positions.update([1, 2, 3]);
+/// positions.swap();
+/// positions.update([4, 5, 6, 7]);
+/// positions.swap();
+///
+/// let (current, previous) = positions.views();
+///
+/// assert_eq!(current.len(), 4);
+/// assert_eq!(previous.len(), 3);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [swap]: DoubleBufferedVec::swap
+/// [views]: DoubleBufferedVec::views
+/// [front_len]: DoubleBufferedVec::front_len
+/// [back_len]: DoubleBufferedVec::back_len
+pub struct DoubleBufferedVec<Rc, T> {
+    front: BufferVec<Rc, T>,
+    back: BufferVec<Rc, T>,
+}
+
+impl<Rc, T> DoubleBufferedVec<Rc, T>
+where
+    Rc: RenderingContext + Clone,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Creates a new double-buffered vector with 0 capacity on both sides for the given
+    /// [RenderingContext].
+    ///
+    /// See [BufferVec::new] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        DoubleBufferedVec {
+            front: BufferVec::new(context.clone(), usage),
+            back: BufferVec::new(context, usage),
+        }
+    }
+}
+
+impl<Rc, T> DoubleBufferedVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Creates a new double-buffered vector from the given `front` and `back` vectors.
+    pub fn from_pair(front: BufferVec<Rc, T>, back: BufferVec<Rc, T>) -> Self {
+        DoubleBufferedVec { front, back }
+    }
+
+    /// Replaces the data in the back vector with the given `data`.
+    ///
+    /// See [BufferVec::update] for details.
+    pub fn update<D>(&mut self, data: D) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.back.update(data)
+    }
+
+    /// Replaces the data in the back vector with the given `data`, or returns an error if a new
+    /// buffer could not be allocated.
+    ///
+    /// See [BufferVec::try_update] for details.
+    pub fn try_update<D>(&mut self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.back.try_update(data)
+    }
+
+    /// Exchanges the front and back vectors.
+    ///
+    /// Does not allocate or submit any GPU work; see the
+    /// [type documentation](DoubleBufferedVec) for how this affects views.
+    pub fn swap(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// The front vector.
+    pub fn front(&self) -> &BufferVec<Rc, T> {
+        &self.front
+    }
+
+    /// The back vector.
+    pub fn back(&self) -> &BufferVec<Rc, T> {
+        &self.back
+    }
+
+    /// The back vector, for modification with any of the [BufferVec] methods.
+    pub fn back_mut(&mut self) -> &mut BufferVec<Rc, T> {
+        &mut self.back
+    }
+
+    /// The number of elements in the front vector.
+    pub fn front_len(&self) -> usize {
+        self.front.len()
+    }
+
+    /// The number of elements in the back vector.
+    pub fn back_len(&self) -> usize {
+        self.back.len()
+    }
+
+    /// Returns views on the data in the front and back vectors, in that order.
+    ///
+    /// Each view covers the length of its own side, see [front_len] and [back_len].
+    ///
+    /// [front_len]: DoubleBufferedVec::front_len
+    /// [back_len]: DoubleBufferedVec::back_len
+    pub fn views(&self) -> (BufferView<'_, [T]>, BufferView<'_, [T]>) {
+        (self.front.as_buffer_view(), self.back.as_buffer_view())
+    }
+
+    /// Consumes the double-buffered vector, returning the front and back vectors, in that order.
+    pub fn into_inner(self) -> (BufferVec<Rc, T>, BufferVec<Rc, T>) {
+        (self.front, self.back)
+    }
+}
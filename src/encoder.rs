@@ -0,0 +1,90 @@
+/// Encodes a single element of source data into the packed format it is uploaded in, see
+/// [BufferVec::update_encoded].
+///
+/// Implemented for any `Fn(&Src) -> Dst` closure or function, as well as for the stock encoders
+/// [Unorm8x4] and [Snorm16x2]. How the packed format is decoded on the GPU (the vertex attribute
+/// format it is bound with) is up to the user.
+///
+/// [BufferVec::update_encoded]: crate::BufferVec::update_encoded
+pub trait VertexEncoder<Src, Dst> {
+    /// Encodes the `src` element.
+    fn encode(&self, src: &Src) -> Dst;
+}
+
+impl<F, Src, Dst> VertexEncoder<Src, Dst> for F
+where
+    F: Fn(&Src) -> Dst,
+{
+    fn encode(&self, src: &Src) -> Dst {
+        self(src)
+    }
+}
+
+/// Encodes four `f32` components, such as an RGBA color, as four unsigned normalized bytes.
+///
+/// Each component is clamped to `0.0..=1.0` and mapped to `0..=255`, rounding to the nearest
+/// value. NaN encodes as `0`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Unorm8x4;
+
+impl VertexEncoder<[f32; 4], [u8; 4]> for Unorm8x4 {
+    fn encode(&self, src: &[f32; 4]) -> [u8; 4] {
+        let mut dst = [0; 4];
+
+        for (dst, src) in dst.iter_mut().zip(src.iter()) {
+            // Float to integer casts saturate and map NaN to 0.
+            *dst = (src.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+
+        dst
+    }
+}
+
+/// Encodes two `f32` components, such as an octahedron-encoded normal, as two signed normalized
+/// 16-bit integers.
+///
+/// Each component is clamped to `-1.0..=1.0` and mapped to `-32767..=32767`, rounding to the
+/// nearest value. NaN encodes as `0`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Snorm16x2;
+
+impl VertexEncoder<[f32; 2], [i16; 2]> for Snorm16x2 {
+    fn encode(&self, src: &[f32; 2]) -> [i16; 2] {
+        let mut dst = [0; 2];
+
+        for (dst, src) in dst.iter_mut().zip(src.iter()) {
+            // Float to integer casts saturate and map NaN to 0.
+            *dst = (src.clamp(-1.0, 1.0) * 32767.0).round() as i16;
+        }
+
+        dst
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unorm8x4() {
+        assert_eq!(Unorm8x4.encode(&[0.0, 0.5, 1.0, 0.2]), [0, 128, 255, 51]);
+        assert_eq!(
+            Unorm8x4.encode(&[-1.0, 2.0, f32::NAN, f32::INFINITY]),
+            [0, 255, 0, 255]
+        );
+    }
+
+    #[test]
+    fn test_snorm16x2() {
+        assert_eq!(Snorm16x2.encode(&[0.0, 1.0]), [0, 32767]);
+        assert_eq!(Snorm16x2.encode(&[-1.0, 0.5]), [-32767, 16384]);
+        assert_eq!(Snorm16x2.encode(&[-2.0, f32::NAN]), [-32767, 0]);
+    }
+
+    #[test]
+    fn test_closure_encoder() {
+        let encoder = |src: &f32| *src as u32;
+
+        assert_eq!(encoder.encode(&3.0), 3);
+    }
+}
@@ -0,0 +1,186 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error returned by the fallible operations on the buffer vector types.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum BufferVecError {
+    /// Variant returned when the size in bytes of the requested capacity does not fit in a
+    /// `usize`.
+    CapacityOverflow(CapacityError),
+
+    /// Variant returned when the requested capacity exceeds the maximum capacity that applies to
+    /// the vector.
+    CapacityLimitExceeded {
+        /// The requested capacity in elements.
+        requested: usize,
+
+        /// The maximum capacity in elements.
+        limit: usize,
+    },
+
+    /// Variant returned when the size in bytes of the requested capacity exceeds the maximum buffer
+    /// size that applies to the vector.
+    BufferSizeLimitExceeded {
+        /// The size in bytes of the requested capacity.
+        requested_bytes: usize,
+
+        /// The maximum buffer size in bytes.
+        limit_bytes: usize,
+    },
+
+    /// Variant returned when an allocation was refused before it was submitted to the GPU.
+    AllocationFailed {
+        /// The size in bytes of the allocation that was refused.
+        requested_bytes: usize,
+    },
+
+    /// Variant returned when an index value does not fit in the target index type.
+    IndexNarrowing {
+        /// The position of the index in the index data.
+        position: usize,
+
+        /// The index value.
+        value: u64,
+
+        /// The largest value the target index type can represent.
+        max: u64,
+    },
+
+    /// Variant returned when a range of elements is not valid for a vector.
+    InvalidRange {
+        /// The start of the range.
+        start: usize,
+
+        /// The end of the range.
+        end: usize,
+
+        /// The length of the vector at the time of the operation.
+        len: usize,
+    },
+
+    /// Variant returned when rebasing an index by a delta results in a value that the index type
+    /// cannot represent.
+    IndexRebaseOutOfRange {
+        /// The position of the index in the vector.
+        position: usize,
+
+        /// The index value before rebasing.
+        value: u64,
+
+        /// The delta the index was rebased by.
+        delta: i64,
+    },
+
+    /// Variant returned when the offset or count of a range of indices does not fit in the `u32`
+    /// parameters of a draw call.
+    DrawRangeTooLarge {
+        /// The start of the range.
+        start: usize,
+
+        /// The end of the range.
+        end: usize,
+    },
+
+    /// Variant returned when an operation needs the CPU-side copy of a vector that does not keep
+    /// one (see [IndexBufferVec::set_resilient](crate::IndexBufferVec::set_resilient)).
+    NotResilient,
+}
+
+impl fmt::Display for BufferVecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BufferVecError::CapacityOverflow(err) => err.fmt(f),
+            BufferVecError::CapacityLimitExceeded { requested, limit } => write!(
+                f,
+                "requested capacity of {} elements exceeds the limit of {} elements",
+                requested, limit
+            ),
+            BufferVecError::BufferSizeLimitExceeded {
+                requested_bytes,
+                limit_bytes,
+            } => write!(
+                f,
+                "requested buffer size of {} bytes exceeds the limit of {} bytes",
+                requested_bytes, limit_bytes
+            ),
+            BufferVecError::AllocationFailed { requested_bytes } => {
+                write!(
+                    f,
+                    "failed to allocate a buffer of {} bytes",
+                    requested_bytes
+                )
+            }
+            BufferVecError::IndexNarrowing {
+                position,
+                value,
+                max,
+            } => write!(
+                f,
+                "index {} at position {} exceeds the maximum value of {} for the index type",
+                value, position, max
+            ),
+            BufferVecError::InvalidRange { start, end, len } => write!(
+                f,
+                "range {}..{} is not valid for a vector of length {}",
+                start, end, len
+            ),
+            BufferVecError::IndexRebaseOutOfRange {
+                position,
+                value,
+                delta,
+            } => write!(
+                f,
+                "rebasing index {} at position {} by {} results in a value outside the range of \
+                 the index type",
+                value, position, delta
+            ),
+            BufferVecError::DrawRangeTooLarge { start, end } => write!(
+                f,
+                "range {}..{} does not fit in the parameters of a draw call",
+                start, end
+            ),
+            BufferVecError::NotResilient => {
+                write!(f, "the vector does not keep a CPU-side copy of its data")
+            }
+        }
+    }
+}
+
+impl Error for BufferVecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BufferVecError::CapacityOverflow(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CapacityError> for BufferVecError {
+    fn from(err: CapacityError) -> Self {
+        BufferVecError::CapacityOverflow(err)
+    }
+}
+
+/// Error returned when the size in bytes of a buffer of `len` elements of `element_size` bytes
+/// each does not fit in a `usize`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CapacityError {
+    /// The requested number of elements.
+    pub len: usize,
+
+    /// The size in bytes of a single element.
+    pub element_size: usize,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a buffer of {} elements of {} bytes each exceeds the maximum buffer size",
+            self.len, self.element_size
+        )
+    }
+}
+
+impl Error for CapacityError {}
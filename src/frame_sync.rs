@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::rc::Weak;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use web_glitz::runtime::RenderingContext;
+
+use crate::budget::MemoryBudget;
+use crate::upload_queue::{UploadQueue, UploadReport};
+
+/// A shared frame counter, advanced by [FrameSync::on_frame].
+///
+/// Cloning a clock produces a handle to the same counter. Vectors that are given a clock with
+/// [BufferVec::set_frame_clock] record the frame in which they were last updated, see
+/// [BufferVec::last_updated_frame].
+///
+/// [BufferVec::set_frame_clock]: crate::BufferVec::set_frame_clock
+/// [BufferVec::last_updated_frame]: crate::BufferVec::last_updated_frame
+#[derive(Clone, Default, Debug)]
+pub struct FrameClock {
+    frame: Arc<AtomicU64>,
+}
+
+impl FrameClock {
+    /// Creates a new clock at frame 0.
+    pub fn new() -> Self {
+        FrameClock::default()
+    }
+
+    /// The current frame.
+    pub fn frame(&self) -> u64 {
+        self.frame.load(Ordering::Relaxed)
+    }
+
+    /// Advances the clock by one frame.
+    pub(crate) fn advance(&self) {
+        self.frame.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Flushes registered [UploadQueue]s and advances registered [MemoryBudget]s once per frame.
+///
+/// Call [on_frame] once at the start of every frame, typically at the top of the
+/// `requestAnimationFrame` callback, before any vectors are updated or drawn for that frame. All
+/// frame-aware state is then advanced from the same place: queued uploads are submitted, the
+/// [frame](MemoryBudget::frame) of every registered budget is advanced and the shared
+/// [FrameClock] is advanced.
+///
+/// The helper only holds weak references to registered queues: registering a queue does not keep
+/// it alive, queues that have been dropped are skipped and forgotten.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use std::cell::RefCell;
+///
+/// use web_glitz_buffer_vec::{BufferVec, FrameSync, UploadQueue};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let frame_sync = FrameSync::new(context.clone());
+/// let queue = std::rc::Rc::new(RefCell::new(UploadQueue::new()));
+///
+/// frame_sync.register_queue(&queue);
+///
+/// let mut vec = BufferVec::new(context, UsageHint::StreamDraw);
+///
+/// vec.set_frame_clock(Some(frame_sync.clock()));
+/// vec.enqueue_update([1, 2, 3], &mut queue.borrow_mut());
+///
+/// // At the top of the next `requestAnimationFrame` callback:
+/// frame_sync.on_frame();
+///
+/// assert!(queue.borrow().is_empty());
+/// assert_eq!(vec.last_updated_frame(), Some(0));
+/// assert_eq!(frame_sync.frame(), 1);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [on_frame]: FrameSync::on_frame
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+pub struct FrameSync<Rc> {
+    context: Rc,
+    clock: FrameClock,
+    queues: RefCell<Vec<Weak<RefCell<UploadQueue>>>>,
+    budgets: RefCell<Vec<MemoryBudget>>,
+}
+
+impl<Rc> FrameSync<Rc>
+where
+    Rc: RenderingContext,
+{
+    /// Creates a new frame synchronization helper that submits uploads to the given `context`.
+    pub fn new(context: Rc) -> Self {
+        FrameSync {
+            context,
+            clock: FrameClock::new(),
+            queues: RefCell::new(Vec::new()),
+            budgets: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The clock advanced by [on_frame](FrameSync::on_frame).
+    pub fn clock(&self) -> &FrameClock {
+        &self.clock
+    }
+
+    /// The current frame, as counted by [on_frame](FrameSync::on_frame).
+    pub fn frame(&self) -> u64 {
+        self.clock.frame()
+    }
+
+    /// Registers the given `queue`, to be submitted on every [on_frame](FrameSync::on_frame).
+    pub fn register_queue(&self, queue: &std::rc::Rc<RefCell<UploadQueue>>) {
+        self.queues.borrow_mut().push(std::rc::Rc::downgrade(queue));
+    }
+
+    /// Registers the given `budget`, to be advanced on every [on_frame](FrameSync::on_frame).
+    ///
+    /// Once registered, [MemoryBudget::advance_frame] should no longer be called separately.
+    pub fn register_budget(&self, budget: &MemoryBudget) {
+        self.budgets.borrow_mut().push(budget.clone());
+    }
+
+    /// Submits all uploads in the registered queues that are still alive, in registration order,
+    /// then advances the registered budgets and the [clock](FrameSync::clock) by one frame.
+    ///
+    /// Returns the combined report of the submitted queues.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the registered queues is currently borrowed, or if a queued upload belongs
+    /// to a vector for a different context.
+    pub fn on_frame(&self) -> UploadReport {
+        let mut report = UploadReport::default();
+
+        self.queues.borrow_mut().retain(|queue| {
+            if let Some(queue) = queue.upgrade() {
+                let queue_report = queue.borrow_mut().submit(&self.context);
+
+                report.commands += queue_report.commands;
+                report.bytes += queue_report.bytes;
+
+                true
+            } else {
+                false
+            }
+        });
+
+        for budget in self.budgets.borrow().iter() {
+            budget.advance_frame();
+        }
+
+        self.clock.advance();
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<FrameClock>();
+    }
+
+    #[test]
+    fn test_frame_clock_shared() {
+        let clock = FrameClock::new();
+        let handle = clock.clone();
+
+        clock.advance();
+        clock.advance();
+
+        assert_eq!(handle.frame(), 2);
+    }
+}
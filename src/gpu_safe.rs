@@ -0,0 +1,115 @@
+use std::mem;
+use std::slice;
+
+/// Marker trait for element types whose values may be uploaded to a GPU buffer byte-wise with
+/// deterministic contents.
+///
+/// The `Copy + Send + Sync + 'static` bound of [BufferVec] rules out types with destructors and borrowed data,
+/// but still admits types with padding bytes, whose contents are unspecified, and types with an
+/// unspecified layout, which may not match the layout a graphics pipeline expects. Vectors created
+/// with [BufferVec::new_gpu_safe] or [BufferVec::with_capacity_gpu_safe] additionally require
+/// their element type to implement this trait.
+///
+/// Implemented for the primitive integer and floating point types, for [InstanceMatrix], for
+/// `half::f16` with the `half` feature, and for arrays of `GpuSafe` types.
+///
+/// # Safety
+///
+/// A type may only implement this trait if it:
+///
+/// - has a defined layout, such as a `#[repr(C)]` or `#[repr(transparent)]` struct, or a
+///   primitive;
+/// - has no padding bytes, including trailing padding;
+/// - only contains fields that implement `GpuSafe`, which rules out references and pointers;
+/// - accepts any bit pattern as a valid value.
+///
+/// For example:
+///
+/// ```
+/// use web_glitz_buffer_vec::GpuSafe;
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Vertex {
+///     position: [f32; 3],
+///     color: [u8; 4],
+/// }
+///
+/// unsafe impl GpuSafe for Vertex {}
+/// ```
+///
+/// [BufferVec]: crate::BufferVec
+/// [BufferVec::new_gpu_safe]: crate::BufferVec::new_gpu_safe
+/// [BufferVec::with_capacity_gpu_safe]: crate::BufferVec::with_capacity_gpu_safe
+/// [InstanceMatrix]: crate::InstanceMatrix
+pub unsafe trait GpuSafe: Copy + Send + Sync + 'static {}
+
+macro_rules! impl_gpu_safe {
+    ($($ty:ty),*) => {
+        $(unsafe impl GpuSafe for $ty {})*
+    };
+}
+
+impl_gpu_safe!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+unsafe impl<T, const N: usize> GpuSafe for [T; N] where T: GpuSafe {}
+
+unsafe impl<const LOCATION: u32> GpuSafe for crate::InstanceMatrix<LOCATION> {}
+
+#[cfg(feature = "half")]
+unsafe impl GpuSafe for half::f16 {}
+
+/// The bytes of the `elements`.
+pub(crate) fn as_bytes<T>(elements: &[T]) -> &[u8]
+where
+    T: GpuSafe,
+{
+    // SAFETY: a `GpuSafe` type has no padding bytes, so all of its bytes are initialized.
+    unsafe { slice::from_raw_parts(elements.as_ptr() as *const u8, mem::size_of_val(elements)) }
+}
+
+/// The bytes of the `elements`, for modification.
+pub(crate) fn as_bytes_mut<T>(elements: &mut [T]) -> &mut [u8]
+where
+    T: GpuSafe,
+{
+    // SAFETY: a `GpuSafe` type has no padding bytes and accepts any bit pattern.
+    unsafe {
+        slice::from_raw_parts_mut(elements.as_mut_ptr() as *mut u8, mem::size_of_val(elements))
+    }
+}
+
+/// Overwrites the field of `field_size` bytes at `field_offset` in each element of `stride` bytes
+/// in `bytes` with the consecutive fields in `fields`.
+pub(crate) fn patch_fields(
+    bytes: &mut [u8],
+    stride: usize,
+    field_offset: usize,
+    field_size: usize,
+    fields: &[u8],
+) {
+    if field_size == 0 {
+        return;
+    }
+
+    for (element, field) in bytes
+        .chunks_exact_mut(stride)
+        .zip(fields.chunks_exact(field_size))
+    {
+        element[field_offset..field_offset + field_size].copy_from_slice(field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_fields() {
+        let mut bytes = [0u8; 9];
+
+        patch_fields(&mut bytes, 3, 1, 2, &[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(bytes, [0, 1, 2, 0, 3, 4, 0, 5, 6]);
+    }
+}
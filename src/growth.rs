@@ -0,0 +1,802 @@
+//! Policies that decide by how much a buffer vector grows when it runs out of capacity, and when
+//! it gives capacity back.
+//!
+//! A vector consults its [GrowthPolicy] whenever an operation needs more capacity than the vector
+//! currently has; the default policy is [Doubling]. See `set_growth_policy` on the vector types.
+//!
+//! A vector consults its [ShrinkPolicy] on every update that replaces all of its data; the
+//! default policy is [Never]. See `set_shrink_policy` on the vector types.
+//!
+//! Custom growth policies can be validated against the invariants the vector types rely on with
+//! [check_policy].
+
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::util::div_ceil;
+
+/// Decides the new capacity of a buffer vector that needs to grow.
+pub trait GrowthPolicy {
+    /// Returns the capacity a vector with a `current` capacity should grow to in order to hold
+    /// `required` elements, or `None` if the `current` capacity suffices.
+    ///
+    /// A returned capacity must be at least `required`. It is clamped to the vector's maximum
+    /// capacity, if any, and falls back to exactly `required` if its size in bytes does not fit in
+    /// a `usize`.
+    fn grow(&self, current: usize, required: usize) -> Option<usize>;
+
+    /// Like [grow](GrowthPolicy::grow), for a vector of elements of `element_size` bytes each.
+    ///
+    /// This is the method the vector types call. The default implementation ignores the
+    /// `element_size`; policies that work in bytes rather than elements override it.
+    fn grow_sized(&self, current: usize, required: usize, element_size: usize) -> Option<usize> {
+        let _ = element_size;
+
+        self.grow(current, required)
+    }
+}
+
+/// Doubles the capacity until it suffices, starting from a capacity of 2.
+///
+/// This is the default policy: it amortizes the cost of reallocation over many small appends, at
+/// the cost of up to half of the capacity going unused.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    fn grow(&self, current: usize, required: usize) -> Option<usize> {
+        new_capacity_amortized(current, required)
+    }
+}
+
+/// Multiplies the current capacity by a factor, or grows to exactly the required capacity if that
+/// is more.
+///
+/// A factor of `1.5` wastes less memory than [Doubling], at the cost of more frequent
+/// reallocation.
+///
+/// # Example
+///
+/// ```
+/// use web_glitz_buffer_vec::growth::{GrowthPolicy, ScaleFactor};
+///
+/// let policy = ScaleFactor::new(1.5);
+///
+/// assert_eq!(policy.grow(400, 401), Some(600));
+/// assert_eq!(policy.grow(400, 700), Some(700));
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScaleFactor {
+    factor: f64,
+}
+
+impl ScaleFactor {
+    /// Creates a policy that grows by the given `factor`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `factor` is less than `1.0` or is NaN.
+    pub fn new(factor: f64) -> Self {
+        assert!(
+            factor >= 1.0,
+            "Growth factor must be at least 1.0, got {}.",
+            factor
+        );
+
+        ScaleFactor { factor }
+    }
+
+    /// The factor by which this policy grows the capacity.
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+}
+
+impl GrowthPolicy for ScaleFactor {
+    fn grow(&self, current: usize, required: usize) -> Option<usize> {
+        if current < required {
+            // Float to integer casts saturate.
+            let scaled = (current as f64 * self.factor).ceil() as usize;
+
+            Some(scaled.max(required))
+        } else {
+            None
+        }
+    }
+}
+
+/// Grows to exactly the required capacity.
+///
+/// Wastes no memory, but reallocates on every growth; suited to vectors that are replaced
+/// wholesale with data of a known size.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Exact;
+
+impl GrowthPolicy for Exact {
+    fn grow(&self, current: usize, required: usize) -> Option<usize> {
+        if current < required {
+            Some(required)
+        } else {
+            None
+        }
+    }
+}
+
+/// Grows to the required capacity rounded up to a multiple of a fixed number of elements.
+///
+/// Wastes less than one chunk of memory, while growth by less than a chunk does not reallocate.
+///
+/// # Panics
+///
+/// Growing with a chunk size of `0` panics.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Chunked(pub usize);
+
+impl GrowthPolicy for Chunked {
+    fn grow(&self, current: usize, required: usize) -> Option<usize> {
+        assert!(self.0 != 0, "Chunk size must not be zero.");
+
+        if current < required {
+            Some(align_capacity(required, self.0))
+        } else {
+            None
+        }
+    }
+}
+
+/// Grows to the smallest multiple of a number of bytes that suffices, converted back to a whole
+/// number of elements.
+///
+/// Element-count growth produces tiny allocations for small element types and huge jumps for
+/// large ones; growing in byte quanta keeps the allocation granularity the same regardless of the
+/// element type. If the quantum is not a multiple of the element size, the capacity is the number
+/// of whole elements that fit in the grown byte size.
+///
+/// [grow](GrowthPolicy::grow) treats elements as single bytes; the vector types call
+/// [grow_sized](GrowthPolicy::grow_sized), which accounts for the element size.
+///
+/// # Panics
+///
+/// Growing with a quantum of `0` bytes panics.
+///
+/// # Example
+///
+/// ```
+/// use web_glitz_buffer_vec::growth::{ByteQuantum, GrowthPolicy};
+///
+/// let policy = ByteQuantum { bytes: 64 * 1024 };
+///
+/// // 12-byte elements: 65536 bytes hold 5461 whole elements.
+/// assert_eq!(policy.grow_sized(0, 1, 12), Some(5461));
+/// assert_eq!(policy.grow_sized(5461, 5462, 12), Some(10922));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ByteQuantum {
+    /// The number of bytes the size of the buffer is a multiple of.
+    pub bytes: usize,
+}
+
+impl GrowthPolicy for ByteQuantum {
+    fn grow(&self, current: usize, required: usize) -> Option<usize> {
+        self.grow_sized(current, required, 1)
+    }
+
+    fn grow_sized(&self, current: usize, required: usize, element_size: usize) -> Option<usize> {
+        assert!(self.bytes != 0, "Byte quantum must not be zero.");
+
+        if current < required {
+            let element_size = element_size.max(1);
+
+            // If the required size in bytes overflows, fall back to the required capacity; its
+            // size in bytes is validated (and rejected) by the vector.
+            let capacity = required
+                .checked_mul(element_size)
+                .map(|bytes| align_capacity(bytes, self.bytes) / element_size)
+                .unwrap_or(required);
+
+            Some(capacity.max(required))
+        } else {
+            None
+        }
+    }
+}
+
+/// Decides whether a buffer vector shrinks when its data is replaced.
+pub trait ShrinkPolicy {
+    /// Returns the capacity a vector with the given `capacity` should shrink to now that its
+    /// data was replaced with `len` elements, or `None` to keep the current capacity.
+    ///
+    /// Called on every update that replaces all of a vector's data, including updates that make
+    /// the vector grow, in which case `capacity` is the grown capacity. A returned capacity is
+    /// raised to at least `len` and rounded up to the vector's capacity alignment; the vector only
+    /// shrinks if the result is less than `capacity`.
+    fn shrink(&mut self, capacity: usize, len: usize) -> Option<usize>;
+}
+
+/// Never shrinks.
+///
+/// This is the default policy.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Never;
+
+impl ShrinkPolicy for Never {
+    fn shrink(&mut self, _capacity: usize, _len: usize) -> Option<usize> {
+        None
+    }
+}
+
+/// Shrinks to exactly the length of the new data on every update that leaves capacity unused.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Immediate;
+
+impl ShrinkPolicy for Immediate {
+    fn shrink(&mut self, capacity: usize, len: usize) -> Option<usize> {
+        if len < capacity {
+            Some(len)
+        } else {
+            None
+        }
+    }
+}
+
+/// Shrinks to exactly the length of the new data once a number of consecutive updates have each
+/// used less than a fraction of the capacity.
+///
+/// Avoids reallocating back and forth for data whose size fluctuates, while still releasing
+/// memory after the size dropped for good.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hysteresis {
+    fraction: f64,
+    updates: usize,
+    below: usize,
+}
+
+impl Hysteresis {
+    /// Creates a policy that shrinks once `updates` consecutive updates have used less than
+    /// `fraction` of the capacity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use web_glitz_buffer_vec::growth::{Hysteresis, ShrinkPolicy};
+    ///
+    /// // Shrink once 8 consecutive updates used less than a quarter of the capacity.
+    /// let mut policy = Hysteresis::new(0.25, 8);
+    ///
+    /// for _ in 0..7 {
+    ///     assert_eq!(policy.shrink(1024, 100), None);
+    /// }
+    ///
+    /// assert_eq!(policy.shrink(1024, 100), Some(100));
+    /// ```
+    pub fn new(fraction: f64, updates: usize) -> Self {
+        Hysteresis {
+            fraction,
+            updates,
+            below: 0,
+        }
+    }
+}
+
+impl ShrinkPolicy for Hysteresis {
+    fn shrink(&mut self, capacity: usize, len: usize) -> Option<usize> {
+        if (len as f64) < capacity as f64 * self.fraction {
+            self.below += 1;
+        } else {
+            self.below = 0;
+        }
+
+        if self.below > 0 && self.below >= self.updates {
+            self.below = 0;
+
+            Some(len)
+        } else {
+            None
+        }
+    }
+}
+
+/// Checks that a [GrowthPolicy] upholds the invariants the vector types rely on, for each
+/// `(current, required, element_size)` triple in the `cases`.
+///
+/// The policy is called the way the vector types call it, through
+/// [grow_sized](GrowthPolicy::grow_sized) with the `element_size` in bytes, so that policies that
+/// work in bytes are checked as well. For each case, checks that:
+///
+/// - [grow_sized](GrowthPolicy::grow_sized) does not panic;
+/// - it returns `None` only if the `current` capacity suffices, that is, if `current >= required`;
+/// - a returned capacity is at least `required`;
+/// - the returned capacity does not decrease when `required` increases by one.
+///
+/// Returns the first violation found. Intended for use in the tests of custom policies; a panic
+/// is only caught on targets that unwind, which excludes `wasm32-unknown-unknown` by default.
+///
+/// # Example
+///
+/// ```
+/// use web_glitz_buffer_vec::growth::{check_policy, GrowthPolicy, PolicyViolation};
+///
+/// struct Halving;
+///
+/// impl GrowthPolicy for Halving {
+///     fn grow(&self, current: usize, required: usize) -> Option<usize> {
+///         if current < required {
+///             Some(required / 2)
+///         } else {
+///             None
+///         }
+///     }
+/// }
+///
+/// let cases = (0..64).flat_map(|current| (0..64).map(move |required| (current, required, 4)));
+///
+/// assert_eq!(
+///     check_policy(&Halving, cases),
+///     Err(PolicyViolation::TooSmall {
+///         current: 0,
+///         required: 1,
+///         element_size: 4,
+///         returned: 0
+///     })
+/// );
+/// ```
+pub fn check_policy<P>(
+    policy: &P,
+    cases: impl Iterator<Item = (usize, usize, usize)>,
+) -> Result<(), PolicyViolation>
+where
+    P: GrowthPolicy + ?Sized,
+{
+    let grow = |current, required, element_size| {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            policy.grow_sized(current, required, element_size)
+        }))
+        .map_err(|_| PolicyViolation::Panicked {
+            current,
+            required,
+            element_size,
+        })
+    };
+
+    for (current, required, element_size) in cases {
+        let returned = match grow(current, required, element_size)? {
+            Some(returned) => returned,
+            None if current < required => {
+                return Err(PolicyViolation::MissingGrowth {
+                    current,
+                    required,
+                    element_size,
+                })
+            }
+            None => continue,
+        };
+
+        if returned < required {
+            return Err(PolicyViolation::TooSmall {
+                current,
+                required,
+                element_size,
+                returned,
+            });
+        }
+
+        if let Some(next_required) = required.checked_add(1) {
+            if let Some(next_returned) = grow(current, next_required, element_size)? {
+                if next_returned < returned {
+                    return Err(PolicyViolation::NotMonotone {
+                        current,
+                        required,
+                        element_size,
+                        returned,
+                        next_returned,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A violation of the [GrowthPolicy] invariants, found by [check_policy].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PolicyViolation {
+    /// Variant returned when the policy panicked.
+    Panicked {
+        /// The current capacity passed to the policy.
+        current: usize,
+
+        /// The required capacity passed to the policy.
+        required: usize,
+
+        /// The element size in bytes passed to the policy.
+        element_size: usize,
+    },
+
+    /// Variant returned when the policy returned `None` even though the current capacity does not
+    /// suffice.
+    MissingGrowth {
+        /// The current capacity passed to the policy.
+        current: usize,
+
+        /// The required capacity passed to the policy.
+        required: usize,
+
+        /// The element size in bytes passed to the policy.
+        element_size: usize,
+    },
+
+    /// Variant returned when the policy returned a capacity smaller than the required capacity.
+    TooSmall {
+        /// The current capacity passed to the policy.
+        current: usize,
+
+        /// The required capacity passed to the policy.
+        required: usize,
+
+        /// The element size in bytes passed to the policy.
+        element_size: usize,
+
+        /// The capacity the policy returned.
+        returned: usize,
+    },
+
+    /// Variant returned when the policy returned a smaller capacity for a required capacity that
+    /// is one larger.
+    NotMonotone {
+        /// The current capacity passed to the policy.
+        current: usize,
+
+        /// The required capacity passed to the policy.
+        required: usize,
+
+        /// The element size in bytes passed to the policy.
+        element_size: usize,
+
+        /// The capacity the policy returned for `required`.
+        returned: usize,
+
+        /// The capacity the policy returned for `required + 1`.
+        next_returned: usize,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyViolation::Panicked {
+                current,
+                required,
+                element_size,
+            } => write!(
+                f,
+                "growth policy panicked growing from {} to {} elements of {} bytes",
+                current, required, element_size
+            ),
+            PolicyViolation::MissingGrowth {
+                current,
+                required,
+                element_size,
+            } => write!(
+                f,
+                "growth policy did not grow from {} to {} elements of {} bytes",
+                current, required, element_size
+            ),
+            PolicyViolation::TooSmall {
+                current,
+                required,
+                element_size,
+                returned,
+            } => write!(
+                f,
+                "growth policy returned a capacity of {} elements growing from {} to {} elements \
+                 of {} bytes",
+                returned, current, required, element_size
+            ),
+            PolicyViolation::NotMonotone {
+                current,
+                required,
+                element_size,
+                returned,
+                next_returned,
+            } => write!(
+                f,
+                "growth policy returned a capacity of {} elements growing from {} to {} elements \
+                 of {} bytes, but {} elements growing to {} elements",
+                returned,
+                current,
+                required,
+                element_size,
+                next_returned,
+                required + 1
+            ),
+        }
+    }
+}
+
+impl Error for PolicyViolation {}
+
+/// Rounds `capacity` up to the next multiple of `alignment`, or returns `capacity` unchanged if the
+/// rounded capacity does not fit in a `usize`.
+///
+/// # Panics
+///
+/// Panics if `alignment` is `0`.
+pub(crate) fn align_capacity(capacity: usize, alignment: usize) -> usize {
+    div_ceil(capacity, alignment)
+        .checked_mul(alignment)
+        .unwrap_or(capacity)
+}
+
+pub(crate) fn new_capacity_amortized(
+    current_capacity: usize,
+    required_capacity: usize,
+) -> Option<usize> {
+    if current_capacity < required_capacity {
+        let mut new_capacity = current_capacity;
+
+        if new_capacity == 0 {
+            new_capacity = 2;
+        }
+
+        while new_capacity < required_capacity {
+            new_capacity = new_capacity.checked_mul(2).unwrap_or(required_capacity);
+        }
+
+        Some(new_capacity)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_capacity_amortized() {
+        assert_eq!(new_capacity_amortized(0, 0), None);
+        assert_eq!(new_capacity_amortized(0, 1), Some(2));
+        assert_eq!(new_capacity_amortized(2, 2), None);
+        assert_eq!(new_capacity_amortized(2, 3), Some(4));
+        assert_eq!(new_capacity_amortized(4, 4), None);
+        assert_eq!(new_capacity_amortized(4, 5), Some(8));
+        assert_eq!(
+            new_capacity_amortized(usize::MAX / 2 + 1, usize::MAX),
+            Some(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn test_align_capacity() {
+        assert_eq!(align_capacity(0, 256), 0);
+        assert_eq!(align_capacity(255, 256), 256);
+        assert_eq!(align_capacity(256, 256), 256);
+        assert_eq!(align_capacity(257, 256), 512);
+        assert_eq!(align_capacity(7, 1), 7);
+        assert_eq!(align_capacity(usize::MAX - 1, 256), usize::MAX - 1);
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        assert_eq!(ScaleFactor::new(1.5).grow(0, 3), Some(3));
+        assert_eq!(ScaleFactor::new(1.5).grow(4, 5), Some(6));
+        assert_eq!(ScaleFactor::new(1.5).grow(4, 10), Some(10));
+        assert_eq!(ScaleFactor::new(1.5).grow(4, 4), None);
+        assert_eq!(
+            ScaleFactor::new(1.5).grow(usize::MAX - 1, usize::MAX),
+            Some(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn test_scale_factor_sequence() {
+        let policy = ScaleFactor::new(1.5);
+        let mut capacity = 2;
+        let mut capacities = vec![capacity];
+
+        for _ in 0..6 {
+            capacity = policy.grow(capacity, capacity + 1).unwrap();
+            capacities.push(capacity);
+        }
+
+        assert_eq!(capacities, vec![2, 3, 5, 8, 12, 18, 27]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Growth factor must be at least 1.0")]
+    fn test_scale_factor_below_one() {
+        ScaleFactor::new(0.9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Growth factor must be at least 1.0")]
+    fn test_scale_factor_nan() {
+        ScaleFactor::new(f64::NAN);
+    }
+
+    #[test]
+    fn test_exact() {
+        assert_eq!(Exact.grow(4, 5), Some(5));
+        assert_eq!(Exact.grow(4, 4), None);
+    }
+
+    #[test]
+    fn test_byte_quantum() {
+        let policy = ByteQuantum { bytes: 64 * 1024 };
+
+        assert_eq!(policy.grow_sized(0, 1, 1), Some(65536));
+        assert_eq!(policy.grow_sized(65536, 65537, 1), Some(131072));
+        assert_eq!(policy.grow_sized(65536, 65536, 1), None);
+
+        // 12 does not divide 65536: 5461 elements take 65532 bytes.
+        assert_eq!(policy.grow_sized(0, 1, 12), Some(5461));
+        assert_eq!(policy.grow_sized(0, 5461, 12), Some(5461));
+        assert_eq!(policy.grow_sized(5461, 5462, 12), Some(10922));
+
+        assert_eq!(policy.grow_sized(0, 1, 64), Some(1024));
+        assert_eq!(policy.grow_sized(1024, 1025, 64), Some(2048));
+
+        assert_eq!(policy.grow(0, 1), Some(65536));
+        assert_eq!(
+            policy.grow_sized(0, usize::MAX / 2, 64),
+            Some(usize::MAX / 2)
+        );
+    }
+
+    #[test]
+    fn test_immediate() {
+        assert_eq!(Immediate.shrink(8, 5), Some(5));
+        assert_eq!(Immediate.shrink(8, 8), None);
+        assert_eq!(Never.shrink(8, 0), None);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut policy = Hysteresis::new(0.5, 2);
+
+        assert_eq!(policy.shrink(16, 4), None);
+        assert_eq!(policy.shrink(16, 12), None);
+        assert_eq!(policy.shrink(16, 4), None);
+        assert_eq!(policy.shrink(16, 4), Some(4));
+        assert_eq!(policy.shrink(4, 4), None);
+    }
+
+    #[test]
+    fn test_chunked() {
+        assert_eq!(Chunked(16).grow(0, 1), Some(16));
+        assert_eq!(Chunked(16).grow(16, 17), Some(32));
+        assert_eq!(Chunked(16).grow(16, 32), Some(32));
+        assert_eq!(Chunked(16).grow(32, 20), None);
+        assert_eq!(Chunked(16).grow(0, usize::MAX), Some(usize::MAX));
+    }
+
+    fn exhaustive_cases(
+        max: usize,
+        element_size: usize,
+    ) -> impl Iterator<Item = (usize, usize, usize)> {
+        (0..max).flat_map(move |current| {
+            (0..max).map(move |required| (current, required, element_size))
+        })
+    }
+
+    #[test]
+    fn test_check_policy_built_in() {
+        let policies: Vec<Box<dyn GrowthPolicy>> = vec![
+            Box::new(Doubling),
+            Box::new(ScaleFactor::new(1.0)),
+            Box::new(ScaleFactor::new(1.5)),
+            Box::new(ScaleFactor::new(4.0)),
+            Box::new(Exact),
+            Box::new(Chunked(1)),
+            Box::new(Chunked(48)),
+            Box::new(ByteQuantum { bytes: 1 }),
+            Box::new(ByteQuantum { bytes: 4096 }),
+        ];
+
+        for policy in &policies {
+            for element_size in [1, 4, 12, 64].iter().copied() {
+                assert_eq!(
+                    check_policy(&**policy, exhaustive_cases(130, element_size)),
+                    Ok(())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_policy_violations() {
+        struct Fixed(Option<usize>);
+
+        impl GrowthPolicy for Fixed {
+            fn grow(&self, _current: usize, _required: usize) -> Option<usize> {
+                self.0
+            }
+        }
+
+        struct Sawtooth;
+
+        impl GrowthPolicy for Sawtooth {
+            fn grow(&self, current: usize, required: usize) -> Option<usize> {
+                if current < required {
+                    Some(required + required % 2 * 2)
+                } else {
+                    None
+                }
+            }
+        }
+
+        assert_eq!(
+            check_policy(&Fixed(None), exhaustive_cases(4, 1)),
+            Err(PolicyViolation::MissingGrowth {
+                current: 0,
+                required: 1,
+                element_size: 1
+            })
+        );
+        assert_eq!(
+            check_policy(&Fixed(Some(2)), exhaustive_cases(4, 1)),
+            Err(PolicyViolation::TooSmall {
+                current: 0,
+                required: 3,
+                element_size: 1,
+                returned: 2
+            })
+        );
+        assert_eq!(
+            check_policy(&Sawtooth, exhaustive_cases(4, 1)),
+            Err(PolicyViolation::NotMonotone {
+                current: 0,
+                required: 1,
+                element_size: 1,
+                returned: 3,
+                next_returned: 2
+            })
+        );
+        assert_eq!(
+            check_policy(&Chunked(0), exhaustive_cases(4, 1)),
+            Err(PolicyViolation::Panicked {
+                current: 0,
+                required: 0,
+                element_size: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_policy_calls_grow_sized() {
+        // Correct in elements, but sizes the buffer as if its capacity were in bytes.
+        struct BytesAsElements;
+
+        impl GrowthPolicy for BytesAsElements {
+            fn grow(&self, current: usize, required: usize) -> Option<usize> {
+                Exact.grow(current, required)
+            }
+
+            fn grow_sized(
+                &self,
+                current: usize,
+                required: usize,
+                element_size: usize,
+            ) -> Option<usize> {
+                self.grow(current, required)
+                    .map(|capacity| capacity / element_size)
+            }
+        }
+
+        assert_eq!(
+            check_policy(&BytesAsElements, exhaustive_cases(4, 1)),
+            Ok(())
+        );
+        assert_eq!(
+            check_policy(&BytesAsElements, exhaustive_cases(4, 4)),
+            Err(PolicyViolation::TooSmall {
+                current: 0,
+                required: 1,
+                element_size: 4,
+                returned: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use web_glitz::pipeline::graphics::IndexFormat;
+
+use crate::error::BufferVecError;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// Implemented for the integer types that may be stored in an [IndexBufferVec].
+///
+/// Provides lossless conversion of index values to and from `u64`, which allows the vector to
+/// inspect and transform index data independently of the index type.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+///
+/// [IndexBufferVec]: crate::IndexBufferVec
+pub trait IndexValue: IndexFormat + Ord + Send + Sync + sealed::Sealed {
+    /// The largest index value that can be represented by this type.
+    const MAX: u64;
+
+    /// Converts the index to a `u64`.
+    fn to_u64(self) -> u64;
+
+    /// Converts a `u64` into an index of this type, or returns `None` if the `value` is greater
+    /// than [MAX](IndexValue::MAX).
+    fn from_u64(value: u64) -> Option<Self>;
+}
+
+impl IndexValue for u8 {
+    const MAX: u64 = u8::MAX as u64;
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+
+    fn from_u64(value: u64) -> Option<Self> {
+        if value <= <Self as IndexValue>::MAX {
+            Some(value as u8)
+        } else {
+            None
+        }
+    }
+}
+
+impl IndexValue for u16 {
+    const MAX: u64 = u16::MAX as u64;
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+
+    fn from_u64(value: u64) -> Option<Self> {
+        if value <= <Self as IndexValue>::MAX {
+            Some(value as u16)
+        } else {
+            None
+        }
+    }
+}
+
+impl IndexValue for u32 {
+    const MAX: u64 = u32::MAX as u64;
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+
+    fn from_u64(value: u64) -> Option<Self> {
+        if value <= <Self as IndexValue>::MAX {
+            Some(value as u32)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the given `indices` with the signed `delta` added to each, or an error for the first
+/// index that would fall outside the range of `T`. The indices are reported at their position in
+/// the vector, where the first of the `indices` is at `offset`.
+pub(crate) fn rebased_indices<T>(
+    indices: &[T],
+    delta: i64,
+    offset: usize,
+) -> Result<Vec<T>, BufferVecError>
+where
+    T: IndexValue,
+{
+    indices
+        .iter()
+        .enumerate()
+        .map(|(i, index)| {
+            let value = index.to_u64();
+
+            // Index values are at most 32 bits wide, so they fit in an `i64`.
+            (value as i64)
+                .checked_add(delta)
+                .filter(|rebased| *rebased >= 0)
+                .and_then(|rebased| T::from_u64(rebased as u64))
+                .ok_or(BufferVecError::IndexRebaseOutOfRange {
+                    position: offset + i,
+                    value,
+                    delta,
+                })
+        })
+        .collect()
+}
+
+/// Tracks the largest index in an index vector and the number of times it occurs, so that the
+/// largest index can be kept up to date as indices are added or overwritten without scanning the
+/// whole vector.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct MaxIndex<T> {
+    max: Option<T>,
+    count: usize,
+}
+
+impl<T> MaxIndex<T>
+where
+    T: IndexValue + Copy,
+{
+    /// Tracks the largest of the given `indices`.
+    pub(crate) fn of(indices: &[T]) -> Self {
+        let mut max_index = MaxIndex::default();
+
+        max_index.add(indices);
+
+        max_index
+    }
+
+    /// The largest index, or `None` if no indices were added.
+    pub(crate) fn get(&self) -> Option<T> {
+        self.max
+    }
+
+    /// Accounts for the given `indices` being added.
+    pub(crate) fn add(&mut self, indices: &[T]) {
+        for &index in indices {
+            match self.max {
+                Some(max) if index < max => (),
+                Some(max) if index == max => self.count += 1,
+                _ => {
+                    self.max = Some(index);
+                    self.count = 1;
+                }
+            }
+        }
+    }
+
+    /// Accounts for the `old` indices being overwritten with the `new` indices.
+    ///
+    /// Returns `false` if every occurrence of the largest index was overwritten with a smaller
+    /// index, in which case the new largest index is unknown and must be found with [of].
+    ///
+    /// [of]: MaxIndex::of
+    pub(crate) fn overwrite(&mut self, old: &[T], new: &[T]) -> bool {
+        let overwritten = old.iter().filter(|index| Some(**index) == self.max).count();
+
+        self.count -= overwritten;
+        self.add(new);
+
+        self.max.is_none() || self.count > 0
+    }
+}
+
+impl<T> Default for MaxIndex<T> {
+    fn default() -> Self {
+        MaxIndex {
+            max: None,
+            count: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_index_add() {
+        let mut max_index = MaxIndex::of(&[3u16, 7, 7, 1]);
+
+        assert_eq!(max_index.get(), Some(7));
+
+        max_index.add(&[7, 2]);
+        max_index.add(&[]);
+
+        assert_eq!(max_index, MaxIndex::of(&[3, 7, 7, 1, 7, 2]));
+
+        max_index.add(&[9]);
+
+        assert_eq!(max_index.get(), Some(9));
+        assert_eq!(MaxIndex::<u8>::of(&[]).get(), None);
+    }
+
+    #[test]
+    fn test_max_index_overwrite() {
+        let mut indices = vec![3u16, 7, 7, 1];
+        let mut max_index = MaxIndex::of(&indices);
+
+        // One of two occurrences of the largest index is overwritten.
+        assert!(max_index.overwrite(&indices[1..2], &[2]));
+        indices[1] = 2;
+        assert_eq!(max_index, MaxIndex::of(&indices));
+
+        // A larger index is written.
+        assert!(max_index.overwrite(&indices[0..1], &[8]));
+        indices[0] = 8;
+        assert_eq!(max_index, MaxIndex::of(&indices));
+
+        // The largest index is overwritten with an equal index.
+        assert!(max_index.overwrite(&indices[0..1], &[8]));
+        assert_eq!(max_index, MaxIndex::of(&indices));
+
+        // The only occurrence of the largest index is overwritten with a smaller index.
+        assert!(!max_index.overwrite(&indices[0..1], &[4]));
+
+        // Overwriting nothing on an empty vector.
+        assert!(MaxIndex::<u32>::default().overwrite(&[], &[]));
+    }
+
+    #[test]
+    fn test_index_value_conversion() {
+        assert_eq!(u8::from_u64(255), Some(255u8));
+        assert_eq!(u8::from_u64(256), None);
+        assert_eq!(u16::from_u64(65535), Some(65535u16));
+        assert_eq!(u16::from_u64(65536), None);
+        assert_eq!(u32::from_u64(u32::MAX as u64 + 1), None);
+        assert_eq!(40000u16.to_u64(), 40000);
+    }
+
+    #[test]
+    fn test_rebased_indices() {
+        assert_eq!(rebased_indices(&[3u16, 4, 5], 2, 0), Ok(vec![5, 6, 7]));
+        assert_eq!(rebased_indices(&[3u16, 4, 5], -3, 0), Ok(vec![0, 1, 2]));
+        assert_eq!(
+            rebased_indices(&[3u16, 2, 5], -3, 10),
+            Err(BufferVecError::IndexRebaseOutOfRange {
+                position: 11,
+                value: 2,
+                delta: -3
+            })
+        );
+        assert_eq!(
+            rebased_indices(&[250u8, 5], 6, 0),
+            Err(BufferVecError::IndexRebaseOutOfRange {
+                position: 0,
+                value: 250,
+                delta: 6
+            })
+        );
+        assert_eq!(
+            rebased_indices(&[u32::MAX], i64::MAX, 0),
+            Err(BufferVecError::IndexRebaseOutOfRange {
+                position: 0,
+                value: u32::MAX as u64,
+                delta: i64::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn test_index_value_types() {
+        use web_glitz::pipeline::graphics::IndexType;
+
+        assert_eq!(<u8 as IndexFormat>::TYPE, IndexType::UnsignedByte);
+        assert_eq!(<u16 as IndexFormat>::TYPE, IndexType::UnsignedShort);
+        assert_eq!(<u32 as IndexFormat>::TYPE, IndexType::UnsignedInt);
+    }
+}
@@ -1,16 +1,70 @@
+use std::any::type_name;
 use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::future::{ready, Future};
+use std::marker::PhantomData;
+use std::mem;
 use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::slice;
+use std::sync::Arc;
 
 use web_glitz::buffer::UsageHint;
-use web_glitz::pipeline::graphics::{IndexBuffer, IndexBufferView, IndexFormat};
-use web_glitz::runtime::RenderingContext;
+use web_glitz::pipeline::graphics::{IndexBuffer, IndexBufferView};
+use web_glitz::runtime::{Connection, RenderingContext};
+use web_glitz::task::{sequence_iter, GpuTask};
 
-use crate::util::new_capacity_amortized;
+use crate::budget::{BudgetHandle, MemoryBudget};
+use crate::debug_info::DebugInfo;
+use crate::error::{BufferVecError, CapacityError};
+use crate::frame_sync::FrameClock;
+use crate::growth::{align_capacity, Doubling, GrowthPolicy, Never, ShrinkPolicy};
+use crate::index::{rebased_indices, IndexValue, MaxIndex};
+use crate::leak_tracking::LiveBuffer;
+use crate::maintenance::{plan_shrink, MaintenanceBudget, MaintenanceReport};
+use crate::realloc_guard::{ReallocGuard, ReallocWatchers};
+#[cfg(feature = "stats")]
+use crate::stats::CapacityHistograms;
+use crate::stats::{suggest_shrink_capacity, LengthHistory, ReallocTracker, VecStats};
+use crate::topology::{
+    flip_winding, stitch_strip, strip_to_list, triangle_edges, triangulate_fans, PolygonIndices,
+    Winding,
+};
+use crate::upload_queue::{next_source_id, Rollback, UploadQueue, UploadTicket};
+use crate::util::{
+    bug, capacity_limit, check_buffer_size, checked_byte_size, checked_draw_range,
+    checked_new_capacity, checked_range, checked_read_range, describe_vec, error_message,
+    range_bug, UploadData,
+};
+#[cfg(all(feature = "debug-validate", debug_assertions))]
+use crate::validation::assert_invariants;
+use crate::validation::{
+    check_ranges, find_out_of_range_indices, violations_result, InvariantViolation,
+};
+#[cfg(feature = "wasm-test")]
+use crate::wasm_test::check_allocation;
+
+/// A buffer that was replaced by a reallocation, together with its leak-tracking token.
+type ReplacedBuffer<T> = (IndexBuffer<MaybeUninit<T>>, LiveBuffer);
+
+/// The state of an [IndexBufferVec] from before a queued update, which the vector returns to if
+/// the update is cancelled with [IndexBufferVec::cancel_queued_update].
+struct PreviousState<T> {
+    len: usize,
+    restore_progress: Option<usize>,
+    max_index: MaxIndex<T>,
+    shadow: Option<Vec<T>>,
+    sections: HashMap<String, Range<usize>>,
+    lods: HashMap<String, Vec<Range<usize>>>,
+    buffer: Option<ReplacedBuffer<T>>,
+}
 
 /// A growable GPU buffer for data that may be used to specify vertex indices in a WebGlitz draw
 /// task.
 ///
-/// Elements must implement [IndexFormat].
+/// Elements must implement [IndexValue], which is implemented for `u8`, `u16` and `u32`.
 ///
 /// # Example
 /// ```
@@ -57,18 +111,104 @@ use crate::util::new_capacity_amortized;
 /// Here `context` is a WebGlitz [RenderingContext]. For details on indexed rendering with WebGlitz,
 /// see the [web_glitz::rendering] module documentation.
 ///
-/// [IndexFormat]: web_glitz::pipeline::graphics::vertex::IndexFormat
+/// # Index types
+///
+/// The element type determines the index type WebGL reads the indices as: `u8` indices are drawn
+/// as `UNSIGNED_BYTE`, `u16` indices as `UNSIGNED_SHORT` and `u32` indices as `UNSIGNED_INT`.
+/// `u8` indices can address at most 256 vertices, which is enough for small meshes such as
+/// markers or rounded rectangles, at half the size of `u16` indices. Note that some WebGL
+/// implementations (e.g. ANGLE on Direct3D) convert 8-bit indices to 16-bit indices internally,
+/// so the saving may only apply to the CPU-side copy.
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+/// use web_glitz_buffer_vec::IndexBufferVec;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut indices = IndexBufferVec::with_capacity(context, UsageHint::StaticDraw, 6);
+///
+/// indices.update([0u8, 1, 2, 2, 3, 0]);
+///
+/// let indices_view = indices.as_buffer_view();
+///
+/// assert_eq!(indices_view.len(), 6);
+/// # }
+/// ```
+///
+/// # CPU-side copy
+///
+/// WebGL does not allow index data to be read back or copied on the GPU. A resilient
+/// [IndexBufferVec] (see [set_resilient]) therefore keeps a CPU-side copy of its indices, which
+/// serves operations that need to read the data, such as [read_range], and operations that move
+/// the data to a new buffer, such as growing a vector that is not empty. WebGL implementations
+/// keep a CPU-side copy of index data themselves (to validate index ranges), so this roughly
+/// doubles the memory an index buffer already takes up on the CPU; vectors are therefore not
+/// resilient by default. On a vector that is not resilient, operations that need the copy fail
+/// with [BufferVecError::NotResilient] (or panic for the infallible variants), but replacing all
+/// indices with [update] always works.
+///
+/// # Context loss
+///
+/// When the WebGL context is lost, the GPU buffer backing an [IndexBufferVec] is lost with it. A
+/// resilient vector can be restored from its CPU-side copy with [recreate]; any vector can be moved
+/// to a new context with [rebind_context], given its indices. A resilient [IndexBufferVec] may be
+/// registered with a [MemoryRegistry] alongside the [BufferVec]s of a scene, so that a single call
+/// restores all of them. Until it is restored, any operation on the vector submits its commands to
+/// the old context, which discards them: no operation fails or panics because the context was
+/// lost, but neither does it have any effect on the GPU. Views obtained with [as_buffer_view] refer
+/// to the lost buffer; use [generation] to detect this.
+///
+/// # Threads
+///
+/// Like that of a [BufferVec], the bookkeeping of an [IndexBufferVec] is [Send], but WebGlitz
+/// buffers are not; see [BufferVec](crate::BufferVec#threads) for details.
+///
 /// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [set_resilient]: IndexBufferVec::set_resilient
+/// [read_range]: IndexBufferVec::read_range
+/// [update]: IndexBufferVec::update
+/// [recreate]: IndexBufferVec::recreate
+/// [rebind_context]: IndexBufferVec::rebind_context
+/// [as_buffer_view]: IndexBufferVec::as_buffer_view
+/// [generation]: IndexBufferVec::generation
+/// [MemoryRegistry]: crate::MemoryRegistry
+/// [BufferVec]: crate::BufferVec
 pub struct IndexBufferVec<Rc, T> {
     context: Rc,
     len: usize,
     buffer: IndexBuffer<MaybeUninit<T>>,
+    live_buffer: LiveBuffer,
+    max_capacity: Option<usize>,
+    max_index: MaxIndex<T>,
+    shadow: Option<Vec<T>>,
+    generation: u64,
+    stats: VecStats,
+    orphaning: bool,
+    sections: HashMap<String, Range<usize>>,
+    lods: HashMap<String, Vec<Range<usize>>>,
+    source_id: u64,
+    realloc_tracker: ReallocTracker,
+    realloc_watchers: ReallocWatchers,
+    length_history: LengthHistory,
+    #[cfg(feature = "stats")]
+    histograms: CapacityHistograms,
+    label: Option<String>,
+    budget: Option<BudgetHandle>,
+    frame_clock: Option<FrameClock>,
+    updated_frame: Option<u64>,
+    restore_progress: Option<usize>,
+    growth: Arc<dyn GrowthPolicy + Send + Sync>,
+    capacity_alignment: usize,
+    shrink: Box<dyn ShrinkPolicy + Send>,
+    max_buffer_size: Option<usize>,
+    modifications: u64,
 }
 
 impl<Rc, T> IndexBufferVec<Rc, T>
 where
     Rc: RenderingContext,
-    T: IndexFormat + 'static,
+    T: IndexValue + 'static,
 {
     /// Creates a new buffer-backed vector with 0 capacity for the given [RenderingContext].
     ///
@@ -96,11 +236,7 @@ where
     pub fn new(context: Rc, usage: UsageHint) -> Self {
         let buffer = context.create_index_buffer_uninit(0, usage);
 
-        IndexBufferVec {
-            context,
-            len: 0,
-            buffer,
-        }
+        Self::from_parts(context, buffer, 0, 0)
     }
 
     /// Creates a new buffer-backed vector with the specified `capacity` for the given
@@ -125,15 +261,91 @@ where
     ///
     /// Here context is a [RenderingContext].
     ///
+    /// # Panics
+    ///
+    /// Panics if the size in bytes of `capacity` elements does not fit in a `usize`.
+    ///
     /// [RenderingContext]: web_glitz::runtime::RenderingContext
     /// [UsageHint]: web_glitz::buffer::UsageHint
     pub fn with_capacity(context: Rc, usage: UsageHint, capacity: usize) -> Self {
+        Self::try_with_capacity(context, usage, capacity).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a new buffer-backed vector with the specified `capacity` for the given
+    /// [RenderingContext], or returns an error if the buffer cannot be allocated.
+    ///
+    /// See [with_capacity] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let indices = IndexBufferVec::<_, u16>::try_with_capacity(context, UsageHint::StaticDraw, 10);
+    ///
+    /// assert!(indices.is_ok());
+    /// # }
+    /// ```
+    ///
+    /// Here context is a [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [with_capacity]: IndexBufferVec::with_capacity
+    pub fn try_with_capacity(
+        context: Rc,
+        usage: UsageHint,
+        capacity: usize,
+    ) -> Result<Self, BufferVecError> {
+        let bytes = checked_byte_size::<T>(capacity)?;
+
+        #[cfg(feature = "wasm-test")]
+        check_allocation(bytes)?;
+
         let buffer = context.create_index_buffer_uninit(capacity, usage);
 
+        Ok(Self::from_parts(context, buffer, bytes, capacity))
+    }
+
+    /// Creates a vector for the given `context` that is backed by `buffer`, which holds `capacity`
+    /// elements and occupies `bytes` bytes.
+    fn from_parts(
+        context: Rc,
+        buffer: IndexBuffer<MaybeUninit<T>>,
+        bytes: usize,
+        capacity: usize,
+    ) -> Self {
         IndexBufferVec {
             context,
             len: 0,
             buffer,
+            live_buffer: LiveBuffer::new(bytes),
+            max_capacity: None,
+            max_index: MaxIndex::default(),
+            shadow: None,
+            generation: 0,
+            stats: VecStats::new(capacity),
+            orphaning: false,
+            sections: HashMap::new(),
+            lods: HashMap::new(),
+            source_id: next_source_id(),
+            realloc_tracker: ReallocTracker::new(),
+            realloc_watchers: ReallocWatchers::new(),
+            length_history: LengthHistory::new(),
+            #[cfg(feature = "stats")]
+            histograms: CapacityHistograms::default(),
+            label: None,
+            budget: None,
+            frame_clock: None,
+            updated_frame: None,
+            restore_progress: None,
+            growth: Arc::new(Doubling),
+            capacity_alignment: 1,
+            shrink: Box::new(Never),
+            max_buffer_size: None,
+            modifications: 0,
         }
     }
 
@@ -141,6 +353,11 @@ where
     ///
     /// Returns `true` if a new buffer was allocated, `false` otherwise.
     ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to grow and the size in bytes of the `data` does not fit in a
+    /// `usize`.
+    ///
     /// # Guarantees
     ///
     /// Any task submitted from the same thread that called `update` after the update will see the
@@ -168,45 +385,341 @@ where
     where
         D: Borrow<[T]> + Send + Sync + 'static,
     {
-        let IndexBufferVec {
-            context,
-            len,
-            buffer,
-        } = self;
+        self.try_update(data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the data in the buffer with the given `data`, resizing the buffer if necessary, or
+    /// returns an error if the buffer needed to be resized and a new buffer could not be allocated.
+    ///
+    /// Returns `Ok(true)` if a new buffer was allocated, `Ok(false)` otherwise. If an error is
+    /// returned, the vector is left unmodified.
+    ///
+    /// See [update] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_max_capacity(Some(2));
+    ///
+    /// assert!(indices.try_update([1u16, 2u16, 3u16]).is_err());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: IndexBufferVec::update
+    pub fn try_update<D>(&mut self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "update",
+            label = self.label.as_deref().unwrap_or_else(|| type_name::<Self>()),
+            len = data.borrow().len() as u64,
+            bytes = (data.borrow().len() * mem::size_of::<T>()) as u64,
+            reallocated = tracing::field::Empty
+        )
+        .entered();
 
-        *len = data.borrow().len();
+        let reallocated = self.prepare_update(data.borrow())?.is_some();
 
-        let current_capacity = buffer.len();
+        #[cfg(feature = "tracing")]
+        span.record("reallocated", &reallocated);
 
-        let reallocated = if let Some(new_capacity) = new_capacity_amortized(current_capacity, *len) {
-            *buffer = context
-                .create_index_buffer_uninit(new_capacity, buffer.usage_hint())
-                .into();
+        self.submit_upload(0..self.len, data);
+        self.debug_check();
 
-            true
-        } else {
-            false
+        Ok(reallocated)
+    }
+
+    /// Replaces the indices in the buffer with the given `data` like [update], but enqueues the
+    /// upload in the given `queue` rather than submitting it.
+    ///
+    /// The length and capacity of the vector change immediately; the new indices are uploaded
+    /// when the `queue` is submitted. Replaces any update for this vector that is still in the
+    /// `queue`. See [UploadQueue] for details.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// [update]: IndexBufferVec::update
+    pub fn enqueue_update<D>(&mut self, data: D, queue: &mut UploadQueue) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.try_enqueue_update(data, queue)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the indices in the buffer with the given `data` like [try_update], but enqueues
+    /// the upload in the given `queue` rather than submitting it.
+    ///
+    /// See [enqueue_update] for details.
+    ///
+    /// [try_update]: IndexBufferVec::try_update
+    /// [enqueue_update]: IndexBufferVec::enqueue_update
+    pub fn try_enqueue_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.enqueue_full_update(data, queue)
+            .map(|(reallocated, _)| reallocated)
+    }
+
+    /// Replaces the indices in the buffer with the given `data` like [enqueue_update], but returns
+    /// an [UploadTicket] that may be used to cancel the upload before the `queue` is submitted.
+    ///
+    /// Intended for data that may become obsolete before the `queue` is submitted, such as a
+    /// streamed chunk that is unloaded again. Pass the ticket to [cancel_queued_update] to remove
+    /// the upload from the `queue` and restore the vector's previous length, capacity and indices.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// [update]: IndexBufferVec::update
+    /// [enqueue_update]: IndexBufferVec::enqueue_update
+    /// [cancel_queued_update]: IndexBufferVec::cancel_queued_update
+    pub fn enqueue_cancellable_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> UploadTicket
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.try_enqueue_cancellable_update(data, queue)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Replaces the indices in the buffer with the given `data` like [try_enqueue_update], but
+    /// returns an [UploadTicket] that may be used to cancel the upload before the `queue` is
+    /// submitted.
+    ///
+    /// See [enqueue_cancellable_update] for details.
+    ///
+    /// [try_enqueue_update]: IndexBufferVec::try_enqueue_update
+    /// [enqueue_cancellable_update]: IndexBufferVec::enqueue_cancellable_update
+    pub fn try_enqueue_cancellable_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> Result<UploadTicket, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.enqueue_full_update(data, queue)
+            .map(|(_, ticket)| ticket)
+    }
+
+    /// Removes the upload identified by the `ticket` from its [UploadQueue] and restores the
+    /// vector to its state from before the update, including its length, capacity, buffer,
+    /// CPU-side copy and sections.
+    ///
+    /// Returns `true` if the upload was cancelled. Returns `false` and does nothing if the upload
+    /// is no longer pending (see [UploadTicket::is_pending]), or if the vector was modified after
+    /// the update was enqueued. See [BufferVec::cancel_queued_update] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `ticket` was issued for an update of a different vector.
+    ///
+    /// [BufferVec::cancel_queued_update]: crate::BufferVec::cancel_queued_update
+    pub fn cancel_queued_update(&mut self, ticket: UploadTicket) -> bool {
+        if ticket.source() != self.source_id {
+            panic!(
+                "Cannot cancel the queued update of another vector with {}.",
+                describe_vec("index buffer vec", self.label.as_deref())
+            );
+        }
+
+        let previous = match ticket.cancel(self.modifications) {
+            Some(state) => match state.downcast::<PreviousState<T>>() {
+                Ok(previous) => *previous,
+                Err(_) => bug(format_args!(
+                    "the rollback of a queued update does not belong to an index buffer vec"
+                )),
+            },
+            None => return false,
+        };
+
+        self.len = previous.len;
+        self.restore_progress = previous.restore_progress;
+        self.max_index = previous.max_index;
+        self.sections = previous.sections;
+        self.lods = previous.lods;
+
+        if let (Some(shadow), Some(previous)) = (&mut self.shadow, previous.shadow) {
+            *shadow = previous;
+        }
+
+        if let Some((buffer, live_buffer)) = previous.buffer {
+            self.buffer = buffer;
+            self.live_buffer = live_buffer;
+            self.generation += 1;
+            self.sync_budget();
+        }
+
+        self.modifications += 1;
+        self.debug_check();
+
+        true
+    }
+
+    /// Enqueues the upload of the given `data` as the new indices of the vector in the `queue`,
+    /// returning whether a new buffer was allocated and the ticket for the upload.
+    ///
+    /// The queue keeps the state of the vector from before the update, to restore it if the upload
+    /// is cancelled.
+    fn enqueue_full_update<D>(
+        &mut self,
+        data: D,
+        queue: &mut UploadQueue,
+    ) -> Result<(bool, UploadTicket), BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        let stamp = self.modifications;
+        let previous = PreviousState {
+            len: self.len,
+            restore_progress: self.restore_progress,
+            max_index: self.max_index,
+            shadow: self.shadow.as_mut().map(mem::take),
+            sections: mem::take(&mut self.sections),
+            lods: mem::take(&mut self.lods),
+            buffer: None,
         };
 
-        let view = buffer.get(0..*len).unwrap();
+        let replaced = match self.prepare_update(data.borrow()) {
+            Ok(replaced) => replaced,
+            Err(err) => {
+                if let (Some(shadow), Some(previous)) = (&mut self.shadow, previous.shadow) {
+                    *shadow = previous;
+                }
+
+                self.sections = previous.sections;
+                self.lods = previous.lods;
 
-        let upload_task = unsafe {
-            // Note: the view data range is not actually guaranteed to be initialized, but we're
-            // only writing, not reading.
-            view.assume_init().upload_command(data)
+                return Err(err);
+            }
         };
+        let reallocated = replaced.is_some();
 
-        context.submit(upload_task);
+        // See BufferVec::enqueue_full_update: an earlier update that is still queued is never
+        // uploaded, so this update can only be cancelled if the earlier one could be, and the
+        // vector was not modified in between.
+        let previous = match queue.take_rollback(self.source_id) {
+            None => Some(previous),
+            Some(Some(rollback)) if rollback.stamp == stamp => rollback
+                .state
+                .downcast::<PreviousState<T>>()
+                .ok()
+                .map(|previous| *previous),
+            Some(_) => None,
+        };
+        let previous = previous.map(|mut previous| {
+            if previous.buffer.is_none() {
+                previous.buffer = replaced;
+            }
+
+            previous
+        });
+
+        let bytes = self.len as u64 * mem::size_of::<T>() as u64;
+        let upload_task = upload_command(self.uninit_view(0..self.len), data);
+
+        self.stats.record_upload(self.len, mem::size_of::<T>());
+
+        #[cfg(feature = "stats")]
+        self.histograms.update_lengths.record(self.len);
+
+        self.realloc_tracker.record_upload();
+        self.length_history.record(self.len);
+        self.record_updated_frame();
+        self.modifications += 1;
+
+        let rollback = previous.map(|previous| Rollback {
+            stamp: self.modifications,
+            state: Box::new(previous),
+        });
+        let ticket = queue.enqueue_full_update(self.source_id, bytes, upload_task, rollback);
+
+        self.debug_check();
 
-        reallocated
+        Ok((reallocated, ticket))
     }
 
-    /// The number of elements this vector can hold without allocating a new buffer.
-    pub fn capacity(&self) -> usize {
-        self.buffer.len()
+    /// Sets up the vector to receive the given `data` as its new indices, allocating a new buffer
+    /// if necessary, but does not upload the `data`.
+    ///
+    /// Returns the buffer that was replaced if a new buffer was allocated.
+    fn prepare_update(&mut self, data: &[T]) -> Result<Option<ReplacedBuffer<T>>, BufferVecError> {
+        let new_len = data.len();
+        let grown_capacity = self.grown_capacity(new_len)?;
+        let shrunk_capacity =
+            self.shrunk_capacity(grown_capacity.unwrap_or_else(|| self.capacity()), new_len);
+        let new_capacity = grown_capacity.or(shrunk_capacity);
+
+        self.len = new_len;
+        self.restore_progress = None;
+        self.max_index = MaxIndex::of(data);
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.clear();
+            shadow.extend_from_slice(data);
+        }
+
+        self.sections.clear();
+        self.lods.clear();
+
+        if let Some(new_capacity) = new_capacity {
+            Ok(Some(
+                self.reallocate(new_capacity, self.buffer.usage_hint()),
+            ))
+        } else if self.orphaning {
+            Ok(Some(
+                self.reallocate(self.capacity(), self.buffer.usage_hint()),
+            ))
+        } else {
+            Ok(None)
+        }
     }
 
-    /// Returns a view on the data in the buffer.
+    /// Whether or not this vector allocates a new buffer on every [update].
+    ///
+    /// See [set_orphaning].
+    ///
+    /// [update]: IndexBufferVec::update
+    /// [set_orphaning]: IndexBufferVec::set_orphaning
+    pub fn is_orphaning(&self) -> bool {
+        self.orphaning
+    }
+
+    /// Enables or disables orphaning: when enabled, every [update] allocates a new buffer of the
+    /// same capacity (or larger, if the vector needs to grow) and uploads into the new buffer,
+    /// rather than into the current buffer. Disabled by default.
+    ///
+    /// Intended for index data that is replaced every frame: uploading into a buffer that is still
+    /// being read by a previously submitted draw may cause the driver to wait for that draw to
+    /// finish; uploading into a new buffer avoids that wait. In this mode, [update] always returns
+    /// `true` and increments the [generation].
     ///
     /// # Example
     ///
@@ -216,25 +729,2353 @@ where
     /// use web_glitz_buffer_vec::IndexBufferVec;
     /// use web_glitz::buffer::UsageHint;
     ///
-    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StreamDraw);
     ///
-    /// indices.update([1u16, 2u16, 3u16]);
+    /// indices.set_orphaning(true);
+    /// indices.update([0u16, 1, 2]);
     ///
-    /// let view = indices.as_buffer_view();
+    /// let generation = indices.generation();
     ///
-    /// assert_eq!(view.len(), 3);
+    /// indices.update([2u16, 1, 0]);
+    ///
+    /// assert_eq!(indices.generation(), generation + 1);
     /// # }
     /// ```
     ///
     /// Here `context` is a WebGlitz [RenderingContext].
     ///
     /// [RenderingContext]: web_glitz::runtime::RenderingContext
-    pub fn as_buffer_view(&self) -> IndexBufferView<T>
+    /// [update]: IndexBufferVec::update
+    /// [generation]: IndexBufferVec::generation
+    pub fn set_orphaning(&mut self, orphaning: bool) {
+        self.orphaning = orphaning;
+    }
+
+    /// Migrates the vector to a new buffer with the given usage hint, preserving the data.
+    ///
+    /// If `shrink_to_fit` is `true`, the new buffer's capacity is the vector's length, otherwise
+    /// the current capacity is kept. As index data cannot be copied on the GPU, the data is
+    /// uploaded again from the CPU-side copy (see the [type documentation](IndexBufferVec)). Does
+    /// nothing if the buffer already uses the `new_hint`. Otherwise, increments the [generation].
+    ///
+    /// See also [BufferVec::convert_usage].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not empty and not resilient (see [set_resilient]).
+    ///
+    /// [generation]: IndexBufferVec::generation
+    /// [BufferVec::convert_usage]: crate::BufferVec::convert_usage
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn convert_usage(&mut self, new_hint: UsageHint, shrink_to_fit: bool) {
+        if mem::discriminant(&new_hint) == mem::discriminant(&self.buffer.usage_hint()) {
+            return;
+        }
+
+        if self.check_preservable().is_err() {
+            panic!(
+                "Cannot convert the usage of {}, as it is not resilient.",
+                describe_vec("index buffer vec", self.label.as_deref())
+            );
+        }
+
+        let capacity = if shrink_to_fit {
+            self.len
+        } else {
+            self.capacity()
+        };
+
+        self.reallocate(capacity, new_hint);
+        self.reupload_shadow();
+        self.restore_progress = None;
+    }
+
+    /// Returns `false` while the vector is being restored by a [RecoveryPlan] and not all of its
+    /// indices have been re-uploaded yet, `true` otherwise.
+    ///
+    /// See [BufferVec::is_restored].
+    ///
+    /// [RecoveryPlan]: crate::RecoveryPlan
+    /// [BufferVec::is_restored]: crate::BufferVec::is_restored
+    pub fn is_restored(&self) -> bool {
+        self.restore_progress.is_none()
+    }
+
+    /// Allocates a new buffer for the given `context` at the current capacity like [recreate], but
+    /// leaves the indices to be uploaded in chunks with [restore_step].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient (see [set_resilient]).
+    ///
+    /// [recreate]: IndexBufferVec::recreate
+    /// [restore_step]: IndexBufferVec::restore_step
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub(crate) fn begin_restore(&mut self, context: Rc) {
+        if self.shadow.is_none() {
+            panic!(
+                "Cannot recreate {}, as it is not resilient.",
+                describe_vec("index buffer vec", self.label.as_deref())
+            );
+        }
+
+        self.context = context;
+        self.reallocate(self.capacity(), self.buffer.usage_hint());
+        self.restore_progress = if self.len > 0 { Some(0) } else { None };
+    }
+
+    /// Re-uploads the next indices after [begin_restore], at most as many as fit in `max_bytes`
+    /// but at least one, and returns the number of bytes uploaded.
+    ///
+    /// [begin_restore]: IndexBufferVec::begin_restore
+    pub(crate) fn restore_step(&mut self, max_bytes: usize) -> usize {
+        let restored = match self.restore_progress {
+            Some(restored) => restored.min(self.len),
+            None => return 0,
+        };
+        let end = self
+            .len
+            .min(restored + (max_bytes / mem::size_of::<T>()).max(1));
+
+        if end > restored {
+            let data = match &self.shadow {
+                Some(shadow) => UploadData(shadow[restored..end].to_vec()),
+                None => bug(format_args!(
+                    "restoring an index buffer vec without a CPU-side copy"
+                )),
+            };
+
+            self.submit_upload(restored..end, data);
+        }
+
+        self.restore_progress = if end < self.len { Some(end) } else { None };
+
+        (end - restored) * mem::size_of::<T>()
+    }
+
+    /// The number of bytes of indices that remain to be re-uploaded by [restore_step].
+    ///
+    /// [restore_step]: IndexBufferVec::restore_step
+    pub(crate) fn restore_remaining_bytes(&self) -> usize {
+        self.restore_progress.map_or(0, |restored| {
+            self.len.saturating_sub(restored) * mem::size_of::<T>()
+        })
+    }
+
+    /// Shrinks the capacity of the vector to the larger of its [len] and `min_capacity`,
+    /// preserving the data.
+    ///
+    /// Does nothing if the capacity is already at or below that size. Otherwise, allocates a new
+    /// buffer, uploads the indices again from the CPU-side copy and increments the [generation].
+    ///
+    /// See also [BufferVec::shrink_to].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to shrink, is not empty and is not resilient (see
+    /// [set_resilient]).
+    ///
+    /// [len]: IndexBufferVec::len
+    /// [generation]: IndexBufferVec::generation
+    /// [BufferVec::shrink_to]: crate::BufferVec::shrink_to
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let capacity = min_capacity.max(self.len);
+
+        if capacity < self.capacity() {
+            if self.check_preservable().is_err() {
+                panic!(
+                    "Cannot shrink {}, as it is not resilient.",
+                    describe_vec("index buffer vec", self.label.as_deref())
+                );
+            }
+
+            self.reallocate(capacity, self.buffer.usage_hint());
+            self.reupload_shadow();
+        }
+
+        self.debug_check();
+    }
+
+    /// Whether or not this vector keeps a CPU-side copy of its indices.
+    ///
+    /// See [set_resilient].
+    ///
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn is_resilient(&self) -> bool {
+        self.shadow.is_some()
+    }
+
+    /// Enables or disables keeping a CPU-side copy of the indices in this vector, which allows the
+    /// indices to be read back, the vector to grow or shrink while preserving its indices, and the
+    /// vector to be restored with [recreate] after the context was lost. Disabled by default; see
+    /// the [type documentation](IndexBufferVec#cpu-side-copy) for the operations that need it.
+    ///
+    /// Disabling resilience releases the CPU-side copy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `resilient` is `true`, the vector is not already resilient and the vector is not
+    /// empty: the vector cannot copy indices that were uploaded before resilience was enabled.
+    ///
+    /// [recreate]: IndexBufferVec::recreate
+    pub fn set_resilient(&mut self, resilient: bool) {
+        if resilient {
+            if self.shadow.is_none() {
+                if self.len != 0 {
+                    panic!(
+                        "Cannot make a non-empty index buffer vec resilient; enable resilience \
+                         before the first update."
+                    );
+                }
+
+                self.shadow = Some(Vec::new());
+                // A queued update enqueued without a CPU-side copy cannot restore one.
+                self.modifications += 1;
+            }
+        } else {
+            self.shadow = None;
+        }
+    }
+
+    /// Allocates a new buffer for the given `context` at the current capacity and re-uploads the
+    /// indices from the CPU-side copy, preserving the length of the vector.
+    ///
+    /// Intended to be used to restore the vector after the context was lost: `context` may be the
+    /// restored context or an entirely new context. Increments the [generation].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not resilient (see [set_resilient]).
+    ///
+    /// [generation]: IndexBufferVec::generation
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn recreate(&mut self, context: Rc) {
+        if self.shadow.is_none() {
+            panic!(
+                "Cannot recreate {}, as it is not resilient.",
+                describe_vec("index buffer vec", self.label.as_deref())
+            );
+        }
+
+        self.context = context;
+        self.reallocate(self.capacity(), self.buffer.usage_hint());
+        self.reupload_shadow();
+    }
+
+    /// Moves the vector to the given `context`, allocating a new buffer for it and uploading the
+    /// given `data`.
+    ///
+    /// The vector's length becomes the length of the `data`. Keeps the current capacity if it
+    /// suffices for the `data`. Like [update], accepts any data that can be borrowed as a slice,
+    /// and uploads it without copying. Allocates exactly one buffer and increments the [generation]
+    /// once, even if orphaning is enabled. See also [recreate], which restores the current
+    /// indices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow to fit the `data` and cannot (see [update]).
+    ///
+    /// [generation]: IndexBufferVec::generation
+    /// [recreate]: IndexBufferVec::recreate
+    /// [update]: IndexBufferVec::update
+    pub fn rebind_context<D>(&mut self, context: Rc, data: D)
     where
-        T: Copy + 'static,
+        D: Borrow<[T]> + Send + Sync + 'static,
     {
-        let IndexBufferVec { len, buffer, .. } = self;
+        let len = data.borrow().len();
+        let capacity = self
+            .grown_capacity(len)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+            .unwrap_or_else(|| self.capacity());
+
+        self.context = context;
+        self.len = len;
+        self.restore_progress = None;
+        self.max_index = MaxIndex::of(data.borrow());
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.clear();
+            shadow.extend_from_slice(data.borrow());
+        }
+
+        self.sections.clear();
+        self.lods.clear();
+        self.reallocate(capacity, self.buffer.usage_hint());
+        self.submit_upload(0..len, data);
+        self.debug_check();
+    }
+
+    /// Returns the capacity to which the vector would grow to hold `new_len` indices, or `None` if
+    /// the current capacity suffices, without charging a [MemoryBudget].
+    fn planned_capacity(&self, new_len: usize) -> Result<Option<usize>, BufferVecError> {
+        check_buffer_size::<T>(self.capacity(), new_len, self.max_buffer_size)?;
+
+        checked_new_capacity::<T>(
+            self.capacity(),
+            new_len,
+            capacity_limit::<T>(self.max_capacity, self.max_buffer_size),
+            &*self.growth,
+            self.capacity_alignment,
+        )
+    }
+
+    /// Returns the capacity to which the vector must grow to hold `new_len` indices, or `None` if
+    /// the current capacity suffices.
+    ///
+    /// If the vector must grow and is registered with a [MemoryBudget], the growth is charged to
+    /// the budget, which may evict other vectors to make room.
+    fn grown_capacity(&self, new_len: usize) -> Result<Option<usize>, BufferVecError> {
+        let new_capacity = self.planned_capacity(new_len)?;
+
+        if let Some(new_capacity) = new_capacity {
+            self.charge_budget(new_capacity)?;
+        }
+
+        Ok(new_capacity)
+    }
+
+    /// Charges growth to a buffer of the given `capacity` to the [MemoryBudget] of this vector, if
+    /// any, which may evict other vectors to make room. With the `wasm-test` feature, also fails if
+    /// a `TestContext` injected a failure for the allocation.
+    ///
+    /// Call only once the growth is certain, right before reallocating.
+    fn charge_budget(&self, capacity: usize) -> Result<(), BufferVecError> {
+        let bytes = capacity * mem::size_of::<T>();
+
+        #[cfg(feature = "wasm-test")]
+        check_allocation(bytes)?;
+
+        match &self.budget {
+            Some(budget) => budget.request(bytes),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns an error if moving the vector to a new buffer would lose its indices, because the
+    /// vector is not empty and keeps no CPU-side copy to upload them from.
+    fn check_preservable(&self) -> Result<(), BufferVecError> {
+        if self.len > 0 && self.shadow.is_none() {
+            Err(BufferVecError::NotResilient)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Uploads all indices again from the CPU-side copy after the vector moved to a new buffer.
+    ///
+    /// Callers must first check that the move preserves the indices, see [check_preservable].
+    ///
+    /// [check_preservable]: IndexBufferVec::check_preservable
+    fn reupload_shadow(&mut self) {
+        if self.len > 0 {
+            let data = match &self.shadow {
+                Some(shadow) => shadow.clone(),
+                None => bug(format_args!(
+                    "moved a non-empty index buffer vec without a CPU-side copy"
+                )),
+            };
+
+            self.submit_upload(0..self.len, UploadData(data));
+        }
+    }
+
+    /// Overwrites the indices starting at `offset` in the CPU-side copy, if the vector keeps one,
+    /// and updates the largest index accordingly.
+    fn overwrite_shadow(&mut self, offset: usize, data: &[T]) {
+        match &mut self.shadow {
+            Some(shadow) => {
+                let overwritten = &mut shadow[offset..offset + data.len()];
+                let known = self.max_index.overwrite(overwritten, data);
+
+                overwritten.copy_from_slice(data);
+
+                // Only rescans if all occurrences of the largest index were overwritten.
+                if !known {
+                    self.max_index = MaxIndex::of(shadow);
+                }
+            }
+            // The overwritten indices are unknown, so the tracked maximum cannot decrease.
+            None => self.max_index.add(data),
+        }
+    }
+
+    /// Consults the [ShrinkPolicy] of this vector for a vector of the given `capacity` that now
+    /// holds `len` elements, and returns the capacity to shrink to, if any.
+    fn shrunk_capacity(&mut self, capacity: usize, len: usize) -> Option<usize> {
+        let alignment = self.capacity_alignment;
+
+        self.shrink
+            .shrink(capacity, len)
+            .map(|new_capacity| align_capacity(new_capacity.max(len), alignment))
+            .filter(|new_capacity| *new_capacity < capacity)
+    }
+
+    /// Marks this vector as used in the current frame of its [MemoryBudget], if any.
+    fn touch_budget(&self) {
+        if let Some(budget) = &self.budget {
+            budget.touch();
+        }
+    }
+
+    /// Records the current size of the buffer with the [MemoryBudget] of this vector, if any.
+    fn sync_budget(&self) {
+        if let Some(budget) = &self.budget {
+            budget.set_bytes(self.capacity() * mem::size_of::<T>());
+        }
+    }
+
+    /// Allocates a new buffer with the given `capacity` and `usage` hint.
+    ///
+    /// The caller is responsible for uploading the data for the current length. Returns the buffer
+    /// that was replaced.
+    fn reallocate(&mut self, capacity: usize, usage: UsageHint) -> ReplacedBuffer<T> {
+        self.track_reallocation(self.buffer.len(), capacity);
+
+        let replaced_buffer = mem::replace(
+            &mut self.buffer,
+            self.context.create_index_buffer_uninit(capacity, usage),
+        );
+        let replaced_live_buffer = mem::replace(
+            &mut self.live_buffer,
+            LiveBuffer::new(capacity * mem::size_of::<T>()),
+        );
+
+        self.modifications += 1;
+        self.stats.record_reallocation(capacity);
+        #[cfg(feature = "stats")]
+        self.histograms.reallocation_capacities.record(capacity);
+        self.generation += 1;
+        self.sync_budget();
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            label = self.label.as_deref().unwrap_or_else(|| type_name::<Self>()),
+            capacity = capacity as u64,
+            "index buffer vec reallocated"
+        );
+
+        (replaced_buffer, replaced_live_buffer)
+    }
+
+    /// Submits a command that uploads the `data` to the `range` of the buffer.
+    fn submit_upload<D>(&mut self, range: Range<usize>, data: D)
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.stats.record_upload(range.len(), mem::size_of::<T>());
+
+        #[cfg(feature = "stats")]
+        self.histograms.update_lengths.record(range.len());
+        self.realloc_tracker.record_upload();
+        self.length_history.record(self.len);
+        self.record_updated_frame();
+        self.modifications += 1;
+        self.context
+            .submit(upload_command(self.uninit_view(range), data));
+    }
+
+    /// A view on the `range` of the buffer, which may not be initialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the state of this vector if the `range` is out of bounds for the buffer, which
+    /// indicates a bug in the bookkeeping of this vector.
+    fn uninit_view(&self, range: Range<usize>) -> IndexBufferView<'_, MaybeUninit<T>> {
+        match self.buffer.get(range.clone()) {
+            Some(view) => view,
+            None => range_bug(
+                "index buffer vec",
+                self.label.as_deref(),
+                &range,
+                self.buffer.len(),
+                self.len,
+                self.capacity(),
+            ),
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more indices than the current length of the
+    /// vector, preserving the current data.
+    ///
+    /// Does nothing if the capacity is already sufficient. If a new buffer is allocated, the
+    /// current indices are uploaded again from the CPU-side copy (see the
+    /// [type documentation](IndexBufferVec)) and the [generation] is incremented.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the required capacity exceeds the [max_capacity] or if the size in bytes of the
+    /// required capacity does not fit in a `usize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_resilient(true);
+    /// indices.update([0u16, 1, 2]);
+    /// indices.reserve(10);
+    ///
+    /// assert!(indices.capacity() >= 13);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [max_capacity]: IndexBufferVec::max_capacity
+    /// [generation]: IndexBufferVec::generation
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Reserves capacity for at least `additional` more indices than the current length of the
+    /// vector, preserving the current data, or returns an error if a new buffer could not be
+    /// allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [reserve] for details, and
+    /// [BufferVec::try_reserve] for the checks that are made. Growing a vector that is not empty
+    /// and not resilient (see [set_resilient]) fails with [BufferVecError::NotResilient].
+    ///
+    /// [reserve]: IndexBufferVec::reserve
+    /// [BufferVec::try_reserve]: crate::BufferVec::try_reserve
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), BufferVecError> {
+        let required_capacity = self.len.checked_add(additional).ok_or(CapacityError {
+            len: usize::MAX,
+            element_size: mem::size_of::<T>(),
+        })?;
+
+        // Plan without charging the budget: eviction runs only once the growth is certain.
+        if let Some(new_capacity) = self.planned_capacity(required_capacity)? {
+            self.check_preservable()?;
+            self.charge_budget(new_capacity)?;
+            self.reallocate(new_capacity, self.buffer.usage_hint());
+            self.reupload_shadow();
+        }
+
+        self.debug_check();
+
+        Ok(())
+    }
+
+    /// Appends the given `data` to the end of the vector, growing the buffer if necessary.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise. As index data cannot be
+    /// copied on the GPU, growing the buffer uploads all indices again from the CPU-side copy (see
+    /// the [type documentation](IndexBufferVec)); otherwise, only the appended indices are
+    /// uploaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the required capacity exceeds the [max_capacity], if the size in bytes of the
+    /// required capacity does not fit in a `usize`, or if the vector needs to grow, is not empty
+    /// and is not resilient (see [set_resilient]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_resilient(true);
+    /// indices.update([0u16, 1, 2]);
+    /// indices.append([2u16, 3, 0]);
+    ///
+    /// assert_eq!(indices.len(), 6);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [max_capacity]: IndexBufferVec::max_capacity
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn append<D>(&mut self, data: D) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.try_append(data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Appends the given `data` to the end of the vector, growing the buffer if necessary, or
+    /// returns an error if the buffer needed to grow and a new buffer could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [append] for details.
+    ///
+    /// [append]: IndexBufferVec::append
+    pub fn try_append<D>(&mut self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        let start = self.len;
+        let additional = data.borrow().len();
+        let new_len = start.checked_add(additional).ok_or(CapacityError {
+            len: usize::MAX,
+            element_size: mem::size_of::<T>(),
+        })?;
+        let new_capacity = self.planned_capacity(new_len)?;
+
+        if let Some(new_capacity) = new_capacity {
+            self.check_preservable()?;
+            self.charge_budget(new_capacity)?;
+        }
+
+        self.len = new_len;
+        self.max_index.add(data.borrow());
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.extend_from_slice(data.borrow());
+        }
+
+        let reallocated = if let Some(new_capacity) = new_capacity {
+            self.reallocate(new_capacity, self.buffer.usage_hint());
+
+            // A vector without a CPU-side copy only grows if it was empty.
+            match self.shadow.clone() {
+                Some(shadow) => self.submit_upload(0..new_len, UploadData(shadow)),
+                None => self.submit_upload(start..new_len, data),
+            }
+
+            true
+        } else {
+            if additional > 0 {
+                self.submit_upload(start..new_len, data);
+            }
+
+            false
+        };
+
+        self.debug_check();
+
+        Ok(reallocated)
+    }
+
+    /// Replaces the data in the buffer with the fan triangulations of the given convex
+    /// `polygons`, resizing the buffer if necessary, and returns the number of triangles.
+    ///
+    /// Each polygon `v0, v1, ..., vn` is triangulated as `(v0, v1, v2), (v0, v2, v3), ...`, and
+    /// the triangles of all polygons are uploaded as a single list. Polygons with fewer than 3
+    /// indices are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StreamDraw);
+    ///
+    /// let quad = [0u16, 1, 2, 3];
+    /// let pentagon = [4u16, 5, 6, 7, 8];
+    ///
+    /// let triangle_count = indices.update_from_polygons(&[&quad[..], &pentagon[..]]);
+    ///
+    /// assert_eq!(triangle_count, 5);
+    /// assert_eq!(indices.len(), 15);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: IndexBufferVec::update
+    pub fn update_from_polygons(&mut self, polygons: &[PolygonIndices<T>]) -> usize {
+        let mut data = Vec::new();
+        let triangle_count = triangulate_fans(polygons, &mut data);
+
+        self.update(UploadData(data));
+
+        triangle_count
+    }
+
+    /// Replaces the data in the buffer with the triangle list equivalent of the given triangle
+    /// `strip`, resizing the buffer if necessary, and returns the number of triangles.
+    ///
+    /// Triangle `i` of the strip consists of the indices `i, i + 1, i + 2`; the winding of every
+    /// odd triangle is flipped so that all triangles in the list have the same winding.
+    /// Degenerate triangles (triangles that repeat an index) are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// // Uploads `0, 1, 2, 2, 1, 3`.
+    /// let triangle_count = indices.update_from_strip(&[0u16, 1, 2, 3]);
+    ///
+    /// assert_eq!(triangle_count, 2);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: IndexBufferVec::update
+    pub fn update_from_strip(&mut self, strip: &[T]) -> usize {
+        let mut data = Vec::new();
+        let triangle_count = strip_to_list(strip, &mut data);
+
+        self.update(UploadData(data));
+
+        triangle_count
+    }
+
+    /// Appends the triangle list equivalent of the given triangle `strip` to the end of the
+    /// vector, growing the buffer if necessary, and returns the number of triangles appended.
+    ///
+    /// Allows batching several strips into a single triangle list. See [update_from_strip] and
+    /// [append] for details.
+    ///
+    /// [update_from_strip]: IndexBufferVec::update_from_strip
+    /// [append]: IndexBufferVec::append
+    pub fn extend_from_strip(&mut self, strip: &[T]) -> usize {
+        let mut data = Vec::new();
+        let triangle_count = strip_to_list(strip, &mut data);
+
+        self.append(UploadData(data));
+
+        triangle_count
+    }
+
+    /// Replaces the data in the buffer with a line list of the unique edges of the given triangle
+    /// list, resizing the buffer if necessary, and returns the number of lines.
+    ///
+    /// Intended for drawing a wireframe overlay of a triangle mesh. Edges that are shared by
+    /// several triangles are included only once, regardless of their direction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `tris` is not a multiple of 3, or for the same reasons as
+    /// [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut wireframe = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// // A quad made of two triangles that share an edge.
+    /// let line_count = wireframe.update_wireframe_from_triangles(&[0u16, 1, 2, 2, 3, 0]);
+    ///
+    /// assert_eq!(line_count, 5);
+    /// assert_eq!(wireframe.len(), 10);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: IndexBufferVec::update
+    pub fn update_wireframe_from_triangles(&mut self, tris: &[T]) -> usize {
+        let mut data = Vec::new();
+        let line_count = triangle_edges(tris, &mut data);
+
+        self.update(UploadData(data));
+
+        line_count
+    }
+
+    /// Replaces the data in the buffer with the given triangle list `data`, with the given
+    /// `winding`, resizing the buffer if necessary.
+    ///
+    /// With [Winding::AsIs], this uploads the `data` exactly as [update] does. With
+    /// [Winding::Flipped], the second and third index of each triangle are swapped, which reverses
+    /// the triangle's winding, e.g. for drawing a mesh with a mirroring (negatively scaled)
+    /// transform without changing the pipeline's face culling. Returns `true` if a new buffer was
+    /// allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `winding` is [Winding::Flipped] and the length of `data` is not a multiple of
+    /// 3, or for the same reasons as [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::{IndexBufferVec, Winding};
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// // Uploads `0, 2, 1, 2, 0, 3`.
+    /// indices.update_with_winding(&[0u16, 1, 2, 2, 3, 0], Winding::Flipped);
+    ///
+    /// assert_eq!(indices.len(), 6);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: IndexBufferVec::update
+    pub fn update_with_winding(&mut self, data: &[T], winding: Winding) -> bool {
+        self.update(UploadData(with_winding(data, winding)))
+    }
+
+    /// Appends the given triangle list `data` to the end of the vector, with the given `winding`,
+    /// growing the buffer if necessary.
+    ///
+    /// See [update_with_winding] and [append] for details.
+    ///
+    /// [update_with_winding]: IndexBufferVec::update_with_winding
+    /// [append]: IndexBufferVec::append
+    pub fn extend_with_winding(&mut self, data: &[T], winding: Winding) -> bool {
+        self.append(UploadData(with_winding(data, winding)))
+    }
+
+    /// Appends the given triangle `strip` to the strip in this vector, joining the strips with
+    /// degenerate triangles, and returns the range of the vector that holds the `strip`'s indices.
+    ///
+    /// If the vector is not empty, its last index and the `strip`'s first index are repeated
+    /// before the `strip`, which produces only degenerate triangles that are not rasterized. If
+    /// the vector's length is odd, its last index is repeated once more, so that the `strip`'s
+    /// triangles keep their winding. The returned range excludes these stitching indices, so
+    /// that it can be used to draw the `strip` on its own. See also [append].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to grow and cannot (see [append]), or if the vector is not empty
+    /// and not resilient (see [set_resilient]): the stitching indices are taken from the CPU-side
+    /// copy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_resilient(true);
+    ///
+    /// assert_eq!(indices.append_strip_stitched(&[0u16, 1, 2, 3]), 0..4);
+    ///
+    /// // Stitched with `3, 4`.
+    /// assert_eq!(indices.append_strip_stitched(&[4u16, 5, 6, 7]), 6..10);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [append]: IndexBufferVec::append
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn append_strip_stitched(&mut self, strip: &[T]) -> Range<usize> {
+        self.check_preservable()
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())));
+
+        let len = self.len;
+        let last = self
+            .shadow
+            .as_ref()
+            .and_then(|shadow| shadow.last().copied());
+        let mut data = Vec::with_capacity(strip.len() + 3);
+        let offset = stitch_strip(len, last, strip, &mut data);
+
+        self.append(UploadData(data));
+
+        len + offset..self.len
+    }
+
+    /// Overwrites several ranges of indices at once: each `(offset, data)` part overwrites the
+    /// indices starting at `offset` with its `data`.
+    ///
+    /// All parts are submitted together as a single task. Parts may overlap, in which case the
+    /// part that comes last in `parts` wins. Never allocates a new buffer; the length of the
+    /// vector does not change. See also [BufferVec::update_parts].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the parts extends beyond the length of the vector. All parts are checked
+    /// before any data is uploaded.
+    ///
+    /// [BufferVec::update_parts]: crate::BufferVec::update_parts
+    pub fn update_parts(&mut self, parts: &[(usize, &[T])]) {
+        self.try_update_parts(parts)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Overwrites several ranges of indices at once, or returns an error if any of the parts
+    /// extends beyond the length of the vector.
+    ///
+    /// If an error is returned, nothing is uploaded and the vector is left unmodified. See
+    /// [update_parts] for details.
+    ///
+    /// [update_parts]: IndexBufferVec::update_parts
+    pub fn try_update_parts(&mut self, parts: &[(usize, &[T])]) -> Result<(), BufferVecError> {
+        for (offset, data) in parts {
+            checked_range(*offset, data.len(), self.len)?;
+        }
+
+        for (offset, data) in parts {
+            self.overwrite_shadow(*offset, data);
+        }
+
+        for (_, data) in parts.iter().filter(|(_, data)| !data.is_empty()) {
+            self.stats.record_upload(data.len(), mem::size_of::<T>());
+
+            #[cfg(feature = "stats")]
+            self.histograms.update_lengths.record(data.len());
+            self.realloc_tracker.record_upload();
+            self.length_history.record(self.len);
+            self.record_updated_frame();
+        }
+
+        let commands: Vec<_> = parts
+            .iter()
+            .filter(|(_, data)| !data.is_empty())
+            .map(|(offset, data)| {
+                let view = self.uninit_view(*offset..offset + data.len());
+
+                unsafe {
+                    // Note: the view data range is not actually guaranteed to be initialized, but
+                    // we're only writing, not reading.
+                    view.assume_init().upload_command(UploadData(data.to_vec()))
+                }
+            })
+            .collect();
+
+        if !commands.is_empty() {
+            self.context.submit(sequence_iter(commands));
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the data in the buffer with the indices of the given triangles, resizing the buffer
+    /// if necessary.
+    ///
+    /// The triangles are uploaded as `3 * tris.len()` contiguous indices, without flattening them
+    /// into a separate allocation first. Returns `true` if a new buffer was allocated, `false`
+    /// otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.update_from_triangles(vec![[0u32, 1, 2], [2, 3, 0]]);
+    ///
+    /// assert_eq!(indices.len(), 6);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update]: IndexBufferVec::update
+    pub fn update_from_triangles<D>(&mut self, tris: D) -> bool
+    where
+        D: Borrow<[[T; 3]]> + Send + Sync + 'static,
+    {
+        self.update(Triangles::new(tris))
+    }
+
+    /// Appends the indices of the given triangles to the end of the vector, growing the buffer if
+    /// necessary.
+    ///
+    /// See [update_from_triangles] and [append] for details.
+    ///
+    /// [update_from_triangles]: IndexBufferVec::update_from_triangles
+    /// [append]: IndexBufferVec::append
+    pub fn extend_from_triangles<D>(&mut self, tris: D) -> bool
+    where
+        D: Borrow<[[T; 3]]> + Send + Sync + 'static,
+    {
+        self.append(Triangles::new(tris))
+    }
+
+    /// Adds the signed `delta` to each of the indices in the given `range`.
+    ///
+    /// Intended for when the vertices that the indices in the `range` refer to have moved, e.g.
+    /// from offset `a` to offset `b` in a shared vertex buffer, in which case the `delta` is
+    /// `b - a`. The indices are rebased on the CPU-side copy (see the
+    /// [type documentation](IndexBufferVec)) and only the `range` is uploaded. Never allocates a
+    /// new buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `range` is not within the vector's length, if a rebased index would be
+    /// negative or exceed the maximum value of the index type, or if the vector is not resilient
+    /// (see [set_resilient]): the indices are rebased from the CPU-side copy. See
+    /// [try_rebase_range] for a fallible version.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_resilient(true);
+    /// indices.update([0u16, 1, 2, 10, 11, 12]);
+    ///
+    /// // The vertices of the second triangle moved from offset 10 to offset 3.
+    /// indices.rebase_range(3..6, -7);
+    ///
+    /// assert_eq!(indices.max_index(), Some(5));
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [try_rebase_range]: IndexBufferVec::try_rebase_range
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn rebase_range(&mut self, range: Range<usize>, delta: i64) {
+        self.try_rebase_range(range, delta)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Adds the signed `delta` to each of the indices in the given `range`, or returns an error if
+    /// the `range` is not within the vector's length, if a rebased index would be negative or
+    /// exceed the maximum value of the index type, or if the vector is not resilient.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [rebase_range] for details.
+    ///
+    /// [rebase_range]: IndexBufferVec::rebase_range
+    pub fn try_rebase_range(
+        &mut self,
+        range: Range<usize>,
+        delta: i64,
+    ) -> Result<(), BufferVecError> {
+        let range = checked_read_range(range, self.len)?;
+        let shadow = self.shadow.as_ref().ok_or(BufferVecError::NotResilient)?;
+
+        if delta == 0 || range.is_empty() {
+            return Ok(());
+        }
+
+        let rebased = rebased_indices(&shadow[range.clone()], delta, range.start)?;
+
+        self.overwrite_shadow(range.start, &rebased);
+        self.submit_upload(range, UploadData(rebased));
+        self.debug_check();
+
+        Ok(())
+    }
+
+    /// The maximum number of elements this vector may grow to, or `None` if no limit applies.
+    ///
+    /// See [set_max_capacity].
+    ///
+    /// [set_max_capacity]: IndexBufferVec::set_max_capacity
+    pub fn max_capacity(&self) -> Option<usize> {
+        self.max_capacity
+    }
+
+    /// Sets the maximum number of elements this vector may grow to.
+    ///
+    /// When the vector grows, the new capacity is clamped to the `max_capacity`. Operations that
+    /// require a greater capacity fail with [BufferVecError::CapacityLimitExceeded] (or panic for
+    /// the infallible variants). Does not affect a vector's current capacity if it already exceeds
+    /// the new `max_capacity`.
+    pub fn set_max_capacity(&mut self, max_capacity: Option<usize>) {
+        self.max_capacity = max_capacity;
+    }
+
+    /// The maximum size in bytes of the buffer backing this vector, if any.
+    ///
+    /// See [set_max_buffer_size].
+    ///
+    /// [set_max_buffer_size]: IndexBufferVec::set_max_buffer_size
+    pub fn max_buffer_size(&self) -> Option<usize> {
+        self.max_buffer_size
+    }
+
+    /// Sets the maximum size in bytes of the buffer backing this vector.
+    ///
+    /// See [BufferVec::set_max_buffer_size].
+    ///
+    /// [BufferVec::set_max_buffer_size]: crate::BufferVec::set_max_buffer_size
+    pub fn set_max_buffer_size(&mut self, max_buffer_size: Option<usize>) {
+        self.max_buffer_size = max_buffer_size;
+    }
+
+    /// Sets the maximum size in bytes of the buffer backing this vector and returns the vector,
+    /// for use when creating the vector.
+    ///
+    /// See [BufferVec::set_max_buffer_size].
+    ///
+    /// [BufferVec::set_max_buffer_size]: crate::BufferVec::set_max_buffer_size
+    pub fn with_max_buffer_size(mut self, max_buffer_size: usize) -> Self {
+        self.set_max_buffer_size(Some(max_buffer_size));
+
+        self
+    }
+
+    /// The largest capacity in indices this vector may grow to.
+    ///
+    /// Accounts for the [max_capacity], the [max_buffer_size] and the largest size in bytes that
+    /// fits in a `usize`.
+    ///
+    /// [max_capacity]: IndexBufferVec::max_capacity
+    /// [max_buffer_size]: IndexBufferVec::max_buffer_size
+    pub fn max_supported_capacity(&self) -> usize {
+        let size_limit = self.max_buffer_size.unwrap_or(usize::MAX) / mem::size_of::<T>();
+
+        self.max_capacity
+            .map_or(size_limit, |max_capacity| max_capacity.min(size_limit))
+    }
+
+    /// Sets the [GrowthPolicy] that decides the new capacity when this vector needs to grow and
+    /// returns the vector, for use when creating the vector.
+    ///
+    /// See [BufferVec::with_growth_policy].
+    ///
+    /// [BufferVec::with_growth_policy]: crate::BufferVec::with_growth_policy
+    pub fn with_growth_policy<P>(mut self, policy: P) -> Self
+    where
+        P: GrowthPolicy + Send + Sync + 'static,
+    {
+        self.set_growth_policy(policy);
+
+        self
+    }
+
+    /// Sets the [GrowthPolicy] that decides the new capacity when this vector needs to grow.
+    ///
+    /// See [BufferVec::set_growth_policy].
+    ///
+    /// [BufferVec::set_growth_policy]: crate::BufferVec::set_growth_policy
+    pub fn set_growth_policy<P>(&mut self, policy: P)
+    where
+        P: GrowthPolicy + Send + Sync + 'static,
+    {
+        self.growth = Arc::new(policy);
+    }
+
+    /// The multiple to which the capacity is rounded up when this vector grows.
+    ///
+    /// See [set_capacity_alignment].
+    ///
+    /// [set_capacity_alignment]: IndexBufferVec::set_capacity_alignment
+    pub fn capacity_alignment(&self) -> usize {
+        self.capacity_alignment
+    }
+
+    /// Sets the multiple to which the capacity is rounded up when this vector grows; the default
+    /// is `1`.
+    ///
+    /// See [BufferVec::set_capacity_alignment].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alignment` is `0`.
+    ///
+    /// [BufferVec::set_capacity_alignment]: crate::BufferVec::set_capacity_alignment
+    pub fn set_capacity_alignment(&mut self, alignment: usize) {
+        assert!(alignment != 0, "Capacity alignment must not be zero.");
+
+        self.capacity_alignment = alignment;
+    }
+
+    /// Sets the [ShrinkPolicy] that decides whether this vector shrinks when its data is replaced
+    /// and returns the vector, for use when creating the vector.
+    ///
+    /// See [BufferVec::with_shrink_policy].
+    ///
+    /// [BufferVec::with_shrink_policy]: crate::BufferVec::with_shrink_policy
+    pub fn with_shrink_policy<P>(mut self, policy: P) -> Self
+    where
+        P: ShrinkPolicy + Send + 'static,
+    {
+        self.set_shrink_policy(policy);
+
+        self
+    }
+
+    /// Sets the [ShrinkPolicy] that decides whether this vector shrinks when its data is
+    /// replaced.
+    ///
+    /// See [BufferVec::set_shrink_policy].
+    ///
+    /// [BufferVec::set_shrink_policy]: crate::BufferVec::set_shrink_policy
+    pub fn set_shrink_policy<P>(&mut self, policy: P)
+    where
+        P: ShrinkPolicy + Send + 'static,
+    {
+        self.shrink = Box::new(policy);
+    }
+
+    /// Configures the reallocation warning: when more than `max_reallocations` reallocations
+    /// happen within `window` uploads, a warning is logged with the `log` crate. Defaults to more
+    /// than 4 reallocations within 16 uploads.
+    ///
+    /// Frequent reallocations usually mean the vector should have been created with a larger
+    /// initial capacity. Only has an effect if the `log` feature is enabled. Reallocations that do
+    /// not change the capacity (such as those caused by [convert_usage]) are not counted.
+    ///
+    /// [convert_usage]: IndexBufferVec::convert_usage
+    pub fn set_realloc_warning(&mut self, max_reallocations: usize, window: usize) {
+        self.realloc_tracker
+            .set_thresholds(max_reallocations, window);
+    }
+
+    /// Disables the reallocation warning for this vector, for vectors that are expected to
+    /// reallocate often.
+    ///
+    /// See [set_realloc_warning] to enable the warning again.
+    ///
+    /// [set_realloc_warning]: IndexBufferVec::set_realloc_warning
+    pub fn suppress_realloc_warnings(&mut self) {
+        self.realloc_tracker.suppress();
+    }
+
+    /// Returns a guard that asserts that this vector does not reallocate while it is alive.
+    ///
+    /// With debug assertions enabled, any reallocation of the vector while the guard is alive
+    /// panics, with the label of the vector and its old and new capacities in the message;
+    /// otherwise the reallocation is recorded on the guard, see [ReallocGuard]. The guard does not
+    /// borrow the vector. Useful in performance tests, to verify that a steady-state frame never
+    /// allocates a new buffer.
+    ///
+    /// Reallocations that do not change the capacity (such as those caused by [convert_usage])
+    /// count as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = IndexBufferVec::<_, u16>::with_capacity(context, UsageHint::DynamicDraw, 64);
+    ///
+    /// let guard = vec.expect_no_realloc();
+    ///
+    /// vec.update([1, 2, 3]);
+    ///
+    /// assert!(guard.is_clean());
+    /// # }
+    /// ```
+    ///
+    /// [convert_usage]: IndexBufferVec::convert_usage
+    pub fn expect_no_realloc(&mut self) -> ReallocGuard {
+        let guard = ReallocGuard::new();
+
+        self.realloc_watchers.add(&guard);
+
+        guard
+    }
+
+    /// Adds the given `guard` to the guards watching this vector for reallocations.
+    pub(crate) fn watch_reallocations(&mut self, guard: &ReallocGuard) {
+        self.realloc_watchers.add(guard);
+    }
+
+    /// Records a reallocation from `old_capacity` to `new_capacity` and warns if the vector
+    /// reallocates suspiciously often.
+    fn track_reallocation(&mut self, old_capacity: usize, new_capacity: usize) {
+        self.realloc_watchers
+            .check(self.label.as_deref(), old_capacity, new_capacity);
+
+        if old_capacity != new_capacity && self.realloc_tracker.record_reallocation() {
+            #[cfg(feature = "log")]
+            log::warn!(
+                "{} reallocated from a capacity of {} to {} elements and has reallocated \
+                 often; consider creating it with a larger initial capacity.",
+                self.label.as_deref().unwrap_or_else(|| type_name::<Self>()),
+                old_capacity,
+                new_capacity
+            );
+        }
+    }
+
+    /// Sets the label of this vector and returns it, for use when creating the vector.
+    ///
+    /// See [set_label].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let vec = IndexBufferVec::<_, u16>::new(context, UsageHint::StaticDraw).with_label("terrain");
+    ///
+    /// assert_eq!(vec.label(), Some("terrain"));
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [set_label]: IndexBufferVec::set_label
+    pub fn with_label<L>(mut self, label: L) -> Self
+    where
+        L: Into<String>,
+    {
+        self.set_label(label);
+
+        self
+    }
+
+    /// Sets the label of this vector.
+    ///
+    /// The label identifies the vector in panic messages, in its [Debug] output and, if the
+    /// `log` or `tracing` features are enabled, in logs and traces. Vectors without a label are
+    /// identified by their type.
+    ///
+    /// [Debug]: std::fmt::Debug
+    pub fn set_label<L>(&mut self, label: L)
+    where
+        L: Into<String>,
+    {
+        self.label = Some(label.into());
+
+        if let Some(budget) = &self.budget {
+            budget.set_label(self.label.as_deref());
+        }
+    }
+
+    /// The label of this vector, if it has one.
+    ///
+    /// See [set_label].
+    ///
+    /// [set_label]: IndexBufferVec::set_label
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Registers this vector with the given [MemoryBudget], or unregisters it from its current
+    /// budget if `None`.
+    ///
+    /// See [BufferVec::set_budget].
+    ///
+    /// [BufferVec::set_budget]: crate::BufferVec::set_budget
+    pub fn set_budget(&mut self, budget: Option<&MemoryBudget>) {
+        // Drop the current registration first, in case the vector is re-registered with the same
+        // budget.
+        self.budget = None;
+        self.budget = budget.map(|budget| {
+            budget.register(
+                self.source_id,
+                self.label.as_deref(),
+                self.capacity() * mem::size_of::<T>(),
+            )
+        });
+    }
+
+    /// Sets the [FrameClock] used to record the frame in which this vector was last updated, or
+    /// stops recording if `None`.
+    ///
+    /// See [BufferVec::set_frame_clock].
+    ///
+    /// [BufferVec::set_frame_clock]: crate::BufferVec::set_frame_clock
+    pub fn set_frame_clock(&mut self, clock: Option<&FrameClock>) {
+        self.frame_clock = clock.cloned();
+        self.updated_frame = None;
+    }
+
+    /// The frame of this vector's [FrameClock] in which the vector was last updated.
+    ///
+    /// See [BufferVec::last_updated_frame].
+    ///
+    /// [BufferVec::last_updated_frame]: crate::BufferVec::last_updated_frame
+    pub fn last_updated_frame(&self) -> Option<u64> {
+        self.updated_frame
+    }
+
+    /// Records the current frame of the vector's clock as the frame of its last update.
+    fn record_updated_frame(&mut self) {
+        if let Some(clock) = &self.frame_clock {
+            self.updated_frame = Some(clock.frame());
+        }
+    }
+
+    /// The number of indices in this vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this vector contains no indices.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements this vector can hold without allocating a new buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the capacity this vector would have after an [update] with `len` indices, or an
+    /// error if such an update would fail.
+    ///
+    /// See [BufferVec::capacity_after].
+    ///
+    /// [update]: IndexBufferVec::update
+    /// [BufferVec::capacity_after]: crate::BufferVec::capacity_after
+    pub fn capacity_after(&self, len: usize) -> Result<usize, BufferVecError> {
+        Ok(self
+            .planned_capacity(len)?
+            .unwrap_or_else(|| self.capacity()))
+    }
+
+    /// Returns whether an [update] with `len` indices would allocate a new buffer, or an error if
+    /// such an update would fail.
+    ///
+    /// Always `true` for an [orphaning] vector. See [BufferVec::capacity_after].
+    ///
+    /// [update]: IndexBufferVec::update
+    /// [orphaning]: IndexBufferVec::set_orphaning
+    /// [BufferVec::capacity_after]: crate::BufferVec::capacity_after
+    pub fn will_reallocate(&self, len: usize) -> Result<bool, BufferVecError> {
+        Ok(self.planned_capacity(len)?.is_some() || self.orphaning)
+    }
+
+    /// Counter that is incremented every time this vector allocates a new buffer.
+    ///
+    /// Views obtained with [as_buffer_view] before the generation changed refer to a buffer that is
+    /// no longer used by this vector.
+    ///
+    /// [as_buffer_view]: IndexBufferVec::as_buffer_view
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Upload and allocation statistics for this vector.
+    ///
+    /// See [VecStats] for details.
+    pub fn stats(&self) -> VecStats {
+        self.stats
+    }
+
+    /// Resets the [stats] for this vector; the peak capacity is reset to the current capacity.
+    ///
+    /// [stats]: IndexBufferVec::stats
+    pub fn reset_stats(&mut self) {
+        self.stats = VecStats::new(self.capacity());
+    }
+
+    /// Histograms of the update lengths and reallocation capacities this vector saw, for tuning
+    /// growth policies.
+    ///
+    /// Recording is allocation-free. Only available with the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn stats_histograms(&self) -> CapacityHistograms {
+        self.histograms
+    }
+
+    /// Resets the [stats_histograms] for this vector.
+    ///
+    /// [stats_histograms]: IndexBufferVec::stats_histograms
+    #[cfg(feature = "stats")]
+    pub fn reset_stats_histograms(&mut self) {
+        self.histograms = CapacityHistograms::default();
+    }
+
+    /// The fraction of the capacity that is in use, between `0.0` and `1.0`.
+    ///
+    /// See [BufferVec::occupancy].
+    ///
+    /// [BufferVec::occupancy]: crate::BufferVec::occupancy
+    pub fn occupancy(&self) -> f32 {
+        let capacity = self.capacity();
+
+        if capacity == 0 {
+            1.0
+        } else {
+            self.len as f32 / capacity as f32
+        }
+    }
+
+    /// The number of bytes allocated for this vector's buffer beyond its [len].
+    ///
+    /// [len]: IndexBufferVec::len
+    pub fn wasted_bytes(&self) -> usize {
+        (self.capacity() - self.len) * mem::size_of::<T>()
+    }
+
+    /// A snapshot of the state of this vector, for display in diagnostics overlays.
+    ///
+    /// See [DebugInfo] for details.
+    pub fn debug_info(&self) -> DebugInfo {
+        DebugInfo {
+            label: self.label.clone(),
+            element_type: type_name::<T>(),
+            len: self.len,
+            capacity: self.capacity(),
+            bytes: self.capacity() * mem::size_of::<T>(),
+            usage: self.buffer.usage_hint(),
+            generation: self.generation,
+            reallocations: self.stats.reallocations,
+            last_updated_frame: self.updated_frame,
+        }
+    }
+
+    /// Checks the internal invariants of this vector, returning all violations found.
+    ///
+    /// Verifies that the length does not exceed the capacity, that the CPU-side copy of the indices
+    /// holds exactly [len] indices, and that the sections (see [insert_section]) lie within [len]
+    /// and do not overlap. Does not involve the GPU. See also [BufferVec::debug_validate].
+    ///
+    /// [len]: IndexBufferVec::len
+    /// [insert_section]: IndexBufferVec::insert_section
+    /// [BufferVec::debug_validate]: crate::BufferVec::debug_validate
+    pub fn debug_validate(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut violations = Vec::new();
+        let capacity = self.capacity();
+
+        if self.len > capacity {
+            violations.push(InvariantViolation::LenExceedsCapacity {
+                len: self.len,
+                capacity,
+            });
+        }
+
+        if let Some(shadow) = &self.shadow {
+            if shadow.len() != self.len {
+                violations.push(InvariantViolation::CpuLenMismatch {
+                    cpu_len: shadow.len(),
+                    len: self.len,
+                });
+            }
+        }
+
+        check_ranges(
+            self.sections
+                .iter()
+                .map(|(key, range)| (format!("section {:?}", key), range.clone())),
+            self.len,
+            &mut violations,
+        );
+
+        violations_result(violations)
+    }
+
+    /// Checks the invariants of this vector in debug builds with the `debug-validate` feature.
+    fn debug_check(&self) {
+        #[cfg(all(feature = "debug-validate", debug_assertions))]
+        assert_invariants(
+            self.debug_validate(),
+            "index buffer vec",
+            self.label.as_deref(),
+        );
+    }
+
+    /// Suggests a smaller capacity for this vector if it is consistently using only a small part
+    /// of its capacity, or `None` if shrinking is not worthwhile.
+    ///
+    /// Always `None` for a vector that is not empty and not resilient, which cannot move its
+    /// indices to a smaller buffer (see [set_resilient]). See [BufferVec::suggest_shrink].
+    ///
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    /// [BufferVec::suggest_shrink]: crate::BufferVec::suggest_shrink
+    pub fn suggest_shrink(&self) -> Option<usize> {
+        self.check_preservable().ok()?;
+
+        let recent_max = self.length_history.max()?;
+
+        suggest_shrink_capacity(self.capacity(), self.len, recent_max)
+    }
+
+    /// Performs pending housekeeping for this vector, moving at most as many bytes as the `budget`
+    /// allows, and reports the work done and the work that remains.
+    ///
+    /// Intended to be called opportunistically when nothing time-critical is happening, such as
+    /// during a loading screen. Currently the only housekeeping is shrinking the vector to the
+    /// capacity suggested by [suggest_shrink], which copies the indices to a new buffer on the GPU;
+    /// it is skipped if that copy does not fit in the budget. With [MaintenanceBudget::ZERO],
+    /// does nothing and only reports the pending work.
+    ///
+    /// [suggest_shrink]: IndexBufferVec::suggest_shrink
+    pub fn maintenance(&mut self, budget: MaintenanceBudget) -> MaintenanceReport {
+        let (target, report) = plan_shrink(
+            self.capacity(),
+            self.len,
+            mem::size_of::<T>(),
+            self.suggest_shrink(),
+            budget,
+        );
+
+        if let Some(target) = target {
+            self.shrink_to(target);
+        }
+
+        report
+    }
+
+    /// The largest index value in this vector, or `None` if the vector is empty.
+    ///
+    /// Tracked on the CPU as the data is updated, at a cost proportional to the number of indices
+    /// that are uploaded: the vector is only scanned again if every occurrence of the largest
+    /// index is overwritten with a smaller index. See also [validate_indexed_pair].
+    ///
+    /// The indices that are overwritten by [update_parts] and [insert_section] can only be
+    /// inspected in the CPU-side copy of a resilient vector (see [set_resilient]). For a vector
+    /// that is not resilient, overwriting indices never lowers the tracked maximum, so it may
+    /// exceed the largest index until all indices are replaced with [update].
+    ///
+    /// [validate_indexed_pair]: crate::validate_indexed_pair
+    /// [update_parts]: IndexBufferVec::update_parts
+    /// [insert_section]: IndexBufferVec::insert_section
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    /// [update]: IndexBufferVec::update
+    pub fn max_index(&self) -> Option<T> {
+        self.max_index.get()
+    }
+
+    /// Stores the given `indices` as a section identified by `key` and returns the range of the
+    /// vector the section occupies.
+    ///
+    /// Sections let the vector keep track of where the indices for a particular mesh live; use
+    /// [section_view] to obtain a view on a section's indices. A new section is appended to the end
+    /// of the vector, growing the buffer if necessary (see [append]). If a section with the same
+    /// `key` already exists and has the same length, its indices are overwritten in place;
+    /// otherwise the existing section is removed (see [remove_section]) and the section is
+    /// relocated to the end of the vector.
+    ///
+    /// Sections survive growth of the buffer, but replacing all indices with [update] removes all
+    /// sections.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to grow and cannot (see [append]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_resilient(true);
+    ///
+    /// let quad = indices.insert_section("quad", &[0u16, 1, 2, 2, 3, 0]);
+    /// let triangle = indices.insert_section("triangle", &[4u16, 5, 6]);
+    ///
+    /// assert_eq!(quad, 0..6);
+    /// assert_eq!(triangle, 6..9);
+    /// assert_eq!(indices.section_view("triangle").unwrap().len(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [section_view]: IndexBufferVec::section_view
+    /// [append]: IndexBufferVec::append
+    /// [remove_section]: IndexBufferVec::remove_section
+    /// [update]: IndexBufferVec::update
+    pub fn insert_section<K>(&mut self, key: K, indices: &[T]) -> Range<usize>
+    where
+        K: Into<String>,
+    {
+        let key = key.into();
+
+        // The section no longer holds the levels of a LOD set, if it did.
+        self.lods.remove(&key);
+
+        if let Some(range) = self.sections.get(&key).cloned() {
+            if range.len() == indices.len() {
+                self.overwrite_shadow(range.start, indices);
+
+                if !range.is_empty() {
+                    self.submit_upload(range.clone(), UploadData(indices.to_vec()));
+                }
+
+                self.debug_check();
+
+                return range;
+            }
+        }
+
+        let start = self.len;
+
+        self.append(UploadData(indices.to_vec()));
+
+        let range = start..self.len;
+
+        self.sections.insert(key, range.clone());
+        self.debug_check();
+
+        range
+    }
+
+    /// Returns the range of the vector occupied by the section identified by `key`, or `None` if
+    /// no such section exists.
+    ///
+    /// See [insert_section].
+    ///
+    /// [insert_section]: IndexBufferVec::insert_section
+    pub fn section_range(&self, key: &str) -> Option<Range<usize>> {
+        self.sections.get(key).cloned()
+    }
+
+    /// Returns a view on the indices in the section identified by `key`, or `None` if no such
+    /// section exists.
+    ///
+    /// See [insert_section].
+    ///
+    /// [insert_section]: IndexBufferVec::insert_section
+    pub fn section_view(&self, key: &str) -> Option<IndexBufferView<T>> {
+        let range = self.sections.get(key)?.clone();
+
+        self.touch_budget();
+
+        Some(unsafe { self.uninit_view(range).assume_init() })
+    }
+
+    /// Removes the section identified by `key` and returns the range it occupied, or `None` if no
+    /// such section exists.
+    ///
+    /// The indices of the section remain in the vector: the range becomes unused, but is not
+    /// reclaimed. See [section_occupancy] to determine how much of the vector is in use by
+    /// sections.
+    ///
+    /// If the section holds a LOD set (see [insert_lods]), all levels of the set are removed.
+    ///
+    /// [section_occupancy]: IndexBufferVec::section_occupancy
+    /// [insert_lods]: IndexBufferVec::insert_lods
+    pub fn remove_section(&mut self, key: &str) -> Option<Range<usize>> {
+        self.lods.remove(key);
+        self.sections.remove(key)
+    }
+
+    /// Stores the index lists of the levels of detail of a mesh as a single section identified
+    /// by `key`, and returns a [LodHandle] for selecting a level.
+    ///
+    /// The `lods` are stored back to back, in order, so that `level` `0` is `lods[0]`; all levels
+    /// typically index the same vertex data. The set is stored as a section (see
+    /// [insert_section]): it survives growth of the buffer, is overwritten in place if a section
+    /// with the same `key` and the same total length exists, and removing the section with
+    /// [remove_lods] or [remove_section] removes all levels together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to grow and cannot (see [append]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// let detailed: &[u16] = &[0, 1, 2, 2, 3, 0, 0, 3, 4];
+    /// let coarse: &[u16] = &[0, 1, 4];
+    ///
+    /// let rock = indices.insert_lods("rock", &[detailed, coarse]);
+    ///
+    /// assert_eq!(indices.lod_count(&rock), 2);
+    /// assert_eq!(indices.lod_view(&rock, 1).unwrap().len(), 3);
+    /// assert!(indices.lod_view(&rock, 2).is_none());
+    ///
+    /// indices.remove_lods(&rock);
+    ///
+    /// assert!(indices.lod_view(&rock, 0).is_none());
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [insert_section]: IndexBufferVec::insert_section
+    /// [remove_lods]: IndexBufferVec::remove_lods
+    /// [remove_section]: IndexBufferVec::remove_section
+    /// [append]: IndexBufferVec::append
+    pub fn insert_lods<K>(&mut self, key: K, lods: &[&[T]]) -> LodHandle
+    where
+        K: Into<String>,
+    {
+        let key = key.into();
+
+        self.insert_section(key.clone(), &lods.concat());
+        self.lods.insert(key.clone(), lod_ranges(lods));
+
+        LodHandle { key }
+    }
+
+    /// Returns a view on the indices of the given `level` of the LOD set identified by `lods`,
+    /// or `None` if the set was removed or has no such level.
+    ///
+    /// See [insert_lods].
+    ///
+    /// [insert_lods]: IndexBufferVec::insert_lods
+    pub fn lod_view(&self, lods: &LodHandle, level: usize) -> Option<IndexBufferView<'_, T>> {
+        let section = self.sections.get(&lods.key)?;
+        let level = self.lods.get(&lods.key)?.get(level)?;
+
+        self.touch_budget();
+
+        Some(unsafe {
+            self.uninit_view(section.start + level.start..section.start + level.end)
+                .assume_init()
+        })
+    }
+
+    /// The number of levels in the LOD set identified by `lods`, or `0` if the set was removed.
+    ///
+    /// See [insert_lods].
+    ///
+    /// [insert_lods]: IndexBufferVec::insert_lods
+    pub fn lod_count(&self, lods: &LodHandle) -> usize {
+        self.lods.get(&lods.key).map_or(0, |levels| levels.len())
+    }
+
+    /// Removes all levels of the LOD set identified by `lods` and returns the range the set
+    /// occupied, or `None` if the set was already removed.
+    ///
+    /// Equivalent to calling [remove_section] with the key of the set.
+    ///
+    /// [remove_section]: IndexBufferVec::remove_section
+    pub fn remove_lods(&mut self, lods: &LodHandle) -> Option<Range<usize>> {
+        self.remove_section(&lods.key)
+    }
+
+    /// The number of indices in this vector that belong to a section.
+    ///
+    /// The difference with the vector's [len] is the number of indices in unused ranges left
+    /// behind by removed or relocated sections (and any indices that were not added as a section).
+    ///
+    /// [len]: IndexBufferVec::len
+    pub fn section_occupancy(&self) -> usize {
+        self.sections.values().map(|range| range.len()).sum()
+    }
+
+    /// Reads the index at position `index`, or returns an error if `index` is out of bounds or if
+    /// the vector is not resilient.
+    ///
+    /// WebGL cannot read index data back from the GPU, so the index is read from the CPU-side copy
+    /// that a resilient vector keeps (see [set_resilient]) and the returned future is ready
+    /// immediately. On a vector that is not resilient, fails with [BufferVecError::NotResilient].
+    /// See also [BufferVec::read_element].
+    ///
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    /// [BufferVec::read_element]: crate::BufferVec::read_element
+    pub fn read_element(&self, index: usize) -> Result<impl Future<Output = T>, BufferVecError> {
+        let range = checked_range(index, 1, self.len)?;
+        let shadow = self.shadow.as_ref().ok_or(BufferVecError::NotResilient)?;
+
+        Ok(ready(shadow[range.start]))
+    }
+
+    /// Reads the indices in the given `range`, or returns an error if the `range` is not within
+    /// the vector's length or if the vector is not resilient.
+    ///
+    /// WebGL cannot read index data back from the GPU, so the indices are read from the CPU-side
+    /// copy that a resilient vector keeps (see [set_resilient]) and the returned future is ready
+    /// immediately. On a vector that is not resilient, fails with [BufferVecError::NotResilient].
+    /// See also [BufferVec::read_range].
+    ///
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    /// [BufferVec::read_range]: crate::BufferVec::read_range
+    pub fn read_range(
+        &self,
+        range: Range<usize>,
+    ) -> Result<impl Future<Output = Vec<T>>, BufferVecError> {
+        let range = checked_read_range(range, self.len)?;
+        let shadow = self.shadow.as_ref().ok_or(BufferVecError::NotResilient)?;
+
+        Ok(ready(shadow[range].to_vec()))
+    }
+
+    /// Checks every index in this vector against `vertex_count`, the number of vertices the
+    /// indices refer to, or returns an error if the vector is not resilient.
+    ///
+    /// Resolves to `Ok(())` if all indices are in range, or to an error that lists the position
+    /// and value of every index that is greater than or equal to `vertex_count`; this is the same
+    /// convention as [validate_indexed_pair], which only checks the largest index. Intended as an
+    /// opt-in debugging tool that can also be used in release builds.
+    ///
+    /// WebGL cannot read index data back from the GPU, so the indices are checked in place in the
+    /// CPU-side copy that a resilient vector keeps (see [set_resilient]): no further copy of the
+    /// indices is made, however large the vector, and the returned future is ready immediately.
+    /// On a vector that is not resilient, fails with [BufferVecError::NotResilient].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # async fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_resilient(true);
+    /// indices.update([0u16, 1, 3, 2]);
+    ///
+    /// // Index `3` is out of range for 3 vertices.
+    /// assert_eq!(indices.validate_indices(3).unwrap().await, Err(vec![(2, 3)]));
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [validate_indexed_pair]: crate::validate_indexed_pair
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn validate_indices(
+        &self,
+        vertex_count: usize,
+    ) -> Result<impl Future<Output = Result<(), Vec<(usize, T)>>>, BufferVecError> {
+        let shadow = self.shadow.as_ref().ok_or(BufferVecError::NotResilient)?;
+        let out_of_range = find_out_of_range_indices(shadow, vertex_count);
+
+        Ok(ready(if out_of_range.is_empty() {
+            Ok(())
+        } else {
+            Err(out_of_range)
+        }))
+    }
+
+    /// Returns a view on the data in the buffer.
+    ///
+    /// The view covers the first [len] elements of the buffer. On a vector that has never been
+    /// updated (or that was updated with empty data) this is a valid, empty view, regardless of the
+    /// vector's capacity.
+    ///
+    /// If the vector is registered with a [MemoryBudget], marks the vector as used in the budget's
+    /// current frame.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// assert_eq!(indices.as_buffer_view().len(), 0);
+    ///
+    /// indices.update([1u16, 2u16, 3u16]);
+    ///
+    /// let view = indices.as_buffer_view();
+    ///
+    /// assert_eq!(view.len(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [len]: IndexBufferVec::len
+    pub fn as_buffer_view(&self) -> IndexBufferView<T>
+    where
+        T: Copy + 'static,
+    {
+        self.touch_budget();
+
+        unsafe { self.uninit_view(0..self.len).assume_init() }
+    }
+
+    /// Returns the parameters for drawing the indices in the given `range`.
+    ///
+    /// WebGlitz draw calls take the number of indices to draw, and start drawing at the start of
+    /// the bound index view. The returned [DrawParams] pair a [view] that starts at the start of
+    /// the `range` with the number of indices in the `range`, so that the `range` can be drawn with
+    /// `draw_indexed(params.count as usize, instance_count)` after binding `params.view`.
+    ///
+    /// If the vector is registered with a [MemoryBudget], marks the vector as used in the budget's
+    /// current frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `range` is not within the vector's length, or if its start or length does not
+    /// fit in a `u32`. See [try_draw_params] for a fallible version.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.update([0u16, 1, 2, 2, 3, 0]);
+    ///
+    /// let params = indices.draw_params(3..6);
+    ///
+    /// assert_eq!(params.first, 3);
+    /// assert_eq!(params.count, 3);
+    /// assert_eq!(params.view.len(), 3);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [view]: DrawParams::view
+    /// [try_draw_params]: IndexBufferVec::try_draw_params
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn draw_params(&self, range: Range<usize>) -> DrawParams<'_, T>
+    where
+        T: Copy + 'static,
+    {
+        self.try_draw_params(range)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.label.as_deref())))
+    }
+
+    /// Returns the parameters for drawing the indices in the given `range`, or returns an error if
+    /// the `range` is not within the vector's length, or if its start or length does not fit in a
+    /// `u32`.
+    ///
+    /// See [draw_params] for details.
+    ///
+    /// [draw_params]: IndexBufferVec::draw_params
+    pub fn try_draw_params(&self, range: Range<usize>) -> Result<DrawParams<'_, T>, BufferVecError>
+    where
+        T: Copy + 'static,
+    {
+        let (first, count) = checked_draw_range(range.clone(), self.len)?;
+
+        self.touch_budget();
+
+        Ok(DrawParams {
+            view: unsafe { self.uninit_view(range).assume_init() },
+            first,
+            count,
+        })
+    }
+
+    /// Returns the parameters for drawing all indices in the vector.
+    ///
+    /// See [draw_params] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector does not fit in a `u32`.
+    ///
+    /// [draw_params]: IndexBufferVec::draw_params
+    pub fn draw_params_all(&self) -> DrawParams<'_, T>
+    where
+        T: Copy + 'static,
+    {
+        self.draw_params(0..self.len)
+    }
+
+    /// Returns a view on the first `len` indices in the vector, or `None` if `len` is greater than
+    /// the length of the vector.
+    pub(crate) fn prefix_view(&self, len: usize) -> Option<IndexBufferView<T>> {
+        if len > self.len {
+            return None;
+        }
+
+        self.touch_budget();
+
+        Some(unsafe { self.uninit_view(0..len).assume_init() })
+    }
+}
+
+impl<Rc> IndexBufferVec<Rc, u32>
+where
+    Rc: RenderingContext + Clone,
+{
+    /// The number of bytes [compact_to_u16] would free, or `None` if the indices in this vector do
+    /// not fit in `u16` indices, or if the vector is not empty and not resilient (see
+    /// [set_resilient]).
+    ///
+    /// Intended for logging or for deciding whether compaction is worthwhile before consuming the
+    /// vector.
+    ///
+    /// [compact_to_u16]: IndexBufferVec::compact_to_u16
+    /// [set_resilient]: IndexBufferVec::set_resilient
+    pub fn compact_to_u16_savings(&self) -> Option<usize> {
+        if self.check_preservable().is_ok() && self.max_index().unwrap_or(0) <= u16::MAX as u32 {
+            Some(self.capacity() * (mem::size_of::<u32>() - mem::size_of::<u16>()))
+        } else {
+            None
+        }
+    }
+
+    /// Converts this vector into a vector of `u16` indices if its largest index fits in a `u16`,
+    /// or returns the vector unchanged otherwise, or if the vector is not empty and not resilient.
+    ///
+    /// The indices are narrowed from the CPU-side copy (see the [type documentation]) and uploaded
+    /// to a new buffer with the same capacity in elements, which takes half the memory. The new
+    /// vector keeps the label, sections, LOD sets, [MemoryBudget] registration, frame clock,
+    /// capacity limits and growth, shrink, orphaning and resilience settings of this vector; its
+    /// statistics start anew. Its [generation] is greater than that of this vector, so that bind
+    /// groups built from views on this vector are rebuilt. See [compact_to_u16_savings] for the
+    /// number of bytes saved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+    /// use web_glitz_buffer_vec::IndexBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+    ///
+    /// indices.set_resilient(true);
+    /// indices.update([0u32, 1, 70000]);
+    ///
+    /// // An index exceeds the maximum `u16` value, so the vector is returned unchanged.
+    /// let mut indices = indices.compact_to_u16().unwrap_err();
+    ///
+    /// indices.update([0u32, 1, 2]);
+    ///
+    /// assert_eq!(indices.compact_to_u16_savings(), Some(indices.capacity() * 2));
+    ///
+    /// let indices = indices.compact_to_u16().unwrap();
+    ///
+    /// assert_eq!(indices.max_index(), Some(2u16));
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [type documentation]: IndexBufferVec
+    /// [generation]: IndexBufferVec::generation
+    /// [compact_to_u16_savings]: IndexBufferVec::compact_to_u16_savings
+    // Like `Rc::try_unwrap`, hands the vector back by value if it cannot be converted.
+    #[allow(clippy::result_large_err)]
+    pub fn compact_to_u16(mut self) -> Result<IndexBufferVec<Rc, u16>, Self> {
+        if self.compact_to_u16_savings().is_none() {
+            return Err(self);
+        }
+
+        // Only an empty vector that is not resilient gets here, see `compact_to_u16_savings`.
+        let narrowed: Vec<u16> = self
+            .shadow
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|&index| {
+                u16::try_from(index).unwrap_or_else(|_| {
+                    bug(format_args!(
+                        "index {} exceeds the tracked maximum index {:?}",
+                        index,
+                        self.max_index()
+                    ))
+                })
+            })
+            .collect();
+
+        let mut compacted = IndexBufferVec::with_capacity(
+            self.context.clone(),
+            self.buffer.usage_hint(),
+            self.capacity(),
+        );
+
+        compacted.label = self.label.take();
+        compacted.max_capacity = self.max_capacity;
+        compacted.max_buffer_size = self.max_buffer_size;
+        compacted.growth = self.growth.clone();
+        compacted.capacity_alignment = self.capacity_alignment;
+        compacted.shrink = mem::replace(&mut self.shrink, Box::new(Never));
+        compacted.orphaning = self.orphaning;
+        compacted.shadow = self.shadow.as_ref().map(|_| Vec::new());
+        compacted.frame_clock = self.frame_clock.take();
+        compacted.generation = compacted.generation.max(self.generation + 1);
+
+        // Keep the budget registration, which identifies the vector by its source ID.
+        compacted.source_id = self.source_id;
+        compacted.budget = self.budget.take();
+        compacted.sync_budget();
+
+        // Replacing the data removes all sections, so restore them afterwards.
+        compacted.update(UploadData(narrowed));
+        compacted.sections = mem::take(&mut self.sections);
+        compacted.lods = mem::take(&mut self.lods);
+        compacted.debug_check();
+
+        Ok(compacted)
+    }
+}
+
+/// Identifies a set of levels of detail stored in an [IndexBufferVec] with
+/// [IndexBufferVec::insert_lods].
+///
+/// A handle refers to the set by its section key: inserting another set or section with the same
+/// key replaces the set the handle refers to.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct LodHandle {
+    key: String,
+}
+
+impl LodHandle {
+    /// The key of the section that holds the set.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+/// The parameters for drawing a range of the indices in an [IndexBufferVec], as returned by
+/// [IndexBufferVec::draw_params].
+pub struct DrawParams<'a, T> {
+    /// A view on the indices in the range, to be bound as the index buffer of the draw call.
+    pub view: IndexBufferView<'a, T>,
+
+    /// The position of the first index of the range in the vector.
+    pub first: u32,
+
+    /// The number of indices in the range.
+    pub count: u32,
+}
+
+impl<Rc, T> fmt::Debug for IndexBufferVec<Rc, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IndexBufferVec")
+            .field("label", &self.label)
+            .field("len", &self.len)
+            .field("capacity", &self.buffer.len())
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// Triangle index data, viewed as a flat slice of indices.
+struct Triangles<T, D> {
+    data: D,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, D> Triangles<T, D> {
+    fn new(data: D) -> Self {
+        Triangles {
+            data,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, D> Borrow<[T]> for Triangles<T, D>
+where
+    D: Borrow<[[T; 3]]>,
+{
+    fn borrow(&self) -> &[T] {
+        let tris = self.data.borrow();
+
+        // An array of 3 `T`s has the same layout as 3 consecutive `T`s.
+        unsafe { slice::from_raw_parts(tris.as_ptr() as *const T, tris.len() * 3) }
+    }
+}
+
+/// Returns a command that uploads the given `data` to the `view`.
+fn upload_command<T, D>(
+    view: IndexBufferView<'_, MaybeUninit<T>>,
+    data: D,
+) -> impl GpuTask<Connection, Output = ()>
+where
+    T: IndexValue + 'static,
+    D: Borrow<[T]> + Send + Sync + 'static,
+{
+    unsafe {
+        // Note: the view data range is not actually guaranteed to be initialized, but we're only
+        // writing, not reading.
+        view.assume_init().upload_command(data)
+    }
+}
+
+/// Returns a copy of the triangle list `data` with the given `winding`.
+///
+/// # Panics
+///
+/// Panics if the `winding` is [Winding::Flipped] and the length of `data` is not a multiple of 3.
+fn with_winding<T>(data: &[T], winding: Winding) -> Vec<T>
+where
+    T: Copy,
+{
+    match winding {
+        Winding::AsIs => data.to_vec(),
+        Winding::Flipped => {
+            let mut flipped = Vec::with_capacity(data.len());
+
+            flip_winding(data, &mut flipped);
+
+            flipped
+        }
+    }
+}
+
+/// Returns the range of each of the `lods` when they are stored back to back.
+fn lod_ranges<T>(lods: &[&[T]]) -> Vec<Range<usize>> {
+    let mut start = 0;
+
+    lods.iter()
+        .map(|lod| {
+            let range = start..start + lod.len();
+
+            start = range.end;
+
+            range
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lod_ranges() {
+        assert_eq!(lod_ranges::<u16>(&[]), vec![]);
+        assert_eq!(
+            lod_ranges::<u16>(&[&[0, 1, 2, 2, 3, 0], &[], &[0, 1, 2]]),
+            vec![0..6, 6..6, 6..9]
+        );
+    }
+
+    #[test]
+    fn test_triangles_borrow_flattens() {
+        let tris = Triangles::new(vec![[0u16, 1, 2], [2, 3, 0]]);
+        let indices: &[u16] = tris.borrow();
 
-        unsafe { buffer.get(0..*len).unwrap().assume_init() }
+        assert_eq!(indices, &[0, 1, 2, 2, 3, 0]);
     }
 }
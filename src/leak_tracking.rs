@@ -0,0 +1,210 @@
+#[cfg(feature = "leak-tracking")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "leak-tracking")]
+static LIVE_BUFFER_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "leak-tracking")]
+static LIVE_BUFFER_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of GPU buffers this crate currently keeps alive.
+///
+/// Counts the buffers owned by vectors (including those wrapped by the other vector types), by
+/// [GpuSnapshot]s and by [SlicedUpload]s in progress. Buffers with a capacity of `0` are not
+/// counted, as they hold no GPU memory; a [destroyed] vector therefore no longer counts. The
+/// temporary buffers used to stage copies within a buffer are released before the operation
+/// returns and are not counted either. Only available with the `leak-tracking` feature.
+///
+/// The count is global to the process. A buffer that is ever handed out of this crate by value
+/// leaves this crate's accounting: it is no longer counted, even while it stays alive.
+///
+/// [GpuSnapshot]: crate::GpuSnapshot
+/// [SlicedUpload]: crate::SlicedUpload
+/// [destroyed]: crate::BufferVec::destroy
+#[cfg(feature = "leak-tracking")]
+pub fn live_buffer_count() -> usize {
+    LIVE_BUFFER_COUNT.load(Ordering::Relaxed)
+}
+
+/// The total size in bytes of the GPU buffers counted by [live_buffer_count].
+///
+/// Only available with the `leak-tracking` feature.
+#[cfg(feature = "leak-tracking")]
+pub fn live_buffer_bytes() -> usize {
+    LIVE_BUFFER_BYTES.load(Ordering::Relaxed)
+}
+
+/// Asserts that the GPU buffers this crate keeps alive return to the count and size they had when
+/// the guard was created.
+///
+/// Intended for tests: create a guard at the start of a test, and any vector that was created
+/// during the test and is still alive (for example because it was stored in a cache that is never
+/// cleared) makes the guard panic when it is dropped. Only available with the `leak-tracking`
+/// feature.
+///
+/// The counters are global, so a guard also observes buffers allocated or released by other
+/// threads; tests that use guards should not run concurrently with other tests that allocate
+/// buffers (e.g. run them with `--test-threads=1`).
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+/// use web_glitz_buffer_vec::{BufferVec, LeakGuard};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let guard = LeakGuard::new();
+///
+/// {
+///     let mut vec = BufferVec::new(context, UsageHint::StaticDraw);
+///
+///     vec.update([1, 2, 3]);
+///
+///     assert_eq!(guard.leaked_count(), 1);
+/// }
+///
+/// // The vector was dropped, so dropping the guard does not panic.
+/// drop(guard);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+#[cfg(feature = "leak-tracking")]
+#[must_use = "the guard asserts the counts when it is dropped"]
+#[derive(Debug)]
+pub struct LeakGuard {
+    count: usize,
+    bytes: usize,
+}
+
+#[cfg(feature = "leak-tracking")]
+impl LeakGuard {
+    /// Creates a new guard that records the current [live_buffer_count] and [live_buffer_bytes]
+    /// as its baseline.
+    pub fn new() -> Self {
+        LeakGuard {
+            count: live_buffer_count(),
+            bytes: live_buffer_bytes(),
+        }
+    }
+
+    /// The number of buffers alive beyond the baseline of this guard; negative if buffers that
+    /// were alive when the guard was created have since been released.
+    pub fn leaked_count(&self) -> isize {
+        live_buffer_count() as isize - self.count as isize
+    }
+
+    /// The number of bytes alive beyond the baseline of this guard, see [leaked_count].
+    ///
+    /// [leaked_count]: LeakGuard::leaked_count
+    pub fn leaked_bytes(&self) -> isize {
+        live_buffer_bytes() as isize - self.bytes as isize
+    }
+}
+
+#[cfg(feature = "leak-tracking")]
+impl Default for LeakGuard {
+    fn default() -> Self {
+        LeakGuard::new()
+    }
+}
+
+#[cfg(feature = "leak-tracking")]
+impl Drop for LeakGuard {
+    fn drop(&mut self) {
+        let count = self.leaked_count();
+        let bytes = self.leaked_bytes();
+
+        // Don't turn a failing test into an abort by panicking while unwinding.
+        if (count != 0 || bytes != 0) && !std::thread::panicking() {
+            panic!(
+                "Leak guard found {} more live GPU buffers ({} more bytes) than when it was \
+                 created.",
+                count, bytes
+            );
+        }
+    }
+}
+
+/// Accounts for a GPU buffer of a given size for as long as it is alive; owned next to the
+/// buffer it accounts for and replaced along with it.
+///
+/// Does nothing without the `leak-tracking` feature.
+pub(crate) struct LiveBuffer {
+    #[cfg(feature = "leak-tracking")]
+    bytes: Option<usize>,
+}
+
+impl LiveBuffer {
+    /// Starts accounting for a buffer of `bytes` bytes; buffers of `0` bytes are not counted.
+    pub(crate) fn new(bytes: usize) -> Self {
+        #[cfg(feature = "leak-tracking")]
+        {
+            let bytes = if bytes > 0 {
+                LIVE_BUFFER_COUNT.fetch_add(1, Ordering::Relaxed);
+                LIVE_BUFFER_BYTES.fetch_add(bytes, Ordering::Relaxed);
+
+                Some(bytes)
+            } else {
+                None
+            };
+
+            LiveBuffer { bytes }
+        }
+
+        #[cfg(not(feature = "leak-tracking"))]
+        {
+            let _ = bytes;
+
+            LiveBuffer {}
+        }
+    }
+}
+
+#[cfg(feature = "leak-tracking")]
+impl Drop for LiveBuffer {
+    fn drop(&mut self) {
+        if let Some(bytes) = self.bytes {
+            LIVE_BUFFER_COUNT.fetch_sub(1, Ordering::Relaxed);
+            LIVE_BUFFER_BYTES.fetch_sub(bytes, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "leak-tracking"))]
+mod tests {
+    use super::*;
+
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[test]
+    fn test_leak_guard() {
+        let guard = LeakGuard::new();
+        let live = LiveBuffer::new(64);
+        let empty = LiveBuffer::new(0);
+
+        assert_eq!(guard.leaked_count(), 1);
+        assert_eq!(guard.leaked_bytes(), 64);
+
+        drop(empty);
+        drop(live);
+        drop(guard);
+
+        let live = LiveBuffer::new(16);
+        let result = catch_unwind(AssertUnwindSafe(|| drop(LeakGuard::new())));
+
+        assert!(result.is_ok());
+
+        let guard = LeakGuard::new();
+        let leaked = LiveBuffer::new(16);
+        let result = catch_unwind(AssertUnwindSafe(|| drop(guard)));
+
+        assert!(result.is_err());
+
+        drop(leaked);
+        drop(live);
+    }
+}
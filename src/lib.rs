@@ -4,11 +4,131 @@
 //!
 //! For generic data, see [BufferVec]. For data that may be bound as vertex index data in draw
 //! tasks, see [IndexBufferVec].
+//!
+//! # Features
+//!
+//! - `debug-validate`: checks the internal invariants of [BufferVec] and [IndexBufferVec] at the
+//!   end of mutating methods in debug builds, panicking on violations (see
+//!   [BufferVec::debug_validate]). Without it, invariants are only checked on demand.
+//! - `leak-tracking`: counts the GPU buffers this crate keeps alive (see [live_buffer_count]) and
+//!   adds [LeakGuard] for asserting in tests that vectors are released.
+//! - `log`: logs a warning with the [log](https://crates.io/crates/log) crate when a vector
+//!   reallocates suspiciously often (see [BufferVec::set_realloc_warning]).
+//! - `tracing`: emits [tracing](https://crates.io/crates/tracing) spans around updates and
+//!   warn-level events when a vector allocates a new buffer.
+//! - `half`: adds [BufferVec::update_from_f32] for uploading `f32` data as
+//!   [half](https://crates.io/crates/half) precision `f16` elements.
+//! - `serde`: implements `serde::Serialize` for [DebugInfo].
+//! - `stats`: records histograms of update lengths and reallocation capacities per vector (see
+//!   [BufferVec::stats_histograms]).
+//! - `wasm-test`: adds the [wasm_test] module with fixtures for testing against a real WebGL2
+//!   context in the browser.
+
+mod budget;
+pub use self::budget::{BudgetEntry, MemoryBudget};
 
 mod buffer_vec;
 pub use self::buffer_vec::BufferVec;
 
+mod debug_info;
+pub use self::debug_info::DebugInfo;
+
+mod double_buffered_vec;
+pub use self::double_buffered_vec::DoubleBufferedVec;
+
+mod encoder;
+pub use self::encoder::{Snorm16x2, Unorm8x4, VertexEncoder};
+
+mod error;
+pub use self::error::{BufferVecError, CapacityError};
+
+mod frame_sync;
+pub use self::frame_sync::{FrameClock, FrameSync};
+
+mod gpu_safe;
+pub use self::gpu_safe::GpuSafe;
+
+pub mod growth;
+
+mod index;
+pub use self::index::IndexValue;
+
 mod index_buffer_vec;
-pub use self::index_buffer_vec::IndexBufferVec;
+pub use self::index_buffer_vec::{DrawParams, IndexBufferVec, LodHandle};
+
+mod leak_tracking;
+#[cfg(feature = "leak-tracking")]
+pub use self::leak_tracking::{live_buffer_bytes, live_buffer_count, LeakGuard};
+
+mod maintenance;
+pub use self::maintenance::{MaintenanceBudget, MaintenanceReport};
+
+mod matrix_buffer_vec;
+pub use self::matrix_buffer_vec::{InstanceMatrix, MatrixBufferVec};
+
+mod mesh_buffers;
+pub use self::mesh_buffers::{MeshBuffers, WeldStats};
+
+mod mirrored_buffer_vec;
+pub use self::mirrored_buffer_vec::{EditGuard, MirroredBufferVec, TrackedIterMut, TrackedMut};
+
+mod multi_mesh_batch;
+pub use self::multi_mesh_batch::{BatchEntry, MultiMeshBatch};
+
+mod quad_batch;
+pub use self::quad_batch::{QuadBatch, QuadIndexView};
+
+mod realloc_guard;
+pub use self::realloc_guard::{ReallocGuard, ReallocViolation};
+
+mod registry;
+pub use self::registry::{ManagedVec, MemoryRegistry, RecoveryPlan, RecoveryProgress};
+
+mod shared_buffer_vec;
+pub use self::shared_buffer_vec::SharedBufferVec;
+
+mod sliced_upload;
+pub use self::sliced_upload::{SlicedLen, SlicedProgress, SlicedUpload, UploadProgress};
+
+mod snapshot;
+pub use self::snapshot::GpuSnapshot;
+
+mod staged_buffer_vec;
+pub use self::staged_buffer_vec::StagedBufferVec;
+
+mod stats;
+pub use self::stats::VecStats;
+#[cfg(feature = "stats")]
+pub use self::stats::{CapacityHistograms, Log2Histogram};
+
+mod strided_buffer_vec;
+pub use self::strided_buffer_vec::StridedBufferVec;
+
+mod uniform_buffer_vec;
+pub use self::uniform_buffer_vec::{Std140Element, UniformBufferVec};
+
+mod update_log;
+pub use self::update_log::{replay_capacity, UpdateRecord};
+
+mod update_transaction;
+pub use self::update_transaction::UpdateTransaction;
+
+mod upload_queue;
+pub use self::upload_queue::{UploadQueue, UploadReport, UploadTicket};
+
+mod upload_verification;
+pub use self::upload_verification::UploadMismatch;
+
+mod validation;
+pub use self::validation::{validate_indexed_pair, IndexedDrawError, InvariantViolation};
+
+mod topology;
+pub use self::topology::{PolygonIndices, Winding};
+
+mod vertex_stream;
+pub use self::vertex_stream::VertexStream;
+
+#[cfg(feature = "wasm-test")]
+pub mod wasm_test;
 
 mod util;
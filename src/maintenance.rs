@@ -0,0 +1,193 @@
+use std::ops::{Add, AddAssign};
+
+/// Limits the work done by a single call to `maintenance` on one of the vector types.
+///
+/// Maintenance, such as shrinking a vector that uses only a small part of its capacity, moves
+/// data between GPU buffers. The budget caps the number of bytes moved; work that does not fit in
+/// the budget is left for a later call. A zero budget does no work at all.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MaintenanceBudget {
+    /// The maximum number of bytes that may be moved.
+    pub max_bytes_moved: usize,
+}
+
+impl MaintenanceBudget {
+    /// A budget that does no work, for querying how much work is pending.
+    pub const ZERO: MaintenanceBudget = MaintenanceBudget { max_bytes_moved: 0 };
+
+    /// A budget that does all pending work.
+    pub const UNLIMITED: MaintenanceBudget = MaintenanceBudget {
+        max_bytes_moved: usize::MAX,
+    };
+
+    /// Creates a new budget that moves at most `max_bytes_moved` bytes.
+    pub fn new(max_bytes_moved: usize) -> Self {
+        MaintenanceBudget { max_bytes_moved }
+    }
+
+    /// Returns `true` if work that moves `bytes` bytes fits in this budget.
+    ///
+    /// No work fits in a zero budget, not even work that moves no bytes.
+    pub(crate) fn allows(&self, bytes: usize) -> bool {
+        self.max_bytes_moved > 0 && bytes <= self.max_bytes_moved
+    }
+}
+
+/// Reports the work done by a call to `maintenance` on one of the vector types, and the work that
+/// remains.
+///
+/// Reports for several vectors may be combined with `+`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct MaintenanceReport {
+    /// The number of bytes that were moved.
+    pub bytes_moved: usize,
+
+    /// The number of bytes that were released by shrinking buffers.
+    pub bytes_released: usize,
+
+    /// The number of new buffers that were allocated.
+    pub reallocations: usize,
+
+    /// The number of pending tasks that did not fit in the budget.
+    pub remaining_tasks: usize,
+
+    /// The number of bytes that the pending tasks that did not fit in the budget would move.
+    pub remaining_bytes: usize,
+}
+
+impl MaintenanceReport {
+    /// Returns `true` if no work remains.
+    pub fn is_done(&self) -> bool {
+        self.remaining_tasks == 0
+    }
+}
+
+impl Add for MaintenanceReport {
+    type Output = MaintenanceReport;
+
+    fn add(self, other: MaintenanceReport) -> MaintenanceReport {
+        MaintenanceReport {
+            bytes_moved: self.bytes_moved + other.bytes_moved,
+            bytes_released: self.bytes_released + other.bytes_released,
+            reallocations: self.reallocations + other.reallocations,
+            remaining_tasks: self.remaining_tasks + other.remaining_tasks,
+            remaining_bytes: self.remaining_bytes + other.remaining_bytes,
+        }
+    }
+}
+
+impl AddAssign for MaintenanceReport {
+    fn add_assign(&mut self, other: MaintenanceReport) {
+        *self = *self + other;
+    }
+}
+
+/// Plans shrinking a buffer of `capacity` elements of `element_size` bytes, `len` of which are in
+/// use, to the `target` capacity suggested by `suggest_shrink`, within the `budget`.
+///
+/// Returns the capacity to shrink to, if any, along with the report for the plan.
+pub(crate) fn plan_shrink(
+    capacity: usize,
+    len: usize,
+    element_size: usize,
+    target: Option<usize>,
+    budget: MaintenanceBudget,
+) -> (Option<usize>, MaintenanceReport) {
+    let target = match target {
+        Some(target) if target < capacity => target.max(len),
+        _ => return (None, MaintenanceReport::default()),
+    };
+
+    let bytes = len * element_size;
+
+    if budget.allows(bytes) {
+        let report = MaintenanceReport {
+            bytes_moved: bytes,
+            bytes_released: (capacity - target) * element_size,
+            reallocations: 1,
+            ..MaintenanceReport::default()
+        };
+
+        (Some(target), report)
+    } else {
+        let report = MaintenanceReport {
+            remaining_tasks: 1,
+            remaining_bytes: bytes,
+            ..MaintenanceReport::default()
+        };
+
+        (None, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_shrink_nothing_pending() {
+        assert_eq!(
+            plan_shrink(64, 10, 4, None, MaintenanceBudget::UNLIMITED),
+            (None, MaintenanceReport::default())
+        );
+    }
+
+    #[test]
+    fn test_plan_shrink_within_budget() {
+        let (target, report) = plan_shrink(64, 10, 4, Some(16), MaintenanceBudget::new(40));
+
+        assert_eq!(target, Some(16));
+        assert_eq!(report.bytes_moved, 40);
+        assert_eq!(report.bytes_released, 48 * 4);
+        assert_eq!(report.reallocations, 1);
+        assert!(report.is_done());
+    }
+
+    #[test]
+    fn test_plan_shrink_exceeds_budget() {
+        let (target, report) = plan_shrink(64, 10, 4, Some(16), MaintenanceBudget::new(39));
+
+        assert_eq!(target, None);
+        assert_eq!(report.bytes_moved, 0);
+        assert_eq!(report.remaining_tasks, 1);
+        assert_eq!(report.remaining_bytes, 40);
+    }
+
+    #[test]
+    fn test_plan_shrink_zero_budget_does_nothing() {
+        // Even an empty vector, which moves no bytes, is not shrunk with a zero budget.
+        let (target, report) = plan_shrink(64, 0, 4, Some(0), MaintenanceBudget::ZERO);
+
+        assert_eq!(target, None);
+        assert_eq!(report.reallocations, 0);
+        assert!(!report.is_done());
+    }
+
+    #[test]
+    fn test_report_add() {
+        let mut report = MaintenanceReport {
+            bytes_moved: 1,
+            bytes_released: 2,
+            reallocations: 1,
+            remaining_tasks: 0,
+            remaining_bytes: 0,
+        };
+
+        report += MaintenanceReport {
+            remaining_tasks: 1,
+            remaining_bytes: 5,
+            ..report
+        };
+
+        assert_eq!(
+            report,
+            MaintenanceReport {
+                bytes_moved: 2,
+                bytes_released: 4,
+                reallocations: 2,
+                remaining_tasks: 1,
+                remaining_bytes: 5,
+            }
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use std::ops::{Deref, DerefMut};
+
+use web_glitz::buffer::UsageHint;
+use web_glitz::pipeline::graphics::attribute_format::VertexAttributeFormat;
+use web_glitz::pipeline::graphics::{InputRate, Vertex, VertexAttributeDescriptor};
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::util::error_message;
+use crate::BufferVec;
+
+/// A column-major 4x4 matrix that is bound as per-instance vertex data to the four consecutive
+/// attribute locations starting at `LOCATION`.
+///
+/// WebGL has no matrix vertex attributes: a `mat4` attribute in a shader occupies four consecutive
+/// attribute locations, one `vec4` per column. This type describes that layout to WebGlitz: column
+/// `i` is bound to location `LOCATION + i`, at a byte offset of `16 * i`, with a stride of 64 bytes
+/// and a per-instance input rate. For example, for `InstanceMatrix<2>` the shader may declare:
+///
+/// ```glsl
+/// layout(location=2) in mat4 model;
+/// ```
+///
+/// Converts from the two common matrix layouts, `[f32; 16]` and `[[f32; 4]; 4]`, both of which are
+/// interpreted as column-major.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct InstanceMatrix<const LOCATION: u32> {
+    /// The columns of the matrix.
+    pub columns: [[f32; 4]; 4],
+}
+
+unsafe impl<const LOCATION: u32> Vertex for InstanceMatrix<LOCATION> {
+    const INPUT_RATE: InputRate = InputRate::PerInstance;
+
+    const ATTRIBUTE_DESCRIPTORS: &'static [VertexAttributeDescriptor] = &[
+        VertexAttributeDescriptor {
+            location: LOCATION,
+            offset_in_bytes: 0,
+            format: VertexAttributeFormat::Float4_f32,
+        },
+        VertexAttributeDescriptor {
+            location: LOCATION + 1,
+            offset_in_bytes: 16,
+            format: VertexAttributeFormat::Float4_f32,
+        },
+        VertexAttributeDescriptor {
+            location: LOCATION + 2,
+            offset_in_bytes: 32,
+            format: VertexAttributeFormat::Float4_f32,
+        },
+        VertexAttributeDescriptor {
+            location: LOCATION + 3,
+            offset_in_bytes: 48,
+            format: VertexAttributeFormat::Float4_f32,
+        },
+    ];
+}
+
+impl<const LOCATION: u32> From<[[f32; 4]; 4]> for InstanceMatrix<LOCATION> {
+    fn from(columns: [[f32; 4]; 4]) -> Self {
+        InstanceMatrix { columns }
+    }
+}
+
+impl<const LOCATION: u32> From<[f32; 16]> for InstanceMatrix<LOCATION> {
+    fn from(elements: [f32; 16]) -> Self {
+        let mut columns = [[0.0; 4]; 4];
+
+        for (column, chunk) in columns.iter_mut().zip(elements.chunks_exact(4)) {
+            column.copy_from_slice(chunk);
+        }
+
+        InstanceMatrix { columns }
+    }
+}
+
+/// A [BufferVec] of column-major 4x4 matrices, bound as per-instance data to the four attribute
+/// locations starting at `LOCATION`.
+///
+/// The attribute location is part of the type because WebGlitz describes vertex layouts with
+/// constants, see [InstanceMatrix]. Dereferences to the wrapped [BufferVec], so that the matrices
+/// may be bound with [BufferVec::as_buffer_view], or together with per-vertex data with a
+/// [VertexStream].
+///
+/// # Example
+///
+/// ```
+/// # #![feature(const_fn, const_maybe_uninit_as_ptr, const_ptr_offset_from, const_raw_ptr_deref, ptr_offset_from)]
+/// # use web_glitz::rendering::DefaultRGBBuffer;
+/// # use web_glitz::rendering::DefaultRenderTarget;
+/// # use web_glitz::pipeline::graphics::{GraphicsPipeline, Vertex};
+/// # use web_glitz::runtime::RenderingContext;
+/// # use web_glitz_buffer_vec::BufferVec;
+/// use web_glitz::buffer::UsageHint;
+/// use web_glitz_buffer_vec::{InstanceMatrix, MatrixBufferVec, VertexStream};
+///
+/// # fn wrapper<Rc, V>(
+/// #     context: Rc,
+/// #     mut render_target: DefaultRenderTarget<DefaultRGBBuffer, ()>,
+/// #     graphics_pipeline: GraphicsPipeline<(V, InstanceMatrix<1>), (), ()>,
+/// #     vertices: BufferVec<Rc, V>,
+/// # )
+/// # where
+/// #     Rc: RenderingContext,
+/// #     V: Vertex + Copy + Send + Sync + 'static,
+/// # {
+/// # let resources = ();
+/// // The vertex shader declares `layout(location=1) in mat4 model;`.
+/// let mut models: MatrixBufferVec<Rc, 1> = MatrixBufferVec::new(context, UsageHint::StreamDraw);
+///
+/// let identity = [
+///     1.0, 0.0, 0.0, 0.0,
+///     0.0, 1.0, 0.0, 0.0,
+///     0.0, 0.0, 1.0, 0.0,
+///     0.0, 0.0, 0.0, 1.0,
+/// ];
+///
+/// models.update_from_mat4s(&[identity, identity]);
+///
+/// let stream = VertexStream::new(&vertices, &models);
+/// let vertex_count = stream.min_vertex_count();
+/// let instance_count = stream.instance_count();
+///
+/// let render_pass = render_target.create_render_pass(|framebuffer| {
+///     framebuffer.pipeline_task(&graphics_pipeline, |active_pipeline| {
+///         active_pipeline.task_builder()
+///             .bind_vertex_buffers(stream)
+///             .bind_resources(resources)
+///             .draw(vertex_count, instance_count)
+///             .finish()
+///     })
+/// });
+/// # }
+/// ```
+///
+/// [VertexStream]: crate::VertexStream
+pub struct MatrixBufferVec<Rc, const LOCATION: u32> {
+    inner: BufferVec<Rc, InstanceMatrix<LOCATION>>,
+}
+
+impl<Rc, const LOCATION: u32> MatrixBufferVec<Rc, LOCATION>
+where
+    Rc: RenderingContext,
+{
+    /// Creates a new matrix vector with 0 capacity for the given [RenderingContext].
+    ///
+    /// See [BufferVec::new] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        MatrixBufferVec {
+            inner: BufferVec::new(context, usage),
+        }
+    }
+
+    /// Creates a new matrix vector with the specified `capacity` for the given
+    /// [RenderingContext].
+    ///
+    /// See [BufferVec::with_capacity] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn with_capacity(context: Rc, usage: UsageHint, capacity: usize) -> Self {
+        MatrixBufferVec {
+            inner: BufferVec::with_capacity(context, usage, capacity),
+        }
+    }
+
+    /// Replaces the matrices in the buffer with the given `matrices`, resizing the buffer if
+    /// necessary.
+    ///
+    /// Accepts column-major matrices as either `[f32; 16]` or `[[f32; 4]; 4]`. Returns `true` if a
+    /// new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [BufferVec::update].
+    pub fn update_from_mat4s<M>(&mut self, matrices: &[M]) -> bool
+    where
+        M: Into<InstanceMatrix<LOCATION>> + Copy,
+    {
+        self.try_update_from_mat4s(matrices)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.inner.label())))
+    }
+
+    /// Replaces the matrices in the buffer with the given `matrices`, resizing the buffer if
+    /// necessary, or returns an error if a new buffer could not be allocated.
+    ///
+    /// See [update_from_mat4s](MatrixBufferVec::update_from_mat4s) for details.
+    pub fn try_update_from_mat4s<M>(&mut self, matrices: &[M]) -> Result<bool, BufferVecError>
+    where
+        M: Into<InstanceMatrix<LOCATION>> + Copy,
+    {
+        let data: Vec<InstanceMatrix<LOCATION>> =
+            matrices.iter().map(|matrix| (*matrix).into()).collect();
+
+        self.inner.try_update(data)
+    }
+
+    /// Consumes the matrix vector, returning the wrapped [BufferVec].
+    pub fn into_inner(self) -> BufferVec<Rc, InstanceMatrix<LOCATION>> {
+        self.inner
+    }
+}
+
+impl<Rc, const LOCATION: u32> Deref for MatrixBufferVec<Rc, LOCATION> {
+    type Target = BufferVec<Rc, InstanceMatrix<LOCATION>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<Rc, const LOCATION: u32> DerefMut for MatrixBufferVec<Rc, LOCATION> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_matrix_layout() {
+        let descriptors = <InstanceMatrix<3> as Vertex>::ATTRIBUTE_DESCRIPTORS;
+
+        assert_eq!(std::mem::size_of::<InstanceMatrix<3>>(), 64);
+        assert_eq!(
+            <InstanceMatrix<3> as Vertex>::INPUT_RATE,
+            InputRate::PerInstance
+        );
+
+        for (i, descriptor) in descriptors.iter().enumerate() {
+            assert_eq!(descriptor.location, 3 + i as u32);
+            assert_eq!(descriptor.offset_in_bytes as usize, 16 * i);
+            assert_eq!(descriptor.format, VertexAttributeFormat::Float4_f32);
+        }
+
+        assert_eq!(descriptors.len(), 4);
+    }
+
+    #[test]
+    fn test_instance_matrix_from_flat_is_column_major() {
+        let mut flat = [0.0; 16];
+
+        for (i, element) in flat.iter_mut().enumerate() {
+            *element = i as f32;
+        }
+
+        let matrix: InstanceMatrix<0> = flat.into();
+
+        assert_eq!(matrix.columns[1], [4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(matrix, InstanceMatrix::from(matrix.columns));
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use web_glitz::buffer::UsageHint;
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::index::IndexValue;
+use crate::util::{error_message, UploadData};
+use crate::{BufferVec, IndexBufferVec};
+
+/// The result of welding a vertex soup with [MeshBuffers::update_from_soup].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WeldStats {
+    /// The number of vertices in the soup; this is also the number of indices uploaded.
+    pub input_vertices: usize,
+
+    /// The number of distinct vertices that remained after welding; this is the number of
+    /// vertices uploaded.
+    pub unique_vertices: usize,
+}
+
+/// The vertices and indices of a single indexed mesh, backed by a [BufferVec] and an
+/// [IndexBufferVec].
+///
+/// Mainly useful to build an indexed mesh from un-indexed vertex data (a "triangle soup", such as
+/// is produced by many importers) with [update_from_soup], which welds duplicate vertices.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use web_glitz_buffer_vec::MeshBuffers;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut mesh: MeshBuffers<_, [i32; 2], u16> = MeshBuffers::new(context, UsageHint::StaticDraw);
+///
+/// // Two triangles that share an edge.
+/// let stats = mesh.update_from_soup(&[[0, 0], [1, 0], [0, 1], [0, 1], [1, 0], [1, 1]]);
+///
+/// assert_eq!(stats.unique_vertices, 4);
+/// assert_eq!(mesh.vertices().len(), 4);
+/// assert_eq!(mesh.indices().len(), 6);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [update_from_soup]: MeshBuffers::update_from_soup
+pub struct MeshBuffers<Rc, V, I> {
+    vertices: BufferVec<Rc, V>,
+    indices: IndexBufferVec<Rc, I>,
+}
+
+impl<Rc, V, I> MeshBuffers<Rc, V, I>
+where
+    Rc: RenderingContext + Clone,
+    V: Copy + Send + Sync + 'static,
+    I: IndexValue + 'static,
+{
+    /// Creates a new empty mesh for the given [RenderingContext], backed by buffers with the given
+    /// `usage` hint.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        MeshBuffers {
+            vertices: BufferVec::new(context.clone(), usage),
+            indices: IndexBufferVec::new(context, usage),
+        }
+    }
+
+    /// Replaces the data of the mesh with the welded `soup`, an un-indexed list of vertices.
+    ///
+    /// Equal vertices are merged into a single vertex: each distinct vertex is uploaded once, in
+    /// the order of its first occurrence in the `soup`, and one index is uploaded for every vertex
+    /// in the `soup`. The indices therefore describe the same primitives as the `soup` (e.g. a
+    /// triangle list for a triangle soup).
+    ///
+    /// To weld vertices that are not [Eq] (such as vertices with floating point attributes), see
+    /// [update_from_soup_by_key].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of distinct vertices cannot be indexed with the index type `I`, or if
+    /// either buffer needs to grow and cannot (see [BufferVec::update]). See
+    /// [try_update_from_soup] for a fallible version.
+    ///
+    /// [update_from_soup_by_key]: MeshBuffers::update_from_soup_by_key
+    /// [try_update_from_soup]: MeshBuffers::try_update_from_soup
+    pub fn update_from_soup(&mut self, soup: &[V]) -> WeldStats
+    where
+        V: Eq + Hash,
+    {
+        self.update_from_soup_by_key(soup, |vertex| *vertex)
+    }
+
+    /// Replaces the data of the mesh with the welded `soup`, or returns an error if the number of
+    /// distinct vertices cannot be indexed with the index type `I`, or if either buffer needs to
+    /// grow and cannot.
+    ///
+    /// If the vertices cannot be indexed, nothing is uploaded. See [update_from_soup] for details.
+    ///
+    /// [update_from_soup]: MeshBuffers::update_from_soup
+    pub fn try_update_from_soup(&mut self, soup: &[V]) -> Result<WeldStats, BufferVecError>
+    where
+        V: Eq + Hash,
+    {
+        self.try_update_from_soup_by_key(soup, |vertex| *vertex)
+    }
+
+    /// Replaces the data of the mesh with the `soup`, welding the vertices for which the `key`
+    /// function returns equal keys.
+    ///
+    /// Of the vertices that share a key, the first one in the `soup` is kept. The key may, for
+    /// example, quantize a vertex's position to weld vertices that lie within an epsilon of each
+    /// other. See [update_from_soup] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the same reasons as [update_from_soup].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+    /// use web_glitz_buffer_vec::MeshBuffers;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut mesh: MeshBuffers<_, [f32; 2], u16> =
+    ///     MeshBuffers::new(context, UsageHint::StaticDraw);
+    ///
+    /// let soup = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [0.0, 1.0001], [1.0, 0.0], [1.0, 1.0]];
+    ///
+    /// // Weld vertices that lie within 0.001 of each other.
+    /// let stats = mesh.update_from_soup_by_key(&soup, |[x, y]| {
+    ///     ((x * 1000.0).round() as i32, (y * 1000.0).round() as i32)
+    /// });
+    ///
+    /// assert_eq!(stats.unique_vertices, 4);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [update_from_soup]: MeshBuffers::update_from_soup
+    pub fn update_from_soup_by_key<K, F>(&mut self, soup: &[V], key: F) -> WeldStats
+    where
+        K: Eq + Hash,
+        F: FnMut(&V) -> K,
+    {
+        self.try_update_from_soup_by_key(soup, key)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, None)))
+    }
+
+    /// Replaces the data of the mesh with the `soup`, welding the vertices for which the `key`
+    /// function returns equal keys, or returns an error if the number of distinct vertices cannot
+    /// be indexed with the index type `I`, or if either buffer needs to grow and cannot.
+    ///
+    /// If the vertices cannot be indexed, nothing is uploaded. See [update_from_soup_by_key] for
+    /// details.
+    ///
+    /// [update_from_soup_by_key]: MeshBuffers::update_from_soup_by_key
+    pub fn try_update_from_soup_by_key<K, F>(
+        &mut self,
+        soup: &[V],
+        key: F,
+    ) -> Result<WeldStats, BufferVecError>
+    where
+        K: Eq + Hash,
+        F: FnMut(&V) -> K,
+    {
+        let (vertices, indices) = weld(soup, key)?;
+
+        let stats = WeldStats {
+            input_vertices: soup.len(),
+            unique_vertices: vertices.len(),
+        };
+
+        self.vertices.try_update(UploadData(vertices))?;
+        self.indices.try_update(UploadData(indices))?;
+
+        Ok(stats)
+    }
+
+    /// The vector that backs the vertex data of this mesh.
+    pub fn vertices(&self) -> &BufferVec<Rc, V> {
+        &self.vertices
+    }
+
+    /// The vector that backs the index data of this mesh.
+    pub fn indices(&self) -> &IndexBufferVec<Rc, I> {
+        &self.indices
+    }
+}
+
+/// Deduplicates the vertices in the `soup` by `key` and returns the distinct vertices, in order of
+/// first occurrence, together with an index into them for each vertex in the `soup`.
+///
+/// Returns an error if the index of a distinct vertex cannot be represented by `I`.
+fn weld<V, I, K, F>(soup: &[V], mut key: F) -> Result<(Vec<V>, Vec<I>), BufferVecError>
+where
+    V: Copy,
+    I: IndexValue,
+    K: Eq + Hash,
+    F: FnMut(&V) -> K,
+{
+    let mut positions: HashMap<K, I> = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(soup.len());
+
+    for (position, vertex) in soup.iter().enumerate() {
+        let index = match positions.entry(key(vertex)) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let value = vertices.len() as u64;
+                let index = I::from_u64(value).ok_or(BufferVecError::IndexNarrowing {
+                    position,
+                    value,
+                    max: I::MAX,
+                })?;
+
+                vertices.push(*vertex);
+
+                *entry.insert(index)
+            }
+        };
+
+        indices.push(index);
+    }
+
+    Ok((vertices, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weld() {
+        let (vertices, indices) =
+            weld::<_, u16, _, _>(&[1, 2, 3, 3, 2, 4], |vertex| *vertex).unwrap();
+
+        assert_eq!(vertices, vec![1, 2, 3, 4]);
+        assert_eq!(indices, vec![0, 1, 2, 2, 1, 3]);
+
+        let (vertices, indices) =
+            weld::<_, u16, _, _>(&[10, 21, 12, 25], |vertex| vertex / 10).unwrap();
+
+        assert_eq!(vertices, vec![10, 21]);
+        assert_eq!(indices, vec![0, 1, 0, 1]);
+
+        let (vertices, indices) = weld::<i32, u16, _, _>(&[], |vertex| *vertex).unwrap();
+
+        assert!(vertices.is_empty());
+        assert!(indices.is_empty());
+    }
+
+    #[test]
+    fn test_weld_narrowing() {
+        let soup: Vec<u32> = (0..300).collect();
+
+        assert_eq!(
+            weld::<_, u8, _, _>(&soup, |vertex| *vertex).unwrap_err(),
+            BufferVecError::IndexNarrowing {
+                position: 256,
+                value: 256,
+                max: 255
+            }
+        );
+
+        // Duplicates of vertices that can still be indexed do not overflow.
+        let mut soup: Vec<u32> = (0..256).collect();
+
+        soup.extend(0..256);
+
+        assert!(weld::<_, u8, _, _>(&soup, |vertex| *vertex).is_ok());
+    }
+}
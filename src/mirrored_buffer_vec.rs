@@ -0,0 +1,799 @@
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut, Index, IndexMut, Range};
+use std::slice;
+use std::thread;
+
+use web_glitz::buffer::UsageHint;
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::gpu_safe::GpuSafe;
+use crate::util::{checked_range, extend_range};
+use crate::BufferVec;
+
+/// A [BufferVec] that always keeps a CPU-side copy of its data.
+///
+/// The CPU-side copy allows reading the data without a GPU read-back (see [as_slice]) and enables
+/// operations that compute the new data on the CPU and upload only the elements that changed, such
+/// as [retain] and [swap_remove]. A [MirroredBufferVec] is always resilient (see
+/// [BufferVec::set_resilient]).
+///
+/// Dereferences to the wrapped [BufferVec] for read-only operations, such as
+/// [BufferVec::as_buffer_view].
+///
+/// Can be indexed like a slice, backed by the CPU-side copy: `vec[i]` and `vec[start..end]`, or
+/// [get] for the non-panicking form. There is intentionally no mutable indexing: writes go through
+/// methods such as [update_range], which keep the GPU buffer in sync with the CPU-side copy. To
+/// modify elements while walking the data, use [iter_tracked_mut], which uploads only the elements
+/// that were written, or [edit], which uploads the range of elements that was written.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+/// use web_glitz_buffer_vec::MirroredBufferVec;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut particles = MirroredBufferVec::new(context, UsageHint::StreamDraw);
+///
+/// particles.update([1, 2, 3, 4, 5]);
+///
+/// // Only the elements from the first removed element onward are uploaded again.
+/// particles.retain(|p| p % 2 == 1);
+///
+/// assert_eq!(particles.as_slice(), &[1, 3, 5]);
+/// assert_eq!(particles.as_buffer_view().len(), 3);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [as_slice]: MirroredBufferVec::as_slice
+/// [retain]: MirroredBufferVec::retain
+/// [swap_remove]: MirroredBufferVec::swap_remove
+/// [get]: MirroredBufferVec::get
+/// [update_range]: MirroredBufferVec::update_range
+/// [iter_tracked_mut]: MirroredBufferVec::iter_tracked_mut
+/// [edit]: MirroredBufferVec::edit
+pub struct MirroredBufferVec<Rc, T> {
+    inner: BufferVec<Rc, T>,
+    // Writes recorded by tracked elements that have not been uploaded yet.
+    pending: RefCell<Vec<(usize, T)>>,
+}
+
+impl<Rc, T> MirroredBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Creates a new mirrored vector with 0 capacity for the given [RenderingContext].
+    ///
+    /// See [BufferVec::new] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        let mut inner = BufferVec::new(context, usage);
+
+        inner.set_resilient(true);
+
+        MirroredBufferVec {
+            inner,
+            pending: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Creates a new mirrored vector with the specified `capacity` for the given
+    /// [RenderingContext].
+    ///
+    /// See [BufferVec::with_capacity] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn with_capacity(context: Rc, usage: UsageHint, capacity: usize) -> Self {
+        let mut inner = BufferVec::with_capacity(context, usage, capacity);
+
+        inner.set_resilient(true);
+
+        MirroredBufferVec {
+            inner,
+            pending: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The data in this vector.
+    pub fn as_slice(&self) -> &[T] {
+        self.inner.shadow()
+    }
+
+    /// Returns the element at `index`, or `None` if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// Returns an iterator over the elements in this vector.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// Returns an iterator that allows modifying each element, uploading only the elements that
+    /// were written.
+    ///
+    /// Each [TrackedMut] element records whether it was written to (mutably dereferenced). When the
+    /// iterator is dropped, or when [TrackedIterMut::flush] is called, the written elements are
+    /// uploaded, coalesced into contiguous runs. An iteration that writes nothing uploads nothing.
+    ///
+    /// Writes to elements that are kept alive beyond the iterator are uploaded by the next call to
+    /// this method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::MirroredBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = MirroredBufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.update([1, 2, 3, 4, 5]);
+    ///
+    /// // Uploads 2 runs: the element at index `1` and the elements at indices `3` and `4`.
+    /// for mut element in vec.iter_tracked_mut() {
+    ///     if *element % 2 == 0 || *element == 5 {
+    ///         *element *= 10;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(vec.as_slice(), &[1, 20, 3, 40, 50]);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn iter_tracked_mut(&mut self) -> TrackedIterMut<'_, Rc, T> {
+        let mut iter = TrackedIterMut {
+            inner: &mut self.inner,
+            pending: &self.pending,
+            position: 0,
+        };
+
+        iter.flush();
+
+        iter
+    }
+
+    /// Returns a guard that allows modifying the data in this vector like a slice, uploading the
+    /// modified elements when the guard is dropped.
+    ///
+    /// Writing through the guard's [IndexMut] implementations (`guard[i]` or `guard[start..end]`)
+    /// tracks the written elements coarsely, as the range from the lowest to the highest written
+    /// index; only that range is uploaded. Any other mutable access to the slice (through
+    /// [DerefMut]) marks all elements as written. Use [EditGuard::commit] rather than dropping the
+    /// guard to observe errors.
+    ///
+    /// If a panic occurs while the guard is alive, all elements are uploaded when the guard is
+    /// dropped during unwinding, so that the GPU buffer remains consistent with the CPU-side copy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+    /// use web_glitz_buffer_vec::MirroredBufferVec;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut vec = MirroredBufferVec::new(context, UsageHint::DynamicDraw);
+    ///
+    /// vec.update(vec![0; 1000]);
+    ///
+    /// {
+    ///     let mut guard = vec.edit();
+    ///
+    ///     guard[5] = 1;
+    ///     guard[900..910].copy_from_slice(&[2; 10]);
+    ///
+    ///     // Uploads the elements at indices `5..910` when the guard is dropped.
+    /// }
+    ///
+    /// assert_eq!(vec[5], 1);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn edit(&mut self) -> EditGuard<'_, Rc, T> {
+        EditGuard {
+            inner: &mut self.inner,
+            written: None,
+        }
+    }
+
+    /// Replaces the data in the vector with the given `data`.
+    ///
+    /// See [BufferVec::update] for details.
+    pub fn update<D>(&mut self, data: D) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.inner.update(data)
+    }
+
+    /// Replaces the data in the vector with the given `data`, or returns an error if a new buffer
+    /// could not be allocated.
+    ///
+    /// See [BufferVec::try_update] for details.
+    pub fn try_update<D>(&mut self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.inner.try_update(data)
+    }
+
+    /// Appends the given `data` to the end of the vector.
+    ///
+    /// See [BufferVec::append] for details.
+    pub fn append<D>(&mut self, data: D) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.inner.append(data)
+    }
+
+    /// Appends the given `data` to the end of the vector, or returns an error if a new buffer
+    /// could not be allocated.
+    ///
+    /// See [BufferVec::try_append] for details.
+    pub fn try_append<D>(&mut self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.inner.try_append(data)
+    }
+
+    /// Appends the `value` to the end of the vector if it fits in the current capacity, returning
+    /// its index, or returns the `value` back if the vector is at capacity.
+    ///
+    /// See [BufferVec::push_within_capacity] for details.
+    pub fn push_within_capacity(&mut self, value: T) -> Result<usize, T> {
+        self.inner.push_within_capacity(value)
+    }
+
+    /// Overwrites the elements starting at `offset` with the given `data`.
+    ///
+    /// See [BufferVec::update_range] for details.
+    pub fn update_range<D>(&mut self, offset: usize, data: D)
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.inner.update_range(offset, data)
+    }
+
+    /// Overwrites the elements starting at `offset` with the given `data`, or returns an error if
+    /// the range is not valid for the vector.
+    ///
+    /// See [BufferVec::try_update_range] for details.
+    pub fn try_update_range<D>(&mut self, offset: usize, data: D) -> Result<(), BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        self.inner.try_update_range(offset, data)
+    }
+
+    /// Overwrites a single field of each element in the `elements` range with the consecutive
+    /// field values in `data`, leaving the other fields unchanged.
+    ///
+    /// See [BufferVec::patch_field_range] for details.
+    pub fn patch_field_range(
+        &mut self,
+        byte_offset_in_element: usize,
+        field_size: usize,
+        elements: Range<usize>,
+        data: &[u8],
+    ) where
+        T: GpuSafe,
+    {
+        self.inner
+            .patch_field_range(byte_offset_in_element, field_size, elements, data)
+    }
+
+    /// Overwrites a single field of each element in the `elements` range with the consecutive
+    /// field values in `data`, or returns an error if the range extends beyond the length of the
+    /// vector.
+    ///
+    /// See [BufferVec::try_patch_field_range] for details.
+    pub fn try_patch_field_range(
+        &mut self,
+        byte_offset_in_element: usize,
+        field_size: usize,
+        elements: Range<usize>,
+        data: &[u8],
+    ) -> Result<(), BufferVecError>
+    where
+        T: GpuSafe,
+    {
+        self.inner
+            .try_patch_field_range(byte_offset_in_element, field_size, elements, data)
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// See [BufferVec::reserve] for details.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional)
+    }
+
+    /// Reserves capacity for at least `additional` more elements, or returns an error if a new
+    /// buffer could not be allocated.
+    ///
+    /// See [BufferVec::try_reserve] for details.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), BufferVecError> {
+        self.inner.try_reserve(additional)
+    }
+
+    /// Retains only the elements for which `f` returns `true`, preserving the order of the
+    /// retained elements.
+    ///
+    /// Only the elements from the first removed element onward are uploaded; the capacity of the
+    /// vector does not change.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.inner.modify_shadow(|shadow| {
+            let changed = match retain_from(shadow, f) {
+                Some(first_removed) => first_removed..shadow.len(),
+                None => 0..0,
+            };
+
+            ((), changed)
+        })
+    }
+
+    /// Removes the element at `index` and returns it, replacing it with the last element.
+    ///
+    /// Does not preserve the order of the elements, but uploads at most a single element; the
+    /// capacity of the vector does not change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.inner.len();
+
+        if index >= len {
+            panic!(
+                "Swap-remove index {} is out of bounds for a buffer vec of length {}.",
+                index, len
+            );
+        }
+
+        self.inner
+            .modify_shadow(|shadow| (shadow.swap_remove(index), index..index + 1))
+    }
+
+    /// Consumes the wrapper, returning the wrapped (resilient) vector.
+    pub fn into_inner(mut self) -> BufferVec<Rc, T> {
+        // Upload the writes of tracked elements that outlived their iterator.
+        drop(self.iter_tracked_mut());
+
+        self.inner
+    }
+}
+
+impl<Rc, T> Deref for MirroredBufferVec<Rc, T> {
+    type Target = BufferVec<Rc, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<Rc, T> Index<usize> for MirroredBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.as_slice()[index]
+    }
+}
+
+impl<Rc, T> Index<Range<usize>> for MirroredBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    type Output = [T];
+
+    fn index(&self, range: Range<usize>) -> &[T] {
+        &self.as_slice()[range]
+    }
+}
+
+/// Iterator over the elements of a [MirroredBufferVec] that tracks which elements were written.
+///
+/// See [MirroredBufferVec::iter_tracked_mut] for details.
+pub struct TrackedIterMut<'a, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    inner: &'a mut BufferVec<Rc, T>,
+    pending: &'a RefCell<Vec<(usize, T)>>,
+    position: usize,
+}
+
+impl<Rc, T> TrackedIterMut<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Uploads the elements written so far, coalesced into contiguous runs.
+    ///
+    /// Only elements that have already been dropped are uploaded; this is called automatically
+    /// when the iterator is dropped.
+    pub fn flush(&mut self) {
+        let mut pending = self.pending.borrow_mut();
+        let len = self.inner.len();
+
+        // The vector may have shrunk since writes were recorded by elements that outlived their
+        // iterator.
+        pending.retain(|(index, _)| *index < len);
+
+        if !pending.is_empty() {
+            let runs = coalesce_writes(&mut pending);
+            let parts: Vec<(usize, &[T])> = runs
+                .iter()
+                .map(|(offset, data)| (*offset, data.as_slice()))
+                .collect();
+
+            self.inner.update_parts(&parts);
+            pending.clear();
+        }
+    }
+}
+
+impl<'a, Rc, T> Iterator for TrackedIterMut<'a, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    type Item = TrackedMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.position;
+        let value = *self.inner.shadow().get(index)?;
+
+        self.position += 1;
+
+        Some(TrackedMut {
+            index,
+            value,
+            written: false,
+            pending: self.pending,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.inner.len() - self.position;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl<Rc, T> Drop for TrackedIterMut<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// An element yielded by [TrackedIterMut] that records whether it was written.
+///
+/// Dereferences to a copy of the element; mutably dereferencing it marks the element as written,
+/// in which case the new value is recorded for upload when the element is dropped.
+pub struct TrackedMut<'a, T>
+where
+    T: Copy,
+{
+    index: usize,
+    value: T,
+    written: bool,
+    pending: &'a RefCell<Vec<(usize, T)>>,
+}
+
+impl<T> TrackedMut<'_, T>
+where
+    T: Copy,
+{
+    /// The index of the element in the vector.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Whether the element was written to.
+    pub fn is_written(&self) -> bool {
+        self.written
+    }
+}
+
+impl<T> Deref for TrackedMut<'_, T>
+where
+    T: Copy,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for TrackedMut<'_, T>
+where
+    T: Copy,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.written = true;
+
+        &mut self.value
+    }
+}
+
+impl<T> Drop for TrackedMut<'_, T>
+where
+    T: Copy,
+{
+    fn drop(&mut self) {
+        if self.written {
+            self.pending.borrow_mut().push((self.index, self.value));
+        }
+    }
+}
+
+/// Guard that allows modifying the data in a [MirroredBufferVec] like a slice, uploading the
+/// modified elements when it is dropped.
+///
+/// See [MirroredBufferVec::edit] for details.
+pub struct EditGuard<'a, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    inner: &'a mut BufferVec<Rc, T>,
+    written: Option<Range<usize>>,
+}
+
+impl<Rc, T> EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Uploads the modified elements and consumes the guard, or returns an error if the upload
+    /// could not be submitted.
+    ///
+    /// Dropping the guard has the same effect, but discards the error.
+    pub fn commit(mut self) -> Result<(), BufferVecError> {
+        self.flush()
+    }
+
+    fn mark_written(&mut self, range: Range<usize>) {
+        self.written = extend_range(self.written.take(), range);
+    }
+
+    fn flush(&mut self) -> Result<(), BufferVecError> {
+        let written = if thread::panicking() {
+            Some(0..self.inner.len())
+        } else {
+            self.written.take()
+        };
+
+        if let Some(range) = written {
+            checked_range(range.start, range.len(), self.inner.len())?;
+
+            self.inner.modify_shadow(|_| ((), range));
+        }
+
+        Ok(())
+    }
+}
+
+impl<Rc, T> Deref for EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.inner.shadow()
+    }
+}
+
+impl<Rc, T> DerefMut for EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.mark_written(0..self.inner.len());
+
+        self.inner.shadow_mut()
+    }
+}
+
+impl<Rc, T> Index<usize> for EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &(**self)[index]
+    }
+}
+
+impl<Rc, T> IndexMut<usize> for EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.mark_written(index..index + 1);
+
+        &mut self.inner.shadow_mut()[index]
+    }
+}
+
+impl<Rc, T> Index<Range<usize>> for EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    type Output = [T];
+
+    fn index(&self, range: Range<usize>) -> &[T] {
+        &(**self)[range]
+    }
+}
+
+impl<Rc, T> IndexMut<Range<usize>> for EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    fn index_mut(&mut self, range: Range<usize>) -> &mut [T] {
+        self.mark_written(range.clone());
+
+        &mut self.inner.shadow_mut()[range]
+    }
+}
+
+impl<Rc, T> Drop for EditGuard<'_, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Sorts the recorded `writes` by index and coalesces writes to consecutive indices into runs of
+/// `(offset, data)`; when an index was written more than once, the last write wins.
+fn coalesce_writes<T>(writes: &mut [(usize, T)]) -> Vec<(usize, Vec<T>)>
+where
+    T: Copy,
+{
+    // A stable sort keeps repeated writes to an index in the order they were recorded.
+    writes.sort_by_key(|(index, _)| *index);
+
+    let mut runs: Vec<(usize, Vec<T>)> = Vec::new();
+
+    for &(index, value) in writes.iter() {
+        match runs.last_mut() {
+            Some((offset, data)) if *offset + data.len() - 1 == index => {
+                *data.last_mut().unwrap() = value;
+            }
+            Some((offset, data)) if *offset + data.len() == index => data.push(value),
+            _ => runs.push((index, vec![value])),
+        }
+    }
+
+    runs
+}
+
+/// Retains only the elements of `vec` for which `f` returns `true` and returns the index of the
+/// first element that was removed, if any.
+fn retain_from<T, F>(vec: &mut Vec<T>, mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> bool,
+{
+    let first_removed = vec.iter().position(|element| !f(element))?;
+
+    let tail = vec.split_off(first_removed + 1);
+
+    vec.pop();
+    vec.extend(tail.into_iter().filter(|element| f(element)));
+
+    Some(first_removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retain_from_none_removed() {
+        let mut vec = vec![1, 2, 3];
+
+        assert_eq!(retain_from(&mut vec, |_| true), None);
+        assert_eq!(vec, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_retain_from() {
+        let mut vec = vec![1, 3, 4, 5, 6, 7];
+
+        assert_eq!(retain_from(&mut vec, |e| e % 2 == 1), Some(2));
+        assert_eq!(vec, vec![1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn test_retain_from_calls_predicate_once_per_element() {
+        let mut vec = vec![1, 2, 3, 4];
+        let mut calls = 0;
+
+        retain_from(&mut vec, |e| {
+            calls += 1;
+
+            *e != 2
+        });
+
+        assert_eq!(calls, 4);
+        assert_eq!(vec, vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn test_coalesce_writes_scattered() {
+        let mut writes = vec![(7, 'c'), (2, 'a'), (4, 'b')];
+
+        assert_eq!(
+            coalesce_writes(&mut writes),
+            vec![(2, vec!['a']), (4, vec!['b']), (7, vec!['c'])]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_writes_adjacent() {
+        let mut writes = vec![(3, 'a'), (4, 'b'), (5, 'c'), (9, 'd'), (10, 'e')];
+
+        assert_eq!(
+            coalesce_writes(&mut writes),
+            vec![(3, vec!['a', 'b', 'c']), (9, vec!['d', 'e'])]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_writes_repeated() {
+        let mut writes = vec![(1, 'a'), (2, 'b'), (1, 'c')];
+
+        assert_eq!(coalesce_writes(&mut writes), vec![(1, vec!['c', 'b'])]);
+    }
+
+    #[test]
+    fn test_coalesce_writes_none() {
+        let mut writes: Vec<(usize, char)> = Vec::new();
+
+        assert!(coalesce_writes(&mut writes).is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+use std::future::Future;
+use std::mem;
+use std::ops::Range;
+
+use web_glitz::buffer::UsageHint;
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::index::IndexValue;
+use crate::util::{bug, checked_read_range, UploadData};
+use crate::validation::{check_ranges, violations_result, InvariantViolation};
+use crate::{BufferVec, IndexBufferVec};
+
+/// The draw range of a single mesh in a [MultiMeshBatch].
+///
+/// Besides the draw range, records what is needed to recover the mesh as it was added, see
+/// [MultiMeshBatch::export_mesh].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BatchEntry {
+    /// The range of the mesh's indices in the batch's index buffer. Its length is the mesh's
+    /// original index count.
+    pub index_range: Range<usize>,
+
+    /// The offset of the mesh's first vertex in the batch's vertex buffer; this offset was added
+    /// to each of the mesh's indices.
+    pub base_vertex: usize,
+
+    /// The number of vertices of the mesh.
+    pub vertex_count: usize,
+
+    /// The largest of the mesh's indices before they were rebased, or `0` if the mesh has no
+    /// indices.
+    pub original_max_index: u32,
+}
+
+impl BatchEntry {
+    /// The range of the mesh's vertices in the batch's vertex buffer.
+    pub fn vertex_range(&self) -> Range<usize> {
+        self.base_vertex..self.base_vertex + self.vertex_count
+    }
+}
+
+/// Packs the vertices and indices of many meshes into a single shared [BufferVec] and
+/// [IndexBufferVec], while recording the draw range of each mesh.
+///
+/// Meshes are added with [add_mesh], which stages the data on the CPU; [flush] then uploads all
+/// staged data with a single upload for each of the backing buffers. As WebGL 2 does not support
+/// drawing with a base vertex, the indices of each mesh are rebased by the mesh's vertex offset
+/// when the mesh is added, so that every [BatchEntry] can be drawn from the shared buffers as is.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use web_glitz_buffer_vec::MultiMeshBatch;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut batch = MultiMeshBatch::new(context, UsageHint::StreamDraw);
+///
+/// batch.add_mesh(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]], &[0u16, 1, 2]);
+///
+/// let second = batch.add_mesh(&[[2.0, 0.0], [3.0, 0.0], [2.0, 1.0]], &[0u16, 1, 2]);
+///
+/// assert_eq!(second.index_range, 3..6);
+/// assert_eq!(second.base_vertex, 3);
+///
+/// batch.flush();
+///
+/// assert_eq!(batch.indices().len(), 6);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [add_mesh]: MultiMeshBatch::add_mesh
+/// [flush]: MultiMeshBatch::flush
+pub struct MultiMeshBatch<Rc, V, I> {
+    vertices: BufferVec<Rc, V>,
+    indices: IndexBufferVec<Rc, I>,
+    staged_vertices: Vec<V>,
+    staged_indices: Vec<I>,
+    vertex_len: usize,
+    index_len: usize,
+    entries: Vec<BatchEntry>,
+    cleared: bool,
+}
+
+impl<Rc, V, I> MultiMeshBatch<Rc, V, I>
+where
+    Rc: RenderingContext + Clone,
+    V: Copy + Send + Sync + 'static,
+    I: IndexValue + 'static,
+{
+    /// Creates a new empty batch for the given [RenderingContext], backed by buffers with the
+    /// given `usage` hint.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        let mut indices = IndexBufferVec::new(context.clone(), usage);
+
+        // Appending meshes grows the index buffer, and exporting a mesh reads its indices back.
+        indices.set_resilient(true);
+
+        MultiMeshBatch {
+            vertices: BufferVec::new(context, usage),
+            indices,
+            staged_vertices: Vec::new(),
+            staged_indices: Vec::new(),
+            vertex_len: 0,
+            index_len: 0,
+            entries: Vec::new(),
+            cleared: false,
+        }
+    }
+
+    /// Removes all meshes from the batch.
+    ///
+    /// The backing buffers keep their data and capacity until the next [flush], which replaces
+    /// their data with the meshes added after this call.
+    ///
+    /// [flush]: MultiMeshBatch::flush
+    pub fn clear(&mut self) {
+        self.staged_vertices.clear();
+        self.staged_indices.clear();
+        self.vertex_len = 0;
+        self.index_len = 0;
+        self.entries.clear();
+        self.cleared = true;
+    }
+
+    /// Stages the given `vertices` and `indices` of a mesh for the next [flush] and returns the
+    /// mesh's [BatchEntry].
+    ///
+    /// The `indices` index into the mesh's own `vertices`; they are rebased by the number of
+    /// vertices already in the batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a rebased index cannot be represented by the index type `I`.
+    ///
+    /// [flush]: MultiMeshBatch::flush
+    pub fn add_mesh(&mut self, vertices: &[V], indices: &[I]) -> BatchEntry {
+        let base_vertex = self.vertex_len;
+
+        rebase_indices(indices, base_vertex, &mut self.staged_indices);
+        self.staged_vertices.extend_from_slice(vertices);
+
+        // Index types are at most 32 bits wide, so the largest index fits in a `u32`.
+        let original_max_index = indices
+            .iter()
+            .map(|index| index.to_u64())
+            .max()
+            .unwrap_or(0);
+
+        let entry = BatchEntry {
+            index_range: self.index_len..self.index_len + indices.len(),
+            base_vertex,
+            vertex_count: vertices.len(),
+            original_max_index: original_max_index as u32,
+        };
+
+        self.vertex_len += vertices.len();
+        self.index_len += indices.len();
+        self.entries.push(entry.clone());
+
+        entry
+    }
+
+    /// Uploads the meshes staged since the last flush, with a single upload for the vertex buffer
+    /// and a single upload for the index buffer.
+    ///
+    /// If the batch was [cleared] since the last flush, the data in the backing buffers is
+    /// replaced; otherwise, the staged data is appended to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a backing buffer needs to grow and cannot (see [BufferVec::update]).
+    ///
+    /// [cleared]: MultiMeshBatch::clear
+    pub fn flush(&mut self) {
+        let vertices = UploadData(mem::take(&mut self.staged_vertices));
+        let indices = UploadData(mem::take(&mut self.staged_indices));
+
+        if self.cleared {
+            self.vertices.update(vertices);
+            self.indices.update(indices);
+            self.cleared = false;
+        } else {
+            if !vertices.0.is_empty() {
+                self.vertices.append(vertices);
+            }
+
+            if !indices.0.is_empty() {
+                self.indices.append(indices);
+            }
+        }
+    }
+
+    /// Returns an iterator over the entries of the meshes in this batch, in the order in which
+    /// they were added.
+    ///
+    /// Includes the entries of meshes that have not yet been [flushed].
+    ///
+    /// [flushed]: MultiMeshBatch::flush
+    pub fn entries(&self) -> impl Iterator<Item = &BatchEntry> {
+        self.entries.iter()
+    }
+
+    /// Reads the vertices and indices of the mesh with the given `entry` back from the batch's
+    /// buffers, or returns an error if the mesh's data is not within the data flushed to the
+    /// buffers.
+    ///
+    /// The indices are returned as they were passed to [add_mesh]: the mesh's base vertex is
+    /// subtracted from each index. Together with the mesh's vertices, this recovers the mesh
+    /// exactly, e.g. to move it to another batch. Only meshes that were [flushed] can be exported;
+    /// nothing is submitted if an error is returned.
+    ///
+    /// The vertices are downloaded from the GPU; the indices are read from the CPU-side copy that
+    /// the batch keeps of the index buffer (see [IndexBufferVec::set_resilient]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz::runtime::RenderingContext;
+    /// # async fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+    /// use web_glitz_buffer_vec::MultiMeshBatch;
+    /// use web_glitz::buffer::UsageHint;
+    ///
+    /// let mut batch = MultiMeshBatch::new(context, UsageHint::StaticDraw);
+    ///
+    /// batch.add_mesh(&[1u32, 2, 3], &[0u16, 1, 2]);
+    ///
+    /// let second = batch.add_mesh(&[4u32, 5], &[1u16, 0]);
+    ///
+    /// batch.flush();
+    ///
+    /// let (vertices, indices) = batch.export_mesh(&second).unwrap().await;
+    ///
+    /// assert_eq!(vertices, vec![4, 5]);
+    /// assert_eq!(indices, vec![1, 0]);
+    /// # }
+    /// ```
+    ///
+    /// Here `context` is a WebGlitz [RenderingContext].
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    /// [add_mesh]: MultiMeshBatch::add_mesh
+    /// [flushed]: MultiMeshBatch::flush
+    pub fn export_mesh(
+        &self,
+        entry: &BatchEntry,
+    ) -> Result<impl Future<Output = (Vec<V>, Vec<I>)>, BufferVecError> {
+        // After a clear, the buffers still hold data that no longer belongs to any entry.
+        let (flushed_vertices, flushed_indices) = if self.cleared {
+            (0, 0)
+        } else {
+            (self.vertices.len(), self.indices.len())
+        };
+
+        let vertex_range = checked_read_range(entry.vertex_range(), flushed_vertices)?;
+        let index_range = checked_read_range(entry.index_range.clone(), flushed_indices)?;
+
+        let vertices = self.vertices.read_range(vertex_range)?;
+        let indices = self.indices.read_range(index_range)?;
+        let base_vertex = entry.base_vertex;
+
+        Ok(async move {
+            let mut indices = indices.await;
+
+            unrebase_indices(&mut indices, base_vertex);
+
+            (vertices.await, indices)
+        })
+    }
+
+    /// The vector that backs the vertex data of this batch.
+    pub fn vertices(&self) -> &BufferVec<Rc, V> {
+        &self.vertices
+    }
+
+    /// The vector that backs the index data of this batch.
+    pub fn indices(&self) -> &IndexBufferVec<Rc, I> {
+        &self.indices
+    }
+
+    /// Checks the internal invariants of this batch, returning all violations found.
+    ///
+    /// Verifies that the index ranges of the entries lie within the batch's indices and do not
+    /// overlap, and that the backing buffers together with the staged data hold as many vertices
+    /// and indices as the batch has. Reports the violations of the backing buffers, and flags
+    /// meshes that were added but not yet [flushed] as [InvariantViolation::Unflushed], counting
+    /// both their vertices and indices.
+    ///
+    /// [flushed]: MultiMeshBatch::flush
+    pub fn debug_validate(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut violations = self.vertices.debug_validate().err().unwrap_or_default();
+
+        violations.extend(self.indices.debug_validate().err().unwrap_or_default());
+
+        check_ranges(
+            self.entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| (format!("entry {}", i), entry.index_range.clone())),
+            self.index_len,
+            &mut violations,
+        );
+
+        let (flushed_vertices, flushed_indices) = if self.cleared {
+            (0, 0)
+        } else {
+            (self.vertices.len(), self.indices.len())
+        };
+
+        for (cpu_len, len) in [
+            (
+                flushed_vertices + self.staged_vertices.len(),
+                self.vertex_len,
+            ),
+            (flushed_indices + self.staged_indices.len(), self.index_len),
+        ] {
+            if cpu_len != len {
+                violations.push(InvariantViolation::CpuLenMismatch { cpu_len, len });
+            }
+        }
+
+        if self.cleared || !self.staged_vertices.is_empty() || !self.staged_indices.is_empty() {
+            violations.push(InvariantViolation::Unflushed {
+                elements: self.staged_vertices.len() + self.staged_indices.len(),
+            });
+        }
+
+        violations_result(violations)
+    }
+}
+
+/// Appends the given `indices` to `out`, with `base_vertex` added to each index.
+///
+/// # Panics
+///
+/// Panics if a rebased index cannot be represented by `T`.
+fn rebase_indices<T>(indices: &[T], base_vertex: usize, out: &mut Vec<T>)
+where
+    T: IndexValue,
+{
+    let base_vertex = base_vertex as u64;
+
+    out.extend(indices.iter().map(|index| {
+        let rebased = index.to_u64() + base_vertex;
+
+        T::from_u64(rebased).unwrap_or_else(|| {
+            panic!(
+                "Rebased index {} exceeds the maximum value for the index type ({}).",
+                rebased,
+                T::MAX
+            )
+        })
+    }));
+}
+
+/// Subtracts `base_vertex` from each of the given `indices`, undoing [rebase_indices].
+///
+/// # Panics
+///
+/// Panics if an index is smaller than `base_vertex`, which means the indices were not rebased by
+/// `base_vertex`.
+fn unrebase_indices<T>(indices: &mut [T], base_vertex: usize)
+where
+    T: IndexValue,
+{
+    let base_vertex = base_vertex as u64;
+
+    for index in indices {
+        *index = index
+            .to_u64()
+            .checked_sub(base_vertex)
+            .and_then(T::from_u64)
+            .unwrap_or_else(|| {
+                bug(format_args!(
+                    "batch index {} is smaller than its mesh's base vertex {}",
+                    index.to_u64(),
+                    base_vertex
+                ))
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebase_indices() {
+        let mut out = vec![0u16, 1, 2];
+
+        rebase_indices(&[0u16, 2, 1], 3, &mut out);
+
+        assert_eq!(out, vec![0, 1, 2, 3, 5, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum value")]
+    fn test_rebase_indices_overflow() {
+        rebase_indices(&[0u8, 10], 250, &mut Vec::new());
+    }
+
+    #[test]
+    fn test_unrebase_indices() {
+        let mut indices = Vec::new();
+
+        rebase_indices(&[0u16, 2, 1], 3, &mut indices);
+        unrebase_indices(&mut indices, 3);
+
+        assert_eq!(indices, vec![0, 2, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "smaller than its mesh's base vertex")]
+    fn test_unrebase_indices_underflow() {
+        unrebase_indices(&mut [2u16], 3);
+    }
+}
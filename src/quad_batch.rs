@@ -0,0 +1,199 @@
+use std::mem;
+
+use web_glitz::buffer::{BufferView, UsageHint};
+use web_glitz::pipeline::graphics::{IndexBufferView, IndexData, IndexDataDescriptor};
+use web_glitz::runtime::RenderingContext;
+
+use crate::topology::quad_indices;
+use crate::util::UploadData;
+use crate::{BufferVec, IndexBufferVec};
+
+/// The largest number of quads that can be indexed with `u16` indices.
+const MAX_U16_QUADS: usize = (u16::MAX as usize + 1) / 4;
+
+/// Batches quads (such as sprites) into a [BufferVec] of vertices and a shared [IndexBufferVec]
+/// that holds the quad index pattern.
+///
+/// Quads are pushed with [push_quad] and uploaded with [flush], after which the batch can be drawn
+/// with [vertex_view], [index_view] and [index_count]. Each [flush] replaces the vertex data with
+/// the quads pushed since the previous [flush].
+///
+/// As the index pattern only depends on the number of quads, the indices are generated lazily and
+/// the index buffer is only extended when the number of quads exceeds the largest number of quads
+/// flushed so far. Indices are `u16` while the batch holds at most 16384 quads; past that, the
+/// index buffer is widened to `u32` indices.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use web_glitz_buffer_vec::QuadBatch;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut batch = QuadBatch::new(context, UsageHint::StreamDraw);
+///
+/// batch.push_quad([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+/// batch.push_quad([[2.0, 0.0], [3.0, 0.0], [3.0, 1.0], [2.0, 1.0]]);
+/// batch.flush();
+///
+/// assert_eq!(batch.vertex_view().len(), 8);
+/// assert_eq!(batch.index_count(), 12);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [push_quad]: QuadBatch::push_quad
+/// [flush]: QuadBatch::flush
+/// [vertex_view]: QuadBatch::vertex_view
+/// [index_view]: QuadBatch::index_view
+/// [index_count]: QuadBatch::index_count
+pub struct QuadBatch<Rc, V> {
+    context: Rc,
+    vertices: BufferVec<Rc, V>,
+    indices: QuadIndices<Rc>,
+    staged: Vec<V>,
+    quad_count: usize,
+}
+
+impl<Rc, V> QuadBatch<Rc, V>
+where
+    Rc: RenderingContext + Clone,
+    V: Copy + Send + Sync + 'static,
+{
+    /// Creates a new empty batch for the given [RenderingContext], with a vertex buffer that uses
+    /// the given `usage` hint.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        QuadBatch {
+            vertices: BufferVec::new(context.clone(), usage),
+            indices: QuadIndices::U16(IndexBufferVec::new(context.clone(), UsageHint::StaticDraw)),
+            context,
+            staged: Vec::new(),
+            quad_count: 0,
+        }
+    }
+
+    /// Stages a quad with the given `corners`, in winding order, for the next [flush].
+    ///
+    /// [flush]: QuadBatch::flush
+    pub fn push_quad(&mut self, corners: [V; 4]) {
+        self.staged.extend_from_slice(&corners);
+    }
+
+    /// Uploads the quads pushed since the previous flush, replacing the quads in the batch.
+    ///
+    /// Extends the index buffer if the batch now holds more quads than it has held before,
+    /// widening it to `u32` indices if the batch holds more than 16384 quads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a backing buffer needs to grow and cannot (see [BufferVec::update]).
+    pub fn flush(&mut self) {
+        self.quad_count = self.staged.len() / 4;
+        self.vertices
+            .update(UploadData(mem::take(&mut self.staged)));
+        self.reserve_indices(self.quad_count);
+    }
+
+    /// Returns a view on the vertices of the quads in the batch.
+    pub fn vertex_view(&self) -> BufferView<[V]> {
+        self.vertices.as_buffer_view()
+    }
+
+    /// Returns a view on the indices for the quads in the batch.
+    pub fn index_view(&self) -> QuadIndexView {
+        let len = self.index_count();
+
+        match &self.indices {
+            QuadIndices::U16(indices) => QuadIndexView::U16(indices.prefix_view(len).unwrap()),
+            QuadIndices::U32(indices) => QuadIndexView::U32(indices.prefix_view(len).unwrap()),
+        }
+    }
+
+    /// The number of indices needed to draw the quads in the batch.
+    pub fn index_count(&self) -> usize {
+        self.quad_count * 6
+    }
+
+    /// Ensures the index buffer holds the index pattern for at least `quad_count` quads.
+    fn reserve_indices(&mut self, quad_count: usize) {
+        let indexed = self.indices.len() / 6;
+
+        if quad_count <= indexed {
+            return;
+        }
+
+        match &mut self.indices {
+            QuadIndices::U16(indices) if quad_count <= MAX_U16_QUADS => {
+                let mut data = Vec::new();
+
+                // The index vector keeps no CPU-side copy, so it cannot grow while keeping its
+                // indices: regenerate the whole pattern instead.
+                if indices.capacity() >= quad_count * 6 {
+                    quad_indices::<u16>(indexed..quad_count, &mut data);
+                    indices.append(UploadData(data));
+                } else {
+                    quad_indices::<u16>(0..quad_count, &mut data);
+                    indices.update(UploadData(data));
+                }
+            }
+            _ => {
+                let mut data = Vec::new();
+
+                quad_indices::<u32>(0..quad_count, &mut data);
+
+                let mut indices = IndexBufferVec::new(self.context.clone(), UsageHint::StaticDraw);
+
+                indices.update(UploadData(data));
+                self.indices = QuadIndices::U32(indices);
+            }
+        }
+    }
+}
+
+/// The index buffer of a [QuadBatch], which switches from `u16` to `u32` indices once the batch
+/// grows too large for `u16` indices.
+enum QuadIndices<Rc> {
+    U16(IndexBufferVec<Rc, u16>),
+    U32(IndexBufferVec<Rc, u32>),
+}
+
+impl<Rc> QuadIndices<Rc>
+where
+    Rc: RenderingContext,
+{
+    fn len(&self) -> usize {
+        match self {
+            QuadIndices::U16(indices) => indices.len(),
+            QuadIndices::U32(indices) => indices.len(),
+        }
+    }
+}
+
+/// A view on the indices of a [QuadBatch], which may be `u16` or `u32` indices depending on the
+/// size of the batch.
+///
+/// Implements [IndexData], so that it may be bound directly as the index buffer of a draw.
+///
+/// [IndexData]: web_glitz::pipeline::graphics::IndexData
+#[derive(Clone, Copy)]
+pub enum QuadIndexView<'a> {
+    /// A view on `u16` indices.
+    U16(IndexBufferView<'a, u16>),
+
+    /// A view on `u32` indices.
+    U32(IndexBufferView<'a, u32>),
+}
+
+impl<'a> IndexData for QuadIndexView<'a> {
+    fn descriptor(&self) -> IndexDataDescriptor {
+        match self {
+            QuadIndexView::U16(view) => view.descriptor(),
+            QuadIndexView::U32(view) => view.descriptor(),
+        }
+    }
+}
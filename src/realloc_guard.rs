@@ -0,0 +1,175 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use crate::util::describe_vec;
+
+/// A reallocation that happened while a [ReallocGuard] was alive.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReallocViolation {
+    /// The label of the vector that reallocated, if it has one.
+    pub label: Option<String>,
+
+    /// The capacity of the vector before the reallocation.
+    pub old_capacity: usize,
+
+    /// The capacity of the vector after the reallocation.
+    pub new_capacity: usize,
+}
+
+impl fmt::Display for ReallocViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} reallocated from a capacity of {} to {} elements while no reallocation was \
+             expected.",
+            describe_vec("buffer vec", self.label.as_deref()),
+            self.old_capacity,
+            self.new_capacity
+        )
+    }
+}
+
+/// Asserts that the vectors it watches do not reallocate while it is alive.
+///
+/// Returned by [BufferVec::expect_no_realloc], [IndexBufferVec::expect_no_realloc] and
+/// [MemoryRegistry::expect_no_realloc]. With debug assertions enabled (as in tests and debug
+/// builds), a reallocation of a watched vector panics; otherwise the reallocation is recorded as a
+/// [ReallocViolation] that may be retrieved with [violations](ReallocGuard::violations).
+///
+/// The guard does not borrow the vectors it watches, so they may be updated while it is alive.
+/// Dropping the guard ends the watch.
+///
+/// [BufferVec::expect_no_realloc]: crate::BufferVec::expect_no_realloc
+/// [IndexBufferVec::expect_no_realloc]: crate::IndexBufferVec::expect_no_realloc
+/// [MemoryRegistry::expect_no_realloc]: crate::MemoryRegistry::expect_no_realloc
+#[must_use = "the guard only watches for reallocations while it is alive"]
+pub struct ReallocGuard {
+    violations: Arc<Mutex<Vec<ReallocViolation>>>,
+}
+
+impl ReallocGuard {
+    pub(crate) fn new() -> Self {
+        ReallocGuard {
+            violations: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// The reallocations recorded while this guard was alive.
+    ///
+    /// Always empty with debug assertions enabled, as reallocations panic instead.
+    pub fn violations(&self) -> Vec<ReallocViolation> {
+        self.violations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns `true` if no reallocations were recorded while this guard was alive.
+    pub fn is_clean(&self) -> bool {
+        self.violations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+}
+
+impl fmt::Debug for ReallocGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReallocGuard")
+            .field(
+                "violations",
+                &*self
+                    .violations
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner),
+            )
+            .finish()
+    }
+}
+
+/// The [ReallocGuard]s watching a vector.
+pub(crate) struct ReallocWatchers {
+    guards: Vec<Weak<Mutex<Vec<ReallocViolation>>>>,
+}
+
+impl ReallocWatchers {
+    /// Creates a new set without any guards.
+    pub(crate) fn new() -> Self {
+        ReallocWatchers { guards: Vec::new() }
+    }
+
+    /// Adds the given `guard` to the set.
+    pub(crate) fn add(&mut self, guard: &ReallocGuard) {
+        self.guards.retain(|guard| guard.strong_count() > 0);
+        self.guards.push(Arc::downgrade(&guard.violations));
+    }
+
+    /// Reports a reallocation of the vector with the given `label` from `old_capacity` to
+    /// `new_capacity` to the guards that are still alive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if debug assertions are enabled and any guard is alive.
+    pub(crate) fn check(&mut self, label: Option<&str>, old_capacity: usize, new_capacity: usize) {
+        self.guards.retain(|guard| guard.strong_count() > 0);
+
+        if self.guards.is_empty() {
+            return;
+        }
+
+        let violation = ReallocViolation {
+            label: label.map(|label| label.to_string()),
+            old_capacity,
+            new_capacity,
+        };
+
+        if cfg!(debug_assertions) {
+            panic!("{}", violation);
+        }
+
+        for guard in &self.guards {
+            if let Some(violations) = guard.upgrade() {
+                violations
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(violation.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<ReallocGuard>();
+        assert_send_sync::<ReallocWatchers>();
+    }
+
+    #[test]
+    fn test_dropped_guard_does_not_watch() {
+        let mut watchers = ReallocWatchers::new();
+
+        watchers.add(&ReallocGuard::new());
+        watchers.check(Some("terrain"), 4, 8);
+
+        assert!(watchers.guards.is_empty());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "buffer vec \"terrain\" reallocated from a capacity of 4 to 8")]
+    fn test_live_guard_panics() {
+        let mut watchers = ReallocWatchers::new();
+        let guard = ReallocGuard::new();
+
+        watchers.add(&guard);
+        watchers.check(Some("terrain"), 4, 8);
+
+        assert!(guard.is_clean());
+    }
+}
@@ -0,0 +1,698 @@
+use std::cell::RefCell;
+use std::rc::Weak;
+
+use web_glitz::runtime::RenderingContext;
+
+use crate::debug_info::DebugInfo;
+use crate::index::IndexValue;
+use crate::realloc_guard::ReallocGuard;
+use crate::util::describe_vec;
+use crate::{BufferVec, IndexBufferVec};
+
+/// Common interface for the buffer vector types that may be registered with a [MemoryRegistry].
+pub trait ManagedVec<Rc> {
+    /// Whether or not the vector keeps the CPU-side shadow copy of its data that is required to
+    /// [recreate](ManagedVec::recreate) it.
+    fn is_resilient(&self) -> bool;
+
+    /// Allocates a new buffer for the given `context` and re-uploads the shadowed data.
+    fn recreate(&mut self, context: Rc);
+
+    /// The label of the vector, if it has one.
+    fn label(&self) -> Option<&str> {
+        None
+    }
+
+    /// Allocates a new buffer for the given `context`, leaving the shadowed data to be re-uploaded
+    /// in chunks with [restore_step](ManagedVec::restore_step). Used by [RecoveryPlan].
+    ///
+    /// The default implementation [recreate](ManagedVec::recreate)s the vector at once.
+    fn begin_restore(&mut self, context: Rc) {
+        self.recreate(context)
+    }
+
+    /// Re-uploads the next chunk of the shadowed data after
+    /// [begin_restore](ManagedVec::begin_restore), of at most `max_bytes` but at least one
+    /// element, and returns the number of bytes uploaded.
+    ///
+    /// The default implementation does nothing and returns `0`.
+    fn restore_step(&mut self, _max_bytes: usize) -> usize {
+        0
+    }
+
+    /// The number of bytes of shadowed data that remain to be re-uploaded.
+    ///
+    /// The default implementation returns `0`.
+    fn restore_remaining_bytes(&self) -> usize {
+        0
+    }
+
+    /// Returns `true` unless the vector was restored with
+    /// [begin_restore](ManagedVec::begin_restore) and not all of its shadowed data has been
+    /// re-uploaded yet.
+    ///
+    /// The default implementation returns `true`.
+    fn is_restored(&self) -> bool {
+        true
+    }
+
+    /// Adds the given `guard` to the guards watching the vector for reallocations, see
+    /// [MemoryRegistry::expect_no_realloc].
+    ///
+    /// The default implementation does nothing.
+    fn watch_reallocations(&mut self, _guard: &ReallocGuard) {}
+
+    /// A snapshot of the state of the vector, see [MemoryRegistry::iter_debug_info].
+    ///
+    /// The default implementation returns `None`.
+    fn debug_info(&self) -> Option<DebugInfo> {
+        None
+    }
+}
+
+impl<Rc, T> ManagedVec<Rc> for BufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    fn is_resilient(&self) -> bool {
+        BufferVec::is_resilient(self)
+    }
+
+    fn recreate(&mut self, context: Rc) {
+        BufferVec::recreate(self, context)
+    }
+
+    fn label(&self) -> Option<&str> {
+        BufferVec::label(self)
+    }
+
+    fn begin_restore(&mut self, context: Rc) {
+        BufferVec::begin_restore(self, context)
+    }
+
+    fn restore_step(&mut self, max_bytes: usize) -> usize {
+        BufferVec::restore_step(self, max_bytes)
+    }
+
+    fn restore_remaining_bytes(&self) -> usize {
+        BufferVec::restore_remaining_bytes(self)
+    }
+
+    fn is_restored(&self) -> bool {
+        BufferVec::is_restored(self)
+    }
+
+    fn watch_reallocations(&mut self, guard: &ReallocGuard) {
+        BufferVec::watch_reallocations(self, guard)
+    }
+
+    fn debug_info(&self) -> Option<DebugInfo> {
+        Some(BufferVec::debug_info(self))
+    }
+}
+
+impl<Rc, T> ManagedVec<Rc> for IndexBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: IndexValue + 'static,
+{
+    fn is_resilient(&self) -> bool {
+        IndexBufferVec::is_resilient(self)
+    }
+
+    fn recreate(&mut self, context: Rc) {
+        IndexBufferVec::recreate(self, context)
+    }
+
+    fn label(&self) -> Option<&str> {
+        IndexBufferVec::label(self)
+    }
+
+    fn begin_restore(&mut self, context: Rc) {
+        IndexBufferVec::begin_restore(self, context)
+    }
+
+    fn restore_step(&mut self, max_bytes: usize) -> usize {
+        IndexBufferVec::restore_step(self, max_bytes)
+    }
+
+    fn restore_remaining_bytes(&self) -> usize {
+        IndexBufferVec::restore_remaining_bytes(self)
+    }
+
+    fn is_restored(&self) -> bool {
+        IndexBufferVec::is_restored(self)
+    }
+
+    fn watch_reallocations(&mut self, guard: &ReallocGuard) {
+        IndexBufferVec::watch_reallocations(self, guard)
+    }
+
+    fn debug_info(&self) -> Option<DebugInfo> {
+        Some(IndexBufferVec::debug_info(self))
+    }
+}
+
+/// Tracks a set of shared buffer vectors for scene-level operations, such as restoring all vectors
+/// after the context was lost.
+///
+/// The registry only holds weak references: registering a vector does not keep it alive, vectors
+/// that have been dropped are skipped and forgotten. Both [BufferVec]s and [IndexBufferVec]s may be
+/// registered, as long as they are resilient (see [BufferVec::set_resilient] and
+/// [IndexBufferVec::set_resilient]).
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone + 'static {
+/// use std::cell::RefCell;
+///
+/// use web_glitz_buffer_vec::{BufferVec, IndexBufferVec, MemoryRegistry};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut registry = MemoryRegistry::new();
+///
+/// let mut vec = BufferVec::new(context.clone(), UsageHint::StaticDraw);
+/// let mut indices = IndexBufferVec::new(context.clone(), UsageHint::StaticDraw);
+///
+/// vec.set_resilient(true);
+/// vec.update([1, 2, 3]);
+/// indices.set_resilient(true);
+/// indices.update([0u16, 1, 2]);
+///
+/// let vec = std::rc::Rc::new(RefCell::new(vec));
+/// let indices = std::rc::Rc::new(RefCell::new(indices));
+///
+/// registry.register(&vec);
+/// registry.register(&indices);
+///
+/// // After the context was restored (e.g. in a `webglcontextrestored` event handler):
+/// registry.recreate_all(&context);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+pub struct MemoryRegistry<Rc> {
+    entries: Vec<RegistryEntry<Rc>>,
+}
+
+/// A vector registered with a [MemoryRegistry].
+struct RegistryEntry<Rc> {
+    priority: i32,
+    vec: Weak<RefCell<dyn ManagedVec<Rc>>>,
+}
+
+impl<Rc> MemoryRegistry<Rc> {
+    /// Creates a new empty registry.
+    pub fn new() -> Self {
+        MemoryRegistry {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Registers the given `vec` with this registry, with priority `0`.
+    ///
+    /// See [register_with_priority].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `vec` is not resilient.
+    ///
+    /// [register_with_priority]: MemoryRegistry::register_with_priority
+    pub fn register<V>(&mut self, vec: &std::rc::Rc<RefCell<V>>)
+    where
+        V: ManagedVec<Rc> + 'static,
+    {
+        self.register_with_priority(vec, 0)
+    }
+
+    /// Registers the given `vec` with this registry, with the given `priority`.
+    ///
+    /// Vectors with a higher priority are restored first, both by [recreate_all] and by a
+    /// [RecoveryPlan]; vectors with equal priorities are restored in the order in which they were
+    /// registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `vec` is not resilient.
+    ///
+    /// [recreate_all]: MemoryRegistry::recreate_all
+    pub fn register_with_priority<V>(&mut self, vec: &std::rc::Rc<RefCell<V>>, priority: i32)
+    where
+        V: ManagedVec<Rc> + 'static,
+    {
+        if !vec.borrow().is_resilient() {
+            panic!(
+                "Only resilient buffer vecs can be registered; {} is not resilient.",
+                describe_vec("buffer vec", vec.borrow().label())
+            );
+        }
+
+        let vec: std::rc::Rc<RefCell<dyn ManagedVec<Rc>>> = vec.clone();
+
+        let entry = RegistryEntry {
+            priority,
+            vec: std::rc::Rc::downgrade(&vec),
+        };
+
+        // Keep the entries ordered by descending priority; equal priorities keep their
+        // registration order.
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.priority < priority)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(index, entry);
+    }
+
+    /// The number of registered vectors that are still alive.
+    pub fn len(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.vec.strong_count() > 0)
+            .count()
+    }
+
+    /// Returns `true` if none of the registered vectors are still alive.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Recreates all registered vectors that are still alive for the given `context`, in order of
+    /// priority.
+    ///
+    /// See [BufferVec::recreate] and [RecoveryPlan], which spreads the re-uploads over several
+    /// frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the registered vectors is currently mutably borrowed.
+    pub fn recreate_all(&mut self, context: &Rc)
+    where
+        Rc: Clone,
+    {
+        self.entries.retain(|entry| {
+            if let Some(vec) = entry.vec.upgrade() {
+                vec.borrow_mut().recreate(context.clone());
+
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Returns a [DebugInfo] snapshot for each registered vector that is still alive, in order of
+    /// priority.
+    ///
+    /// Vectors whose [ManagedVec] implementation does not provide debug info are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz_buffer_vec::MemoryRegistry;
+    /// # fn wrapper<Rc>(registry: &MemoryRegistry<Rc>) {
+    /// for info in registry.iter_debug_info() {
+    ///     println!(
+    ///         "{}: {}/{} x {} ({} bytes)",
+    ///         info.label.as_deref().unwrap_or("<unlabeled>"),
+    ///         info.len,
+    ///         info.capacity,
+    ///         info.element_type,
+    ///         info.bytes
+    ///     );
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the registered vectors is currently mutably borrowed when the iterator
+    /// reaches it.
+    pub fn iter_debug_info(&self) -> impl Iterator<Item = DebugInfo> + '_ {
+        self.entries.iter().filter_map(|entry| {
+            let vec = entry.vec.upgrade()?;
+            let info = vec.borrow().debug_info();
+
+            info
+        })
+    }
+
+    /// Returns a guard that asserts that none of the registered vectors reallocates while it is
+    /// alive.
+    ///
+    /// Covers the vectors that are registered and alive when the guard is created; vectors that
+    /// are registered later are not watched. See [BufferVec::expect_no_realloc] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use web_glitz_buffer_vec::MemoryRegistry;
+    /// # fn render_frame() {}
+    /// # fn wrapper<Rc>(registry: &mut MemoryRegistry<Rc>) {
+    /// let guard = registry.expect_no_realloc();
+    ///
+    /// render_frame();
+    ///
+    /// assert!(guard.is_clean());
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the registered vectors is currently mutably borrowed.
+    pub fn expect_no_realloc(&mut self) -> ReallocGuard {
+        let guard = ReallocGuard::new();
+
+        self.entries.retain(|entry| {
+            if let Some(vec) = entry.vec.upgrade() {
+                vec.borrow_mut().watch_reallocations(&guard);
+
+                true
+            } else {
+                false
+            }
+        });
+
+        guard
+    }
+}
+
+impl<Rc> Default for MemoryRegistry<Rc> {
+    fn default() -> Self {
+        MemoryRegistry::new()
+    }
+}
+
+/// Restores the vectors of a [MemoryRegistry] after the context was restored, spreading the
+/// re-uploads of their data over several frames.
+///
+/// Re-uploading the data of all vectors at once with [MemoryRegistry::recreate_all] may stall
+/// the application for a noticeable amount of time. Creating a plan instead allocates new buffers
+/// for all registered vectors at once, but leaves their data to be re-uploaded by [step], which
+/// uploads at most a fixed number of bytes per call (see [budget_bytes_per_frame]). Call [step]
+/// once per frame until the plan [is_finished]. Vectors are restored in order of priority (see
+/// [MemoryRegistry::register_with_priority]); a large vector may be restored in chunks over
+/// several steps.
+///
+/// While a vector is being restored, its [is_restored](BufferVec::is_restored) method returns
+/// `false` and it should not be drawn. The plan only holds weak references to the vectors:
+/// vectors that are dropped before they are restored are skipped.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone + 'static {
+/// use std::cell::RefCell;
+///
+/// use web_glitz_buffer_vec::{BufferVec, MemoryRegistry, RecoveryPlan};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut registry = MemoryRegistry::new();
+///
+/// let mut vec = BufferVec::new(context.clone(), UsageHint::StaticDraw);
+///
+/// vec.set_resilient(true);
+/// vec.update(vec![0u32; 1024]);
+///
+/// let vec = std::rc::Rc::new(RefCell::new(vec));
+///
+/// registry.register(&vec);
+///
+/// // After the context was restored:
+/// let mut plan = RecoveryPlan::new(&registry, &context).budget_bytes_per_frame(1024);
+///
+/// // Then once per frame:
+/// let progress = plan.step();
+///
+/// assert_eq!(progress.remaining_bytes, 3 * 1024);
+/// assert!(!vec.borrow().is_restored());
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [step]: RecoveryPlan::step
+/// [budget_bytes_per_frame]: RecoveryPlan::budget_bytes_per_frame
+/// [is_finished]: RecoveryPlan::is_finished
+pub struct RecoveryPlan<Rc> {
+    pending: Vec<Weak<RefCell<dyn ManagedVec<Rc>>>>,
+    restored: usize,
+    budget_bytes_per_frame: usize,
+}
+
+impl<Rc> RecoveryPlan<Rc>
+where
+    Rc: Clone,
+{
+    /// Creates a plan that restores all vectors registered with the `registry` that are still
+    /// alive for the given `context`, allocating new buffers for all of them.
+    ///
+    /// By default, a single [step](RecoveryPlan::step) restores all vectors at once; see
+    /// [budget_bytes_per_frame](RecoveryPlan::budget_bytes_per_frame).
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the registered vectors is currently mutably borrowed.
+    pub fn new(registry: &MemoryRegistry<Rc>, context: &Rc) -> Self {
+        let mut pending = Vec::new();
+        let mut restored = 0;
+
+        for entry in &registry.entries {
+            if let Some(vec) = entry.vec.upgrade() {
+                let mut vec_mut = vec.borrow_mut();
+
+                vec_mut.begin_restore(context.clone());
+
+                if vec_mut.is_restored() {
+                    restored += 1;
+                } else {
+                    pending.push(entry.vec.clone());
+                }
+            }
+        }
+
+        RecoveryPlan {
+            pending,
+            restored,
+            budget_bytes_per_frame: usize::MAX,
+        }
+    }
+}
+
+impl<Rc> RecoveryPlan<Rc> {
+    /// Sets the maximum number of bytes that a single [step](RecoveryPlan::step) uploads.
+    ///
+    /// A step may exceed the budget by less than the size of a single element, as it uploads at
+    /// least one element of every vector it starts on.
+    pub fn budget_bytes_per_frame(mut self, bytes: usize) -> Self {
+        self.budget_bytes_per_frame = bytes;
+
+        self
+    }
+
+    /// Re-uploads the data of the pending vectors in order of priority, until the
+    /// [budget](RecoveryPlan::budget_bytes_per_frame) for this step is used up, and reports the
+    /// progress of the plan.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the pending vectors is currently borrowed.
+    pub fn step(&mut self) -> RecoveryProgress {
+        let mut budget = self.budget_bytes_per_frame;
+        let mut restored = 0;
+
+        self.pending.retain(|entry| {
+            let vec = match entry.upgrade() {
+                Some(vec) => vec,
+                None => return false,
+            };
+
+            let mut vec = vec.borrow_mut();
+
+            if budget > 0 && !vec.is_restored() {
+                budget = budget.saturating_sub(vec.restore_step(budget));
+            }
+
+            if vec.is_restored() {
+                restored += 1;
+
+                false
+            } else {
+                true
+            }
+        });
+
+        self.restored += restored;
+
+        self.progress()
+    }
+
+    /// The progress of this plan.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the pending vectors is currently mutably borrowed.
+    pub fn progress(&self) -> RecoveryProgress {
+        let live = self.pending.iter().filter_map(|entry| entry.upgrade());
+
+        let mut remaining = 0;
+        let mut remaining_bytes = 0;
+
+        for vec in live {
+            remaining += 1;
+            remaining_bytes += vec.borrow().restore_remaining_bytes();
+        }
+
+        RecoveryProgress {
+            restored: self.restored,
+            remaining,
+            remaining_bytes,
+        }
+    }
+
+    /// Returns `true` if all vectors of this plan that are still alive have been restored.
+    pub fn is_finished(&self) -> bool {
+        self.pending.iter().all(|entry| entry.strong_count() == 0)
+    }
+}
+
+/// The progress of a [RecoveryPlan].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct RecoveryProgress {
+    /// The number of vectors that have been restored.
+    pub restored: usize,
+
+    /// The number of vectors that have not been restored yet.
+    pub remaining: usize,
+
+    /// The number of bytes that remain to be re-uploaded.
+    pub remaining_bytes: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::rc::Rc;
+
+    /// A vector of `len` bytes that records the chunks it restores.
+    struct FakeVec {
+        len: usize,
+        restored: Option<usize>,
+        log: Rc<RefCell<Vec<(&'static str, usize)>>>,
+        name: &'static str,
+    }
+
+    impl FakeVec {
+        fn new(
+            name: &'static str,
+            len: usize,
+            log: &Rc<RefCell<Vec<(&'static str, usize)>>>,
+        ) -> Rc<RefCell<Self>> {
+            Rc::new(RefCell::new(FakeVec {
+                len,
+                restored: None,
+                log: log.clone(),
+                name,
+            }))
+        }
+    }
+
+    impl ManagedVec<()> for FakeVec {
+        fn is_resilient(&self) -> bool {
+            true
+        }
+
+        fn recreate(&mut self, _context: ()) {
+            self.restored = None;
+        }
+
+        fn begin_restore(&mut self, _context: ()) {
+            self.restored = if self.len > 0 { Some(0) } else { None };
+        }
+
+        fn restore_step(&mut self, max_bytes: usize) -> usize {
+            let restored = self.restored.unwrap();
+            let end = self.len.min(restored + max_bytes.max(1));
+
+            self.log.borrow_mut().push((self.name, end - restored));
+            self.restored = if end < self.len { Some(end) } else { None };
+
+            end - restored
+        }
+
+        fn restore_remaining_bytes(&self) -> usize {
+            self.restored.map_or(0, |restored| self.len - restored)
+        }
+
+        fn is_restored(&self) -> bool {
+            self.restored.is_none()
+        }
+    }
+
+    #[test]
+    fn test_recovery_plan_priority_and_budget() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let low = FakeVec::new("low", 4, &log);
+        let high = FakeVec::new("high", 6, &log);
+        let empty = FakeVec::new("empty", 0, &log);
+        let mut registry = MemoryRegistry::new();
+
+        registry.register(&low);
+        registry.register_with_priority(&high, 1);
+        registry.register(&empty);
+
+        let mut plan = RecoveryPlan::new(&registry, &()).budget_bytes_per_frame(4);
+
+        assert_eq!(
+            plan.progress(),
+            RecoveryProgress {
+                restored: 1,
+                remaining: 2,
+                remaining_bytes: 10
+            }
+        );
+
+        assert_eq!(plan.step().remaining_bytes, 6);
+        assert!(!high.borrow().is_restored());
+
+        assert_eq!(plan.step().restored, 2);
+        assert!(high.borrow().is_restored());
+        assert!(!low.borrow().is_restored());
+
+        assert_eq!(plan.step().remaining_bytes, 0);
+        assert!(plan.is_finished());
+
+        assert_eq!(
+            *log.borrow(),
+            vec![("high", 4), ("high", 2), ("low", 2), ("low", 2)]
+        );
+    }
+
+    #[test]
+    fn test_recovery_plan_skips_dropped_vecs() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let first = FakeVec::new("first", 2, &log);
+        let second = FakeVec::new("second", 2, &log);
+        let mut registry = MemoryRegistry::new();
+
+        registry.register(&first);
+        registry.register(&second);
+
+        let mut plan = RecoveryPlan::new(&registry, &()).budget_bytes_per_frame(2);
+
+        drop(first);
+
+        assert_eq!(plan.step().restored, 1);
+        assert!(plan.is_finished());
+        assert_eq!(*log.borrow(), vec![("second", 2)]);
+    }
+}
@@ -0,0 +1,238 @@
+use std::borrow::Borrow;
+use std::cell::{BorrowMutError, Ref, RefCell, RefMut};
+
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::BufferVec;
+
+/// A [BufferVec] that may be written to through a shared reference, intended for vectors that are
+/// filled by several independent systems.
+///
+/// Any number of systems may hold a (reference counted) reference to the same [SharedBufferVec]
+/// and write to it with [append] and [update_range]. Only a single write may be in progress at any
+/// time, and no write may happen while the vector is borrowed for reading (see [borrow]). This is
+/// enforced at runtime: a write that conflicts with an outstanding borrow panics (the `try_`
+/// variants return an error instead).
+///
+/// # Send and Sync
+///
+/// A [SharedBufferVec] is neither [Send] nor [Sync]. WebGlitz buffers are bound to the thread that
+/// owns the rendering context, so a [BufferVec] cannot be moved to or shared with other threads in
+/// the first place; the interior mutability of a [SharedBufferVec] is therefore based on a
+/// [RefCell] rather than a lock. To share a vector between systems, wrap it in an
+/// [Rc](std::rc::Rc).
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+/// use std::rc::Rc;
+///
+/// use web_glitz_buffer_vec::{BufferVec, SharedBufferVec};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let vec = Rc::new(SharedBufferVec::new(BufferVec::new(context, UsageHint::StreamDraw)));
+///
+/// let system_a = vec.clone();
+/// let system_b = vec.clone();
+///
+/// system_a.append([1, 2, 3]);
+/// system_b.append([4, 5]);
+///
+/// assert_eq!(vec.len(), 5);
+///
+/// let guard = vec.borrow();
+/// let view = guard.as_buffer_view();
+///
+/// assert_eq!(view.len(), 5);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [append]: SharedBufferVec::append
+/// [update_range]: SharedBufferVec::update_range
+/// [borrow]: SharedBufferVec::borrow
+pub struct SharedBufferVec<Rc, T> {
+    inner: RefCell<BufferVec<Rc, T>>,
+}
+
+impl<Rc, T> SharedBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Wraps the given `vec` for shared access.
+    pub fn new(vec: BufferVec<Rc, T>) -> Self {
+        SharedBufferVec {
+            inner: RefCell::new(vec),
+        }
+    }
+
+    /// Appends the given `data` to the end of the vector, growing the buffer if necessary.
+    ///
+    /// See [BufferVec::append] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently borrowed, or for any of the reasons
+    /// [BufferVec::append] panics.
+    pub fn append<D>(&self, data: D) -> bool
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        borrow_for_write(&self.inner).append(data)
+    }
+
+    /// Appends the given `data` to the end of the vector, growing the buffer if necessary, or
+    /// returns an error if a new buffer could not be allocated.
+    ///
+    /// See [BufferVec::try_append] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently borrowed.
+    pub fn try_append<D>(&self, data: D) -> Result<bool, BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        borrow_for_write(&self.inner).try_append(data)
+    }
+
+    /// Overwrites the elements starting at `offset` with the given `data`.
+    ///
+    /// See [BufferVec::update_range] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently borrowed, or for any of the reasons
+    /// [BufferVec::update_range] panics.
+    pub fn update_range<D>(&self, offset: usize, data: D)
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        borrow_for_write(&self.inner).update_range(offset, data)
+    }
+
+    /// Overwrites the elements starting at `offset` with the given `data`, or returns an error if
+    /// the range is not valid for the vector.
+    ///
+    /// See [BufferVec::try_update_range] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is currently borrowed.
+    pub fn try_update_range<D>(&self, offset: usize, data: D) -> Result<(), BufferVecError>
+    where
+        D: Borrow<[T]> + Send + Sync + 'static,
+    {
+        borrow_for_write(&self.inner).try_update_range(offset, data)
+    }
+
+    /// The number of elements in this vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a write to the vector is in progress.
+    pub fn len(&self) -> usize {
+        self.inner.borrow().len()
+    }
+
+    /// Returns `true` if this vector contains no elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a write to the vector is in progress.
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().is_empty()
+    }
+
+    /// The number of elements this vector can hold without allocating a new buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a write to the vector is in progress.
+    pub fn capacity(&self) -> usize {
+        self.inner.borrow().capacity()
+    }
+
+    /// Borrows the vector for reading, e.g. to obtain a view on its data with
+    /// [BufferVec::as_buffer_view].
+    ///
+    /// Writes to the vector panic for as long as the returned guard is alive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a write to the vector is in progress.
+    pub fn borrow(&self) -> Ref<BufferVec<Rc, T>> {
+        self.inner.borrow()
+    }
+
+    /// Borrows the vector for writing, e.g. to call any of the [BufferVec] methods that are not
+    /// directly exposed on [SharedBufferVec].
+    ///
+    /// Returns an error if the vector is currently borrowed.
+    pub fn try_borrow_mut(&self) -> Result<RefMut<BufferVec<Rc, T>>, BorrowMutError> {
+        self.inner.try_borrow_mut()
+    }
+
+    /// Consumes the wrapper, returning the wrapped vector.
+    pub fn into_inner(self) -> BufferVec<Rc, T> {
+        self.inner.into_inner()
+    }
+}
+
+impl<Rc, T> From<BufferVec<Rc, T>> for SharedBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    fn from(vec: BufferVec<Rc, T>) -> Self {
+        SharedBufferVec::new(vec)
+    }
+}
+
+/// Borrows the `cell` mutably, panicking with a descriptive message if it is already borrowed.
+fn borrow_for_write<V>(cell: &RefCell<V>) -> RefMut<V> {
+    cell.try_borrow_mut().unwrap_or_else(|_| {
+        panic!(
+            "Cannot write to a shared buffer vec while it is borrowed; drop any guards obtained \
+             with `borrow` before writing."
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borrow_for_write_uncontended() {
+        let cell = RefCell::new(1);
+
+        *borrow_for_write(&cell) += 1;
+
+        assert_eq!(*cell.borrow(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot write to a shared buffer vec while it is borrowed")]
+    fn test_borrow_for_write_while_reading() {
+        let cell = RefCell::new(1);
+        let _guard = cell.borrow();
+
+        borrow_for_write(&cell);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot write to a shared buffer vec while it is borrowed")]
+    fn test_borrow_for_write_reentrant() {
+        let cell = RefCell::new(1);
+        let _outer = borrow_for_write(&cell);
+
+        borrow_for_write(&cell);
+    }
+}
@@ -0,0 +1,344 @@
+use std::borrow::Borrow;
+use std::mem;
+use std::mem::MaybeUninit;
+
+use web_glitz::buffer::Buffer;
+use web_glitz::runtime::RenderingContext;
+
+use crate::budget::BudgetReservation;
+use crate::leak_tracking::LiveBuffer;
+use crate::BufferVec;
+
+/// Decides the length of a [BufferVec] while a [SlicedUpload] to it is in progress.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlicedLen {
+    /// The vector keeps its previous length and data until the upload completes.
+    ///
+    /// The new data is uploaded to a separate buffer, which replaces the vector's buffer when the
+    /// upload completes; this requires memory for both buffers while the upload is in progress.
+    Previous,
+
+    /// The vector's length is the length of the prefix of the new data that has been uploaded so
+    /// far.
+    ///
+    /// The new data is uploaded to the vector's own buffer; the previous data is discarded when
+    /// the upload starts.
+    CompletedPrefix,
+}
+
+/// The progress of a [SlicedUpload], reported by [SlicedUpload::step].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct SlicedProgress {
+    /// Whether all data has been uploaded.
+    pub done: bool,
+
+    /// Whether the upload was cancelled before it completed, see [SlicedUpload].
+    pub cancelled: bool,
+
+    /// The number of bytes uploaded so far.
+    pub uploaded_bytes: usize,
+
+    /// The total number of bytes to upload.
+    pub total_bytes: usize,
+}
+
+/// The progress of an upload, passed to a progress callback after each chunk of the upload is
+/// submitted.
+///
+/// See [SlicedUpload::on_progress].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct UploadProgress {
+    /// The index of the chunk that was submitted.
+    pub chunk_index: usize,
+
+    /// The number of bytes submitted so far, including this chunk.
+    pub uploaded_bytes: usize,
+
+    /// The total number of bytes to upload.
+    pub total_bytes: usize,
+
+    /// Whether this is the final invocation of the callback for the upload.
+    ///
+    /// The final invocation happens exactly once per upload: after its last chunk is submitted,
+    /// or, if the upload does not complete, when it is found to be cancelled or is dropped.
+    pub done: bool,
+
+    /// Whether the upload was cancelled or dropped before it completed; only ever `true` for the
+    /// final invocation.
+    pub cancelled: bool,
+}
+
+/// Invokes a progress callback, guaranteeing a single final invocation.
+struct ProgressHook {
+    callback: Option<Box<dyn FnMut(UploadProgress)>>,
+    last: UploadProgress,
+}
+
+impl ProgressHook {
+    fn new(total_bytes: usize) -> Self {
+        ProgressHook {
+            callback: None,
+            last: UploadProgress {
+                total_bytes,
+                ..UploadProgress::default()
+            },
+        }
+    }
+
+    /// Reports the submission of a chunk, ending at `uploaded_bytes`.
+    fn chunk(&mut self, chunk_index: usize, uploaded_bytes: usize, done: bool) {
+        self.last.chunk_index = chunk_index;
+        self.last.uploaded_bytes = uploaded_bytes;
+        self.last.done = done;
+
+        self.invoke();
+    }
+
+    /// Makes the final invocation for an upload that did not complete, unless the final
+    /// invocation already happened.
+    fn cancel(&mut self) {
+        if !self.last.done {
+            self.last.done = true;
+            self.last.cancelled = true;
+
+            self.invoke();
+        }
+    }
+
+    fn invoke(&mut self) {
+        if let Some(callback) = &mut self.callback {
+            callback(self.last);
+        }
+    }
+}
+
+impl Drop for ProgressHook {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// The buffer a [SlicedUpload] uploads its slices to.
+pub(crate) enum SliceTarget<T> {
+    /// The vector's own buffer, for [SlicedLen::CompletedPrefix].
+    Vec,
+
+    /// A separate buffer that replaces the vector's buffer on completion, for
+    /// [SlicedLen::Previous], along with its accounting and its reservation in the vector's
+    /// [MemoryBudget], if any.
+    ///
+    /// [MemoryBudget]: crate::MemoryBudget
+    Staging(
+        Buffer<[MaybeUninit<T>]>,
+        LiveBuffer,
+        Option<BudgetReservation>,
+    ),
+}
+
+/// An upload of new data to a [BufferVec] that is spread over several frames.
+///
+/// Created with [BufferVec::update_sliced]; owns the new data. Each call to [step] uploads the next
+/// slice of the data, within the byte budget per step the upload was created with. What the
+/// vector's length is while the upload is in progress is decided by [SlicedLen].
+///
+/// The upload is cancelled if another update of all data of the vector is started before it
+/// completes (such as with [BufferVec::update] or another [BufferVec::update_sliced]). For a
+/// [SlicedLen::CompletedPrefix] upload, any other operation that changes the length of the vector
+/// or reallocates its buffer also cancels the upload. A cancelled upload reports
+/// [cancelled](SlicedProgress::cancelled) and uploads nothing further; the vector is left as the
+/// other operation left it.
+///
+/// [step]: SlicedUpload::step
+pub struct SlicedUpload<T, D> {
+    data: D,
+    source_id: u64,
+    ticket: u64,
+    generation: u64,
+    slice_len: usize,
+    uploaded: usize,
+    slices: usize,
+    target: Option<SliceTarget<T>>,
+    cancelled: bool,
+    progress: ProgressHook,
+}
+
+impl<T, D> SlicedUpload<T, D>
+where
+    T: Copy + Send + Sync + 'static,
+    D: Borrow<[T]>,
+{
+    pub(crate) fn new(
+        data: D,
+        source_id: u64,
+        ticket: u64,
+        generation: u64,
+        bytes_per_step: usize,
+        target: SliceTarget<T>,
+    ) -> Self {
+        let total_bytes = mem::size_of_val(data.borrow());
+
+        SlicedUpload {
+            data,
+            source_id,
+            ticket,
+            generation,
+            slice_len: (bytes_per_step / mem::size_of::<T>()).max(1),
+            uploaded: 0,
+            slices: 0,
+            target: Some(target),
+            cancelled: false,
+            progress: ProgressHook::new(total_bytes),
+        }
+    }
+
+    /// Sets the `callback` that is invoked after each slice of this upload is submitted, and
+    /// returns the upload.
+    ///
+    /// The callback receives the cumulative progress of the upload, see [UploadProgress]. It is
+    /// invoked with [done](UploadProgress::done) set exactly once: after the last slice is
+    /// submitted (for data that fits in a single slice, that is the only invocation), or if the
+    /// upload does not complete, when [step](SlicedUpload::step) finds that it was cancelled or
+    /// when the upload is dropped.
+    ///
+    /// The callback is invoked while the vector is mutably borrowed by
+    /// [step](SlicedUpload::step), so it has no access to the vector; it is intended for
+    /// reporting, such as updating a progress bar.
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(UploadProgress) + 'static,
+    {
+        self.progress.callback = Some(Box::new(callback));
+
+        self
+    }
+
+    /// Uploads the next slice of the data to the `vec`, at most as many bytes as the budget per
+    /// step allows but at least one element, and reports the progress of the upload.
+    ///
+    /// Once the last slice is uploaded, the vector holds the new data. Does nothing if the upload
+    /// is already done or was cancelled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `vec` is not the vector this upload was created for.
+    pub fn step<Rc>(&mut self, vec: &mut BufferVec<Rc, T>) -> SlicedProgress
+    where
+        Rc: RenderingContext,
+    {
+        assert!(
+            vec.source_id() == self.source_id,
+            "A sliced upload can only be stepped with the buffer vec it was created for."
+        );
+
+        if self.target.is_some() && !self.is_current(vec) {
+            self.target = None;
+            self.cancelled = true;
+            self.progress.cancel();
+        }
+
+        if let Some(target) = &self.target {
+            let data = self.data.borrow();
+            let end = data.len().min(self.uploaded + self.slice_len);
+
+            vec.upload_slice(target, self.uploaded, &data[self.uploaded..end]);
+
+            self.uploaded = end;
+
+            let done = end == data.len();
+
+            if done {
+                vec.finish_sliced_upload(self.target.take().unwrap(), data);
+            }
+
+            self.progress
+                .chunk(self.slices, end * mem::size_of::<T>(), done);
+            self.slices += 1;
+        }
+
+        self.progress()
+    }
+
+    /// The progress of this upload.
+    pub fn progress(&self) -> SlicedProgress {
+        let element_size = mem::size_of::<T>();
+
+        SlicedProgress {
+            done: self.target.is_none() && !self.cancelled,
+            cancelled: self.cancelled,
+            uploaded_bytes: self.uploaded * element_size,
+            total_bytes: mem::size_of_val(self.data.borrow()),
+        }
+    }
+
+    /// Consumes the upload, returning the data.
+    pub fn into_data(self) -> D {
+        self.data
+    }
+
+    /// Returns `true` if the `vec` has not started another update since this upload started.
+    fn is_current<Rc>(&self, vec: &BufferVec<Rc, T>) -> bool
+    where
+        Rc: RenderingContext,
+    {
+        if vec.sliced_upload() != Some(self.ticket) {
+            return false;
+        }
+
+        match self.target {
+            Some(SliceTarget::Vec) => {
+                vec.len() == self.uploaded && vec.generation() == self.generation
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn recorded_hook(total_bytes: usize) -> (ProgressHook, Rc<RefCell<Vec<UploadProgress>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut hook = ProgressHook::new(total_bytes);
+        let recorder = log.clone();
+
+        hook.callback = Some(Box::new(move |progress| {
+            recorder.borrow_mut().push(progress)
+        }));
+
+        (hook, log)
+    }
+
+    #[test]
+    fn test_progress_hook_single_final_invocation() {
+        let (mut hook, log) = recorded_hook(8);
+
+        hook.chunk(0, 4, false);
+        hook.chunk(1, 8, true);
+        drop(hook);
+
+        let log = log.take();
+
+        assert_eq!(log.len(), 2);
+        assert_eq!((log[1].chunk_index, log[1].uploaded_bytes), (1, 8));
+        assert!(log[1].done && !log[1].cancelled);
+    }
+
+    #[test]
+    fn test_progress_hook_dropped_before_done() {
+        let (mut hook, log) = recorded_hook(8);
+
+        hook.chunk(0, 4, false);
+        hook.cancel();
+        drop(hook);
+
+        let log = log.take();
+
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[1].uploaded_bytes, 4);
+        assert!(log[1].done && log[1].cancelled);
+    }
+}
@@ -0,0 +1,41 @@
+use std::mem::MaybeUninit;
+
+use web_glitz::buffer::{Buffer, BufferView};
+
+use crate::leak_tracking::LiveBuffer;
+
+/// A frozen copy of the data in a [BufferVec] that lives on the GPU, independent of the vector it
+/// was taken from.
+///
+/// Created with [BufferVec::snapshot_gpu] and restored with [BufferVec::restore_gpu]; neither
+/// direction stages data on the CPU.
+///
+/// [BufferVec]: crate::BufferVec
+/// [BufferVec::snapshot_gpu]: crate::BufferVec::snapshot_gpu
+/// [BufferVec::restore_gpu]: crate::BufferVec::restore_gpu
+pub struct GpuSnapshot<T> {
+    pub(crate) buffer: Buffer<[MaybeUninit<T>]>,
+    // Only held for its accounting of the buffer.
+    pub(crate) _live_buffer: LiveBuffer,
+    pub(crate) shadow: Option<Vec<T>>,
+}
+
+impl<T> GpuSnapshot<T>
+where
+    T: Copy + 'static,
+{
+    /// The number of elements in this snapshot.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if this snapshot contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.len() == 0
+    }
+
+    /// Returns a view on the data in this snapshot.
+    pub fn as_buffer_view(&self) -> BufferView<[T]> {
+        unsafe { self.buffer.get(..).unwrap().assume_init() }
+    }
+}
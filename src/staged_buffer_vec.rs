@@ -0,0 +1,230 @@
+use std::ops::{Deref, Range};
+
+use web_glitz::buffer::UsageHint;
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::util::{checked_range, error_message, extend_range, UploadData};
+use crate::validation::{violations_result, InvariantViolation};
+use crate::BufferVec;
+
+/// A [BufferVec] paired with a CPU-side staging [Vec] that is the source of truth for its data,
+/// and that is only synchronized with the GPU buffer by [upload].
+///
+/// WebGL has no persistently mapped buffers, but the pattern they enable can be emulated: the
+/// staging data may be modified freely during a frame, in the layout the GPU buffer expects, and
+/// is then uploaded once with [upload]. Unlike a [MirroredBufferVec], where every write is uploaded
+/// as it is made, writes to the staging data are not uploaded until [upload] is called; until then,
+/// the GPU buffer holds the data of the previous upload.
+///
+/// The staging data is accessed with [staging_mut], after which [upload] uploads all staging data,
+/// or with [staging_range_mut], which tracks the range of elements that may have been written (the
+/// watermark), after which [upload] only uploads that range. If the length of the staging data
+/// differs from the length of the GPU buffer, [upload] always uploads all staging data.
+///
+/// Dereferences to the wrapped [BufferVec] for read-only operations on the GPU side, such as
+/// [BufferVec::as_buffer_view].
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+/// use web_glitz_buffer_vec::StagedBufferVec;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut transforms = StagedBufferVec::new(context, UsageHint::DynamicDraw);
+///
+/// transforms.staging_mut().extend_from_slice(&[0.0f32; 64]);
+/// transforms.upload();
+///
+/// // During the next frame, only a few elements change.
+/// transforms.staging_range_mut(8..12).copy_from_slice(&[1.0; 4]);
+///
+/// assert!(transforms.is_dirty());
+///
+/// // Uploads only elements `8..12`.
+/// transforms.upload();
+///
+/// assert_eq!(transforms.as_buffer_view().len(), 64);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [upload]: StagedBufferVec::upload
+/// [staging_mut]: StagedBufferVec::staging_mut
+/// [staging_range_mut]: StagedBufferVec::staging_range_mut
+/// [MirroredBufferVec]: crate::MirroredBufferVec
+pub struct StagedBufferVec<Rc, T> {
+    inner: BufferVec<Rc, T>,
+    staging: Vec<T>,
+    // Whether all staging data must be uploaded, regardless of the watermark.
+    all_dirty: bool,
+    // The range of elements that may have been written since the last upload.
+    watermark: Option<Range<usize>>,
+}
+
+impl<Rc, T> StagedBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    /// Creates a new staged vector with 0 capacity for the given [RenderingContext].
+    ///
+    /// See [BufferVec::new] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        StagedBufferVec::from_buffer_vec(BufferVec::new(context, usage))
+    }
+
+    /// Creates a new staged vector with the specified `capacity` for the given
+    /// [RenderingContext].
+    ///
+    /// The capacity applies to both the GPU buffer and the staging data. See
+    /// [BufferVec::with_capacity] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn with_capacity(context: Rc, usage: UsageHint, capacity: usize) -> Self {
+        let mut vec =
+            StagedBufferVec::from_buffer_vec(BufferVec::with_capacity(context, usage, capacity));
+
+        vec.staging.reserve(capacity);
+
+        vec
+    }
+
+    fn from_buffer_vec(inner: BufferVec<Rc, T>) -> Self {
+        StagedBufferVec {
+            inner,
+            staging: Vec::new(),
+            all_dirty: false,
+            watermark: None,
+        }
+    }
+
+    /// The staging data.
+    pub fn staging(&self) -> &[T] {
+        &self.staging
+    }
+
+    /// The staging data, for modification; the next [upload] uploads all staging data.
+    ///
+    /// Use [staging_range_mut] to only upload the elements that were modified.
+    ///
+    /// [upload]: StagedBufferVec::upload
+    /// [staging_range_mut]: StagedBufferVec::staging_range_mut
+    pub fn staging_mut(&mut self) -> &mut Vec<T> {
+        self.all_dirty = true;
+
+        &mut self.staging
+    }
+
+    /// The elements in the `range` of the staging data, for modification; the next [upload]
+    /// uploads (at least) these elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `range` is out of bounds for the staging data.
+    ///
+    /// [upload]: StagedBufferVec::upload
+    pub fn staging_range_mut(&mut self, range: Range<usize>) -> &mut [T] {
+        let len = self.staging.len();
+
+        checked_range(range.start, range.end.saturating_sub(range.start), len)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.inner.label())));
+
+        self.watermark = extend_range(self.watermark.take(), range.clone());
+
+        &mut self.staging[range]
+    }
+
+    /// Returns `true` if the staging data may have been modified since the last [upload].
+    ///
+    /// [upload]: StagedBufferVec::upload
+    pub fn is_dirty(&self) -> bool {
+        self.all_dirty || self.watermark.is_some() || self.staging.len() != self.inner.len()
+    }
+
+    /// Uploads the staging data to the GPU buffer, growing the buffer if necessary.
+    ///
+    /// Uploads all staging data if the staging data was accessed with [staging_mut] or its length
+    /// differs from the length of the GPU buffer; otherwise uploads only the range of elements
+    /// accessed with [staging_range_mut] since the last upload, or nothing.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [BufferVec::update].
+    ///
+    /// [staging_mut]: StagedBufferVec::staging_mut
+    /// [staging_range_mut]: StagedBufferVec::staging_range_mut
+    pub fn upload(&mut self) -> bool {
+        self.try_upload()
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.inner.label())))
+    }
+
+    /// Uploads the staging data to the GPU buffer, or returns an error if a new buffer could not
+    /// be allocated.
+    ///
+    /// If an error is returned, the staging data remains dirty. See [upload] for details.
+    ///
+    /// [upload]: StagedBufferVec::upload
+    pub fn try_upload(&mut self) -> Result<bool, BufferVecError> {
+        let reallocated = if self.all_dirty || self.staging.len() != self.inner.len() {
+            self.inner.try_update(UploadData(self.staging.clone()))?
+        } else {
+            if let Some(range) = self.watermark.clone() {
+                let data = UploadData(self.staging[range.clone()].to_vec());
+
+                self.inner.try_update_range(range.start, data)?;
+            }
+
+            false
+        };
+
+        self.all_dirty = false;
+        self.watermark = None;
+
+        Ok(reallocated)
+    }
+
+    /// Checks the internal invariants of this vector, returning all violations found.
+    ///
+    /// Reports the violations of the wrapped [BufferVec] (see [BufferVec::debug_validate]), and
+    /// flags staging data that was modified but not yet uploaded as
+    /// [InvariantViolation::Unflushed].
+    pub fn debug_validate(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut violations = self.inner.debug_validate().err().unwrap_or_default();
+
+        if self.all_dirty || self.staging.len() != self.inner.len() {
+            violations.push(InvariantViolation::Unflushed {
+                elements: self.staging.len(),
+            });
+        } else if let Some(range) = &self.watermark {
+            violations.push(InvariantViolation::Unflushed {
+                elements: range.len(),
+            });
+        }
+
+        violations_result(violations)
+    }
+
+    /// Consumes the staged vector, returning the wrapped [BufferVec] and the staging data.
+    ///
+    /// The [BufferVec] holds the data of the last [upload](StagedBufferVec::upload).
+    pub fn into_parts(self) -> (BufferVec<Rc, T>, Vec<T>) {
+        (self.inner, self.staging)
+    }
+}
+
+impl<Rc, T> Deref for StagedBufferVec<Rc, T> {
+    type Target = BufferVec<Rc, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::VecDeque;
+#[cfg(feature = "stats")]
+use std::fmt;
+
+/// Upload and allocation statistics for a buffer vector.
+///
+/// Obtained with `stats` on the vector types; counters accumulate until they are reset with
+/// `reset_stats`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct VecStats {
+    /// The number of upload commands submitted.
+    pub uploads: u64,
+
+    /// The total size in bytes of the data uploaded.
+    pub bytes_uploaded: u64,
+
+    /// The number of times a new buffer was allocated.
+    pub reallocations: u64,
+
+    /// The largest capacity in elements the vector has had.
+    pub peak_capacity: usize,
+}
+
+impl VecStats {
+    /// Creates new statistics for a vector with the given current `capacity`.
+    pub(crate) fn new(capacity: usize) -> Self {
+        VecStats {
+            peak_capacity: capacity,
+            ..VecStats::default()
+        }
+    }
+
+    /// Records an upload of `len` elements of `element_size` bytes each.
+    pub(crate) fn record_upload(&mut self, len: usize, element_size: usize) {
+        self.uploads += 1;
+        self.bytes_uploaded += len as u64 * element_size as u64;
+    }
+
+    /// Records the allocation of a new buffer with the given `capacity`.
+    pub(crate) fn record_reallocation(&mut self, capacity: usize) {
+        self.reallocations += 1;
+        self.peak_capacity = self.peak_capacity.max(capacity);
+    }
+}
+
+/// The number of buckets in a [Log2Histogram]: one for zero and one for each bit of a `usize`.
+#[cfg(feature = "stats")]
+const LOG2_BUCKETS: usize = usize::BITS as usize + 1;
+
+/// A histogram with logarithmic (base 2) buckets.
+///
+/// Bucket `0` counts the value `0`; bucket `i > 0` counts the values in `2^(i - 1)..2^i`. The
+/// buckets are a fixed-size array, recording a value never allocates.
+///
+/// Implements [Display](fmt::Display), which renders the non-empty buckets as a text table.
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Log2Histogram {
+    buckets: [u64; LOG2_BUCKETS],
+}
+
+#[cfg(feature = "stats")]
+impl Log2Histogram {
+    /// Creates a new empty histogram.
+    pub fn new() -> Self {
+        Log2Histogram {
+            buckets: [0; LOG2_BUCKETS],
+        }
+    }
+
+    /// Records the given `value`.
+    pub fn record(&mut self, value: usize) {
+        self.buckets[log2_bucket(value)] += 1;
+    }
+
+    /// The total number of values recorded.
+    pub fn total(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns an iterator over the non-empty buckets, as the inclusive range of values the
+    /// bucket covers (`(min, max)`) and the number of values recorded in it.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| (log2_bucket_bounds(bucket), *count))
+    }
+}
+
+#[cfg(feature = "stats")]
+impl Default for Log2Histogram {
+    fn default() -> Self {
+        Log2Histogram::new()
+    }
+}
+
+#[cfg(feature = "stats")]
+impl fmt::Display for Log2Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.total() == 0 {
+            return writeln!(f, "(empty)");
+        }
+
+        for ((min, max), count) in self.iter() {
+            writeln!(f, "{:>20} ..= {:<20} | {}", min, max, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The bucket in a [Log2Histogram] for the given `value`.
+#[cfg(feature = "stats")]
+fn log2_bucket(value: usize) -> usize {
+    (usize::BITS - value.leading_zeros()) as usize
+}
+
+/// The inclusive range of values covered by the given `bucket` in a [Log2Histogram].
+#[cfg(feature = "stats")]
+fn log2_bucket_bounds(bucket: usize) -> (usize, usize) {
+    if bucket == 0 {
+        (0, 0)
+    } else {
+        let min = 1 << (bucket - 1);
+
+        (min, min - 1 + min)
+    }
+}
+
+/// Histograms of the update lengths and reallocation capacities a buffer vector saw, for tuning
+/// growth policies.
+///
+/// Obtained with `stats_histograms` on the vector types and reset with `reset_stats_histograms`.
+/// Only available with the `stats` feature.
+///
+/// Implements [Display](fmt::Display), which renders both histograms as text tables.
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CapacityHistograms {
+    /// The lengths, in elements, of the uploads submitted.
+    pub update_lengths: Log2Histogram,
+
+    /// The capacities, in elements, of the buffers allocated.
+    pub reallocation_capacities: Log2Histogram,
+}
+
+#[cfg(feature = "stats")]
+impl fmt::Display for CapacityHistograms {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "update lengths:")?;
+        write!(f, "{}", self.update_lengths)?;
+        writeln!(f, "reallocation capacities:")?;
+        write!(f, "{}", self.reallocation_capacities)
+    }
+}
+
+/// The default maximum number of reallocations within the warning window before a reallocation
+/// warning is emitted.
+const DEFAULT_MAX_REALLOCATIONS: usize = 4;
+
+/// The default size, in uploads, of the reallocation warning window.
+const DEFAULT_REALLOCATION_WINDOW: usize = 16;
+
+/// Tracks when a vector reallocates, to detect vectors that reallocate suspiciously often.
+pub(crate) struct ReallocTracker {
+    max_reallocations: usize,
+    window: usize,
+    uploads: u64,
+    reallocations: VecDeque<u64>,
+    suppressed: bool,
+}
+
+impl ReallocTracker {
+    /// Creates a new tracker with the default thresholds.
+    pub(crate) fn new() -> Self {
+        ReallocTracker {
+            max_reallocations: DEFAULT_MAX_REALLOCATIONS,
+            window: DEFAULT_REALLOCATION_WINDOW,
+            uploads: 0,
+            reallocations: VecDeque::new(),
+            suppressed: false,
+        }
+    }
+
+    /// Sets the tracker to warn when more than `max_reallocations` reallocations happen within
+    /// `window` uploads, and lifts any suppression.
+    pub(crate) fn set_thresholds(&mut self, max_reallocations: usize, window: usize) {
+        self.max_reallocations = max_reallocations;
+        self.window = window;
+        self.suppressed = false;
+        self.reallocations.clear();
+    }
+
+    /// Stops the tracker from ever warning.
+    pub(crate) fn suppress(&mut self) {
+        self.suppressed = true;
+        self.reallocations.clear();
+    }
+
+    /// Records an upload.
+    pub(crate) fn record_upload(&mut self) {
+        self.uploads += 1;
+    }
+
+    /// Records a reallocation and returns `true` if a warning should be emitted for it.
+    ///
+    /// After a warning, the reallocation history is cleared, so that a vector that keeps
+    /// reallocating warns at most once every `max_reallocations + 1` reallocations.
+    pub(crate) fn record_reallocation(&mut self) -> bool {
+        if self.suppressed {
+            return false;
+        }
+
+        self.reallocations.push_back(self.uploads);
+
+        while let Some(&oldest) = self.reallocations.front() {
+            if oldest + (self.window as u64) <= self.uploads {
+                self.reallocations.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.reallocations.len() > self.max_reallocations {
+            self.reallocations.clear();
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The number of recent lengths a [LengthHistory] remembers.
+const LENGTH_HISTORY_LEN: usize = 8;
+
+/// A small ring of the lengths a vector had at its most recent uploads, used to suggest a smaller
+/// capacity.
+pub(crate) struct LengthHistory {
+    lengths: [usize; LENGTH_HISTORY_LEN],
+    next: usize,
+    filled: usize,
+}
+
+impl LengthHistory {
+    /// Creates a new empty history.
+    pub(crate) fn new() -> Self {
+        LengthHistory {
+            lengths: [0; LENGTH_HISTORY_LEN],
+            next: 0,
+            filled: 0,
+        }
+    }
+
+    /// Records the length of the vector at an upload, replacing the oldest recorded length if the
+    /// history is full.
+    pub(crate) fn record(&mut self, len: usize) {
+        self.lengths[self.next] = len;
+        self.next = (self.next + 1) % LENGTH_HISTORY_LEN;
+        self.filled = (self.filled + 1).min(LENGTH_HISTORY_LEN);
+    }
+
+    /// The largest recorded length, or `None` if no lengths have been recorded yet.
+    pub(crate) fn max(&self) -> Option<usize> {
+        self.lengths[..self.filled].iter().copied().max()
+    }
+}
+
+/// Suggests a smaller capacity for a vector with the given `capacity` and `len`, based on the
+/// largest length it had recently, `recent_max`.
+///
+/// Suggests the next power of two at or above the larger of `len` and `recent_max`, but only if
+/// that would at least halve the capacity; returns `None` otherwise.
+pub(crate) fn suggest_shrink_capacity(
+    capacity: usize,
+    len: usize,
+    recent_max: usize,
+) -> Option<usize> {
+    let suggested = recent_max.max(len).max(1).checked_next_power_of_two()?;
+
+    if suggested <= capacity / 2 {
+        Some(suggested)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vec_stats() {
+        let mut stats = VecStats::new(4);
+
+        stats.record_upload(3, 2);
+        stats.record_reallocation(8);
+        stats.record_upload(8, 2);
+        stats.record_reallocation(2);
+
+        assert_eq!(
+            stats,
+            VecStats {
+                uploads: 2,
+                bytes_uploaded: 22,
+                reallocations: 2,
+                peak_capacity: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn test_realloc_tracker() {
+        let mut tracker = ReallocTracker::new();
+
+        tracker.set_thresholds(2, 4);
+
+        assert!(!tracker.record_reallocation());
+        tracker.record_upload();
+        assert!(!tracker.record_reallocation());
+        tracker.record_upload();
+        assert!(tracker.record_reallocation());
+
+        // The reallocations fall outside of the window before the threshold is exceeded.
+        tracker.record_upload();
+        assert!(!tracker.record_reallocation());
+        tracker.record_upload();
+        tracker.record_upload();
+        tracker.record_upload();
+        assert!(!tracker.record_reallocation());
+        tracker.record_upload();
+        tracker.record_upload();
+        tracker.record_upload();
+        tracker.record_upload();
+        assert!(!tracker.record_reallocation());
+    }
+
+    #[test]
+    fn test_realloc_tracker_suppressed() {
+        let mut tracker = ReallocTracker::new();
+
+        tracker.set_thresholds(0, 4);
+        tracker.suppress();
+
+        assert!(!tracker.record_reallocation());
+    }
+
+    #[test]
+    fn test_length_history() {
+        let mut history = LengthHistory::new();
+
+        assert_eq!(history.max(), None);
+
+        history.record(5);
+        history.record(3);
+
+        assert_eq!(history.max(), Some(5));
+
+        for _ in 0..LENGTH_HISTORY_LEN - 1 {
+            history.record(2);
+        }
+
+        // The 5 was the oldest entry and has been replaced.
+        assert_eq!(history.max(), Some(3));
+
+        history.record(2);
+
+        assert_eq!(history.max(), Some(2));
+    }
+
+    #[test]
+    fn test_suggest_shrink_capacity() {
+        assert_eq!(suggest_shrink_capacity(1024, 10, 100), Some(128));
+        assert_eq!(suggest_shrink_capacity(1024, 300, 100), Some(512));
+        assert_eq!(suggest_shrink_capacity(1024, 10, 600), None);
+        assert_eq!(suggest_shrink_capacity(16, 0, 0), Some(1));
+        assert_eq!(suggest_shrink_capacity(1, 0, 0), None);
+        assert_eq!(suggest_shrink_capacity(0, 0, 0), None);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_log2_histogram() {
+        let mut histogram = Log2Histogram::new();
+
+        for value in [0, 1, 2, 3, 4, 100, usize::MAX] {
+            histogram.record(value);
+        }
+
+        assert_eq!(histogram.total(), 7);
+        assert_eq!(
+            histogram.iter().collect::<Vec<_>>(),
+            vec![
+                ((0, 0), 1),
+                ((1, 1), 1),
+                ((2, 3), 2),
+                ((4, 7), 1),
+                ((64, 127), 1),
+                ((1 << (usize::BITS - 1), usize::MAX), 1),
+            ]
+        );
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_log2_histogram_display() {
+        let mut histogram = Log2Histogram::new();
+
+        assert_eq!(histogram.to_string(), "(empty)\n");
+
+        histogram.record(5);
+        histogram.record(6);
+
+        assert_eq!(
+            histogram.to_string(),
+            format!("{:>20} ..= {:<20} | 2\n", 4, 7)
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::marker::PhantomData;
+use std::mem;
+use std::slice;
+
+use web_glitz::buffer::{BufferView, UsageHint};
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::{BufferVecError, CapacityError};
+use crate::gpu_safe::{as_bytes, GpuSafe};
+use crate::util::{error_message, UploadData};
+use crate::BufferVec;
+
+/// A buffer-backed vector that stores its elements at a fixed stride in bytes, leaving a gap
+/// between consecutive elements.
+///
+/// Created with [BufferVec::with_stride]. Element `i` starts at byte `i * stride_bytes`; the
+/// length and capacity are counted in strides. Useful for reserving room for a future attribute,
+/// or for matching a fixed stride defined by a third-party shader.
+///
+/// WebGlitz derives the stride of a typed vertex buffer from the size of its element type, so a
+/// strided vector is bound as raw bytes: bind [as_byte_view] and describe the layout with a
+/// vertex input layout descriptor that declares a buffer slot with a stride of [stride_bytes].
+///
+/// Full updates ([update]) zero the gap bytes; partial updates ([update_range]) only write the
+/// bytes of the updated elements and leave the gap bytes untouched.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+/// use web_glitz_buffer_vec::BufferVec;
+/// use web_glitz::buffer::UsageHint;
+///
+/// // Positions with room for another 16 bytes of attribute data after each position.
+/// let mut positions = BufferVec::<_, [f32; 4]>::with_stride(context, UsageHint::StaticDraw, 32);
+///
+/// positions.update(&[[0.0, 0.0, 0.0, 1.0], [1.0, 0.0, 0.0, 1.0]]);
+///
+/// assert_eq!(positions.len(), 2);
+/// assert_eq!(positions.as_byte_view().len(), 64);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [as_byte_view]: StridedBufferVec::as_byte_view
+/// [stride_bytes]: StridedBufferVec::stride_bytes
+/// [update]: StridedBufferVec::update
+/// [update_range]: StridedBufferVec::update_range
+pub struct StridedBufferVec<Rc, T> {
+    inner: BufferVec<Rc, u8>,
+    stride: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<Rc, T> StridedBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: GpuSafe,
+{
+    pub(crate) fn new(context: Rc, usage: UsageHint, stride_bytes: usize) -> Self {
+        StridedBufferVec::with_capacity(context, usage, stride_bytes, 0)
+    }
+
+    pub(crate) fn with_capacity(
+        context: Rc,
+        usage: UsageHint,
+        stride_bytes: usize,
+        capacity: usize,
+    ) -> Self {
+        assert!(
+            stride_bytes >= mem::size_of::<T>(),
+            "The stride ({} bytes) must not be smaller than the element size ({} bytes).",
+            stride_bytes,
+            mem::size_of::<T>()
+        );
+
+        let byte_capacity = capacity
+            .checked_mul(stride_bytes)
+            .ok_or(CapacityError {
+                len: capacity,
+                element_size: stride_bytes,
+            })
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        StridedBufferVec {
+            inner: BufferVec::with_capacity(context, usage, byte_capacity),
+            stride: stride_bytes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The stride in bytes between the starts of consecutive elements.
+    pub fn stride_bytes(&self) -> usize {
+        self.stride
+    }
+
+    /// The number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.inner.len() / self.stride
+    }
+
+    /// Returns `true` if the vector holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// The number of elements the vector can hold without allocating a new buffer.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity() / self.stride
+    }
+
+    /// Replaces the elements in the buffer with the given `elements`, each written at its stride,
+    /// resizing the buffer if necessary.
+    ///
+    /// The gap bytes between elements are zeroed. Returns `true` if a new buffer was allocated,
+    /// `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [BufferVec::update].
+    pub fn update(&mut self, elements: &[T]) -> bool {
+        self.try_update(elements)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.inner.label())))
+    }
+
+    /// Replaces the elements in the buffer with the given `elements`, each written at its stride,
+    /// resizing the buffer if necessary, or returns an error if a new buffer could not be
+    /// allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See
+    /// [update](StridedBufferVec::update) for details.
+    pub fn try_update(&mut self, elements: &[T]) -> Result<bool, BufferVecError> {
+        let len = elements.len();
+
+        len.checked_mul(self.stride).ok_or(CapacityError {
+            len,
+            element_size: self.stride,
+        })?;
+
+        self.inner
+            .try_update(UploadData(strided_bytes(elements, self.stride)))
+    }
+
+    /// Overwrites the elements starting at element index `offset` with the given `elements`.
+    ///
+    /// Only the bytes of the elements themselves are written; the gap bytes are left untouched.
+    /// Never allocates a new buffer; the length of the vector does not change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `elements` extend beyond the length of the vector.
+    pub fn update_range(&mut self, offset: usize, elements: &[T]) {
+        self.try_update_range(offset, elements)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.inner.label())))
+    }
+
+    /// Overwrites the elements starting at element index `offset` with the given `elements`, or
+    /// returns an error if the `elements` extend beyond the length of the vector.
+    ///
+    /// If an error is returned, nothing is uploaded. See
+    /// [update_range](StridedBufferVec::update_range) for details.
+    pub fn try_update_range(
+        &mut self,
+        offset: usize,
+        elements: &[T],
+    ) -> Result<(), BufferVecError> {
+        let parts: Vec<(usize, &[u8])> = elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| {
+                let start = offset.saturating_add(i).saturating_mul(self.stride);
+
+                (start, as_bytes(slice::from_ref(element)))
+            })
+            .collect();
+
+        self.inner.try_update_parts(&parts)
+    }
+
+    /// Returns a view on the bytes of the buffer, including the gap bytes, for binding as a
+    /// vertex buffer with a stride of [stride_bytes](StridedBufferVec::stride_bytes).
+    pub fn as_byte_view(&self) -> BufferView<'_, [u8]> {
+        self.inner.as_buffer_view()
+    }
+
+    /// Consumes the strided vector, returning the wrapped [BufferVec] of bytes.
+    pub fn into_inner(self) -> BufferVec<Rc, u8> {
+        self.inner
+    }
+}
+
+impl<Rc, T> BufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: GpuSafe,
+{
+    /// Creates a new vector with 0 capacity for the given [RenderingContext] that stores its
+    /// elements at a stride of `stride_bytes` rather than tightly packed.
+    ///
+    /// See [StridedBufferVec] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride_bytes` is smaller than the size of `T`.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn with_stride(
+        context: Rc,
+        usage: UsageHint,
+        stride_bytes: usize,
+    ) -> StridedBufferVec<Rc, T> {
+        StridedBufferVec::new(context, usage, stride_bytes)
+    }
+
+    /// Creates a new vector with the specified `capacity` in elements for the given
+    /// [RenderingContext] that stores its elements at a stride of `stride_bytes`.
+    ///
+    /// See [StridedBufferVec] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride_bytes` is smaller than the size of `T`, or if the size in bytes of
+    /// `capacity` strides does not fit in a `usize`.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn with_stride_and_capacity(
+        context: Rc,
+        usage: UsageHint,
+        stride_bytes: usize,
+        capacity: usize,
+    ) -> StridedBufferVec<Rc, T> {
+        StridedBufferVec::with_capacity(context, usage, stride_bytes, capacity)
+    }
+}
+
+/// Lays out the bytes of the `elements` at the given `stride`, with zeroed gap bytes.
+fn strided_bytes<T>(elements: &[T], stride: usize) -> Vec<u8>
+where
+    T: GpuSafe,
+{
+    let mut bytes = vec![0; elements.len() * stride];
+
+    for (chunk, element) in bytes.chunks_exact_mut(stride).zip(elements) {
+        chunk[..mem::size_of::<T>()].copy_from_slice(as_bytes(slice::from_ref(element)));
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strided_bytes() {
+        let bytes = strided_bytes(&[[1u8, 2], [3, 4]], 3);
+
+        assert_eq!(bytes, vec![1, 2, 0, 3, 4, 0]);
+    }
+
+    #[test]
+    fn test_strided_bytes_tight() {
+        let bytes = strided_bytes(&[1u16.to_le(), 2u16.to_le()], 2);
+
+        assert_eq!(bytes.len(), 4);
+        assert_eq!(bytes, [1u16.to_le_bytes(), 2u16.to_le_bytes()].concat());
+    }
+}
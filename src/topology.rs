@@ -0,0 +1,356 @@
+//! Conversions between index topologies, used by the [IndexBufferVec] upload helpers.
+//!
+//! [IndexBufferVec]: crate::IndexBufferVec
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::index::IndexValue;
+
+/// The vertex indices of a single convex polygon, in winding order.
+pub type PolygonIndices<'a, T> = &'a [T];
+
+/// Whether the winding of triangles is kept or reversed when they are uploaded, see
+/// [IndexBufferVec::update_with_winding].
+///
+/// [IndexBufferVec::update_with_winding]: crate::IndexBufferVec::update_with_winding
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Winding {
+    /// The triangles are uploaded as they are.
+    AsIs,
+
+    /// The winding of each triangle is reversed, e.g. for instances with a mirroring transform.
+    Flipped,
+}
+
+impl Default for Winding {
+    fn default() -> Self {
+        Winding::AsIs
+    }
+}
+
+/// Appends the fan triangulation of each of the `polygons` to `out` and returns the number of
+/// triangles appended.
+///
+/// A polygon `v0, v1, ..., vn` is triangulated as `(v0, v1, v2), (v0, v2, v3), ...`. Polygons
+/// with fewer than 3 indices are skipped.
+pub(crate) fn triangulate_fans<T>(polygons: &[PolygonIndices<T>], out: &mut Vec<T>) -> usize
+where
+    T: Copy,
+{
+    let triangle_count = polygons
+        .iter()
+        .map(|polygon| polygon.len().saturating_sub(2))
+        .sum();
+
+    out.reserve(triangle_count * 3);
+
+    for polygon in polygons {
+        if let Some((&first, rest)) = polygon.split_first() {
+            for edge in rest.windows(2) {
+                out.extend_from_slice(&[first, edge[0], edge[1]]);
+            }
+        }
+    }
+
+    triangle_count
+}
+
+/// Appends the triangle list equivalent of the triangle `strip` to `out` and returns the number of
+/// triangles appended.
+///
+/// Triangle `i` of the strip consists of the indices `i, i + 1, i + 2`; the winding of every odd
+/// triangle is flipped so that all triangles have the same winding. Degenerate triangles (triangles
+/// that repeat an index) are dropped.
+pub(crate) fn strip_to_list<T>(strip: &[T], out: &mut Vec<T>) -> usize
+where
+    T: Copy + PartialEq,
+{
+    let mut triangle_count = 0;
+
+    out.reserve(strip.len().saturating_sub(2) * 3);
+
+    for (i, window) in strip.windows(3).enumerate() {
+        let (a, b, c) = (window[0], window[1], window[2]);
+
+        if a == b || b == c || a == c {
+            continue;
+        }
+
+        if i % 2 == 0 {
+            out.extend_from_slice(&[a, b, c]);
+        } else {
+            out.extend_from_slice(&[b, a, c]);
+        }
+
+        triangle_count += 1;
+    }
+
+    triangle_count
+}
+
+/// Appends the degenerate stitch that joins a triangle strip of `existing_len` indices ending in
+/// `last` to the triangle `strip`, followed by the `strip` itself, to `out`; returns the offset in
+/// `out` at which the `strip`'s own indices start.
+///
+/// The stitch repeats `last` and the first index of the `strip`, which produces only degenerate
+/// triangles. If `existing_len` is odd, `last` is repeated once more, so that the `strip` starts
+/// at an even position and its triangles keep their winding. If the existing strip is empty
+/// (`last` is `None`), or the `strip` is empty, no stitch is emitted.
+pub(crate) fn stitch_strip<T>(
+    existing_len: usize,
+    last: Option<T>,
+    strip: &[T],
+    out: &mut Vec<T>,
+) -> usize
+where
+    T: Copy,
+{
+    if let (Some(last), Some(&first)) = (last, strip.first()) {
+        out.push(last);
+
+        if existing_len % 2 == 1 {
+            out.push(last);
+        }
+
+        out.push(first);
+    }
+
+    let offset = out.len();
+
+    out.extend_from_slice(strip);
+
+    offset
+}
+
+/// Appends the unique edges of the given triangle list to `out` as a line list and returns the
+/// number of lines appended.
+///
+/// Edges are deduplicated regardless of their direction; each edge is emitted once, in the order
+/// in which it is first encountered.
+///
+/// # Panics
+///
+/// Panics if the length of `triangles` is not a multiple of 3.
+pub(crate) fn triangle_edges<T>(triangles: &[T], out: &mut Vec<T>) -> usize
+where
+    T: Copy + Ord,
+{
+    assert!(
+        triangles.len().is_multiple_of(3),
+        "The number of triangle indices ({}) must be a multiple of 3.",
+        triangles.len()
+    );
+
+    let mut seen = BTreeSet::new();
+    let mut line_count = 0;
+
+    for triangle in triangles.chunks_exact(3) {
+        let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+
+        for &(start, end) in &[(a, b), (b, c), (c, a)] {
+            if seen.insert((start.min(end), start.max(end))) {
+                out.extend_from_slice(&[start, end]);
+                line_count += 1;
+            }
+        }
+    }
+
+    line_count
+}
+
+/// Appends the given triangle list to `out` with the winding of each triangle reversed, by
+/// swapping its second and third index.
+///
+/// # Panics
+///
+/// Panics if the length of `triangles` is not a multiple of 3.
+pub(crate) fn flip_winding<T>(triangles: &[T], out: &mut Vec<T>)
+where
+    T: Copy,
+{
+    assert!(
+        triangles.len().is_multiple_of(3),
+        "The number of triangle indices ({}) must be a multiple of 3.",
+        triangles.len()
+    );
+
+    out.reserve(triangles.len());
+
+    for triangle in triangles.chunks_exact(3) {
+        out.extend_from_slice(&[triangle[0], triangle[2], triangle[1]]);
+    }
+}
+
+/// Appends the triangle list indices for the given range of `quads` to `out`, where quad `i`
+/// consists of the vertices `4 * i` through `4 * i + 3` in winding order.
+///
+/// Each quad is split into the triangles `(0, 1, 2)` and `(0, 2, 3)`, relative to its first
+/// vertex.
+///
+/// # Panics
+///
+/// Panics if the vertex index of a quad corner cannot be represented by `T`.
+pub(crate) fn quad_indices<T>(quads: Range<usize>, out: &mut Vec<T>)
+where
+    T: IndexValue,
+{
+    out.reserve(quads.len() * 6);
+
+    for quad in quads {
+        let base = quad as u64 * 4;
+
+        for &corner in &[0, 1, 2, 0, 2, 3] {
+            let index = T::from_u64(base + corner).unwrap_or_else(|| {
+                panic!(
+                    "Quad vertex index {} exceeds the maximum value for the index type ({}).",
+                    base + corner,
+                    T::MAX
+                )
+            });
+
+            out.push(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangulate_fans() {
+        let mut out = Vec::new();
+
+        let count = triangulate_fans(
+            &[&[0u16, 1, 2, 3][..], &[4, 5][..], &[6, 7, 8][..]],
+            &mut out,
+        );
+
+        assert_eq!(count, 3);
+        assert_eq!(out, vec![0, 1, 2, 0, 2, 3, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_triangulate_fans_empty() {
+        let mut out: Vec<u32> = Vec::new();
+
+        assert_eq!(triangulate_fans(&[&[][..], &[1][..]], &mut out), 0);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_strip_to_list() {
+        let mut out = Vec::new();
+
+        assert_eq!(strip_to_list(&[0u16, 1, 2, 3, 4], &mut out), 3);
+        assert_eq!(out, vec![0, 1, 2, 2, 1, 3, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_strip_to_list_drops_degenerates() {
+        let mut out = Vec::new();
+
+        // Two strips joined by the degenerate stitch `2, 2, 5` (with an extra `2` that keeps the
+        // second strip at an even position).
+        assert_eq!(strip_to_list(&[0u16, 1, 2, 2, 2, 5, 5, 6, 7], &mut out), 2);
+        assert_eq!(out, vec![0, 1, 2, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_strip_to_list_short() {
+        let mut out = Vec::new();
+
+        assert_eq!(strip_to_list(&[0u16, 1], &mut out), 0);
+        assert!(out.is_empty());
+    }
+
+    /// Stitches `strip` onto `existing` and checks that the triangle list of the result is the
+    /// concatenation of the triangle lists of both strips.
+    fn check_stitch(existing: &[u16], strip: &[u16]) {
+        let mut stitched = existing.to_vec();
+        let offset = stitch_strip(
+            existing.len(),
+            existing.last().copied(),
+            strip,
+            &mut stitched,
+        );
+
+        assert_eq!(&stitched[offset..], strip);
+        assert_eq!(offset % 2, 0);
+
+        let mut expected = Vec::new();
+
+        strip_to_list(existing, &mut expected);
+        strip_to_list(strip, &mut expected);
+
+        let mut actual = Vec::new();
+
+        strip_to_list(&stitched, &mut actual);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_stitch_strip_even_existing_len() {
+        check_stitch(&[0, 1, 2, 3], &[4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_stitch_strip_odd_existing_len() {
+        check_stitch(&[0, 1, 2], &[4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_stitch_strip_empty_existing() {
+        let mut out = Vec::new();
+
+        assert_eq!(stitch_strip(0, None, &[0u16, 1, 2], &mut out), 0);
+        assert_eq!(out, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_triangle_edges() {
+        let mut out = Vec::new();
+
+        // A quad made of two triangles that share the edge `0-2`.
+        assert_eq!(triangle_edges(&[0u16, 1, 2, 2, 3, 0], &mut out), 5);
+        assert_eq!(out, vec![0, 1, 1, 2, 2, 0, 2, 3, 3, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be a multiple of 3")]
+    fn test_triangle_edges_incomplete_triangle() {
+        triangle_edges(&[0u16, 1, 2, 3], &mut Vec::new());
+    }
+
+    #[test]
+    fn test_flip_winding() {
+        let mut out = Vec::new();
+
+        flip_winding(&[0u16, 1, 2, 2, 3, 0], &mut out);
+
+        assert_eq!(out, vec![0, 2, 1, 2, 0, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be a multiple of 3")]
+    fn test_flip_winding_incomplete_triangle() {
+        flip_winding(&[0u16, 1, 2, 3], &mut Vec::new());
+    }
+
+    #[test]
+    fn test_quad_indices() {
+        let mut out = Vec::new();
+
+        quad_indices::<u16>(1..3, &mut out);
+
+        assert_eq!(out, vec![4, 5, 6, 4, 6, 7, 8, 9, 10, 8, 10, 11]);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum value")]
+    fn test_quad_indices_overflow() {
+        quad_indices::<u8>(63..65, &mut Vec::new());
+    }
+}
@@ -0,0 +1,242 @@
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+
+use web_glitz::buffer::UsageHint;
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::gpu_safe::GpuSafe;
+use crate::util::error_message;
+use crate::BufferVec;
+
+/// An element type of a std140 uniform block array, along with the padded representation it is
+/// uploaded in.
+///
+/// In a std140 array, every element starts at a multiple of 16 bytes: a `vec3` occupies 16 bytes
+/// rather than 12, and even a `float` occupies 16 bytes. Each column of a matrix is likewise padded
+/// to 16 bytes. Implemented for `f32`, `i32` and `u32` scalars, for arrays of 1 to 4 of these
+/// (vectors), and for arrays of such vectors (matrices, or arrays of vectors).
+///
+/// To use a custom struct as an element, implement this trait with a `#[repr(C)]` [GpuSafe]
+/// padded counterpart whose size is a multiple of 16 bytes and whose fields follow the std140
+/// alignment rules. A padded type whose size is not a multiple of 16 bytes is rejected when a
+/// [UniformBufferVec] for the element type is created.
+pub trait Std140Element: Copy + 'static {
+    /// The padded representation of the element, as it is laid out in a std140 array.
+    type Padded: GpuSafe;
+
+    /// Converts the element into its padded representation.
+    fn pad(&self) -> Self::Padded;
+
+    /// The stride of the element in a std140 array, in bytes.
+    fn stride() -> usize {
+        mem::size_of::<Self::Padded>()
+    }
+}
+
+macro_rules! impl_std140_element {
+    ($($ty:ty),*) => {
+        $(
+            impl Std140Element for $ty {
+                type Padded = [$ty; 4];
+
+                fn pad(&self) -> [$ty; 4] {
+                    let mut padded = [<$ty>::default(); 4];
+
+                    padded[0] = *self;
+
+                    padded
+                }
+            }
+
+            impl_std140_element!(@vector $ty, 1, 2, 3, 4);
+        )*
+    };
+    (@vector $ty:ty, $($len:literal),*) => {
+        $(
+            impl Std140Element for [$ty; $len] {
+                type Padded = [$ty; 4];
+
+                fn pad(&self) -> [$ty; 4] {
+                    let mut padded = [<$ty>::default(); 4];
+
+                    padded[..$len].copy_from_slice(self);
+
+                    padded
+                }
+            }
+
+            impl<const N: usize> Std140Element for [[$ty; $len]; N] {
+                type Padded = [[$ty; 4]; N];
+
+                fn pad(&self) -> [[$ty; 4]; N] {
+                    let mut padded = [[<$ty>::default(); 4]; N];
+
+                    for (padded, column) in padded.iter_mut().zip(self.iter()) {
+                        padded[..$len].copy_from_slice(column);
+                    }
+
+                    padded
+                }
+            }
+        )*
+    };
+}
+
+impl_std140_element!(f32, i32, u32);
+
+/// Helper for rejecting std140 element types with an invalid padded stride at compile time.
+struct AssertStd140Stride<T>(PhantomData<T>);
+
+impl<T> AssertStd140Stride<T>
+where
+    T: Std140Element,
+{
+    const OK: () = assert!(
+        mem::size_of::<T::Padded>().is_multiple_of(16),
+        "The padded representation of a std140 element must be a multiple of 16 bytes in size; \
+         add trailing padding fields to the `Std140Element::Padded` type."
+    );
+}
+
+/// A [BufferVec] that holds a std140 array of elements, such as the array in a uniform block.
+///
+/// Elements are padded to their std140 stride on update, see [Std140Element]; the caller supplies
+/// the elements in their natural layout. For example, an array of `[f32; 3]` is uploaded with a
+/// stride of 16 bytes, as a `vec3[]` in a uniform block expects.
+///
+/// Dereferences to the wrapped [BufferVec] of padded elements.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext {
+/// use web_glitz_buffer_vec::UniformBufferVec;
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut light_positions = UniformBufferVec::new(context, UsageHint::DynamicDraw);
+///
+/// light_positions.update(&[[0.0f32, 1.0, 0.0], [2.0, 1.0, 0.0]]);
+///
+/// assert_eq!(light_positions.len(), 2);
+/// assert_eq!(UniformBufferVec::<Rc, [f32; 3]>::stride(), 16);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+pub struct UniformBufferVec<Rc, T>
+where
+    T: Std140Element,
+{
+    inner: BufferVec<Rc, T::Padded>,
+}
+
+impl<Rc, T> UniformBufferVec<Rc, T>
+where
+    Rc: RenderingContext,
+    T: Std140Element,
+{
+    /// Creates a new uniform vector with 0 capacity for the given [RenderingContext].
+    ///
+    /// See [BufferVec::new] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn new(context: Rc, usage: UsageHint) -> Self {
+        let () = AssertStd140Stride::<T>::OK;
+
+        UniformBufferVec {
+            inner: BufferVec::new(context, usage),
+        }
+    }
+
+    /// Creates a new uniform vector with the specified `capacity` for the given
+    /// [RenderingContext].
+    ///
+    /// See [BufferVec::with_capacity] for details.
+    ///
+    /// [RenderingContext]: web_glitz::runtime::RenderingContext
+    pub fn with_capacity(context: Rc, usage: UsageHint, capacity: usize) -> Self {
+        let () = AssertStd140Stride::<T>::OK;
+
+        UniformBufferVec {
+            inner: BufferVec::with_capacity(context, usage, capacity),
+        }
+    }
+
+    /// The stride of an element in the buffer, in bytes.
+    pub fn stride() -> usize {
+        T::stride()
+    }
+
+    /// Replaces the elements in the buffer with the given `elements`, padded to their std140
+    /// stride, resizing the buffer if necessary.
+    ///
+    /// The padded elements are staged in a reusable allocation, see [BufferVec::update_converted].
+    /// Returns `true` if a new buffer was allocated, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer needs to grow and cannot, see [BufferVec::update].
+    pub fn update(&mut self, elements: &[T]) -> bool {
+        self.try_update(elements)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.inner.label())))
+    }
+
+    /// Replaces the elements in the buffer with the given `elements`, padded to their std140
+    /// stride, resizing the buffer if necessary, or returns an error if a new buffer could not be
+    /// allocated.
+    ///
+    /// See [update](UniformBufferVec::update) for details.
+    pub fn try_update(&mut self, elements: &[T]) -> Result<bool, BufferVecError> {
+        self.inner.try_update_converted(elements, T::pad)
+    }
+
+    /// Consumes the uniform vector, returning the wrapped [BufferVec] of padded elements.
+    pub fn into_inner(self) -> BufferVec<Rc, T::Padded> {
+        self.inner
+    }
+}
+
+impl<Rc, T> Deref for UniformBufferVec<Rc, T>
+where
+    T: Std140Element,
+{
+    type Target = BufferVec<Rc, T::Padded>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_std140_vector_padding() {
+        assert_eq!(1.5f32.pad(), [1.5, 0.0, 0.0, 0.0]);
+        assert_eq!([1, 2, 3].pad(), [1, 2, 3, 0]);
+        assert_eq!(<f32 as Std140Element>::stride(), 16);
+        assert_eq!(<[u32; 3] as Std140Element>::stride(), 16);
+    }
+
+    #[test]
+    fn test_std140_matrix_padding() {
+        let mat3 = [[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]];
+
+        assert_eq!(
+            mat3.pad(),
+            [
+                [1.0, 2.0, 3.0, 0.0],
+                [4.0, 5.0, 6.0, 0.0],
+                [7.0, 8.0, 9.0, 0.0]
+            ]
+        );
+        assert_eq!(<[[f32; 3]; 3] as Std140Element>::stride(), 48);
+        assert_eq!(<[[f32; 4]; 4] as Std140Element>::stride(), 64);
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::VecDeque;
+
+use crate::growth::{GrowthPolicy, ShrinkPolicy};
+
+/// The maximum number of operations an update log holds before it discards its oldest records.
+const UPDATE_LOG_LEN: usize = 4096;
+
+/// An operation recorded by a vector with update recording enabled, see
+/// [BufferVec::record_updates].
+///
+/// A sequence of records can be replayed against a growth and shrink policy with
+/// [replay_capacity], without a GPU. Implements `serde::Serialize` with the `serde` feature.
+///
+/// [BufferVec::record_updates]: crate::BufferVec::record_updates
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UpdateRecord {
+    /// The state of the vector before the first of the records that follow.
+    Start {
+        /// The length of the vector.
+        len: usize,
+
+        /// The capacity of the vector.
+        capacity: usize,
+    },
+
+    /// A full update that replaced the data with `len` elements.
+    Update {
+        /// The new length of the vector.
+        len: usize,
+    },
+
+    /// An append of `additional` elements, including single-element inserts.
+    Append {
+        /// The number of elements appended.
+        additional: usize,
+    },
+
+    /// A removal of `count` elements.
+    Remove {
+        /// The number of elements removed.
+        count: usize,
+    },
+
+    /// A reservation of capacity for `additional` more elements.
+    Reserve {
+        /// The number of additional elements reserved for.
+        additional: usize,
+    },
+
+    /// An explicit shrink to a capacity of at least `min_capacity` elements.
+    ShrinkTo {
+        /// The requested minimum capacity.
+        min_capacity: usize,
+    },
+
+    /// An overwrite of `count` elements in place, which changes neither the length nor the
+    /// capacity, such as a partial update, a copy within the vector or a rotation.
+    Write {
+        /// The number of elements written.
+        count: usize,
+    },
+
+    /// An operation that set the length and capacity of the vector without consulting its growth
+    /// or shrink policy, such as a usage conversion, a restore after context loss, a committed
+    /// transaction or destroying the vector.
+    Replace {
+        /// The new length of the vector.
+        len: usize,
+
+        /// The new capacity of the vector.
+        capacity: usize,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for UpdateRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStructVariant;
+
+        let (index, variant, field, value) = match *self {
+            UpdateRecord::Start { len, capacity } => {
+                let mut state =
+                    serializer.serialize_struct_variant("UpdateRecord", 0, "Start", 2)?;
+
+                state.serialize_field("len", &len)?;
+                state.serialize_field("capacity", &capacity)?;
+
+                return state.end();
+            }
+            UpdateRecord::Replace { len, capacity } => {
+                let mut state =
+                    serializer.serialize_struct_variant("UpdateRecord", 7, "Replace", 2)?;
+
+                state.serialize_field("len", &len)?;
+                state.serialize_field("capacity", &capacity)?;
+
+                return state.end();
+            }
+            UpdateRecord::Update { len } => (1, "Update", "len", len),
+            UpdateRecord::Append { additional } => (2, "Append", "additional", additional),
+            UpdateRecord::Remove { count } => (3, "Remove", "count", count),
+            UpdateRecord::Reserve { additional } => (4, "Reserve", "additional", additional),
+            UpdateRecord::ShrinkTo { min_capacity } => {
+                (5, "ShrinkTo", "min_capacity", min_capacity)
+            }
+            UpdateRecord::Write { count } => (6, "Write", "count", count),
+        };
+
+        let mut state = serializer.serialize_struct_variant("UpdateRecord", index, variant, 1)?;
+
+        state.serialize_field(field, &value)?;
+        state.end()
+    }
+}
+
+/// A bounded log of the operations on a vector, along with the length and capacity of the vector
+/// after each operation.
+pub(crate) struct UpdateLog {
+    start: (usize, usize),
+    records: VecDeque<(UpdateRecord, usize, usize)>,
+}
+
+impl UpdateLog {
+    /// Creates a new empty log for a vector with the given current `len` and `capacity`.
+    pub(crate) fn new(len: usize, capacity: usize) -> Self {
+        UpdateLog {
+            start: (len, capacity),
+            records: VecDeque::new(),
+        }
+    }
+
+    /// Records the `record` for an operation after which the vector has the given `len` and
+    /// `capacity`, discarding the oldest record if the log is full.
+    pub(crate) fn record(&mut self, record: UpdateRecord, len: usize, capacity: usize) {
+        if self.records.len() == UPDATE_LOG_LEN {
+            if let Some((_, len, capacity)) = self.records.pop_front() {
+                self.start = (len, capacity);
+            }
+        }
+
+        self.records.push_back((record, len, capacity));
+    }
+
+    /// The recorded operations, preceded by the [UpdateRecord::Start] state of the vector before
+    /// the oldest of them.
+    pub(crate) fn records(&self) -> Vec<UpdateRecord> {
+        let (len, capacity) = self.start;
+
+        let mut records = Vec::with_capacity(self.records.len() + 1);
+
+        records.push(UpdateRecord::Start { len, capacity });
+        records.extend(self.records.iter().map(|(record, ..)| *record));
+
+        records
+    }
+}
+
+/// Simulates the capacity of a vector of elements of `element_size` bytes through the given
+/// sequence of `records`, under the given `growth` and `shrink` policies, and returns the capacity
+/// after each record.
+///
+/// Full updates consult both policies, appends and reservations only consult the `growth` policy,
+/// as the vector types do; [UpdateRecord::Replace] records set the capacity directly. The
+/// simulated vector starts out empty with no capacity, until a [UpdateRecord::Start] record sets
+/// its state. Capacity alignment and capacity limits are not simulated.
+///
+/// Intended for reproducing the capacity evolution of a vector from the records captured with
+/// [BufferVec::record_updates], and for comparing policies against real workloads.
+///
+/// # Example
+///
+/// ```
+/// use web_glitz_buffer_vec::growth::{Doubling, Exact, Never};
+/// use web_glitz_buffer_vec::{replay_capacity, UpdateRecord};
+///
+/// let records = [
+///     UpdateRecord::Update { len: 3 },
+///     UpdateRecord::Append { additional: 2 },
+///     UpdateRecord::Update { len: 2 },
+/// ];
+///
+/// assert_eq!(replay_capacity(&records, 4, &Doubling, &mut Never), vec![4, 8, 8]);
+/// assert_eq!(replay_capacity(&records, 4, &Exact, &mut Never), vec![3, 5, 5]);
+/// ```
+///
+/// [BufferVec::record_updates]: crate::BufferVec::record_updates
+pub fn replay_capacity<G, S>(
+    records: &[UpdateRecord],
+    element_size: usize,
+    growth: &G,
+    shrink: &mut S,
+) -> Vec<usize>
+where
+    G: GrowthPolicy + ?Sized,
+    S: ShrinkPolicy + ?Sized,
+{
+    let mut len = 0usize;
+    let mut capacity = 0;
+
+    let grow = |capacity: usize, required: usize| {
+        growth
+            .grow_sized(capacity, required, element_size)
+            .map(|new_capacity| new_capacity.max(required))
+            .unwrap_or(capacity)
+    };
+
+    records
+        .iter()
+        .map(|record| {
+            match *record {
+                UpdateRecord::Start {
+                    len: start_len,
+                    capacity: start_capacity,
+                } => {
+                    len = start_len;
+                    capacity = start_capacity;
+                }
+                UpdateRecord::Update { len: new_len } => {
+                    len = new_len;
+                    capacity = grow(capacity, len);
+
+                    if let Some(new_capacity) = shrink.shrink(capacity, len) {
+                        capacity = capacity.min(new_capacity.max(len));
+                    }
+                }
+                UpdateRecord::Append { additional } => {
+                    len = len.saturating_add(additional);
+                    capacity = grow(capacity, len);
+                }
+                UpdateRecord::Remove { count } => {
+                    len = len.saturating_sub(count);
+                }
+                UpdateRecord::Reserve { additional } => {
+                    capacity = grow(capacity, len.saturating_add(additional));
+                }
+                UpdateRecord::ShrinkTo { min_capacity } => {
+                    capacity = capacity.min(min_capacity.max(len));
+                }
+                UpdateRecord::Write { .. } => {}
+                UpdateRecord::Replace {
+                    len: new_len,
+                    capacity: new_capacity,
+                } => {
+                    len = new_len;
+                    capacity = new_capacity;
+                }
+            }
+
+            capacity
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::growth::{Doubling, Immediate, Never};
+
+    #[test]
+    fn test_update_log_keeps_start_state() {
+        let mut log = UpdateLog::new(0, 4);
+
+        for i in 0..UPDATE_LOG_LEN + 2 {
+            log.record(UpdateRecord::Update { len: i }, i, i + 1);
+        }
+
+        let records = log.records();
+
+        assert_eq!(records.len(), UPDATE_LOG_LEN + 1);
+        assert_eq!(
+            records[0],
+            UpdateRecord::Start {
+                len: 1,
+                capacity: 2
+            }
+        );
+        assert_eq!(records[1], UpdateRecord::Update { len: 2 });
+    }
+
+    #[test]
+    fn test_replay_capacity() {
+        let records = [
+            UpdateRecord::Start {
+                len: 2,
+                capacity: 4,
+            },
+            UpdateRecord::Append { additional: 3 },
+            UpdateRecord::Remove { count: 4 },
+            UpdateRecord::Reserve { additional: 10 },
+            UpdateRecord::ShrinkTo { min_capacity: 0 },
+            UpdateRecord::Update { len: 20 },
+            UpdateRecord::Update { len: 3 },
+        ];
+
+        assert_eq!(
+            replay_capacity(&records, 4, &Doubling, &mut Immediate),
+            vec![4, 8, 8, 16, 1, 20, 3]
+        );
+    }
+
+    #[test]
+    fn test_replay_write_and_replace() {
+        let records = [
+            UpdateRecord::Update { len: 3 },
+            UpdateRecord::Write { count: 2 },
+            UpdateRecord::Replace {
+                len: 3,
+                capacity: 3,
+            },
+            UpdateRecord::Append { additional: 1 },
+            UpdateRecord::Replace {
+                len: 0,
+                capacity: 0,
+            },
+        ];
+
+        assert_eq!(
+            replay_capacity(&records, 4, &Doubling, &mut Never),
+            vec![4, 4, 3, 6, 0]
+        );
+    }
+}
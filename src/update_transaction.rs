@@ -0,0 +1,204 @@
+use web_glitz::runtime::RenderingContext;
+
+use crate::error::BufferVecError;
+use crate::util::{checked_range, error_message};
+use crate::BufferVec;
+
+/// Groups several modifications of a [BufferVec] into a single update.
+///
+/// Created with [BufferVec::begin_update]. The modifications are staged on the CPU and only
+/// applied to the vector by [commit]: all staged writes are submitted together as a single task,
+/// and the vector reallocates at most once, sized for the final length. Dropping the transaction
+/// without committing it discards the staged modifications.
+///
+/// [commit]: UpdateTransaction::commit
+pub struct UpdateTransaction<'a, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    vec: &'a mut BufferVec<Rc, T>,
+    len: usize,
+    // The number of elements at the start of the vector's current data that survive the
+    // transaction (though they may be overwritten).
+    kept: usize,
+    writes: Vec<(usize, Vec<T>)>,
+}
+
+impl<'a, Rc, T> UpdateTransaction<'a, Rc, T>
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + 'static,
+{
+    pub(crate) fn new(vec: &'a mut BufferVec<Rc, T>) -> Self {
+        let len = vec.len();
+
+        UpdateTransaction {
+            vec,
+            len,
+            kept: len,
+            writes: Vec::new(),
+        }
+    }
+
+    /// The length the vector will have when the transaction is committed.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector will be empty when the transaction is committed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Shortens the vector to `len` elements, discarding staged writes beyond the new length.
+    ///
+    /// Does nothing if `len` is greater than or equal to the staged length.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+            self.kept = self.kept.min(len);
+
+            truncate_writes(&mut self.writes, len);
+        }
+    }
+
+    /// Stages overwriting the elements starting at `offset` with the given `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset + data.len()` exceeds the staged length.
+    pub fn update_range(&mut self, offset: usize, data: &[T]) {
+        self.try_update_range(offset, data)
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, self.vec.label())))
+    }
+
+    /// Stages overwriting the elements starting at `offset` with the given `data`, or returns an
+    /// error if `offset + data.len()` exceeds the staged length.
+    pub fn try_update_range(&mut self, offset: usize, data: &[T]) -> Result<(), BufferVecError> {
+        checked_range(offset, data.len(), self.len)?;
+
+        if !data.is_empty() {
+            self.writes.push((offset, data.to_vec()));
+        }
+
+        Ok(())
+    }
+
+    /// Stages appending the given `data` to the end of the vector.
+    pub fn extend_from_slice(&mut self, data: &[T]) {
+        if !data.is_empty() {
+            self.writes.push((self.len, data.to_vec()));
+            self.len += data.len();
+        }
+    }
+
+    /// Stages appending the `value` to the end of the vector if the staged length is less than the
+    /// vector's current capacity, returning its index, or returns the `value` back otherwise.
+    ///
+    /// Values staged this way never cause the transaction to reallocate on commit by themselves.
+    /// See [BufferVec::push_within_capacity].
+    pub fn push_within_capacity(&mut self, value: T) -> Result<usize, T> {
+        let index = self.len;
+
+        if index >= self.vec.capacity() {
+            return Err(value);
+        }
+
+        self.extend_from_slice(&[value]);
+
+        Ok(index)
+    }
+
+    /// Applies the staged modifications to the vector.
+    ///
+    /// Returns `true` if a new buffer was allocated, `false` otherwise. If a new buffer is
+    /// allocated, the current data that survives the transaction is copied into the new buffer on
+    /// the GPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector needs to grow and cannot, see [BufferVec::append].
+    pub fn commit(self) -> bool {
+        let label = self.vec.label().map(|label| label.to_string());
+
+        self.try_commit()
+            .unwrap_or_else(|err| panic!("{}", error_message(&err, label.as_deref())))
+    }
+
+    /// Applies the staged modifications to the vector, or returns an error if the vector needed
+    /// to grow and a new buffer could not be allocated.
+    ///
+    /// If an error is returned, the vector is left unmodified. See [commit] for details.
+    ///
+    /// [commit]: UpdateTransaction::commit
+    pub fn try_commit(self) -> Result<bool, BufferVecError> {
+        self.vec.try_apply_staged(self.kept, self.len, &self.writes)
+    }
+}
+
+/// Shortens the staged `writes` so that none of them extends beyond `len`, removing writes that
+/// start at or beyond `len`.
+fn truncate_writes<T>(writes: &mut Vec<(usize, Vec<T>)>, len: usize) {
+    writes.retain(|(offset, _)| *offset < len);
+
+    for (offset, data) in writes.iter_mut() {
+        data.truncate(len - *offset);
+    }
+}
+
+/// Applies the staged `writes` to `target` in order, extending `target` where a write extends
+/// beyond its end.
+///
+/// # Panics
+///
+/// Panics if a write starts beyond the end of `target`.
+pub(crate) fn apply_writes<T>(target: &mut Vec<T>, writes: &[(usize, Vec<T>)])
+where
+    T: Copy,
+{
+    for (offset, data) in writes {
+        assert!(
+            *offset <= target.len(),
+            "Staged write starts beyond the end of the data."
+        );
+
+        let overlap = data.len().min(target.len() - offset);
+
+        target[*offset..offset + overlap].copy_from_slice(&data[..overlap]);
+        target.extend_from_slice(&data[overlap..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_writes() {
+        let mut writes = vec![(0, vec![1, 2]), (3, vec![3, 4, 5]), (6, vec![6])];
+
+        truncate_writes(&mut writes, 4);
+
+        assert_eq!(writes, vec![(0, vec![1, 2]), (3, vec![3])]);
+    }
+
+    #[test]
+    fn test_apply_writes() {
+        let mut target = vec![0, 0, 0];
+
+        // Overwrite, then extend across the end, then append.
+        apply_writes(
+            &mut target,
+            &[(1, vec![1]), (2, vec![2, 3]), (4, vec![4]), (3, vec![5])],
+        );
+
+        assert_eq!(target, vec![0, 1, 2, 5, 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_writes_gap() {
+        apply_writes(&mut vec![0], &[(2, vec![1])]);
+    }
+}
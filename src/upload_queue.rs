@@ -0,0 +1,363 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use web_glitz::runtime::{Connection, RenderingContext};
+use web_glitz::task::{sequence_iter, GpuTask};
+
+/// The source identifier for the next vector that is created.
+static NEXT_SOURCE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a new identifier that uniquely identifies a vector as the source of queued uploads.
+pub(crate) fn next_source_id() -> u64 {
+    NEXT_SOURCE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Collects deferred updates from any number of [BufferVec]s and [IndexBufferVec]s, to submit them
+/// all at once with [submit].
+///
+/// Updates are enqueued with [BufferVec::enqueue_update] and [IndexBufferVec::enqueue_update].
+/// The vector's length, capacity and CPU-side state change immediately, but the upload itself is
+/// held by the queue until [submit] is called, which submits all queued uploads as a single task,
+/// in the order in which they were enqueued. This provides a single point at which all uploads for
+/// a frame are submitted, relative to the submission of render passes.
+///
+/// If a vector enqueues an update while an earlier update from the same vector is still queued,
+/// the earlier update is dropped, as the latest update replaces all of the vector's data.
+///
+/// An update enqueued with [BufferVec::enqueue_cancellable_update] or
+/// [IndexBufferVec::enqueue_cancellable_update] can be removed from the queue before it is
+/// submitted by passing the returned [UploadTicket] to [BufferVec::cancel_queued_update] or
+/// [IndexBufferVec::cancel_queued_update], which also restores the vector to its state from before
+/// the update. To make this possible, the queue holds on to the vector's previous state (including
+/// its previous buffer, if the update allocated a new one) until the update is submitted.
+///
+/// Any other operation on a vector with a queued update (such as [BufferVec::append]) submits its
+/// commands immediately, ahead of the queued update; call [submit] first if the operation depends
+/// on the data of the queued update.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use web_glitz_buffer_vec::{BufferVec, IndexBufferVec, UploadQueue};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut queue = UploadQueue::new();
+/// let mut vertices = BufferVec::new(context.clone(), UsageHint::StreamDraw);
+/// let mut indices = IndexBufferVec::new(context.clone(), UsageHint::StreamDraw);
+///
+/// vertices.enqueue_update([1.0f32, 2.0, 3.0], &mut queue);
+/// indices.enqueue_update([0u16, 1, 2], &mut queue);
+///
+/// // Replaces the first update of `vertices`.
+/// vertices.enqueue_update([4.0, 5.0, 6.0], &mut queue);
+///
+/// let report = queue.submit(&context);
+///
+/// assert_eq!(report.commands, 2);
+/// assert_eq!(report.bytes, 3 * 4 + 3 * 2);
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+/// [BufferVec]: crate::BufferVec
+/// [IndexBufferVec]: crate::IndexBufferVec
+/// [BufferVec::enqueue_update]: crate::BufferVec::enqueue_update
+/// [BufferVec::append]: crate::BufferVec::append
+/// [IndexBufferVec::enqueue_update]: crate::IndexBufferVec::enqueue_update
+/// [BufferVec::enqueue_cancellable_update]: crate::BufferVec::enqueue_cancellable_update
+/// [IndexBufferVec::enqueue_cancellable_update]: crate::IndexBufferVec::enqueue_cancellable_update
+/// [BufferVec::cancel_queued_update]: crate::BufferVec::cancel_queued_update
+/// [IndexBufferVec::cancel_queued_update]: crate::IndexBufferVec::cancel_queued_update
+/// [submit]: UploadQueue::submit
+pub struct UploadQueue {
+    uploads: Rc<RefCell<Vec<QueuedUpload<UploadCommand>>>>,
+    next_ticket: u64,
+}
+
+/// A type-erased queued upload command.
+type UploadCommand = Box<dyn GpuTask<Connection, Output = ()>>;
+
+impl UploadQueue {
+    /// Creates a new empty queue.
+    pub fn new() -> Self {
+        UploadQueue {
+            uploads: Rc::new(RefCell::new(Vec::new())),
+            next_ticket: 0,
+        }
+    }
+
+    /// The number of uploads in this queue.
+    pub fn len(&self) -> usize {
+        self.uploads.borrow().len()
+    }
+
+    /// Returns `true` if this queue contains no uploads.
+    pub fn is_empty(&self) -> bool {
+        self.uploads.borrow().is_empty()
+    }
+
+    /// Submits all queued uploads to the given `context` as a single task, in the order in which
+    /// they were enqueued, and empties the queue.
+    ///
+    /// Returns the number of upload commands and the number of bytes submitted. Does not submit a
+    /// task if the queue is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the queued uploads belongs to a vector for a different context.
+    pub fn submit<Rc>(&mut self, context: &Rc) -> UploadReport
+    where
+        Rc: RenderingContext,
+    {
+        let uploads = mem::take(&mut *self.uploads.borrow_mut());
+
+        let report = UploadReport {
+            commands: uploads.len(),
+            bytes: uploads.iter().map(|upload| upload.bytes).sum(),
+        };
+
+        if !uploads.is_empty() {
+            context.submit(sequence_iter(
+                uploads.into_iter().map(|upload| upload.command),
+            ));
+        }
+
+        report
+    }
+
+    /// Enqueues the `command` that replaces all data of the vector identified by `source`,
+    /// dropping any earlier upload from the same vector.
+    ///
+    /// The `rollback` holds the state the vector returns to if the upload is cancelled, or is
+    /// `None` if the upload cannot be cancelled. Returns a ticket that may be used to cancel the
+    /// upload.
+    pub(crate) fn enqueue_full_update<C>(
+        &mut self,
+        source: u64,
+        bytes: u64,
+        command: C,
+        rollback: Option<Rollback>,
+    ) -> UploadTicket
+    where
+        C: GpuTask<Connection, Output = ()> + 'static,
+    {
+        let ticket = self.next_ticket;
+
+        self.next_ticket += 1;
+
+        supersede(
+            &mut self.uploads.borrow_mut(),
+            QueuedUpload {
+                source,
+                ticket,
+                bytes,
+                command: Box::new(command),
+                rollback,
+            },
+        );
+
+        UploadTicket {
+            uploads: Rc::downgrade(&self.uploads),
+            source,
+            ticket,
+        }
+    }
+
+    /// Takes the rollback of the upload from the vector identified by `source` that is still in
+    /// this queue, which is about to be replaced by a new upload from the same vector.
+    ///
+    /// Returns `None` if the queue holds no upload from the vector, or `Some(None)` if it does, but
+    /// the upload cannot be cancelled.
+    pub(crate) fn take_rollback(&mut self, source: u64) -> Option<Option<Rollback>> {
+        self.uploads
+            .borrow_mut()
+            .iter_mut()
+            .find(|upload| upload.source == source)
+            .map(|upload| upload.rollback.take())
+    }
+}
+
+impl Default for UploadQueue {
+    fn default() -> Self {
+        UploadQueue::new()
+    }
+}
+
+/// Summary of the uploads submitted by [UploadQueue::submit] or [BufferVec::update_masked].
+///
+/// [BufferVec::update_masked]: crate::BufferVec::update_masked
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct UploadReport {
+    /// The number of upload commands submitted.
+    pub commands: usize,
+
+    /// The total size in bytes of the data submitted.
+    pub bytes: u64,
+}
+
+/// Identifies an upload held by an [UploadQueue], to cancel it before it is submitted.
+///
+/// Obtained from [BufferVec::enqueue_cancellable_update] or
+/// [IndexBufferVec::enqueue_cancellable_update], and redeemed with
+/// [BufferVec::cancel_queued_update] or [IndexBufferVec::cancel_queued_update] on the vector that
+/// enqueued the upload. Dropping the ticket does not cancel the upload.
+///
+/// [BufferVec::enqueue_cancellable_update]: crate::BufferVec::enqueue_cancellable_update
+/// [IndexBufferVec::enqueue_cancellable_update]: crate::IndexBufferVec::enqueue_cancellable_update
+/// [BufferVec::cancel_queued_update]: crate::BufferVec::cancel_queued_update
+/// [IndexBufferVec::cancel_queued_update]: crate::IndexBufferVec::cancel_queued_update
+#[derive(Debug)]
+pub struct UploadTicket {
+    uploads: Weak<RefCell<Vec<QueuedUpload<UploadCommand>>>>,
+    source: u64,
+    ticket: u64,
+}
+
+impl UploadTicket {
+    /// Returns `true` if the upload is still held by its queue.
+    ///
+    /// Returns `false` once the queue has been submitted or dropped, or if the upload was replaced
+    /// by a later update of the same vector.
+    pub fn is_pending(&self) -> bool {
+        match self.uploads.upgrade() {
+            Some(uploads) => uploads
+                .borrow()
+                .iter()
+                .any(|upload| upload.ticket == self.ticket),
+            None => false,
+        }
+    }
+
+    /// The identifier of the vector that enqueued the upload.
+    pub(crate) fn source(&self) -> u64 {
+        self.source
+    }
+
+    /// Removes the upload from its queue, so that it is never submitted, if it is still pending and
+    /// its rollback was recorded at the given `stamp`.
+    ///
+    /// Returns the state of the rollback if the upload was removed, or `None` otherwise, in which
+    /// case this does nothing.
+    pub(crate) fn cancel(self, stamp: u64) -> Option<Box<dyn Any>> {
+        let uploads = self.uploads.upgrade()?;
+        let mut uploads = uploads.borrow_mut();
+
+        cancel(&mut uploads, self.ticket, stamp)
+    }
+}
+
+/// The state a vector returns to if its queued upload is cancelled.
+pub(crate) struct Rollback {
+    /// The number of modifications the vector had undergone when the upload was enqueued. The
+    /// rollback only applies if the vector has not been modified since.
+    pub(crate) stamp: u64,
+
+    /// The state of the vector, which only the vector itself knows how to restore.
+    pub(crate) state: Box<dyn Any>,
+}
+
+/// An upload held by an [UploadQueue].
+struct QueuedUpload<C> {
+    source: u64,
+    ticket: u64,
+    bytes: u64,
+    command: C,
+    rollback: Option<Rollback>,
+}
+
+/// Removes all uploads from the same source as `upload` from `uploads`, then appends `upload`.
+fn supersede<C>(uploads: &mut Vec<QueuedUpload<C>>, upload: QueuedUpload<C>) {
+    uploads.retain(|queued| queued.source != upload.source);
+    uploads.push(upload);
+}
+
+/// Removes the upload identified by `ticket` from `uploads` and returns the state of its rollback,
+/// if the upload is present and its rollback was recorded at the given `stamp`.
+fn cancel<C>(uploads: &mut Vec<QueuedUpload<C>>, ticket: u64, stamp: u64) -> Option<Box<dyn Any>> {
+    let index = uploads.iter().position(|upload| {
+        upload.ticket == ticket
+            && matches!(&upload.rollback, Some(rollback) if rollback.stamp == stamp)
+    })?;
+
+    uploads
+        .remove(index)
+        .rollback
+        .map(|rollback| rollback.state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(source: u64, ticket: u64, command: &'static str) -> QueuedUpload<&'static str> {
+        QueuedUpload {
+            source,
+            ticket,
+            bytes: 0,
+            command,
+            rollback: Some(Rollback {
+                stamp: ticket,
+                state: Box::new(command),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_supersede() {
+        let mut uploads = Vec::new();
+
+        supersede(&mut uploads, upload(0, 0, "a"));
+        supersede(&mut uploads, upload(1, 1, "b"));
+        supersede(&mut uploads, upload(0, 2, "c"));
+        supersede(&mut uploads, upload(2, 3, "d"));
+
+        let commands: Vec<_> = uploads.iter().map(|upload| upload.command).collect();
+
+        assert_eq!(commands, vec!["b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut uploads = vec![upload(0, 0, "a"), upload(1, 1, "b"), upload(2, 2, "c")];
+
+        let state = cancel(&mut uploads, 1, 1).unwrap();
+
+        assert_eq!(state.downcast_ref::<&str>(), Some(&"b"));
+        assert!(cancel(&mut uploads, 1, 1).is_none());
+
+        let commands: Vec<_> = uploads.iter().map(|upload| upload.command).collect();
+
+        assert_eq!(commands, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_cancel_stale_stamp() {
+        let mut uploads = vec![upload(0, 0, "a"), upload(1, 1, "b")];
+
+        uploads[1].rollback = None;
+
+        // The vector was modified after the upload was enqueued.
+        assert!(cancel(&mut uploads, 0, 1).is_none());
+        // The upload cannot be cancelled.
+        assert!(cancel(&mut uploads, 1, 1).is_none());
+        assert_eq!(uploads.len(), 2);
+    }
+
+    #[test]
+    fn test_cancel_superseded() {
+        let mut uploads = Vec::new();
+
+        supersede(&mut uploads, upload(0, 0, "a"));
+        supersede(&mut uploads, upload(0, 1, "b"));
+
+        assert!(cancel(&mut uploads, 0, 0).is_none());
+        assert_eq!(uploads.len(), 1);
+    }
+}
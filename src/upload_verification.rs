@@ -0,0 +1,185 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use web_glitz::task::{ContextId, GpuTask, Progress};
+
+use crate::gpu_safe::{as_bytes, GpuSafe};
+use crate::util::describe_vec;
+
+/// An upload that read back different data than was uploaded, as detected by a vector with upload
+/// verification enabled, see [BufferVec::set_verify_uploads].
+///
+/// [BufferVec::set_verify_uploads]: crate::BufferVec::set_verify_uploads
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UploadMismatch {
+    /// The label of the vector, if it has one.
+    pub label: Option<String>,
+
+    /// The range of elements that was uploaded.
+    pub range: Range<usize>,
+
+    /// The offset in bytes, from the start of the buffer, of the first byte that differs from the
+    /// uploaded data.
+    pub byte_offset: usize,
+}
+
+impl fmt::Display for UploadMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Upload to elements {}..{} of {} did not take: the data read back first differs at \
+             byte offset {}.",
+            self.range.start,
+            self.range.end,
+            describe_vec("buffer vec", self.label.as_deref()),
+            self.byte_offset
+        )
+    }
+}
+
+/// Verifies the uploads to a vector by reading back the uploaded ranges.
+pub(crate) struct UploadVerifier<T> {
+    // Set by `new` for a `GpuSafe` element type, so that the upload paths, which only require
+    // `T: Copy`, can compare bytes.
+    compare: fn(&[T], &[T]) -> Option<usize>,
+    mismatches: Arc<Mutex<Vec<UploadMismatch>>>,
+}
+
+impl<T> UploadVerifier<T>
+where
+    T: Copy + 'static,
+{
+    /// Creates a new verifier without any mismatches.
+    pub(crate) fn new() -> Self
+    where
+        T: GpuSafe,
+    {
+        UploadVerifier {
+            compare: first_byte_mismatch::<T>,
+            mismatches: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// The mismatches recorded so far.
+    pub(crate) fn mismatches(&self) -> Vec<UploadMismatch> {
+        self.mismatches
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Wraps the `download` of the `range` of a vector with the given `label` in a task that
+    /// compares the downloaded data with the `source` data that was uploaded to the `range`.
+    ///
+    /// With debug assertions enabled, the task panics on a mismatch; otherwise it records the
+    /// mismatch with this verifier.
+    pub(crate) fn verify_task<D>(
+        &self,
+        download: D,
+        source: Vec<T>,
+        range: Range<usize>,
+        label: Option<&str>,
+    ) -> VerifyTask<T, D> {
+        VerifyTask {
+            download,
+            source,
+            range,
+            label: label.map(|label| label.to_string()),
+            compare: self.compare,
+            mismatches: self.mismatches.clone(),
+        }
+    }
+}
+
+/// Task returned by [UploadVerifier::verify_task].
+pub(crate) struct VerifyTask<T, D> {
+    download: D,
+    source: Vec<T>,
+    range: Range<usize>,
+    label: Option<String>,
+    compare: fn(&[T], &[T]) -> Option<usize>,
+    mismatches: Arc<Mutex<Vec<UploadMismatch>>>,
+}
+
+unsafe impl<Ec, T, D> GpuTask<Ec> for VerifyTask<T, D>
+where
+    D: GpuTask<Ec, Output = Box<[T]>>,
+{
+    type Output = ();
+
+    fn context_id(&self) -> ContextId {
+        self.download.context_id()
+    }
+
+    fn progress(&mut self, execution_context: &mut Ec) -> Progress<()> {
+        let data = match self.download.progress(execution_context) {
+            Progress::Finished(data) => data,
+            Progress::ContinueFenced => return Progress::ContinueFenced,
+        };
+
+        if let Some(offset) = (self.compare)(&self.source, &data) {
+            let mismatch = UploadMismatch {
+                label: self.label.take(),
+                range: self.range.clone(),
+                byte_offset: self.range.start * std::mem::size_of::<T>() + offset,
+            };
+
+            if cfg!(debug_assertions) {
+                panic!("{}", mismatch);
+            }
+
+            self.mismatches
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(mismatch);
+        }
+
+        Progress::Finished(())
+    }
+}
+
+/// Returns the offset of the first byte in which `expected` and `actual` differ, or `None` if they
+/// are equal. If one is a prefix of the other, the offset is the length of the shorter one.
+fn first_byte_mismatch<T>(expected: &[T], actual: &[T]) -> Option<usize>
+where
+    T: GpuSafe,
+{
+    let expected = as_bytes(expected);
+    let actual = as_bytes(actual);
+
+    expected
+        .iter()
+        .zip(actual)
+        .position(|(a, b)| a != b)
+        .or_else(|| {
+            if expected.len() == actual.len() {
+                None
+            } else {
+                Some(expected.len().min(actual.len()))
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verifier_is_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<UploadVerifier<u32>>();
+    }
+
+    #[test]
+    fn test_first_byte_mismatch() {
+        assert_eq!(first_byte_mismatch::<u32>(&[1, 2, 3], &[1, 2, 3]), None);
+        assert_eq!(first_byte_mismatch::<u32>(&[], &[]), None);
+        assert_eq!(
+            first_byte_mismatch::<u32>(&[1, 2, 3], &[1, 2 | 0x100, 3]),
+            Some(if cfg!(target_endian = "little") { 5 } else { 6 })
+        );
+        assert_eq!(first_byte_mismatch::<u16>(&[1, 2], &[1]), Some(2));
+    }
+}
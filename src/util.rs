@@ -1,35 +1,898 @@
-pub(crate) fn new_capacity_amortized(
+use std::borrow::{Borrow, Cow};
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Range;
+use std::slice;
+use std::sync::Arc;
+
+use web_glitz::task::{ContextId, GpuTask, Progress};
+
+use crate::error::{BufferVecError, CapacityError};
+use crate::growth::{align_capacity, GrowthPolicy};
+
+/// Returns the size in bytes of `len` elements of type `T`, or an error if the size does not fit
+/// in a `usize`.
+pub(crate) fn checked_byte_size<T>(len: usize) -> Result<usize, CapacityError> {
+    let element_size = mem::size_of::<T>();
+
+    len.checked_mul(element_size)
+        .ok_or(CapacityError { len, element_size })
+}
+
+/// Returns the largest capacity in elements of `T` allowed by both the `max_capacity` in elements
+/// and the `max_buffer_size` in bytes, or `None` if neither applies.
+pub(crate) fn capacity_limit<T>(
+    max_capacity: Option<usize>,
+    max_buffer_size: Option<usize>,
+) -> Option<usize> {
+    let size_limit = max_buffer_size.map(|bytes| bytes / mem::size_of::<T>());
+
+    match (max_capacity, size_limit) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Returns an error if the vector needs to grow from `current_capacity` to hold
+/// `required_capacity` elements of `T`, and their size in bytes exceeds the `max_buffer_size`.
+pub(crate) fn check_buffer_size<T>(
     current_capacity: usize,
     required_capacity: usize,
-) -> Option<usize> {
-    if current_capacity < required_capacity {
-        let mut new_capacity = current_capacity;
+    max_buffer_size: Option<usize>,
+) -> Result<(), BufferVecError> {
+    if let Some(max_buffer_size) = max_buffer_size {
+        let requested_bytes = required_capacity.saturating_mul(mem::size_of::<T>());
 
-        if new_capacity == 0 {
-            new_capacity = 2;
+        if requested_bytes > max_buffer_size && required_capacity > current_capacity {
+            return Err(BufferVecError::BufferSizeLimitExceeded {
+                requested_bytes,
+                limit_bytes: max_buffer_size,
+            });
         }
+    }
+
+    Ok(())
+}
 
-        while new_capacity < required_capacity {
-            new_capacity = new_capacity * 2;
+/// Returns the capacity the growth `policy` decides on, rounded up to a multiple of `alignment` and
+/// clamped to `max_capacity`, or falls back to exactly the `required_capacity` if that capacity's
+/// size in bytes would not fit in a `usize`.
+///
+/// Returns an error if the `required_capacity` exceeds the `max_capacity`, or if the
+/// `required_capacity`'s size in bytes does not fit in a `usize`.
+pub(crate) fn checked_new_capacity<T>(
+    current_capacity: usize,
+    required_capacity: usize,
+    max_capacity: Option<usize>,
+    policy: &dyn GrowthPolicy,
+    alignment: usize,
+) -> Result<Option<usize>, BufferVecError> {
+    if let Some(max_capacity) = max_capacity {
+        if required_capacity > max_capacity && required_capacity > current_capacity {
+            return Err(BufferVecError::CapacityLimitExceeded {
+                requested: required_capacity,
+                limit: max_capacity,
+            });
+        }
+    }
+
+    if let Some(new_capacity) =
+        policy.grow_sized(current_capacity, required_capacity, mem::size_of::<T>())
+    {
+        let mut new_capacity = align_capacity(new_capacity.max(required_capacity), alignment);
+
+        if let Some(max_capacity) = max_capacity {
+            new_capacity = new_capacity.min(max_capacity);
+        }
+
+        if checked_byte_size::<T>(new_capacity).is_err() {
+            checked_byte_size::<T>(required_capacity)?;
+
+            new_capacity = required_capacity;
         }
 
-        Some(new_capacity)
+        Ok(Some(new_capacity))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the range of `count` elements starting at `offset`, or an error if that range is not
+/// within a vector of length `len`.
+pub(crate) fn checked_range(
+    offset: usize,
+    count: usize,
+    len: usize,
+) -> Result<Range<usize>, BufferVecError> {
+    offset
+        .checked_add(count)
+        .filter(|end| *end <= len)
+        .map(|end| offset..end)
+        .ok_or(BufferVecError::InvalidRange {
+            start: offset,
+            end: offset.saturating_add(count),
+            len,
+        })
+}
+
+/// Returns the given `range`, or an error if the `range` is not within a vector of length `len`.
+pub(crate) fn checked_read_range(
+    range: Range<usize>,
+    len: usize,
+) -> Result<Range<usize>, BufferVecError> {
+    if range.start > range.end || range.end > len {
+        Err(BufferVecError::InvalidRange {
+            start: range.start,
+            end: range.end,
+            len,
+        })
+    } else {
+        Ok(range)
+    }
+}
+
+/// Returns the first element and the number of elements of the given `range` as draw call
+/// parameters, or an error if the `range` is not within a vector of length `len` or either value
+/// does not fit in a `u32`.
+pub(crate) fn checked_draw_range(
+    range: Range<usize>,
+    len: usize,
+) -> Result<(u32, u32), BufferVecError> {
+    let range = checked_read_range(range, len)?;
+
+    match (u32::try_from(range.start), u32::try_from(range.len())) {
+        (Ok(first), Ok(count)) => Ok((first, count)),
+        _ => Err(BufferVecError::DrawRangeTooLarge {
+            start: range.start,
+            end: range.end,
+        }),
+    }
+}
+
+/// Splits `0..len` into consecutive ranges of `chunk_len` elements, the last of which may be
+/// shorter.
+///
+/// # Panics
+///
+/// Panics if `chunk_len` is `0`.
+pub(crate) fn chunk_ranges(len: usize, chunk_len: usize) -> impl Iterator<Item = Range<usize>> {
+    assert!(chunk_len != 0, "Chunk length must not be zero.");
+
+    (0..len)
+        .step_by(chunk_len)
+        .map(move |start| start..start.saturating_add(chunk_len).min(len))
+}
+
+/// Divides `dividend` by `divisor`, rounding up, without overflowing.
+///
+/// # Panics
+///
+/// Panics if `divisor` is `0`.
+pub(crate) fn div_ceil(dividend: usize, divisor: usize) -> usize {
+    dividend / divisor + (dividend % divisor != 0) as usize
+}
+
+/// Appends the concatenation of the `parts` to `data`, growing `data` at most once.
+pub(crate) fn extend_concat<T>(data: &mut Vec<T>, parts: &[&[T]]) -> Result<(), CapacityError>
+where
+    T: Copy,
+{
+    let len = parts
+        .iter()
+        .try_fold(0usize, |len, part| len.checked_add(part.len()))
+        .ok_or(CapacityError {
+            len: usize::MAX,
+            element_size: mem::size_of::<T>(),
+        })?;
+
+    data.reserve(len);
+
+    for part in parts {
+        data.extend_from_slice(part);
+    }
+
+    Ok(())
+}
+
+/// The maximum number of chunks [plan_copy_within] splits an overlapping copy into, before it
+/// gives up in favour of copying through a scratch buffer.
+pub(crate) const MAX_COPY_CHUNKS: usize = 4;
+
+/// Splits a copy of the `source` range to the `target` offset within a single buffer into copies
+/// between non-overlapping ranges, as WebGL does not allow copying between overlapping ranges of
+/// the same buffer.
+///
+/// Returns the `(source, target)` pairs in the order in which they must be executed, or `None` if
+/// more than `max_chunks` copies would be required.
+pub(crate) fn plan_copy_within(
+    source: Range<usize>,
+    target: usize,
+    max_chunks: usize,
+) -> Option<Vec<(Range<usize>, usize)>> {
+    let len = source.len();
+
+    if len == 0 || source.start == target {
+        return Some(Vec::new());
+    }
+
+    let distance = if target > source.start {
+        target - source.start
     } else {
-        None
+        source.start - target
+    };
+
+    if distance >= len {
+        return Some(vec![(source, target)]);
+    }
+
+    let chunk_count = div_ceil(len, distance);
+
+    if chunk_count > max_chunks {
+        return None;
+    }
+
+    let mut chunks = Vec::with_capacity(chunk_count);
+
+    if target > source.start {
+        // Moving right: copy back to front, so that no chunk overwrites data that has not been
+        // copied yet.
+        let mut end = source.end;
+
+        while end > source.start {
+            let start = end.saturating_sub(distance).max(source.start);
+
+            chunks.push((start..end, start + distance));
+
+            end = start;
+        }
+    } else {
+        // Moving left: copy front to back.
+        let mut start = source.start;
+
+        while start < source.end {
+            let end = (start + distance).min(source.end);
+
+            chunks.push((start..end, start - distance));
+
+            start = end;
+        }
+    }
+
+    Some(chunks)
+}
+
+/// Returns the `(source, target)` copies from a scratch copy of the elements `0..len` back into
+/// the buffer that rotate the elements left by `mid`, as with [slice::rotate_left].
+///
+/// Requires `mid <= len`.
+pub(crate) fn plan_rotate_left(len: usize, mid: usize) -> [(Range<usize>, usize); 2] {
+    [(mid..len, 0), (0..mid, len - mid)]
+}
+
+/// Extends the `range` to also cover the `other` range; empty ranges cover nothing.
+pub(crate) fn extend_range(
+    range: Option<Range<usize>>,
+    other: Range<usize>,
+) -> Option<Range<usize>> {
+    if other.is_empty() {
+        return range;
+    }
+
+    match range {
+        Some(range) => Some(range.start.min(other.start)..range.end.max(other.end)),
+        None => Some(other),
+    }
+}
+
+/// Returns the ranges covered by the runs of `true` elements in the `mask`, merging runs that are
+/// separated by at most `max_gap` `false` elements.
+pub(crate) fn dirty_runs(mask: &[bool], max_gap: usize) -> Vec<Range<usize>> {
+    let mut runs: Vec<Range<usize>> = Vec::new();
+
+    for index in (0..mask.len()).filter(|index| mask[*index]) {
+        match runs.last_mut() {
+            Some(run) if index - run.end <= max_gap => run.end = index + 1,
+            _ => runs.push(index..index + 1),
+        }
+    }
+
+    runs
+}
+
+/// Owned element data that is handed to an upload command.
+///
+/// Upload commands require their data to be `Send + Sync`, as a runtime may execute the command on
+/// a different thread; this is the case if the elements are `Send + Sync`.
+pub(crate) struct UploadData<T>(pub(crate) Vec<T>);
+
+impl<T> Borrow<[T]> for UploadData<T> {
+    fn borrow(&self) -> &[T] {
+        &self.0
+    }
+}
+
+/// Element data that is either borrowed for the `'static` lifetime or owned, handed to an upload
+/// command without copying.
+pub(crate) struct CowUploadData<T>(pub(crate) Cow<'static, [T]>)
+where
+    T: Clone + 'static;
+
+impl<T> Borrow<[T]> for CowUploadData<T>
+where
+    T: Clone + 'static,
+{
+    fn borrow(&self) -> &[T] {
+        &self.0
+    }
+}
+
+/// Owned element data that is handed to an upload command while a handle to it is kept, so that
+/// the allocation may be reclaimed once the command has been executed and dropped.
+pub(crate) struct SharedUploadData<T>(pub(crate) Arc<Vec<T>>);
+
+impl<T> Borrow<[T]> for SharedUploadData<T> {
+    fn borrow(&self) -> &[T] {
+        &self.0
+    }
+}
+
+/// Data of nested arrays `[[T; N]]` viewed as a flat slice of `N * len` elements, without copying.
+pub(crate) struct Flattened<D, T, const N: usize> {
+    data: D,
+    // Does not own a `T`; does not affect `Send` or `Sync`.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<D, T, const N: usize> Flattened<D, T, N> {
+    pub(crate) fn new(data: D) -> Self {
+        Flattened {
+            data,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<D, T, const N: usize> Borrow<[T]> for Flattened<D, T, N>
+where
+    D: Borrow<[[T; N]]>,
+{
+    fn borrow(&self) -> &[T] {
+        let nested = self.data.borrow();
+
+        // SAFETY: an array `[T; N]` has the layout of `N` consecutive `T`s, so a slice of `len`
+        // arrays has the layout of `N * len` consecutive `T`s.
+        unsafe { slice::from_raw_parts(nested.as_ptr() as *const T, nested.len() * N) }
+    }
+}
+
+/// A task that does nothing but wait for a GPU fence, inserted when the task is first progressed.
+///
+/// As the GPU executes commands in submission order, the task finishes once all commands that were
+/// submitted before it have completed.
+pub(crate) struct FenceTask {
+    fenced: bool,
+}
+
+impl FenceTask {
+    pub(crate) fn new() -> Self {
+        FenceTask { fenced: false }
+    }
+}
+
+unsafe impl<Ec> GpuTask<Ec> for FenceTask {
+    type Output = ();
+
+    fn context_id(&self) -> ContextId {
+        ContextId::Any
+    }
+
+    fn progress(&mut self, _execution_context: &mut Ec) -> Progress<()> {
+        if self.fenced {
+            Progress::Finished(())
+        } else {
+            self.fenced = true;
+
+            Progress::ContinueFenced
+        }
+    }
+}
+
+/// Describes a vector of the given `kind` (such as `"buffer vec"`) for use in messages, by its
+/// `label` if it has one.
+pub(crate) fn describe_vec(kind: &str, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!("{} \"{}\"", kind, label),
+        None if kind.starts_with(|c| "aeiou".contains(c)) => format!("an {}", kind),
+        None => format!("a {}", kind),
+    }
+}
+
+/// Formats the panic message for the given `err`, naming the vector by its `label` if it has one.
+pub(crate) fn error_message(err: &BufferVecError, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!("{} (in \"{}\")", err, label),
+        None => err.to_string(),
     }
 }
 
+/// Panics with the given `message` about a violated internal invariant.
+///
+/// Used instead of `unwrap` on paths that can only fail if the bookkeeping of a vector is wrong,
+/// so that such failures are reported as bugs in this crate, with the state of the vector, rather
+/// than as a context-free `Option::unwrap` panic.
+#[cold]
+#[track_caller]
+pub(crate) fn bug(message: fmt::Arguments) -> ! {
+    panic!(
+        "Internal error in web-glitz-buffer-vec: {}. This is a bug, please report it.",
+        message
+    )
+}
+
+/// Panics because the `range` of a buffer of `buffer_len` elements that belongs to the vector of
+/// the given `kind` and `label`, with the given `len` and `capacity`, is out of bounds.
+#[cold]
+#[track_caller]
+pub(crate) fn range_bug(
+    kind: &str,
+    label: Option<&str>,
+    range: &Range<usize>,
+    buffer_len: usize,
+    len: usize,
+    capacity: usize,
+) -> ! {
+    bug(format_args!(
+        "range {}..{} is out of bounds for a buffer of {} elements of {} (len {}, capacity {})",
+        range.start,
+        range.end,
+        buffer_len,
+        describe_vec(kind, label),
+        len,
+        capacity
+    ))
+}
+
+/// Helper for rejecting zero-sized element types at compile time.
+///
+/// Evaluating [AssertNonZeroSized::OK] for a zero-sized `T` fails compilation (when the generic
+/// function that evaluates it is instantiated for `T`).
+pub(crate) struct AssertNonZeroSized<T>(PhantomData<T>);
+
+impl<T> AssertNonZeroSized<T> {
+    pub(crate) const OK: () = assert!(
+        mem::size_of::<T>() != 0,
+        "Buffer vecs do not support zero-sized element types."
+    );
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::util::new_capacity_amortized;
+    use std::borrow::{Borrow, Cow};
+    use std::ops::Range;
+
+    use crate::error::{BufferVecError, CapacityError};
+    use crate::growth::{Doubling, Exact, ScaleFactor};
+    use crate::util::{
+        capacity_limit, check_buffer_size, checked_byte_size, checked_draw_range,
+        checked_new_capacity, checked_range, checked_read_range, chunk_ranges, describe_vec,
+        dirty_runs, error_message, extend_concat, extend_range, plan_copy_within, plan_rotate_left,
+        range_bug, CowUploadData, FenceTask, Flattened,
+    };
+    use web_glitz::task::{GpuTask, Progress};
+
+    #[test]
+    fn test_checked_byte_size() {
+        let max_len = usize::MAX / 64;
+
+        assert_eq!(checked_byte_size::<[u8; 64]>(max_len), Ok(max_len * 64));
+        assert_eq!(
+            checked_byte_size::<[u8; 64]>(max_len + 1),
+            Err(CapacityError {
+                len: max_len + 1,
+                element_size: 64
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    fn test_checked_byte_size_wasm32() {
+        assert!(checked_byte_size::<[u8; 64]>(70_000_000).is_err());
+    }
+
+    #[test]
+    fn test_checked_new_capacity() {
+        let max_len = usize::MAX / 64;
+
+        assert_eq!(
+            checked_new_capacity::<[u8; 64]>(4, 5, None, &Doubling, 1),
+            Ok(Some(8))
+        );
+        assert_eq!(
+            checked_new_capacity::<[u8; 64]>(8, 5, None, &Doubling, 1),
+            Ok(None)
+        );
+        assert_eq!(
+            checked_new_capacity::<[u8; 64]>(max_len / 2 + 1, max_len, None, &Doubling, 1),
+            Ok(Some(max_len))
+        );
+        assert!(
+            checked_new_capacity::<[u8; 64]>(max_len, max_len + 1, None, &Doubling, 1).is_err()
+        );
+    }
+
+    #[test]
+    fn test_checked_new_capacity_alignment() {
+        assert_eq!(
+            checked_new_capacity::<u32>(0, 5, None, &Doubling, 256),
+            Ok(Some(256))
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(256, 257, None, &Doubling, 256),
+            Ok(Some(512))
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(256, 257, Some(300), &Doubling, 256),
+            Ok(Some(300))
+        );
+    }
+
+    #[test]
+    fn test_checked_new_capacity_policy() {
+        assert_eq!(
+            checked_new_capacity::<u32>(4, 5, None, &Exact, 1),
+            Ok(Some(5))
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(4, 5, None, &ScaleFactor::new(1.5), 1),
+            Ok(Some(6))
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(4, 5, None, &ScaleFactor::new(1.5), 4),
+            Ok(Some(8))
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(4, 5, Some(7), &ScaleFactor::new(1.5), 4),
+            Ok(Some(7))
+        );
+        assert_eq!(checked_new_capacity::<u32>(8, 5, None, &Exact, 4), Ok(None));
+    }
+
+    #[test]
+    fn test_capacity_limit() {
+        assert_eq!(capacity_limit::<u32>(None, None), None);
+        assert_eq!(capacity_limit::<u32>(Some(10), None), Some(10));
+        assert_eq!(capacity_limit::<u32>(None, Some(41)), Some(10));
+        assert_eq!(capacity_limit::<u32>(Some(5), Some(40)), Some(5));
+        assert_eq!(capacity_limit::<u32>(Some(20), Some(40)), Some(10));
+    }
+
+    #[test]
+    fn test_check_buffer_size() {
+        assert_eq!(check_buffer_size::<u32>(0, 10, None), Ok(()));
+        assert_eq!(check_buffer_size::<u32>(0, 10, Some(40)), Ok(()));
+        assert_eq!(
+            check_buffer_size::<u32>(0, 11, Some(40)),
+            Err(BufferVecError::BufferSizeLimitExceeded {
+                requested_bytes: 44,
+                limit_bytes: 40
+            })
+        );
+        assert_eq!(check_buffer_size::<u32>(16, 11, Some(40)), Ok(()));
+        assert_eq!(
+            check_buffer_size::<u32>(0, usize::MAX, Some(40)),
+            Err(BufferVecError::BufferSizeLimitExceeded {
+                requested_bytes: usize::MAX,
+                limit_bytes: 40
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_new_capacity_max_capacity() {
+        assert_eq!(
+            checked_new_capacity::<u32>(4, 5, Some(6), &Doubling, 1),
+            Ok(Some(6))
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(4, 6, Some(6), &Doubling, 1),
+            Ok(Some(6))
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(8, 5, Some(6), &Doubling, 1),
+            Ok(None)
+        );
+        assert_eq!(
+            checked_new_capacity::<u32>(4, 7, Some(6), &Doubling, 1),
+            Err(BufferVecError::CapacityLimitExceeded {
+                requested: 7,
+                limit: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_range() {
+        assert_eq!(checked_range(0, 0, 0), Ok(0..0));
+        assert_eq!(checked_range(2, 3, 5), Ok(2..5));
+        assert_eq!(
+            checked_range(3, 3, 5),
+            Err(BufferVecError::InvalidRange {
+                start: 3,
+                end: 6,
+                len: 5
+            })
+        );
+        assert_eq!(
+            checked_range(usize::MAX, 2, 5),
+            Err(BufferVecError::InvalidRange {
+                start: usize::MAX,
+                end: usize::MAX,
+                len: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_read_range() {
+        assert_eq!(checked_read_range(0..0, 0), Ok(0..0));
+        assert_eq!(checked_read_range(1..3, 3), Ok(1..3));
+        assert_eq!(
+            checked_read_range(1..4, 3),
+            Err(BufferVecError::InvalidRange {
+                start: 1,
+                end: 4,
+                len: 3
+            })
+        );
+        assert_eq!(
+            checked_read_range(Range { start: 2, end: 1 }, 3),
+            Err(BufferVecError::InvalidRange {
+                start: 2,
+                end: 1,
+                len: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_draw_range() {
+        assert_eq!(checked_draw_range(0..0, 0), Ok((0, 0)));
+        assert_eq!(checked_draw_range(2..5, 6), Ok((2, 3)));
+        assert_eq!(
+            checked_draw_range(2..7, 6),
+            Err(BufferVecError::InvalidRange {
+                start: 2,
+                end: 7,
+                len: 6
+            })
+        );
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            let start = u32::MAX as usize + 1;
+
+            assert_eq!(
+                checked_draw_range(start..start + 1, usize::MAX),
+                Err(BufferVecError::DrawRangeTooLarge {
+                    start,
+                    end: start + 1
+                })
+            );
+            assert_eq!(
+                checked_draw_range(0..start, usize::MAX),
+                Err(BufferVecError::DrawRangeTooLarge {
+                    start: 0,
+                    end: start
+                })
+            );
+        }
+    }
 
     #[test]
-    fn test_new_capacity_amortized() {
-        assert_eq!(new_capacity_amortized(0, 0), None);
-        assert_eq!(new_capacity_amortized(0, 1), Some(2));
-        assert_eq!(new_capacity_amortized(2, 2), None);
-        assert_eq!(new_capacity_amortized(2, 3), Some(4));
-        assert_eq!(new_capacity_amortized(4, 4), None);
-        assert_eq!(new_capacity_amortized(4, 5), Some(8));
+    fn test_chunk_ranges() {
+        assert_eq!(chunk_ranges(0, 4).count(), 0);
+        assert_eq!(chunk_ranges(8, 4).collect::<Vec<_>>(), vec![0..4, 4..8]);
+        assert_eq!(
+            chunk_ranges(10, 4).collect::<Vec<_>>(),
+            vec![0..4, 4..8, 8..10]
+        );
+        assert_eq!(chunk_ranges(3, usize::MAX).collect::<Vec<_>>(), vec![0..3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chunk length must not be zero.")]
+    fn test_chunk_ranges_zero_chunk_len() {
+        let _ = chunk_ranges(10, 0);
+    }
+
+    #[test]
+    fn test_extend_concat() {
+        let a = [1, 2, 3];
+        let b: [u32; 0] = [];
+        let c = [4, 5];
+
+        let mut data = Vec::new();
+
+        extend_concat(&mut data, &[&a[..], &b[..], &c[..]]).unwrap();
+
+        assert_eq!(data, [&a[..], &b[..], &c[..]].concat());
+        assert_eq!(data.capacity(), 5);
+
+        data.clear();
+        extend_concat::<u32>(&mut data, &[]).unwrap();
+
+        assert!(data.is_empty());
+    }
+
+    /// Applies the copies in the given `plan` to `data` one by one, checking that no copy is
+    /// between overlapping ranges.
+    fn apply_plan(data: &mut [u32], plan: &[(Range<usize>, usize)]) {
+        for (source, target) in plan {
+            let target_range = *target..target + source.len();
+
+            assert!(source.end <= target_range.start || target_range.end <= source.start);
+
+            data.copy_within(source.clone(), *target);
+        }
+    }
+
+    #[test]
+    fn test_plan_copy_within_matches_slice_copy_within() {
+        let len = 12;
+
+        for start in 0..len {
+            for end in start..=len {
+                for target in 0..=(len - (end - start)) {
+                    let mut expected: Vec<u32> = (0..len as u32).collect();
+                    let mut actual = expected.clone();
+
+                    expected.copy_within(start..end, target);
+
+                    let plan = plan_copy_within(start..end, target, usize::MAX).unwrap();
+
+                    apply_plan(&mut actual, &plan);
+
+                    assert_eq!(actual, expected, "{}..{} to {}", start, end, target);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fence_task() {
+        let mut task = FenceTask::new();
+
+        assert!(matches!(task.progress(&mut ()), Progress::ContinueFenced));
+        assert!(matches!(task.progress(&mut ()), Progress::Finished(())));
+    }
+
+    #[test]
+    fn test_plan_rotate_left_matches_slice_rotate_left() {
+        for len in 0..12 {
+            for mid in 0..=len {
+                let scratch: Vec<u32> = (0..len as u32).collect();
+                let mut expected = scratch.clone();
+                let mut actual = vec![0; len];
+
+                expected.rotate_left(mid);
+
+                for (source, target) in plan_rotate_left(len, mid) {
+                    actual[target..target + source.len()].copy_from_slice(&scratch[source]);
+                }
+
+                assert_eq!(actual, expected, "{} by {}", len, mid);
+            }
+        }
+    }
+
+    #[test]
+    fn test_extend_range() {
+        assert_eq!(extend_range(None, 3..4), Some(3..4));
+        assert_eq!(extend_range(Some(3..4), 900..910), Some(3..910));
+        assert_eq!(extend_range(Some(5..10), 2..6), Some(2..10));
+        assert_eq!(extend_range(Some(5..10), 7..7), Some(5..10));
+        assert_eq!(extend_range(None, 7..7), None);
+    }
+
+    #[test]
+    fn test_dirty_runs() {
+        let mask = [true, true, false, true, false, false, true, false];
+
+        assert_eq!(dirty_runs(&mask, 0), vec![0..2, 3..4, 6..7]);
+        assert_eq!(dirty_runs(&mask, 1), vec![0..4, 6..7]);
+        assert_eq!(dirty_runs(&mask, 2), vec![0..7]);
+        assert_eq!(dirty_runs(&[false; 4], 2), vec![]);
+    }
+
+    #[test]
+    fn test_plan_copy_within_trivial() {
+        assert_eq!(plan_copy_within(2..2, 5, 4), Some(vec![]));
+        assert_eq!(plan_copy_within(2..5, 2, 4), Some(vec![]));
+        assert_eq!(plan_copy_within(0..3, 3, 4), Some(vec![(0..3, 3)]));
+    }
+
+    #[test]
+    fn test_plan_copy_within_overlapping_right() {
+        assert_eq!(
+            plan_copy_within(0..5, 2, 4),
+            Some(vec![(3..5, 5), (1..3, 3), (0..1, 2)])
+        );
+    }
+
+    #[test]
+    fn test_plan_copy_within_overlapping_left() {
+        assert_eq!(
+            plan_copy_within(2..7, 0, 4),
+            Some(vec![(2..4, 0), (4..6, 2), (6..7, 4)])
+        );
+    }
+
+    #[test]
+    fn test_plan_copy_within_too_many_chunks() {
+        assert_eq!(plan_copy_within(0..10, 1, 4), None);
+    }
+
+    #[test]
+    fn test_describe_vec() {
+        assert_eq!(describe_vec("buffer vec", None), "a buffer vec");
+        assert_eq!(
+            describe_vec("index buffer vec", None),
+            "an index buffer vec"
+        );
+        assert_eq!(
+            describe_vec("buffer vec", Some("particles")),
+            "buffer vec \"particles\""
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "range 4..9 is out of bounds for a buffer of 8 elements of buffer vec \
+                               \"particles\" (len 6, capacity 8). This is a bug"
+    )]
+    fn test_range_bug() {
+        range_bug("buffer vec", Some("particles"), &(4..9), 8, 6, 8);
+    }
+
+    #[test]
+    fn test_error_message() {
+        let err = BufferVecError::CapacityLimitExceeded {
+            requested: 4,
+            limit: 2,
+        };
+
+        assert_eq!(
+            error_message(&err, None),
+            "requested capacity of 4 elements exceeds the limit of 2 elements"
+        );
+        assert_eq!(
+            error_message(&err, Some("particles")),
+            "requested capacity of 4 elements exceeds the limit of 2 elements (in \"particles\")"
+        );
+    }
+
+    #[test]
+    fn test_flattened() {
+        let flattened: Flattened<_, u32, 3> = Flattened::new(vec![[1, 2, 3], [4, 5, 6]]);
+
+        assert_eq!(Borrow::<[u32]>::borrow(&flattened), &[1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_cow_upload_data_does_not_copy() {
+        static DATA: [u32; 3] = [1, 2, 3];
+
+        let borrowed = CowUploadData(Cow::Borrowed(&DATA[..]));
+
+        assert!(std::ptr::eq(Borrow::<[u32]>::borrow(&borrowed), &DATA[..]));
+
+        let owned = vec![4, 5, 6];
+        let ptr = owned.as_ptr();
+        let owned = CowUploadData(Cow::Owned(owned));
+
+        assert_eq!(Borrow::<[u32]>::borrow(&owned).as_ptr(), ptr);
     }
 }
@@ -0,0 +1,366 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+use web_glitz::runtime::RenderingContext;
+
+use crate::{BufferVec, IndexBufferVec, IndexValue};
+
+/// Error returned by [validate_indexed_pair] when an index buffer references vertices that are out
+/// of bounds for a vertex buffer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IndexedDrawError {
+    /// The largest index in the index buffer.
+    pub max_index: u64,
+
+    /// The number of vertices in the vertex buffer.
+    pub vertex_count: usize,
+}
+
+impl fmt::Display for IndexedDrawError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "index buffer references vertex {}, but the vertex buffer only contains {} vertices",
+            self.max_index, self.vertex_count
+        )
+    }
+}
+
+impl Error for IndexedDrawError {}
+
+/// An internal invariant of a vector that does not hold, as reported by `debug_validate` on the
+/// vector types.
+///
+/// A violation indicates a bug in this crate (or unsafe code that corrupted a vector), except for
+/// [Unflushed](InvariantViolation::Unflushed) and [Poisoned](InvariantViolation::Poisoned), which
+/// flag state that is legitimate between operations but usually unintended when inspected.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum InvariantViolation {
+    /// The length of the vector exceeds its capacity.
+    LenExceedsCapacity {
+        /// The length of the vector.
+        len: usize,
+
+        /// The capacity of the vector.
+        capacity: usize,
+    },
+
+    /// A CPU-side copy of the data (a shadow copy, staging data or batch data) does not hold the
+    /// same number of elements as the vector.
+    CpuLenMismatch {
+        /// The number of elements in the CPU-side copy.
+        cpu_len: usize,
+
+        /// The length of the vector.
+        len: usize,
+    },
+
+    /// Fewer elements of the buffer were initialized than the length of the vector.
+    UninitializedElements {
+        /// The number of initialized elements.
+        initialized_len: usize,
+
+        /// The length of the vector.
+        len: usize,
+    },
+
+    /// A previous update failed before it completed, so the buffer contents are unspecified.
+    Poisoned,
+
+    /// A named range extends beyond the length of the vector.
+    RangeOutOfBounds {
+        /// The name of the range, e.g. the key of a section.
+        name: String,
+
+        /// The range.
+        range: Range<usize>,
+
+        /// The length of the vector.
+        len: usize,
+    },
+
+    /// Two named ranges overlap.
+    OverlappingRanges {
+        /// The name of the range that starts first.
+        first: String,
+
+        /// The name of the other range.
+        second: String,
+    },
+
+    /// Elements were staged but not yet uploaded to the GPU.
+    Unflushed {
+        /// The number of staged elements, or the length of the staged data if it was marked as
+        /// dirty as a whole.
+        elements: usize,
+    },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvariantViolation::LenExceedsCapacity { len, capacity } => {
+                write!(
+                    f,
+                    "the length ({}) exceeds the capacity ({})",
+                    len, capacity
+                )
+            }
+            InvariantViolation::CpuLenMismatch { cpu_len, len } => write!(
+                f,
+                "the CPU-side data holds {} elements, but the length is {}",
+                cpu_len, len
+            ),
+            InvariantViolation::UninitializedElements {
+                initialized_len,
+                len,
+            } => write!(
+                f,
+                "only the first {} elements are initialized, but the length is {}",
+                initialized_len, len
+            ),
+            InvariantViolation::Poisoned => {
+                write!(f, "a previous update failed before it completed")
+            }
+            InvariantViolation::RangeOutOfBounds { name, range, len } => write!(
+                f,
+                "{} occupies {}..{}, beyond the length ({})",
+                name, range.start, range.end, len
+            ),
+            InvariantViolation::OverlappingRanges { first, second } => {
+                write!(f, "{} overlaps {}", first, second)
+            }
+            InvariantViolation::Unflushed { elements } => {
+                write!(f, "{} staged elements have not been uploaded", elements)
+            }
+        }
+    }
+}
+
+impl Error for InvariantViolation {}
+
+/// Turns the collected `violations` into the result of a `debug_validate` method.
+pub(crate) fn violations_result(
+    violations: Vec<InvariantViolation>,
+) -> Result<(), Vec<InvariantViolation>> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Checks that the named `ranges` lie within `len` and that no two non-empty ranges overlap,
+/// adding a violation to `violations` for every range that does not.
+pub(crate) fn check_ranges<I>(ranges: I, len: usize, violations: &mut Vec<InvariantViolation>)
+where
+    I: IntoIterator<Item = (String, Range<usize>)>,
+{
+    let mut ranges: Vec<_> = ranges.into_iter().collect();
+
+    // Sort so that the reported violations do not depend on the iteration order of the source.
+    ranges.sort_by(|(a_name, a), (b_name, b)| {
+        (a.start, a.end, a_name).cmp(&(b.start, b.end, b_name))
+    });
+
+    let mut furthest: Option<(&str, usize)> = None;
+
+    for (name, range) in &ranges {
+        if range.start > range.end || range.end > len {
+            violations.push(InvariantViolation::RangeOutOfBounds {
+                name: name.clone(),
+                range: range.clone(),
+                len,
+            });
+        }
+
+        if range.is_empty() {
+            continue;
+        }
+
+        match furthest {
+            Some((other, end)) if range.start < end => {
+                violations.push(InvariantViolation::OverlappingRanges {
+                    first: other.to_string(),
+                    second: name.clone(),
+                });
+
+                if range.end > end {
+                    furthest = Some((name, range.end));
+                }
+            }
+            _ => furthest = Some((name, range.end)),
+        }
+    }
+}
+
+/// Panics with the `violations` of the invariants of the vector with the given `kind` and `label`,
+/// if there are any.
+#[cfg(all(feature = "debug-validate", debug_assertions))]
+pub(crate) fn assert_invariants(
+    result: Result<(), Vec<InvariantViolation>>,
+    kind: &str,
+    label: Option<&str>,
+) {
+    if let Err(violations) = result {
+        let violations: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+
+        panic!(
+            "Invariants of {} violated: {}.",
+            crate::util::describe_vec(kind, label),
+            violations.join("; ")
+        );
+    }
+}
+
+/// Verifies that all indices in the `indices` vector are in range for the number of vertices in
+/// the `vertices` vector: every index must be less than the vertex count.
+///
+/// Uses the maximum index value that the [IndexBufferVec] tracks on update (see
+/// [IndexBufferVec::max_index]), so validation does not involve the GPU and does not scan the
+/// indices. Intended to be called before recording an indexed draw in debug builds. On an index
+/// vector that is not resilient, the tracked maximum may exceed the largest index after indices
+/// were overwritten in place, which may cause a spurious error. See
+/// [IndexBufferVec::validate_indices] for a check that reports every out-of-range index.
+///
+/// # Example
+///
+/// ```
+/// # use web_glitz::runtime::RenderingContext;
+/// # fn wrapper<Rc>(context: Rc) where Rc: RenderingContext + Clone {
+/// use web_glitz_buffer_vec::{validate_indexed_pair, BufferVec, IndexBufferVec};
+/// use web_glitz::buffer::UsageHint;
+///
+/// let mut vertices = BufferVec::new(context.clone(), UsageHint::StaticDraw);
+/// let mut indices = IndexBufferVec::new(context, UsageHint::StaticDraw);
+///
+/// vertices.update([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+/// indices.update([0u16, 1, 3]);
+///
+/// #[cfg(debug_assertions)]
+/// assert!(validate_indexed_pair(&indices, &vertices).is_err());
+/// # }
+/// ```
+///
+/// Here `context` is a WebGlitz [RenderingContext].
+///
+/// [RenderingContext]: web_glitz::runtime::RenderingContext
+pub fn validate_indexed_pair<Rc0, Rc1, I, V>(
+    indices: &IndexBufferVec<Rc0, I>,
+    vertices: &BufferVec<Rc1, V>,
+) -> Result<(), IndexedDrawError>
+where
+    Rc0: RenderingContext,
+    Rc1: RenderingContext,
+    I: IndexValue + 'static,
+    V: Copy + Send + Sync + 'static,
+{
+    check_max_index(indices.max_index().map(|i| i.to_u64()), vertices.len())
+}
+
+fn check_max_index(max_index: Option<u64>, vertex_count: usize) -> Result<(), IndexedDrawError> {
+    match max_index {
+        Some(max_index) if max_index >= vertex_count as u64 => Err(IndexedDrawError {
+            max_index,
+            vertex_count,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the positions and values of the indices that are out of range for `vertex_count`
+/// vertices, that is, indices that are greater than or equal to `vertex_count`.
+pub(crate) fn find_out_of_range_indices<T>(indices: &[T], vertex_count: usize) -> Vec<(usize, T)>
+where
+    T: IndexValue,
+{
+    indices
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, index)| index.to_u64() >= vertex_count as u64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_out_of_range_indices() {
+        assert_eq!(find_out_of_range_indices(&[0u16, 1, 2], 3), vec![]);
+        assert_eq!(
+            find_out_of_range_indices(&[0u16, 5, 2, 3], 3),
+            vec![(1, 5), (3, 3)]
+        );
+        assert_eq!(find_out_of_range_indices(&[0u8], 0), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_check_ranges() {
+        let mut violations = Vec::new();
+
+        check_ranges(
+            vec![
+                ("c".to_string(), 6..9),
+                ("a".to_string(), 0..4),
+                ("empty".to_string(), 2..2),
+                ("b".to_string(), 4..6),
+            ],
+            10,
+            &mut violations,
+        );
+
+        assert_eq!(violations, vec![]);
+
+        check_ranges(
+            vec![
+                ("b".to_string(), 2..3),
+                ("a".to_string(), 0..8),
+                ("c".to_string(), 5..12),
+            ],
+            10,
+            &mut violations,
+        );
+
+        assert_eq!(
+            violations,
+            vec![
+                InvariantViolation::OverlappingRanges {
+                    first: "a".to_string(),
+                    second: "b".to_string()
+                },
+                InvariantViolation::RangeOutOfBounds {
+                    name: "c".to_string(),
+                    range: 5..12,
+                    len: 10
+                },
+                InvariantViolation::OverlappingRanges {
+                    first: "a".to_string(),
+                    second: "c".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_max_index() {
+        assert_eq!(check_max_index(None, 0), Ok(()));
+        assert_eq!(check_max_index(Some(2), 3), Ok(()));
+        assert_eq!(
+            check_max_index(Some(3), 3),
+            Err(IndexedDrawError {
+                max_index: 3,
+                vertex_count: 3
+            })
+        );
+        assert_eq!(
+            check_max_index(Some(0), 0),
+            Err(IndexedDrawError {
+                max_index: 0,
+                vertex_count: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use web_glitz::pipeline::graphics::{
+    InputRate, TypedVertexBuffers, Vertex, VertexBuffers, VertexBuffersEncoding,
+    VertexBuffersEncodingContext,
+};
+use web_glitz::runtime::RenderingContext;
+
+use crate::BufferVec;
+
+/// Bundles a per-vertex [BufferVec] and a per-instance [BufferVec] so that both may be bound to a
+/// graphics pipeline in a single `bind_vertex_buffers` call.
+///
+/// The per-vertex data is always bound to the first vertex buffer slot and the per-instance data
+/// to the second slot, which matches a pipeline with a vertex input layout of `(V, I)`.
+///
+/// # Example
+///
+/// ```
+/// # #![feature(const_fn, const_maybe_uninit_as_ptr, const_ptr_offset_from, const_raw_ptr_deref, ptr_offset_from)]
+/// # use web_glitz::rendering::DefaultRGBBuffer;
+/// # use web_glitz::rendering::DefaultRenderTarget;
+/// # use web_glitz::pipeline::graphics::{GraphicsPipeline, Vertex};
+/// # use web_glitz::runtime::RenderingContext;
+/// # use web_glitz_buffer_vec::BufferVec;
+/// use web_glitz_buffer_vec::VertexStream;
+///
+/// # fn wrapper<Rc, V, I>(
+/// #     mut render_target: DefaultRenderTarget<DefaultRGBBuffer, ()>,
+/// #     graphics_pipeline: GraphicsPipeline<(V, I), (), ()>,
+/// #     vertices: BufferVec<Rc, V>,
+/// #     instances: BufferVec<Rc, I>,
+/// # )
+/// # where
+/// #     Rc: RenderingContext,
+/// #     V: Vertex + Copy + Send + Sync + 'static,
+/// #     I: Vertex + Copy + Send + Sync + 'static,
+/// # {
+/// # let resources = ();
+/// let stream = VertexStream::new(&vertices, &instances);
+/// let vertex_count = stream.min_vertex_count();
+/// let instance_count = stream.instance_count();
+///
+/// let render_pass = render_target.create_render_pass(|framebuffer| {
+///     framebuffer.pipeline_task(&graphics_pipeline, |active_pipeline| {
+///         active_pipeline.task_builder()
+///             .bind_vertex_buffers(stream)
+///             .bind_resources(resources)
+///             .draw(vertex_count, instance_count)
+///             .finish()
+///     })
+/// });
+/// # }
+/// ```
+pub struct VertexStream<'a, Rc, V, I> {
+    vertices: &'a BufferVec<Rc, V>,
+    instances: &'a BufferVec<Rc, I>,
+}
+
+impl<'a, Rc, V, I> VertexStream<'a, Rc, V, I>
+where
+    Rc: RenderingContext,
+    V: Vertex + Copy + Send + Sync + 'static,
+    I: Vertex + Copy + Send + Sync + 'static,
+{
+    /// Creates a new stream that binds the given per-vertex `vertices` and per-instance
+    /// `instances`.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the input rate of `V` is not [InputRate::PerVertex], or if the
+    /// input rate of `I` is not [InputRate::PerInstance].
+    pub fn new(vertices: &'a BufferVec<Rc, V>, instances: &'a BufferVec<Rc, I>) -> Self {
+        debug_assert!(
+            V::INPUT_RATE == InputRate::PerVertex,
+            "The per-vertex buffer of a vertex stream must have a per-vertex input rate."
+        );
+        debug_assert!(
+            I::INPUT_RATE == InputRate::PerInstance,
+            "The per-instance buffer of a vertex stream must have a per-instance input rate."
+        );
+
+        VertexStream {
+            vertices,
+            instances,
+        }
+    }
+
+    /// The largest vertex count that may be drawn with this stream without reading past the end of
+    /// the per-vertex data.
+    pub fn min_vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// The largest instance count that may be drawn with this stream without reading past the end
+    /// of the per-instance data.
+    pub fn instance_count(&self) -> usize {
+        self.instances.len()
+    }
+}
+
+impl<'a, Rc, V, I> Clone for VertexStream<'a, Rc, V, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, Rc, V, I> Copy for VertexStream<'a, Rc, V, I> {}
+
+impl<'a, Rc, V, I> VertexBuffers for VertexStream<'a, Rc, V, I>
+where
+    Rc: RenderingContext,
+    V: Vertex + Copy + Send + Sync + 'static,
+    I: Vertex + Copy + Send + Sync + 'static,
+{
+    fn encode<'b>(
+        self,
+        context: &'b mut VertexBuffersEncodingContext,
+    ) -> VertexBuffersEncoding<'b> {
+        let mut encoding = VertexBuffersEncoding::new(context);
+
+        encoding.add_vertex_buffer(self.vertices.as_buffer_view());
+        encoding.add_vertex_buffer(self.instances.as_buffer_view());
+
+        encoding
+    }
+}
+
+unsafe impl<'a, Rc, V, I> TypedVertexBuffers for VertexStream<'a, Rc, V, I>
+where
+    Rc: RenderingContext,
+    V: Vertex + Copy + Send + Sync + 'static,
+    I: Vertex + Copy + Send + Sync + 'static,
+{
+    type Layout = (V, I);
+}
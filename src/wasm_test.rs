@@ -0,0 +1,280 @@
+//! Fixtures for testing buffer vectors against a real WebGL2 context in the browser, with
+//! [wasm-bindgen-test](https://crates.io/crates/wasm-bindgen-test).
+//!
+//! Only available with the `wasm-test` feature. The fixtures must be run in a browser, for example
+//! with `wasm-pack test --headless --firefox --features wasm-test`. They are public so that crates
+//! building on this crate may reuse them for their own tests.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+//! use web_glitz::buffer::UsageHint;
+//! use web_glitz_buffer_vec::wasm_test::{assert_buffer_contents, TestContext};
+//! use web_glitz_buffer_vec::BufferVec;
+//!
+//! wasm_bindgen_test_configure!(run_in_browser);
+//!
+//! #[wasm_bindgen_test]
+//! async fn round_trip() {
+//!     let fixture = TestContext::new();
+//!     let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::StaticDraw);
+//!
+//!     vec.update([1u32, 2, 3]);
+//!
+//!     assert_buffer_contents(&vec, &[1, 2, 3]).await;
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::fmt::Debug;
+
+use wasm_bindgen::JsCast;
+use web_glitz::buffer::UsageHint;
+use web_glitz::image::format::RGBA8;
+use web_glitz::image::texture_2d::{Texture2D, Texture2DDescriptor};
+use web_glitz::image::MipmapLevels;
+use web_glitz::runtime::single_threaded::{self, SingleThreadedContext};
+use web_glitz::runtime::{ContextOptions, RenderingContext};
+use web_sys::{window, HtmlCanvasElement};
+
+use crate::error::BufferVecError;
+use crate::index::IndexValue;
+use crate::{BufferVec, IndexBufferVec};
+
+thread_local! {
+    static ALLOCATION_FAULTS: AllocationFaults = AllocationFaults::default();
+}
+
+/// The allocation failures injected with [TestContext::fail_allocations_above] and
+/// [TestContext::fail_nth_allocation].
+#[derive(Default)]
+struct AllocationFaults {
+    max_bytes: Cell<Option<usize>>,
+    countdown: Cell<Option<usize>>,
+}
+
+impl AllocationFaults {
+    fn reset(&self) {
+        self.max_bytes.set(None);
+        self.countdown.set(None);
+    }
+}
+
+/// Returns an error if a [TestContext] injected a failure for the allocation of a new buffer of
+/// `bytes` bytes.
+///
+/// Vectors call this before they allocate a buffer, so an injected failure leaves them unmodified.
+pub(crate) fn check_allocation(bytes: usize) -> Result<(), BufferVecError> {
+    ALLOCATION_FAULTS.with(|faults| {
+        let refused = BufferVecError::AllocationFailed {
+            requested_bytes: bytes,
+        };
+
+        if faults
+            .max_bytes
+            .get()
+            .map_or(false, |max_bytes| bytes > max_bytes)
+        {
+            return Err(refused);
+        }
+
+        match faults.countdown.get() {
+            Some(0) => {
+                faults.countdown.set(None);
+
+                Err(refused)
+            }
+            Some(n) => {
+                faults.countdown.set(Some(n - 1));
+
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    })
+}
+
+/// A single-threaded WebGlitz context on a canvas that is not attached to the document.
+///
+/// The fixture can also make the buffer allocations of vectors fail, see
+/// [fail_allocations_above] and [fail_nth_allocation].
+///
+/// # Panics
+///
+/// Creating the fixture panics if it is not run in a browser, or if the browser does not support
+/// WebGL2.
+///
+/// [fail_allocations_above]: TestContext::fail_allocations_above
+/// [fail_nth_allocation]: TestContext::fail_nth_allocation
+pub struct TestContext {
+    context: SingleThreadedContext,
+    canvas: HtmlCanvasElement,
+}
+
+impl TestContext {
+    /// Creates a new 1x1 pixel canvas and initializes a WebGlitz context for it.
+    pub fn new() -> Self {
+        let canvas: HtmlCanvasElement = window()
+            .expect("no browser window")
+            .document()
+            .expect("no document")
+            .create_element("canvas")
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        canvas.set_width(1);
+        canvas.set_height(1);
+
+        let options = ContextOptions::begin().disable_antialias().finish();
+
+        // SAFETY: the canvas was just created, so its WebGL2 context is in its original state and
+        // no other handle to it exists.
+        let (context, _) =
+            unsafe { single_threaded::init(&canvas, &options) }.expect("WebGL2 is not supported");
+
+        TestContext { context, canvas }
+    }
+
+    /// The WebGlitz context.
+    pub fn context(&self) -> &SingleThreadedContext {
+        &self.context
+    }
+
+    /// The canvas the context was created for.
+    pub fn canvas(&self) -> &HtmlCanvasElement {
+        &self.canvas
+    }
+
+    /// Makes every allocation of a new buffer of more than `max_bytes` bytes fail, as if the GPU
+    /// were out of memory, or stops doing so if `None`.
+    ///
+    /// Applies to the growth of all vectors on the current thread (including the separate buffer
+    /// of a [SlicedLen::Previous] upload and [BufferVec::try_with_capacity]) until it is reset or
+    /// this fixture is dropped. The failure is injected before the vector asks WebGlitz for the
+    /// buffer: fallible operations such as [BufferVec::try_update] and [BufferVec::try_reserve]
+    /// return [BufferVecError::AllocationFailed] and leave the vector unmodified, and their
+    /// panicking counterparts panic.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn wrapper() {
+    /// use web_glitz::buffer::UsageHint;
+    /// use web_glitz_buffer_vec::wasm_test::{assert_buffer_contents, TestContext};
+    /// use web_glitz_buffer_vec::{BufferVec, BufferVecError};
+    ///
+    /// let fixture = TestContext::new();
+    /// let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::StaticDraw, 2);
+    ///
+    /// vec.update([1u32, 2]);
+    /// fixture.fail_allocations_above(Some(8));
+    ///
+    /// assert!(matches!(
+    ///     vec.try_update([1u32, 2, 3]),
+    ///     Err(BufferVecError::AllocationFailed { .. })
+    /// ));
+    ///
+    /// assert_buffer_contents(&vec, &[1, 2]).await;
+    /// # }
+    /// ```
+    ///
+    /// [SlicedLen::Previous]: crate::SlicedLen::Previous
+    /// [BufferVec::try_with_capacity]: crate::BufferVec::try_with_capacity
+    /// [BufferVec::try_update]: crate::BufferVec::try_update
+    /// [BufferVec::try_reserve]: crate::BufferVec::try_reserve
+    pub fn fail_allocations_above(&self, max_bytes: Option<usize>) {
+        ALLOCATION_FAULTS.with(|faults| faults.max_bytes.set(max_bytes));
+    }
+
+    /// Makes the allocation of a new buffer that follows the next `n` allocations fail, or stops
+    /// doing so if `None`.
+    ///
+    /// `Some(0)` makes the next allocation fail. Only that one allocation fails; allocations that
+    /// fail because of [fail_allocations_above] are not counted. See [fail_allocations_above] for
+    /// which allocations this applies to and how the failure surfaces.
+    ///
+    /// [fail_allocations_above]: TestContext::fail_allocations_above
+    pub fn fail_nth_allocation(&self, n: Option<usize>) {
+        ALLOCATION_FAULTS.with(|faults| faults.countdown.set(n));
+    }
+
+    /// Creates a 1x1 pixel `RGBA8` texture to render into, see [read_pixel].
+    ///
+    /// [read_pixel]: TestContext::read_pixel
+    pub fn create_pixel_texture(&self) -> Texture2D<RGBA8> {
+        self.context
+            .try_create_texture_2d(&Texture2DDescriptor {
+                format: RGBA8,
+                width: 1,
+                height: 1,
+                levels: MipmapLevels::Partial(1),
+            })
+            .unwrap()
+    }
+
+    /// Reads the bottom-left pixel of the base level of the `texture` back from the GPU.
+    pub async fn read_pixel(&self, texture: &Texture2D<RGBA8>) -> [u8; 4] {
+        let buffer = self
+            .context
+            .create_buffer_slice_uninit::<[u8; 4]>(1, UsageHint::StreamRead);
+        // SAFETY: the pack command below initializes the buffer before the download reads it.
+        let buffer = unsafe { buffer.assume_init() };
+
+        self.context.submit(
+            texture
+                .base_level()
+                .pack_to_buffer_command((&buffer).into()),
+        );
+
+        self.context.submit(buffer.download_command()).await[0]
+    }
+}
+
+impl Default for TestContext {
+    fn default() -> Self {
+        TestContext::new()
+    }
+}
+
+impl Drop for TestContext {
+    fn drop(&mut self) {
+        ALLOCATION_FAULTS.with(AllocationFaults::reset);
+    }
+}
+
+/// Reads the elements of the `vec` back from the GPU and asserts that they equal the `expected`
+/// elements.
+///
+/// # Panics
+///
+/// Panics if the elements differ from the `expected` elements.
+pub async fn assert_buffer_contents<Rc, T>(vec: &BufferVec<Rc, T>, expected: &[T])
+where
+    Rc: RenderingContext,
+    T: Copy + Send + Sync + PartialEq + Debug + 'static,
+{
+    let contents = vec.read_range(0..vec.len()).unwrap().await;
+
+    assert_eq!(contents, expected, "unexpected buffer contents");
+}
+
+/// Reads the indices of the `vec` back and asserts that they equal the `expected` indices.
+///
+/// WebGL does not allow index data to be read back from the GPU, so the indices are read from the
+/// vector's CPU-side copy, see [IndexBufferVec::read_range].
+///
+/// # Panics
+///
+/// Panics if the vector is not resilient (see [IndexBufferVec::set_resilient]), or if the indices
+/// differ from the `expected` indices.
+pub async fn assert_index_buffer_contents<Rc, T>(vec: &IndexBufferVec<Rc, T>, expected: &[T])
+where
+    Rc: RenderingContext,
+    T: IndexValue + PartialEq + Debug,
+{
+    let contents = vec.read_range(0..vec.len()).unwrap().await;
+
+    assert_eq!(contents, expected, "unexpected index buffer contents");
+}
@@ -0,0 +1,71 @@
+//! Property tests for the built-in growth policies, run with `cargo test`.
+#![cfg(not(target_arch = "wasm32"))]
+
+use proptest::prelude::*;
+use web_glitz_buffer_vec::growth::{
+    check_policy, ByteQuantum, Chunked, Doubling, Exact, GrowthPolicy, ScaleFactor,
+};
+
+/// Capacities that mix small and huge values, to exercise the overflow fallbacks.
+fn capacity() -> impl Strategy<Value = usize> {
+    prop_oneof![
+        0..256usize,
+        any::<u32>().prop_map(|value| value as usize),
+        (usize::MAX - 16)..=usize::MAX,
+        any::<usize>(),
+    ]
+}
+
+/// Element sizes in bytes, from single bytes to large structs and sizes that are not powers of two.
+fn element_size() -> impl Strategy<Value = usize> {
+    prop_oneof![1..=16usize, Just(64), Just(100), 1..4096usize]
+}
+
+/// `(current, required, element_size)` cases, as passed to [check_policy].
+fn cases() -> impl Strategy<Value = Vec<(usize, usize, usize)>> {
+    prop::collection::vec((capacity(), capacity(), element_size()), 1..64)
+}
+
+fn check(policy: &dyn GrowthPolicy, cases: &[(usize, usize, usize)]) -> Result<(), TestCaseError> {
+    let result = check_policy(policy, cases.iter().copied());
+
+    prop_assert_eq!(result, Ok(()));
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn doubling(cases in cases()) {
+        check(&Doubling, &cases)?;
+    }
+
+    #[test]
+    fn scale_factor(
+        factor in 1.0f64..8.0,
+        cases in cases(),
+    ) {
+        check(&ScaleFactor::new(factor), &cases)?;
+    }
+
+    #[test]
+    fn exact(cases in cases()) {
+        check(&Exact, &cases)?;
+    }
+
+    #[test]
+    fn chunked(
+        chunk in 1..4096usize,
+        cases in cases(),
+    ) {
+        check(&Chunked(chunk), &cases)?;
+    }
+
+    #[test]
+    fn byte_quantum(
+        bytes in 1..65536usize,
+        cases in cases(),
+    ) {
+        check(&ByteQuantum { bytes }, &cases)?;
+    }
+}
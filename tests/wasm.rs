@@ -0,0 +1,871 @@
+//! Tests against a real WebGL2 context, run in a browser with:
+//!
+//! ```sh
+//! wasm-pack test --headless --firefox --features wasm-test
+//! ```
+#![cfg(all(target_arch = "wasm32", feature = "wasm-test"))]
+#![feature(
+    const_fn,
+    const_maybe_uninit_as_ptr,
+    const_ptr_offset_from,
+    const_raw_ptr_deref,
+    ptr_offset_from
+)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+use web_glitz::buffer::UsageHint;
+use web_glitz::pipeline::graphics::{
+    CullingMode, GraphicsPipelineDescriptor, PrimitiveAssembly, WindingOrder,
+};
+use web_glitz::rendering::{LoadOp, RenderTargetDescriptor, StoreOp};
+use web_glitz::runtime::RenderingContext;
+use web_glitz_buffer_vec::wasm_test::{
+    assert_buffer_contents, assert_index_buffer_contents, TestContext,
+};
+use web_glitz_buffer_vec::{
+    BufferVec, BufferVecError, IndexBufferVec, IndexValue, MemoryBudget, MemoryRegistry,
+    MirroredBufferVec, SlicedLen, UploadQueue, UploadReport, VecStats,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[derive(web_glitz::derive::Vertex, Clone, Copy)]
+#[repr(C)]
+struct Position {
+    #[vertex_attribute(location = 0, format = "Float2_f32")]
+    position: [f32; 2],
+}
+
+/// A triangle that covers the entire viewport.
+const COVERING_TRIANGLE: [Position; 3] = [
+    Position {
+        position: [-1.0, -1.0],
+    },
+    Position {
+        position: [3.0, -1.0],
+    },
+    Position {
+        position: [-1.0, 3.0],
+    },
+];
+
+const VERTEX_SHADER: &str = "\
+#version 300 es
+
+layout(location=0) in vec2 position;
+
+void main() {
+    gl_Position = vec4(position, 0, 1);
+}
+";
+
+const FRAGMENT_SHADER: &str = "\
+#version 300 es
+precision mediump float;
+
+out vec4 out_color;
+
+void main() {
+    out_color = vec4(1, 0, 0, 1);
+}
+";
+
+/// Draws the `indices` into a cleared 1x1 pixel render target and returns the resulting pixel.
+async fn draw_indexed_pixel<I>(
+    fixture: &TestContext,
+    indices: &IndexBufferVec<impl RenderingContext, I>,
+) -> [u8; 4]
+where
+    I: IndexValue,
+{
+    let context = fixture.context();
+
+    let vertex_shader = context.try_create_vertex_shader(VERTEX_SHADER).unwrap();
+    let fragment_shader = context.try_create_fragment_shader(FRAGMENT_SHADER).unwrap();
+    let pipeline = context
+        .try_create_graphics_pipeline(
+            &GraphicsPipelineDescriptor::begin()
+                .vertex_shader(&vertex_shader)
+                .primitive_assembly(PrimitiveAssembly::Triangles {
+                    winding_order: WindingOrder::CounterClockwise,
+                    face_culling: CullingMode::None,
+                })
+                .fragment_shader(&fragment_shader)
+                .typed_vertex_attribute_layout::<Position>()
+                .typed_resource_bindings_layout::<()>()
+                .finish(),
+        )
+        .unwrap();
+
+    let mut vertices = BufferVec::new(context.clone(), UsageHint::StaticDraw);
+
+    vertices.update(COVERING_TRIANGLE);
+
+    let mut texture = fixture.create_pixel_texture();
+
+    {
+        let mut render_target =
+            context.create_render_target(RenderTargetDescriptor::new().attach_color_float(
+                texture.base_level_mut(),
+                LoadOp::Clear([0.0; 4]),
+                StoreOp::Store,
+            ));
+
+        let render_pass = render_target.create_render_pass(|framebuffer| {
+            framebuffer.pipeline_task(&pipeline, |active_pipeline| {
+                active_pipeline
+                    .task_builder()
+                    .bind_vertex_buffers(vertices.as_buffer_view())
+                    .bind_index_buffer(indices.as_buffer_view())
+                    .bind_resources(())
+                    .draw_indexed(indices.len(), 1)
+                    .finish()
+            })
+        });
+
+        context.submit(render_pass);
+    }
+
+    fixture.read_pixel(&texture).await
+}
+
+#[wasm_bindgen_test]
+async fn test_update_read_back_round_trip() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::StaticDraw);
+
+    vec.update([1u32, 2, 3]);
+
+    assert_buffer_contents(&vec, &[1, 2, 3]).await;
+
+    vec.update([4u32, 5]);
+
+    assert_buffer_contents(&vec, &[4, 5]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_update_concat_matches_concatenation() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::StreamDraw, 2);
+
+    let terrain = [1u32, 2, 3];
+    let props = [4u32, 5];
+
+    // Grows the buffer once for both parts.
+    assert!(vec.update_concat(&[&terrain[..], &[], &props[..]]));
+    assert_buffer_contents(&vec, &[&terrain[..], &props[..]].concat()).await;
+
+    // Fits the grown buffer.
+    assert!(!vec.update_concat(&[&props[..], &terrain[..1]]));
+    assert_buffer_contents(&vec, &[4, 5, 1]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_rebind_context() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::StaticDraw, 2);
+
+    vec.update([1u32, 2]);
+
+    let generation = vec.generation();
+    let new_fixture = TestContext::new();
+
+    vec.rebind_context(new_fixture.context().clone(), vec![3u32, 4, 5]);
+
+    assert_eq!(vec.generation(), generation + 1);
+    assert_buffer_contents(&vec, &[3, 4, 5]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_cancel_queued_update() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::StreamDraw, 2);
+    let mut queue = UploadQueue::new();
+
+    vec.update([1u32, 2]);
+
+    let generation = vec.generation();
+
+    // Grows the buffer.
+    let ticket = vec.enqueue_cancellable_update(vec![3u32, 4, 5], &mut queue);
+
+    assert_eq!(vec.len(), 3);
+    assert!(vec.cancel_queued_update(ticket));
+    assert_eq!(vec.len(), 2);
+    assert_eq!(vec.capacity(), 2);
+    assert_ne!(vec.generation(), generation);
+    assert_eq!(queue.submit(fixture.context()), UploadReport::default());
+    assert_buffer_contents(&vec, &[1, 2]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_cancel_replaced_queued_update() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::StreamDraw);
+    let mut queue = UploadQueue::new();
+
+    vec.update([1u32, 2]);
+
+    let first = vec.enqueue_cancellable_update([3u32], &mut queue);
+    let second = vec.enqueue_cancellable_update([4u32, 5, 6], &mut queue);
+
+    // The first update was replaced by the second, so it is no longer pending.
+    assert!(!vec.cancel_queued_update(first));
+    assert_eq!(vec.len(), 3);
+
+    // Cancelling the second update returns to the state from before the first.
+    assert!(vec.cancel_queued_update(second));
+    assert_eq!(vec.len(), 2);
+    assert!(queue.is_empty());
+    assert_buffer_contents(&vec, &[1, 2]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_cancel_queued_update_after_modification() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::StreamDraw, 8);
+    let mut queue = UploadQueue::new();
+
+    let ticket = vec.enqueue_cancellable_update([1u32, 2], &mut queue);
+
+    vec.update_range(0, [3u32]);
+
+    // The vector was modified after the update was enqueued, so the update is kept.
+    assert!(!vec.cancel_queued_update(ticket));
+    assert_eq!(vec.len(), 2);
+    assert_eq!(queue.submit(fixture.context()).commands, 1);
+}
+
+#[wasm_bindgen_test]
+async fn test_cancel_queued_index_update() {
+    let fixture = TestContext::new();
+    let mut indices = IndexBufferVec::new(fixture.context().clone(), UsageHint::StreamDraw);
+    let mut queue = UploadQueue::new();
+
+    indices.set_resilient(true);
+    indices.update([0u16, 1, 2]);
+
+    let ticket = indices.enqueue_cancellable_update([2u16, 1, 0, 0, 2, 3], &mut queue);
+
+    assert_eq!(indices.len(), 6);
+    assert!(indices.cancel_queued_update(ticket));
+    assert_eq!(indices.len(), 3);
+    assert_eq!(indices.max_index(), Some(2));
+    assert_eq!(queue.submit(fixture.context()), UploadReport::default());
+    assert_index_buffer_contents(&indices, &[0, 1, 2]).await;
+    assert_eq!(
+        draw_indexed_pixel(&fixture, &indices).await,
+        [255, 0, 0, 255]
+    );
+}
+
+#[wasm_bindgen_test]
+async fn test_patch_field_range() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+
+    vec.set_resilient(true);
+    vec.update([[1u32, 2], [3, 4], [5, 6]]);
+
+    let fields: Vec<u8> = [7u32, 8].iter().flat_map(|v| v.to_ne_bytes()).collect();
+
+    vec.patch_field_range(4, 4, 1..3, &fields);
+
+    assert_buffer_contents(&vec, &[[1, 2], [3, 7], [5, 8]]).await;
+
+    let mut unshadowed = BufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+
+    unshadowed.update([[1u32, 2]]);
+
+    assert_eq!(
+        unshadowed.try_patch_field_range(4, 4, 0..1, &fields[..4]),
+        Err(BufferVecError::NotResilient)
+    );
+    assert_buffer_contents(&unshadowed, &[[1, 2]]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_growth_preserves_contents() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    vec.update([1u32, 2]);
+
+    let generation = vec.generation();
+
+    assert!(vec.append([3u32, 4, 5]));
+    assert_ne!(vec.generation(), generation);
+
+    assert_buffer_contents(&vec, &[1, 2, 3, 4, 5]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_sliced_upload_over_budget() {
+    let fixture = TestContext::new();
+    let budget = MemoryBudget::new(8);
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    vec.set_budget(Some(&budget));
+    vec.update([1u32, 2]);
+
+    let generation = vec.generation();
+    let staging_bytes = vec.capacity_after(4).unwrap() * 4;
+
+    assert_eq!(
+        vec.try_update_sliced(vec![3u32, 4, 5, 6], 8, SlicedLen::Previous)
+            .map(|_| ()),
+        Err(BufferVecError::AllocationFailed {
+            requested_bytes: staging_bytes
+        })
+    );
+    assert_eq!((vec.len(), vec.capacity()), (2, 2));
+    assert_eq!(vec.generation(), generation);
+    assert_eq!(budget.used(), 8);
+
+    assert_buffer_contents(&vec, &[1, 2]).await;
+
+    budget.set_limit(64);
+
+    let mut upload = vec.update_sliced(vec![3u32, 4, 5, 6], 8, SlicedLen::Previous);
+
+    assert_eq!(budget.used(), 8 + staging_bytes);
+    assert!(!upload.step(&mut vec).done);
+    assert!(upload.step(&mut vec).done);
+    assert_eq!(budget.used(), vec.capacity() * 4);
+
+    assert_buffer_contents(&vec, &[3, 4, 5, 6]).await;
+
+    let upload = vec.update_sliced(vec![7u32, 8], 8, SlicedLen::Previous);
+
+    assert_eq!(budget.used(), vec.capacity() * 8);
+
+    drop(upload);
+
+    assert_eq!(budget.used(), vec.capacity() * 4);
+}
+
+#[wasm_bindgen_test]
+async fn test_growth_over_budget_leaves_vec_unmodified() {
+    let fixture = TestContext::new();
+    let budget = MemoryBudget::new(8);
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    vec.set_budget(Some(&budget));
+    vec.update([1u32, 2]);
+
+    let generation = vec.generation();
+    let refused = Err(BufferVecError::AllocationFailed {
+        requested_bytes: vec.capacity_after(3).unwrap() * 4,
+    });
+
+    assert_eq!(vec.try_update([3u32, 4, 5]).map(|_| ()), refused);
+    assert_eq!(vec.try_reserve(1), refused);
+    assert_eq!(vec.try_append([3u32]).map(|_| ()), refused);
+    assert_eq!(
+        vec.try_update_concat(&[&[3u32, 4], &[5]]).map(|_| ()),
+        refused
+    );
+    assert!(matches!(
+        vec.try_update_sliced(vec![3u32, 4, 5], 4, SlicedLen::CompletedPrefix),
+        Err(BufferVecError::AllocationFailed { .. })
+    ));
+
+    assert_eq!((vec.len(), vec.capacity()), (2, 2));
+    assert_eq!(vec.generation(), generation);
+    assert_eq!(budget.used(), 8);
+
+    assert_buffer_contents(&vec, &[1, 2]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_index_buffer_growth_over_budget_leaves_vec_unmodified() {
+    let fixture = TestContext::new();
+    let budget = MemoryBudget::new(4);
+    let mut indices =
+        IndexBufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    indices.set_resilient(true);
+    indices.set_budget(Some(&budget));
+    indices.update([0u16, 1]);
+
+    let generation = indices.generation();
+    let refused = Err(BufferVecError::AllocationFailed {
+        requested_bytes: indices.capacity_after(3).unwrap() * 2,
+    });
+
+    assert_eq!(indices.try_update([2u16, 3, 4]).map(|_| ()), refused);
+    assert_eq!(indices.try_reserve(1), refused);
+    assert_eq!(indices.try_append([2u16]).map(|_| ()), refused);
+
+    assert_eq!((indices.len(), indices.capacity()), (2, 2));
+    assert_eq!(indices.generation(), generation);
+    assert_eq!(budget.used(), 4);
+
+    assert_index_buffer_contents(&indices, &[0, 1]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_allocation_failure_leaves_vec_unmodified() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    vec.update([1u32, 2]);
+    fixture.fail_allocations_above(Some(8));
+
+    let generation = vec.generation();
+    let refused = Err(BufferVecError::AllocationFailed {
+        requested_bytes: vec.capacity_after(3).unwrap() * 4,
+    });
+
+    assert_eq!(vec.try_update([3u32, 4, 5]).map(|_| ()), refused);
+    assert_eq!(vec.try_reserve(1), refused);
+    assert_eq!(vec.try_append([3u32]).map(|_| ()), refused);
+    assert_eq!(
+        vec.try_update_concat(&[&[3u32, 4], &[5]]).map(|_| ()),
+        refused
+    );
+    assert_eq!(
+        vec.try_update_sliced(vec![3u32, 4, 5], 4, SlicedLen::CompletedPrefix)
+            .map(|_| ()),
+        refused
+    );
+    assert_eq!(
+        vec.try_update_sliced(vec![3u32, 4, 5], 4, SlicedLen::Previous)
+            .map(|_| ()),
+        refused
+    );
+    assert_eq!(
+        BufferVec::<_, u32>::try_with_capacity(
+            fixture.context().clone(),
+            UsageHint::DynamicDraw,
+            3
+        )
+        .err(),
+        Some(BufferVecError::AllocationFailed {
+            requested_bytes: 12
+        })
+    );
+
+    assert_eq!((vec.len(), vec.capacity()), (2, 2));
+    assert_eq!(vec.generation(), generation);
+
+    assert_buffer_contents(&vec, &[1, 2]).await;
+
+    fixture.fail_allocations_above(None);
+
+    assert_eq!(vec.try_update([3u32, 4, 5]).map(|_| ()), Ok(()));
+
+    assert_buffer_contents(&vec, &[3, 4, 5]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_fail_nth_allocation() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+
+    fixture.fail_nth_allocation(Some(1));
+
+    assert_eq!(vec.try_update([1u32]).map(|_| ()), Ok(()));
+
+    let additional = vec.capacity();
+    let refused = Err(BufferVecError::AllocationFailed {
+        requested_bytes: vec.capacity_after(additional + 1).unwrap() * 4,
+    });
+
+    assert_eq!(vec.try_append(vec![2u32; additional]).map(|_| ()), refused);
+    assert_eq!(vec.len(), 1);
+    assert_eq!(vec.try_append(vec![2u32; additional]).map(|_| ()), Ok(()));
+
+    let mut expected = vec![1u32];
+
+    expected.resize(additional + 1, 2);
+
+    assert_buffer_contents(&vec, &expected).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_index_buffer_allocation_failure_leaves_vec_unmodified() {
+    let fixture = TestContext::new();
+    let mut indices =
+        IndexBufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    indices.set_resilient(true);
+    indices.update([0u16, 1]);
+    fixture.fail_allocations_above(Some(4));
+
+    let generation = indices.generation();
+    let refused = Err(BufferVecError::AllocationFailed {
+        requested_bytes: indices.capacity_after(3).unwrap() * 2,
+    });
+
+    assert_eq!(indices.try_update([2u16, 3, 4]).map(|_| ()), refused);
+    assert_eq!(indices.try_reserve(1), refused);
+    assert_eq!(indices.try_append([2u16]).map(|_| ()), refused);
+
+    assert_eq!((indices.len(), indices.capacity()), (2, 2));
+    assert_eq!(indices.generation(), generation);
+
+    assert_index_buffer_contents(&indices, &[0, 1]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_index_buffer_growth_preserves_contents() {
+    let fixture = TestContext::new();
+    let mut indices =
+        IndexBufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    indices.set_resilient(true);
+    indices.update([0u16, 1]);
+
+    assert!(indices.append([2u16, 3]));
+
+    assert_index_buffer_contents(&indices, &[0, 1, 2, 3]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_index_buffer_without_cpu_copy() {
+    let fixture = TestContext::new();
+    let mut indices =
+        IndexBufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    indices.update([0u16, 1]);
+
+    assert_eq!(
+        indices.try_append([2u16, 3]),
+        Err(BufferVecError::NotResilient)
+    );
+    assert_eq!(indices.len(), 2);
+    assert!(matches!(
+        indices.read_range(0..2),
+        Err(BufferVecError::NotResilient)
+    ));
+
+    // Replacing all indices needs no CPU-side copy.
+    assert!(indices.try_update([0u16, 1, 2]).unwrap());
+    assert_eq!(
+        draw_indexed_pixel(&fixture, &indices).await,
+        [255, 0, 0, 255]
+    );
+}
+
+#[wasm_bindgen_test]
+async fn test_index_buffer_recreate() {
+    let fixture = TestContext::new();
+    let mut indices = IndexBufferVec::new(fixture.context().clone(), UsageHint::StaticDraw);
+
+    indices.set_resilient(true);
+    indices.update([0u16, 1, 2]);
+
+    let generation = indices.generation();
+    let indices = Rc::new(RefCell::new(indices));
+    let mut registry = MemoryRegistry::new();
+
+    registry.register(&indices);
+    registry.recreate_all(fixture.context());
+
+    // The registry only holds a weak reference.
+    let indices = Rc::try_unwrap(indices).ok().unwrap().into_inner();
+
+    assert!(indices.generation() > generation);
+    assert_index_buffer_contents(&indices, &[0, 1, 2]).await;
+    assert_eq!(
+        draw_indexed_pixel(&fixture, &indices).await,
+        [255, 0, 0, 255]
+    );
+}
+
+#[wasm_bindgen_test]
+#[should_panic(expected = "not resilient")]
+fn test_register_index_buffer_without_cpu_copy() {
+    let fixture = TestContext::new();
+    let indices = IndexBufferVec::<_, u16>::new(fixture.context().clone(), UsageHint::StaticDraw);
+
+    MemoryRegistry::new().register(&Rc::new(RefCell::new(indices)));
+}
+
+#[wasm_bindgen_test]
+async fn test_indexed_draw() {
+    let fixture = TestContext::new();
+    let mut indices = IndexBufferVec::new(fixture.context().clone(), UsageHint::StaticDraw);
+
+    indices.update([0u16, 1, 2]);
+
+    assert_eq!(
+        draw_indexed_pixel(&fixture, &indices).await,
+        [255, 0, 0, 255]
+    );
+
+    // A degenerate triangle covers no pixels, so the pixel keeps its clear color.
+    indices.update([0u16, 0, 0]);
+
+    assert_eq!(draw_indexed_pixel(&fixture, &indices).await, [0, 0, 0, 0]);
+}
+
+#[wasm_bindgen_test]
+async fn test_u8_index_buffer_growth_preserves_contents() {
+    let fixture = TestContext::new();
+    let mut indices =
+        IndexBufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 2);
+
+    indices.set_resilient(true);
+    indices.update([0u8, 1]);
+
+    assert!(indices.append([2u8, 255]));
+
+    assert_index_buffer_contents(&indices, &[0, 1, 2, 255]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_u8_indexed_draw() {
+    let fixture = TestContext::new();
+    let mut indices = IndexBufferVec::new(fixture.context().clone(), UsageHint::StaticDraw);
+
+    indices.update([0u8, 1, 2]);
+
+    assert_eq!(
+        draw_indexed_pixel(&fixture, &indices).await,
+        [255, 0, 0, 255]
+    );
+
+    indices.update([0u8, 0, 0]);
+
+    assert_eq!(draw_indexed_pixel(&fixture, &indices).await, [0, 0, 0, 0]);
+}
+
+#[wasm_bindgen_test]
+async fn test_compact_to_u16_preserves_contents() {
+    let fixture = TestContext::new();
+    let mut indices = IndexBufferVec::new(fixture.context().clone(), UsageHint::StaticDraw);
+
+    indices.set_resilient(true);
+    indices.update([0u32, 1, 2]);
+    indices.insert_section("triangle", &[2u32, 1, 0]);
+
+    let indices = indices.compact_to_u16().unwrap();
+
+    assert_index_buffer_contents(&indices, &[0, 1, 2, 2, 1, 0]).await;
+    assert_eq!(indices.section_range("triangle"), Some(3..6));
+    assert_eq!(
+        draw_indexed_pixel(&fixture, &indices).await,
+        [255, 0, 0, 255]
+    );
+}
+
+#[wasm_bindgen_test]
+async fn test_fresh_vecs_have_empty_views() {
+    let fixture = TestContext::new();
+    let vec = BufferVec::<_, u32>::new(fixture.context().clone(), UsageHint::StaticDraw);
+    let reserved =
+        BufferVec::<_, u32>::with_capacity(fixture.context().clone(), UsageHint::StaticDraw, 4);
+    let indices = IndexBufferVec::<_, u16>::new(fixture.context().clone(), UsageHint::StaticDraw);
+
+    assert_eq!(vec.capacity(), 0);
+    assert_eq!(vec.as_buffer_view().len(), 0);
+    assert_eq!(reserved.as_buffer_view().len(), 0);
+    assert_eq!(indices.capacity(), 0);
+    assert_eq!(indices.as_buffer_view().len(), 0);
+
+    assert_buffer_contents(&vec, &[]).await;
+    assert_buffer_contents(&reserved, &[]).await;
+
+    // Drawing the empty view of a never-updated index vector draws nothing.
+    assert_eq!(draw_indexed_pixel(&fixture, &indices).await, [0, 0, 0, 0]);
+}
+
+#[wasm_bindgen_test]
+async fn test_mirrored_retain_uploads_suffix() {
+    let fixture = TestContext::new();
+    let mut vec = MirroredBufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+
+    vec.update([1u32, 3, 4, 5, 6, 7]);
+
+    let capacity = vec.capacity();
+    let before = vec.stats();
+
+    vec.retain(|element| element % 2 == 1);
+
+    // Only the elements from the first removed element onward are uploaded.
+    assert_eq!(vec.as_slice(), &[1, 3, 5, 7]);
+    assert_eq!(vec.stats().bytes_uploaded - before.bytes_uploaded, 2 * 4);
+    assert_eq!(vec.capacity(), capacity);
+    assert_buffer_contents(&*vec, &[1, 3, 5, 7]).await;
+
+    let before = vec.stats();
+
+    vec.retain(|_| true);
+
+    assert_eq!(vec.stats().uploads, before.uploads);
+    assert_buffer_contents(&*vec, &[1, 3, 5, 7]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_mirrored_swap_remove() {
+    let fixture = TestContext::new();
+    let mut vec = MirroredBufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+
+    vec.update([1u32, 2, 3, 4]);
+
+    let capacity = vec.capacity();
+    let before = vec.stats();
+
+    assert_eq!(vec.swap_remove(1), 2);
+
+    // A single element is uploaded into the hole.
+    assert_eq!(vec.as_slice(), &[1, 4, 3]);
+    assert_eq!(vec.stats().bytes_uploaded - before.bytes_uploaded, 4);
+    assert_buffer_contents(&*vec, &[1, 4, 3]).await;
+
+    let before = vec.stats();
+
+    // Removing the last element uploads nothing.
+    assert_eq!(vec.swap_remove(2), 3);
+    assert_eq!(vec.stats().uploads, before.uploads);
+    assert_eq!(vec.capacity(), capacity);
+    assert_buffer_contents(&vec.into_inner(), &[1, 4]).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_remove_preserves_order() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+
+    vec.update([1u32, 2, 3, 4, 5]);
+
+    let capacity = vec.capacity();
+
+    vec.remove(1);
+
+    assert_buffer_contents(&vec, &[1, 3, 4, 5]).await;
+
+    vec.remove(3);
+
+    assert_buffer_contents(&vec, &[1, 3, 4]).await;
+
+    vec.remove(0);
+
+    assert_buffer_contents(&vec, &[3, 4]).await;
+    assert_eq!(vec.capacity(), capacity);
+}
+
+#[wasm_bindgen_test]
+async fn test_remove_from_long_vec() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+    let mut expected: Vec<u32> = (0..1000).collect();
+
+    vec.update(expected.clone());
+
+    let capacity = vec.capacity();
+
+    // Shifting a long range by one slot takes too many non-overlapping copies, so the copy is
+    // staged through a scratch buffer.
+    vec.remove(0);
+    expected.remove(0);
+    vec.remove(500);
+    expected.remove(500);
+
+    assert_buffer_contents(&vec, &expected).await;
+    assert_eq!(vec.capacity(), capacity);
+}
+
+#[wasm_bindgen_test]
+async fn test_swap_remove_gpu() {
+    let fixture = TestContext::new();
+    let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+
+    vec.update([1u32, 2, 3, 4]);
+
+    let capacity = vec.capacity();
+
+    vec.swap_remove_gpu(1);
+
+    assert_buffer_contents(&vec, &[1, 4, 3]).await;
+
+    vec.swap_remove_gpu(2);
+
+    assert_buffer_contents(&vec, &[1, 4]).await;
+    assert_eq!(vec.capacity(), capacity);
+}
+
+#[wasm_bindgen_test]
+async fn test_copy_within_matches_slice_copy_within() {
+    let fixture = TestContext::new();
+    let data: Vec<u32> = (0..100).collect();
+
+    let cases = [
+        // Disjoint ranges.
+        (0..10, 50),
+        // Overlapping, moving left and right by a few slots.
+        (10..40, 5),
+        (10..40, 15),
+        // Overlapping by all but one slot, which is staged through a scratch buffer.
+        (0..99, 1),
+        (1..100, 0),
+        // Empty and identity copies.
+        (20..20, 70),
+        (30..60, 30),
+    ];
+
+    for (source, target) in cases.iter().cloned() {
+        let mut vec = BufferVec::new(fixture.context().clone(), UsageHint::DynamicDraw);
+        let mut expected = data.clone();
+
+        vec.update(data.clone());
+        vec.copy_within(source.clone(), target);
+        expected.copy_within(source, target);
+
+        assert_buffer_contents(&vec, &expected).await;
+        assert_eq!(vec.len(), data.len());
+    }
+}
+
+#[wasm_bindgen_test]
+async fn test_index_buffer_stats() {
+    let fixture = TestContext::new();
+    let mut indices =
+        IndexBufferVec::with_capacity(fixture.context().clone(), UsageHint::DynamicDraw, 4);
+
+    indices.set_resilient(true);
+
+    assert_eq!(
+        indices.stats(),
+        VecStats {
+            peak_capacity: 4,
+            ..VecStats::default()
+        }
+    );
+
+    // Both fit the current capacity, so each uploads only its own indices.
+    indices.update([0u16, 1, 2]);
+    indices.append([3u16]);
+
+    // Empty parts are not uploaded.
+    indices.update_parts(&[(0, &[2u16, 1, 0][..]), (3, &[][..])]);
+
+    // Grows the buffer, which uploads all 6 indices again.
+    indices.append([4u16, 5]);
+
+    assert_eq!(
+        indices.stats(),
+        VecStats {
+            uploads: 4,
+            bytes_uploaded: (3 + 1 + 3 + 6) * 2,
+            reallocations: 1,
+            peak_capacity: indices.capacity(),
+        }
+    );
+    assert_index_buffer_contents(&indices, &[2, 1, 0, 3, 4, 5]).await;
+
+    indices.reset_stats();
+
+    assert_eq!(
+        indices.stats(),
+        VecStats {
+            peak_capacity: indices.capacity(),
+            ..VecStats::default()
+        }
+    );
+}